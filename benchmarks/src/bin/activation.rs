@@ -0,0 +1,172 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::{
+    parallel_traits::ParallelActivation, rayon_traits::RayonActivation, Activation,
+    ActivationFunction, BaseMatrix,
+};
+use std::path::Path;
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::{number, verify};
+use obpmark_library::number_traits::FixedPoint;
+
+#[cfg(feature = "int")]
+compile_error!(
+    "This benchmark is not supported for int, the activation functions are floating point"
+);
+
+// relative to the magnitude of the reference (at least 1)
+#[cfg(feature = "double")]
+const RELATIVE_TOLERANCE: f64 = 1e-12;
+#[cfg(feature = "half")]
+const RELATIVE_TOLERANCE: f64 = 1e-2;
+#[cfg(not(any(feature = "double", feature = "half")))]
+const RELATIVE_TOLERANCE: f64 = 1e-5;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Function {
+    LeakyRelu,
+    Relu6,
+    Sigmoid,
+    Tanh,
+    Gelu,
+    Swish,
+    HardSigmoid,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Activation functions benchmark")]
+struct Args {
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Activation function to apply
+    #[arg(value_enum, long)]
+    function: Function,
+
+    /// Slope of the negative part of the leaky ReLU
+    #[arg(long, default_value = "0.01")]
+    slope: Number,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let function = match args.function {
+        Function::LeakyRelu => ActivationFunction::LeakyRelu(args.slope),
+        Function::Relu6 => ActivationFunction::Relu6,
+        Function::Sigmoid => ActivationFunction::Sigmoid,
+        Function::Tanh => ActivationFunction::Tanh,
+        Function::Gelu => ActivationFunction::Gelu,
+        Function::Swish => ActivationFunction::Swish,
+        Function::HardSigmoid => ActivationFunction::HardSigmoid,
+    };
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input file, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), args.common.size, args.common.size).unwrap()
+        }
+        None => Matrix::from_random_seed(
+            args.common.seed,
+            args.common.size,
+            args.common.size,
+            number!("-10"),
+            number!("10"),
+        ),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let mut B = Matrix::zeroes(args.common.size, args.common.size);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => {
+                A.activation(B, function).unwrap();
+            }
+            (None, Implementation::Rayon) => {
+                A.rayon_activation(B, function).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (Some(n), Implementation::StdParallel) => {
+                A.parallel_activation(B, function, n).unwrap();
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_activation(B, function, 8).unwrap();
+            }
+        }
+    });
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = args.common.export {
+        B.to_file(Path::new(&filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let B_ref = Matrix::from_file(Path::new(&filename), args.common.size, args.common.size)
+                .unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // verify against the definitions of the functions in double precision
+            let reference = get_ref_function(args.function, args.slope.fixed_to_f64(0));
+            let passed = A
+                .get_data()
+                .iter()
+                .flatten()
+                .zip(B.get_data().iter().flatten())
+                .all(|(x, y)| {
+                    let y_ref = reference(x.fixed_to_f64(0));
+                    (y.fixed_to_f64(0) - y_ref).abs() <= RELATIVE_TOLERANCE * y_ref.abs().max(1.0)
+                });
+            if passed {
+                println!("Verification passed with toll {}", RELATIVE_TOLERANCE);
+            } else {
+                println!("Verification failed");
+            }
+        }
+        None => (),
+    }
+}
+
+fn get_ref_function(function: Function, slope: f64) -> impl Fn(f64) -> f64 {
+    let sigmoid = |x: f64| 1.0 / (1.0 + (-x).exp());
+    move |x| match function {
+        Function::LeakyRelu => {
+            if x < 0.0 {
+                slope * x
+            } else {
+                x
+            }
+        }
+        Function::Relu6 => x.clamp(0.0, 6.0),
+        Function::Sigmoid => sigmoid(x),
+        Function::Tanh => 2.0 * sigmoid(2.0 * x) - 1.0,
+        Function::Gelu => {
+            // tanh approximation, tanh(u) = 2 sigmoid(2u) - 1
+            let u = (2.0 / std::f64::consts::PI).sqrt() * (x + 0.044715 * x.powi(3));
+            x * sigmoid(2.0 * u)
+        }
+        Function::Swish => x * sigmoid(x),
+        Function::HardSigmoid => ((x + 3.0) / 6.0).clamp(0.0, 1.0),
+    }
+}
@@ -0,0 +1,165 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::aes::Aes;
+use obpmark_library::{
+    parallel_traits::ParallelBlockCipher, random_matrix_data, rayon_traits::RayonBlockCipher,
+    BlockCipher,
+};
+use std::{path::Path, thread};
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation};
+use benchmarks::verify;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Ecb,
+    Ctr,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "AES encryption benchmark")]
+struct Args {
+    /// The input is size bytes of plaintext, a multiple of 16 for ECB
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Key size in bits: 128, 192 or 256
+    #[arg(long, default_value_t = 128)]
+    key_size: usize,
+
+    /// Mode of operation
+    #[arg(value_enum, long, default_value_t = Mode::Ctr)]
+    mode: Mode,
+}
+
+/// Random bytes generated from `seed`
+fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    random_matrix_data::<i32>(seed, 1, len, 0, 256)
+        .concat()
+        .into_iter()
+        .map(|byte| byte as u8)
+        .collect()
+}
+
+/// FIPS-197 appendix C, the key is 0, 1, 2, ...
+fn fips_197_ciphertext(key_size: usize) -> [u8; 16] {
+    match key_size {
+        128 => [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a,
+        ],
+        192 => [
+            0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d,
+            0x71, 0x91,
+        ],
+        _ => [
+            0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+            0x60, 0x89,
+        ],
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let size = args.common.size;
+    if ![128, 192, 256].contains(&args.key_size) {
+        panic!("The key size has to be 128, 192 or 256 bits");
+    }
+    if args.mode == Mode::Ecb && !size.is_multiple_of(16) {
+        panic!("The size has to be a multiple of 16 bytes for ECB");
+    }
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            // raw bytes
+            let data = std::fs::read(Path::new(&v[0])).unwrap();
+            if data.len() != size {
+                panic!("Expected {} bytes of input, got {}", size, data.len());
+            }
+            data
+        }
+        None => random_bytes(args.common.seed, size),
+    };
+    let key = random_bytes(args.common.seed + 1, args.key_size / 8);
+    let counter: [u8; 16] = random_bytes(args.common.seed + 2, 16).try_into().unwrap();
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{:02x?}", A);
+    }
+
+    // the key expansion is not timed
+    let aes = Aes::new(&key).unwrap();
+    let mut B = A.clone();
+
+    // the encryption works in place, with --tmr each execution starts from a copy of A
+    let elapsed = args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation, args.mode) {
+            (Some(n), Implementation::Sequential, _) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (Some(_), Implementation::Rayon, _) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (_, Implementation::Sequential, Mode::Ecb) => aes.ecb_encrypt(B).unwrap(),
+            (_, Implementation::Sequential, Mode::Ctr) => aes.ctr(&counter, B),
+            (None, Implementation::Rayon, Mode::Ecb) => aes.rayon_ecb_encrypt(B).unwrap(),
+            (None, Implementation::Rayon, Mode::Ctr) => aes.rayon_ctr(&counter, B),
+            (n, Implementation::StdParallel, mode) => {
+                // one thread per core by default
+                let n = n.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+                match mode {
+                    Mode::Ecb => aes.parallel_ecb_encrypt(B, n).unwrap(),
+                    Mode::Ctr => aes.parallel_ctr(&counter, B, n).unwrap(),
+                }
+            }
+        }
+    });
+
+    if args.common.timing {
+        println!(
+            "Throughput: {:.2} MB/s",
+            size as f64 / elapsed.as_secs_f64() / 1e6
+        );
+    }
+
+    if args.common.output {
+        println!("Output:");
+        println!("{:02x?}", B);
+    }
+
+    if let Some(filename) = &args.common.export {
+        // the ciphertext is exported as raw bytes
+        std::fs::write(Path::new(filename), &B).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported ciphertext
+            let reference = std::fs::read(Path::new(&filename)).unwrap();
+            verify!(B, reference);
+        }
+        Some(None) => {
+            // the NIST test vector of the key size and the decryption of the ciphertext
+            let fips_key: Vec<u8> = (0..(args.key_size / 8) as u8).collect();
+            let mut block: [u8; 16] = std::array::from_fn(|i| (i as u8) * 0x11);
+            Aes::new(&fips_key).unwrap().encrypt_block(&mut block);
+            if block != fips_197_ciphertext(args.key_size) {
+                println!("Verification failed: FIPS-197 test vector");
+                return;
+            }
+
+            match args.mode {
+                Mode::Ecb => aes.ecb_decrypt(&mut B).unwrap(),
+                Mode::Ctr => aes.ctr(&counter, &mut B),
+            }
+            verify!(B, A);
+        }
+        None => (),
+    }
+}
@@ -0,0 +1,152 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::{
+    parallel_traits::ParallelRiceEncoder, rayon_traits::RayonRiceEncoder, BaseMatrix, RiceEncoder,
+    RiceParameters,
+};
+use std::path::Path;
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation};
+use benchmarks::verify;
+
+// the compressor works on integer samples whatever the number type of the benchmarks
+#[cfg(not(feature = "2d"))]
+type Samples = obpmark_library::matrix_1d::Matrix1d<i32>;
+#[cfg(feature = "2d")]
+type Samples = obpmark_library::matrix_2d::Matrix2d<i32>;
+
+#[derive(Parser, Debug)]
+#[command(about = "CCSDS 121.0 lossless compression benchmark")]
+struct Args {
+    /// The input is a size x size matrix of samples, taken in row major order
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Bits of the samples, from 1 to 32
+    #[arg(long, default_value_t = 16)]
+    sample_bits: u32,
+
+    /// Bits of the random samples, lower values give more compressible data
+    #[arg(long, default_value_t = 8)]
+    entropy_bits: u32,
+
+    /// Samples of each block: 8, 16, 32 or 64
+    #[arg(long, default_value_t = 16)]
+    block_size: usize,
+
+    /// Blocks between two reference samples, at most 4096
+    #[arg(long, default_value_t = 64)]
+    reference_interval: usize,
+
+    /// Codes the samples without the preprocessor
+    #[arg(long, default_value_t = false)]
+    no_preprocessor: bool,
+
+    /// Disables the zero block option
+    #[arg(long, default_value_t = false)]
+    no_zero_block: bool,
+
+    /// Disables the second extension option
+    #[arg(long, default_value_t = false)]
+    no_second_extension: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let size = args.common.size;
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Samples::from_file(Path::new(&v[0]), size, size).unwrap()
+        }
+        None => Samples::from_random_seed(
+            args.common.seed,
+            size,
+            size,
+            0,
+            1 << args.entropy_bits.min(args.sample_bits).min(30),
+        ),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let parameters = RiceParameters {
+        sample_bits: args.sample_bits,
+        block_size: args.block_size,
+        reference_interval: args.reference_interval,
+        preprocessor: !args.no_preprocessor,
+        zero_block: !args.no_zero_block,
+        second_extension: !args.no_second_extension,
+    };
+
+    let mut compressed = Vec::new();
+    let elapsed = args.common.run_kernel(&mut compressed, |compressed| {
+        *compressed = match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.rice_encode(&parameters).unwrap(),
+            (None, Implementation::Rayon) => A.rayon_rice_encode(&parameters).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::StdParallel) => {
+                A.parallel_rice_encode(&parameters, n).unwrap()
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_rice_encode(&parameters, 8).unwrap()
+            }
+        }
+    });
+
+    let samples = size * size;
+    let input_bits = samples * args.sample_bits as usize;
+    println!(
+        "Compressed size: {} bytes, {:.3} bits per sample, ratio {:.2}",
+        compressed.len(),
+        (compressed.len() * 8) as f64 / samples as f64,
+        input_bits as f64 / (compressed.len() * 8) as f64
+    );
+
+    if args.common.timing {
+        println!(
+            "Throughput: {:.2} Msamples/s, {:.2} MB/s",
+            samples as f64 / elapsed.as_secs_f64() / 1e6,
+            input_bits as f64 / 8.0 / elapsed.as_secs_f64() / 1e6
+        );
+    }
+
+    if args.common.output {
+        println!("Output:");
+        println!("{:02x?}", compressed);
+    }
+
+    if let Some(filename) = &args.common.export {
+        // the compressed stream is exported as raw bytes
+        std::fs::write(Path::new(filename), &compressed).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported stream
+            let reference = std::fs::read(Path::new(&filename)).unwrap();
+            verify!(compressed, reference);
+        }
+        Some(None) => {
+            // the coding is lossless, the decoder gives back the samples
+            let mut decoded = Samples::zeroes(size, size);
+            decoded.rice_decode(&compressed, &parameters).unwrap();
+            verify!(decoded.get_data(), A.get_data());
+        }
+        None => (),
+    }
+}
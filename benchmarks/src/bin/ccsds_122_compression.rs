@@ -0,0 +1,173 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::{
+    parallel_traits::ParallelBitPlaneEncoder, rayon_traits::RayonBitPlaneEncoder, BaseMatrix,
+    BitPlaneEncoder, BpeParameters, WaveletTransform2dInteger,
+};
+use std::{path::Path, time::Instant};
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation};
+use benchmarks::verify;
+
+// the compressor works on integer images whatever the number type of the benchmarks
+#[cfg(not(feature = "2d"))]
+type Image = obpmark_library::matrix_1d::Matrix1d<i32>;
+#[cfg(feature = "2d")]
+type Image = obpmark_library::matrix_2d::Matrix2d<i32>;
+
+/// Bits of the pixels of the random images
+const PIXEL_BITS: usize = 12;
+
+#[derive(Parser, Debug)]
+#[command(about = "CCSDS 122.0 image compression benchmark")]
+struct Args {
+    /// The input is a size x size image, size has to be a multiple of 8
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Number of 8x8 blocks of each segment
+    #[arg(long, default_value_t = 256)]
+    segment_size: usize,
+
+    /// Maximum number of bytes of each segment
+    #[arg(long)]
+    byte_limit: Option<usize>,
+
+    /// Only codes the DC coefficients
+    #[arg(long, default_value_t = false)]
+    dc_stop: bool,
+
+    /// Last bit plane coded
+    #[arg(long, default_value_t = 0)]
+    bit_plane_stop: u32,
+
+    /// Last stage (1 to 4) coded in the last bit plane
+    #[arg(long, default_value_t = 4)]
+    stage_stop: u32,
+
+    /// Pads the segments up to the byte limit
+    #[arg(long, default_value_t = false)]
+    use_fill: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let size = args.common.size;
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Image::from_file(Path::new(&v[0]), size, size).unwrap()
+        }
+        None => Image::from_random_seed(args.common.seed, size, size, 0, 1 << PIXEL_BITS),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let parameters = BpeParameters {
+        segment_size: args.segment_size,
+        byte_limit: args.byte_limit,
+        dc_stop: args.dc_stop,
+        bit_plane_stop: args.bit_plane_stop,
+        stage_stop: args.stage_stop,
+        use_fill: args.use_fill,
+    };
+
+    // the 3 level wavelet transform is timed separately from the bit plane encoder
+    let mut coefficients = Image::zeroes(size, size);
+    let t = Instant::now();
+    A.wavelet_transform_2d(&mut coefficients, 3).unwrap();
+    let transform_time = t.elapsed();
+
+    if args.common.timing {
+        println!("Wavelet transform: {:.2?}", transform_time);
+    }
+
+    let mut compressed = Vec::new();
+    let elapsed = args.common.run_kernel(&mut compressed, |compressed| {
+        *compressed = match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => coefficients.bit_plane_encode(&parameters).unwrap(),
+            (None, Implementation::Rayon) => {
+                coefficients.rayon_bit_plane_encode(&parameters).unwrap()
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::StdParallel) => coefficients
+                .parallel_bit_plane_encode(&parameters, n)
+                .unwrap(),
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                coefficients
+                    .parallel_bit_plane_encode(&parameters, 8)
+                    .unwrap()
+            }
+        }
+    });
+
+    let pixels = size * size;
+    println!(
+        "Compressed size: {} bytes, {:.3} bits per pixel, ratio {:.2}",
+        compressed.len(),
+        (compressed.len() * 8) as f64 / pixels as f64,
+        (pixels * PIXEL_BITS) as f64 / (compressed.len() * 8) as f64
+    );
+
+    if args.common.timing {
+        println!(
+            "Throughput: {:.2} Mpixel/s",
+            pixels as f64 / elapsed.as_secs_f64() / 1e6
+        );
+    }
+
+    if args.common.output {
+        println!("Output:");
+        println!("{:02x?}", compressed);
+    }
+
+    if let Some(filename) = &args.common.export {
+        // the compressed stream is exported as raw bytes
+        std::fs::write(Path::new(filename), &compressed).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported stream
+            let reference = std::fs::read(Path::new(&filename)).unwrap();
+            verify!(compressed, reference);
+        }
+        Some(None) => {
+            // verify that the decoder gives back the coefficients
+            let mut decoded = Image::zeroes(size, size);
+            decoded.bit_plane_decode(&compressed).unwrap();
+            let lossless = parameters.byte_limit.is_none()
+                && !parameters.dc_stop
+                && parameters.bit_plane_stop == 0
+                && parameters.stage_stop == 4;
+            if lossless {
+                verify!(decoded.get_data(), coefficients.get_data());
+            } else {
+                let max_error = decoded
+                    .get_data()
+                    .iter()
+                    .flatten()
+                    .zip(coefficients.get_data().iter().flatten())
+                    .map(|(decoded, coefficient)| (decoded - coefficient).abs())
+                    .max()
+                    .unwrap();
+                println!("Lossy coding, maximum coefficient error: {}", max_error);
+            }
+        }
+        None => (),
+    }
+}
@@ -0,0 +1,162 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::cube::{Cube, Interleaving};
+use obpmark_library::matrix_1d::Matrix1d;
+use obpmark_library::{
+    parallel_traits::ParallelPredictiveEncoder, rayon_traits::RayonPredictiveEncoder, BaseMatrix,
+    PredictiveEncoder, PredictorParameters,
+};
+use std::path::Path;
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation};
+use benchmarks::verify;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum InputInterleaving {
+    Bsq,
+    Bil,
+    Bip,
+}
+
+impl From<InputInterleaving> for Interleaving {
+    fn from(interleaving: InputInterleaving) -> Self {
+        match interleaving {
+            InputInterleaving::Bsq => Interleaving::Bsq,
+            InputInterleaving::Bil => Interleaving::Bil,
+            InputInterleaving::Bip => Interleaving::Bip,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "CCSDS 123.0 hyperspectral lossless compression benchmark")]
+struct Args {
+    /// The input is a cube of `bands` size x size images
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Number of bands of the cube
+    #[arg(long, default_value_t = 32)]
+    bands: usize,
+
+    /// Order of the samples of the input file
+    #[arg(value_enum, long, default_value_t = InputInterleaving::Bil)]
+    interleaving: InputInterleaving,
+
+    /// Bits of the samples, from 2 to 16
+    #[arg(long, default_value_t = 16)]
+    dynamic_range: u32,
+
+    /// Number of previous bands used for the prediction, at most 15
+    #[arg(long, default_value_t = 3)]
+    prediction_bands: usize,
+}
+
+/// Bands that are scaled copies of the same smooth image, with noise
+fn synthetic_cube(seed: u64, bands: usize, size: usize, dynamic_range: u32) -> Cube<i32> {
+    let max = (1 << dynamic_range) - 1;
+    let noise = Cube::from_random_seed(seed, bands, size, size, 0, 1 << (dynamic_range / 4));
+    let data = noise
+        .get_data()
+        .iter()
+        .enumerate()
+        .map(|(idx, noise)| {
+            let (z, y, x) = (idx / (size * size), idx / size % size, idx % size);
+            let image = ((x * 3 + y * 2) % (max as usize / 8)) as i32;
+            (image * (z as i32 + bands as i32) / bands as i32 + noise).min(max)
+        })
+        .collect();
+    Cube::new(data, bands, size, size)
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let (bands, size) = (args.bands, args.common.size);
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            let samples = Matrix1d::<i32>::from_file(Path::new(&v[0]), bands * size, size).unwrap();
+            let samples: Vec<i32> = samples.get_data().into_iter().flatten().collect();
+            Cube::from_interleaved(&samples, args.interleaving.into(), bands, size, size)
+        }
+        None => synthetic_cube(args.common.seed, bands, size, args.dynamic_range),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{:?}", A.get_data());
+    }
+
+    let parameters = PredictorParameters {
+        dynamic_range: args.dynamic_range,
+        prediction_bands: args.prediction_bands,
+        ..Default::default()
+    };
+
+    let mut compressed = Vec::new();
+    let elapsed = args.common.run_kernel(&mut compressed, |compressed| {
+        *compressed = match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.predictive_encode(&parameters).unwrap(),
+            (None, Implementation::Rayon) => A.rayon_predictive_encode(&parameters).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::StdParallel) => {
+                A.parallel_predictive_encode(&parameters, n).unwrap()
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_predictive_encode(&parameters, 8).unwrap()
+            }
+        }
+    });
+
+    let samples = bands * size * size;
+    let input_bits = samples * args.dynamic_range as usize;
+    println!(
+        "Compressed size: {} bytes, {:.3} bits per sample, ratio {:.2}",
+        compressed.len(),
+        (compressed.len() * 8) as f64 / samples as f64,
+        input_bits as f64 / (compressed.len() * 8) as f64
+    );
+
+    if args.common.timing {
+        println!(
+            "Throughput: {:.2} Msamples/s",
+            samples as f64 / elapsed.as_secs_f64() / 1e6
+        );
+    }
+
+    if args.common.output {
+        println!("Output:");
+        println!("{:02x?}", compressed);
+    }
+
+    if let Some(filename) = &args.common.export {
+        // the compressed stream is exported as raw bytes
+        std::fs::write(Path::new(filename), &compressed).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported stream
+            let reference = std::fs::read(Path::new(&filename)).unwrap();
+            verify!(compressed, reference);
+        }
+        Some(None) => {
+            // the coding is lossless, the decoder gives back the cube
+            let mut decoded = Cube::zeroes(bands, size, size);
+            decoded.predictive_decode(&compressed, &parameters).unwrap();
+            verify!(decoded, A);
+        }
+        None => (),
+    }
+}
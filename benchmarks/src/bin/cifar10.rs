@@ -1,17 +1,24 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use obpmark_library::parallel_traits::{
-    ParallelConvolution, ParallelLRN, ParallelMatMul, ParallelMaxPooling, ParallelRelu,
-    ParallelSoftmax,
+    ParallelConvolution, ParallelDense, ParallelLRN, ParallelMatMul, ParallelMaxPooling,
+    ParallelRelu, ParallelSoftmax, ParallelStableSoftmax,
 };
 use obpmark_library::rayon_traits::{
-    RayonConvolution, RayonLRN, RayonMatMul, RayonMaxPooling, RayonRelu, RayonSoftmax,
+    RayonConvolution, RayonDense, RayonLRN, RayonMatMul, RayonMaxPooling, RayonRelu, RayonSoftmax,
+    RayonStableSoftmax,
 };
+use reference_algorithms::{lrn, matrix_convolution, max_pooling, relu};
 use std::path::Path;
-use std::time::Instant;
 
-use obpmark_library::{BaseMatrix, Convolution, MatMul, MaxPooling, Padding, Relu, Softmax, LRN};
+use obpmark_library::fault_injection::{classify, FaultInjector, FaultReport};
+use obpmark_library::{
+    ActivationFunction, BaseMatrix, Convolution, Dense, MatMul, MaxPooling, Padding, Relu, Softmax,
+    SoftmaxAxis, StableSoftmax, LRN,
+};
 
-use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::benchmark_utils::{
+    verify_toll, CommonArgs, FaultArgs, Implementation, Matrix, Number,
+};
 use benchmarks::number;
 
 const CIFAR_10_INPUT: usize = 32;
@@ -36,10 +43,56 @@ struct Args {
     /// Number of images to process (default: 1)
     #[arg(long, default_value_t = 1)]
     pub images: usize,
+
+    #[clap(flatten)]
+    faults: FaultArgs,
+
+    /// Layer whose output is hit by the injected faults
+    #[arg(value_enum, long, default_value_t = Layer::Conv1)]
+    inject_layer: Layer,
+
+    /// Use dense layers with a bias and a fused ReLU instead of the matrix multiplications
+    /// followed by a ReLU, the biases are read from the 6th and 7th input files
+    #[arg(long)]
+    dense_bias: bool,
+}
+
+/// The input and the outputs of the layers, where faults can be injected
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Layer {
+    Input,
+    Conv1,
+    Relu1,
+    Pool1,
+    Lrn1,
+    Conv2,
+    Relu2,
+    Lrn2,
+    Pool2,
+    /// Output of the first dense layer, after the fused ReLU with --dense-bias
+    Dense1,
+    /// Not available with --dense-bias, the ReLU is fused into the dense layer
+    Relu3,
+    /// Output of the second dense layer, after the fused ReLU with --dense-bias
+    Dense2,
+    /// Not available with --dense-bias, the ReLU is fused into the dense layer
+    Relu4,
+    Output,
 }
 
 fn main() {
     let args = Args::parse();
+    if args.dense_bias
+        && args.faults.injections > 0
+        && matches!(args.inject_layer, Layer::Relu3 | Layer::Relu4)
+    {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "the ReLU of the dense layers is fused with --dense-bias, inject at dense1 or dense2",
+            )
+            .exit();
+    }
 
     let mut input = Vec::new();
 
@@ -67,10 +120,14 @@ fn main() {
     let weights_2_rows = DENSE_2;
     let weights_2_cols = DENSE_1;
 
-    match (args.common.input, args.images) {
+    // with --dense-bias, a bias per output of the dense layers
+    let biases: Option<(Vec<Number>, Vec<Number>)>;
+
+    match (&args.common.input, args.images) {
         (Some(v), 1) => {
-            if v.len() != 2 {
-                panic!("Expected 2 input files, got {}", v.len());
+            let n_files = if args.dense_bias { 7 } else { 5 };
+            if v.len() != n_files {
+                panic!("Expected {} input files, got {}", n_files, v.len());
             }
             input
                 .push(Matrix::from_file(Path::new(&v[0]), CIFAR_10_INPUT, CIFAR_10_INPUT).unwrap());
@@ -80,6 +137,18 @@ fn main() {
                 Matrix::from_file(Path::new(&v[3]), weights_1_rows, weights_1_cols).unwrap();
             weights_2 =
                 Matrix::from_file(Path::new(&v[4]), weights_2_rows, weights_2_cols).unwrap();
+            biases = args.dense_bias.then(|| {
+                (
+                    Matrix::from_file(Path::new(&v[5]), 1, DENSE_1)
+                        .unwrap()
+                        .get_data()
+                        .remove(0),
+                    Matrix::from_file(Path::new(&v[6]), 1, DENSE_2)
+                        .unwrap()
+                        .get_data()
+                        .remove(0),
+                )
+            });
         }
         (Some(_), _) => panic!("Input files not supported for multiple images"),
         (None, n) => {
@@ -120,6 +189,29 @@ fn main() {
                 number!("-10"),
                 number!("10"),
             );
+            // the seeds after the ones of the images
+            biases = args.dense_bias.then(|| {
+                (
+                    Matrix::from_random_seed(
+                        args.common.seed + 5 + n as u64,
+                        1,
+                        DENSE_1,
+                        number!("-10"),
+                        number!("10"),
+                    )
+                    .get_data()
+                    .remove(0),
+                    Matrix::from_random_seed(
+                        args.common.seed + 6 + n as u64,
+                        1,
+                        DENSE_2,
+                        number!("-10"),
+                        number!("10"),
+                    )
+                    .get_data()
+                    .remove(0),
+                )
+            });
         }
     }
 
@@ -136,62 +228,76 @@ fn main() {
         println!("{}", weights_1);
         println!("weights_2:");
         println!("{}", weights_2);
+        if let Some((bias_1, bias_2)) = &biases {
+            println!("bias_1:");
+            println!("{:?}", bias_1);
+            println!("bias_2:");
+            println!("{:?}", bias_2);
+        }
     }
 
-    // Initialization of intermediate matrices
-    let mut conv_1_out = Matrix::zeroes(CIFAR_10_INPUT, CIFAR_10_INPUT);
-    let mut relu_1_out = Matrix::zeroes(CIFAR_10_INPUT, CIFAR_10_INPUT);
-    let mut pooling_1_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
-    let mut lrn_1_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
-    let mut conv_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
-    let mut relu_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
-    let mut lrn_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
-    let mut pooling_2_out = Matrix::zeroes(
-        CIFAR_10_INPUT / STRIDE_1 / STRIDE_2,
-        CIFAR_10_INPUT / STRIDE_1 / STRIDE_2,
-    );
-    let mut dense_layer_1_out = Matrix::zeroes(DENSE_1, 1);
-    let mut relu_3_out = Matrix::zeroes(DENSE_1, 1);
-    let mut dense_layer_2_out = Matrix::zeroes(DENSE_2, 1);
-    let mut relu_4_out = Matrix::zeroes(DENSE_2, 1);
-
     // Initialization of output matrix
     let mut output = Matrix::zeroes(CIFAR_10_OUTPUT, 1);
 
-    let t0 = Instant::now();
+    let implementation = &args.common.implementation;
+    let n_threads = args.common.nthreads.unwrap_or(4);
+    let bias = biases
+        .as_ref()
+        .map(|(bias_1, bias_2)| (bias_1.as_slice(), bias_2.as_slice()));
+    // the intermediate matrices are per run, so that the TMR executions are independent
+    let run = |images: &Vec<Matrix>,
+               output: &mut Matrix,
+               inject: &mut dyn FnMut(Layer, &mut Matrix)| {
+        // Initialization of intermediate matrices
+        let mut conv_1_out = Matrix::zeroes(CIFAR_10_INPUT, CIFAR_10_INPUT);
+        let mut relu_1_out = Matrix::zeroes(CIFAR_10_INPUT, CIFAR_10_INPUT);
+        let mut pooling_1_out =
+            Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
+        let mut lrn_1_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
+        let mut conv_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
+        let mut relu_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
+        let mut lrn_2_out = Matrix::zeroes(CIFAR_10_INPUT / STRIDE_1, CIFAR_10_INPUT / STRIDE_1);
+        let mut pooling_2_out = Matrix::zeroes(
+            CIFAR_10_INPUT / STRIDE_1 / STRIDE_2,
+            CIFAR_10_INPUT / STRIDE_1 / STRIDE_2,
+        );
+        let mut dense_layer_1_out = Matrix::zeroes(DENSE_1, 1);
+        let mut relu_3_out = Matrix::zeroes(DENSE_1, 1);
+        let mut dense_layer_2_out = Matrix::zeroes(DENSE_2, 1);
+        let mut relu_4_out = Matrix::zeroes(DENSE_2, 1);
+
+        cifar_10_multiple(
+            images,
+            images.len(),
+            &kernel_1,
+            &kernel_2,
+            &weights_1,
+            &weights_2,
+            bias,
+            output,
+            &mut conv_1_out,
+            &mut relu_1_out,
+            &mut pooling_1_out,
+            &mut lrn_1_out,
+            &mut conv_2_out,
+            &mut relu_2_out,
+            &mut lrn_2_out,
+            &mut pooling_2_out,
+            &mut dense_layer_1_out,
+            &mut relu_3_out,
+            &mut dense_layer_2_out,
+            &mut relu_4_out,
+            STRIDE_1,
+            STRIDE_2,
+            implementation.clone(),
+            n_threads,
+            inject,
+        )
+    };
 
     // Run the benchmark
-    cifar_10_multiple(
-        &input,
-        args.images,
-        &kernel_1,
-        &kernel_2,
-        &weights_1,
-        &weights_2,
-        &mut output,
-        &mut conv_1_out,
-        &mut relu_1_out,
-        &mut pooling_1_out,
-        &mut lrn_1_out,
-        &mut conv_2_out,
-        &mut relu_2_out,
-        &mut lrn_2_out,
-        &mut pooling_2_out,
-        &mut dense_layer_1_out,
-        &mut relu_3_out,
-        &mut dense_layer_2_out,
-        &mut relu_4_out,
-        STRIDE_1,
-        STRIDE_2,
-        args.common.implementation,
-        args.common.nthreads.unwrap_or(4),
-    );
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    args.common
+        .run_kernel(&mut output, |output| run(&input, output, &mut |_, _| ()));
 
     if args.common.output {
         println!("Output:");
@@ -206,7 +312,157 @@ fn main() {
         None => (),
     }
 
-    // TODO: add verification code
+    // before the fault injection, whose runs overwrite the output
+    match &args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let output_ref = Matrix::from_file(Path::new(filename), CIFAR_10_OUTPUT, 1).unwrap();
+            verify_toll(&output.get_data(), &output_ref.get_data(), 1e-5);
+        }
+        Some(None) => {
+            // verify the output of the last image against cpu implementation
+            let output_ref = get_ref_result(
+                input.last().unwrap(),
+                &kernel_1,
+                &kernel_2,
+                &weights_1,
+                &weights_2,
+                bias,
+            );
+            verify_toll(&output.get_data(), &output_ref, 1e-5);
+        }
+        None => (),
+    }
+
+    if args.faults.injections > 0 {
+        // every run processes the first image, the fault free output is the golden one
+        let fault = args.faults.fault();
+        let mut injector = FaultInjector::new(args.faults.fault_seed);
+        let mut report = FaultReport::default();
+        let mut golden = Matrix::zeroes(CIFAR_10_OUTPUT, 1);
+        run(
+            &vec![Matrix::new(
+                input[0].get_data(),
+                CIFAR_10_INPUT,
+                CIFAR_10_INPUT,
+            )],
+            &mut golden,
+            &mut |_, _| (),
+        );
+        let golden = golden.get_data();
+
+        for _ in 0..args.faults.injections {
+            let mut image = Matrix::new(input[0].get_data(), CIFAR_10_INPUT, CIFAR_10_INPUT);
+            let mut inject = |layer: Layer, matrix: &mut Matrix| {
+                if layer == args.inject_layer {
+                    injector
+                        .inject(matrix, fault, args.faults.faulty_elements)
+                        .unwrap();
+                }
+            };
+            inject(Layer::Input, &mut image);
+            run(&vec![image], &mut output, &mut inject);
+            report.record(classify(&output.get_data(), &golden, false));
+        }
+        println!("Fault injection at {:?}, {:?}:", args.inject_layer, fault);
+        println!("{}", report);
+    }
+}
+
+/// The convolutions, ReLUs, poolings and LRNs run the C reference, the dense layers and the
+/// softmax are naive loops as the C matrix multiplication and softmax only take square matrices
+fn get_ref_result(
+    image: &Matrix,
+    kernel_1: &Matrix,
+    kernel_2: &Matrix,
+    weights_1: &Matrix,
+    weights_2: &Matrix,
+    bias: Option<(&[Number], &[Number])>,
+) -> Vec<Vec<Number>> {
+    let size_1 = CIFAR_10_INPUT;
+    let size_2 = CIFAR_10_INPUT / STRIDE_1;
+    let size_3 = CIFAR_10_INPUT / STRIDE_1 / STRIDE_2;
+
+    let image = image.get_data().concat();
+    let kernel_1 = kernel_1.get_data().concat();
+    let kernel_2 = kernel_2.get_data().concat();
+    let mut conv_1_out = vec![number!("0"); size_1 * size_1];
+    let mut relu_1_out = vec![number!("0"); size_1 * size_1];
+    let mut pool_1_out = vec![number!("0"); size_2 * size_2];
+    let mut lrn_1_out = vec![number!("0"); size_2 * size_2];
+    let mut conv_2_out = vec![number!("0"); size_2 * size_2];
+    let mut relu_2_out = vec![number!("0"); size_2 * size_2];
+    let mut lrn_2_out = vec![number!("0"); size_2 * size_2];
+    let mut pool_2_out = vec![number!("0"); size_3 * size_3];
+
+    unsafe {
+        matrix_convolution(
+            image.as_ptr(),
+            kernel_1.as_ptr(),
+            conv_1_out.as_mut_ptr(),
+            size_1,
+            KERNEL_CON_1,
+        );
+        relu(conv_1_out.as_ptr(), relu_1_out.as_mut_ptr(), size_1);
+        max_pooling(
+            relu_1_out.as_ptr(),
+            pool_1_out.as_mut_ptr(),
+            size_1,
+            STRIDE_1,
+            size_2,
+        );
+        lrn(pool_1_out.as_ptr(), lrn_1_out.as_mut_ptr(), size_2);
+        matrix_convolution(
+            lrn_1_out.as_ptr(),
+            kernel_2.as_ptr(),
+            conv_2_out.as_mut_ptr(),
+            size_2,
+            KERNEL_CON_2,
+        );
+        relu(conv_2_out.as_ptr(), relu_2_out.as_mut_ptr(), size_2);
+        lrn(relu_2_out.as_ptr(), lrn_2_out.as_mut_ptr(), size_2);
+        max_pooling(
+            lrn_2_out.as_ptr(),
+            pool_2_out.as_mut_ptr(),
+            size_2,
+            STRIDE_2,
+            size_3,
+        );
+    }
+
+    // dense layer followed by a ReLU, the bias is only there with --dense-bias
+    let dense = |weights: &Matrix, input: &[Number], bias: Option<&[Number]>| -> Vec<Number> {
+        weights
+            .get_data()
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut sum = bias.map_or(number!("0"), |bias| bias[i]);
+                for (weight, el) in row.iter().zip(input) {
+                    sum += *weight * *el;
+                }
+                if sum > number!("0") {
+                    sum
+                } else {
+                    number!("0")
+                }
+            })
+            .collect()
+    };
+    let relu_3_out = dense(weights_1, &pool_2_out, bias.map(|(bias_1, _)| bias_1));
+    let relu_4_out = dense(weights_2, &relu_3_out, bias.map(|(_, bias_2)| bias_2));
+
+    // the maximum is only subtracted with --dense-bias, as in the benchmark
+    let max = match bias {
+        None => number!("0"),
+        Some(_) => relu_4_out
+            .iter()
+            .fold(number!("0"), |max, el| if *el > max { *el } else { max }),
+    };
+    let exp: Vec<Number> = relu_4_out.iter().map(|el| (*el - max).exp()).collect();
+    let sum: Number = exp.iter().sum();
+
+    exp.iter().map(|el| vec![*el / sum]).collect()
 }
 
 fn cifar_10_multiple(
@@ -216,6 +472,7 @@ fn cifar_10_multiple(
     kernel_2: &Matrix,
     weights_1: &Matrix,
     weights_2: &Matrix,
+    bias: Option<(&[Number], &[Number])>,
     output: &mut Matrix,
     conv_1_out: &mut Matrix,
     relu_1_out: &mut Matrix,
@@ -233,6 +490,7 @@ fn cifar_10_multiple(
     stride_2_size: usize,
     implementation: Implementation,
     n_threads: usize,
+    inject: &mut dyn FnMut(Layer, &mut Matrix),
 ) {
     for i in 0..n_images {
         match implementation {
@@ -243,6 +501,7 @@ fn cifar_10_multiple(
                     kernel_2,
                     weights_1,
                     weights_2,
+                    bias,
                     output,
                     conv_1_out,
                     relu_1_out,
@@ -258,6 +517,7 @@ fn cifar_10_multiple(
                     relu_4_out,
                     stride_1_size,
                     stride_2_size,
+                    inject,
                 );
             }
             Implementation::Rayon => {
@@ -267,6 +527,7 @@ fn cifar_10_multiple(
                     kernel_2,
                     weights_1,
                     weights_2,
+                    bias,
                     output,
                     conv_1_out,
                     relu_1_out,
@@ -282,6 +543,7 @@ fn cifar_10_multiple(
                     relu_4_out,
                     stride_1_size,
                     stride_2_size,
+                    inject,
                 );
             }
             Implementation::StdParallel => {
@@ -291,6 +553,7 @@ fn cifar_10_multiple(
                     kernel_2,
                     weights_1,
                     weights_2,
+                    bias,
                     output,
                     conv_1_out,
                     relu_1_out,
@@ -307,31 +570,10 @@ fn cifar_10_multiple(
                     stride_1_size,
                     stride_2_size,
                     n_threads,
+                    inject,
                 );
             }
         }
-        cifar_10(
-            &input[i],
-            kernel_1,
-            kernel_2,
-            weights_1,
-            weights_2,
-            output,
-            conv_1_out,
-            relu_1_out,
-            pool_1_out,
-            lrn_1_out,
-            conv_2_out,
-            relu_2_out,
-            lrn_2_out,
-            pool_2_out,
-            dense_layer_1_out,
-            relu_3_out,
-            dense_layer_2_out,
-            relu_4_out,
-            stride_1_size,
-            stride_2_size,
-        );
     }
 }
 
@@ -341,6 +583,7 @@ fn cifar_10(
     kernel_2: &Matrix,
     weights_1: &Matrix,
     weights_2: &Matrix,
+    bias: Option<(&[Number], &[Number])>,
     output: &mut Matrix,
     conv_1_out: &mut Matrix,
     relu_1_out: &mut Matrix,
@@ -356,38 +599,47 @@ fn cifar_10(
     relu_4_out: &mut Matrix,
     stride_1_size: usize,
     stride_2_size: usize,
+    inject: &mut dyn FnMut(Layer, &mut Matrix),
 ) {
     // 1-1 Convolution
     input
         .convolute(&kernel_1, Padding::Zeroes, conv_1_out)
         .unwrap();
+    inject(Layer::Conv1, conv_1_out);
 
     // 1-2 Activation (ReLU)
     conv_1_out.relu(relu_1_out).unwrap();
+    inject(Layer::Relu1, relu_1_out);
 
     // 1-3 Max pooling
     relu_1_out
         .max_pooling(pool_1_out, stride_1_size, stride_1_size)
         .unwrap();
+    inject(Layer::Pool1, pool_1_out);
 
     // 1-4 Normalization (LRN)
     pool_1_out.lrn(lrn_1_out, ALPHA, BETA, K).unwrap();
+    inject(Layer::Lrn1, lrn_1_out);
 
     // 2-1 Convolution
     lrn_1_out
         .convolute(kernel_2, Padding::Zeroes, conv_2_out)
         .unwrap();
+    inject(Layer::Conv2, conv_2_out);
 
     // 2-2 Activation (ReLU)
     conv_2_out.relu(relu_2_out).unwrap();
+    inject(Layer::Relu2, relu_2_out);
 
     // 2-3 Normalization (LRN)
     relu_2_out.lrn(lrn_2_out, ALPHA, BETA, K).unwrap();
+    inject(Layer::Lrn2, lrn_2_out);
 
     // 2-4 Max pooling
     lrn_2_out
         .max_pooling(pool_2_out, stride_2_size, stride_2_size)
         .unwrap();
+    inject(Layer::Pool2, pool_2_out);
 
     // Reshape
     pool_2_out
@@ -397,20 +649,59 @@ fn cifar_10(
         )
         .unwrap();
 
-    // Dense layer 1
-    weights_1.multiply(pool_2_out, dense_layer_1_out).unwrap();
+    match bias {
+        None => {
+            // Dense layer 1
+            weights_1.multiply(pool_2_out, dense_layer_1_out).unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
 
-    // Activation (ReLU)
-    dense_layer_1_out.relu(relu_3_out).unwrap();
+            // Activation (ReLU)
+            dense_layer_1_out.relu(relu_3_out).unwrap();
+            inject(Layer::Relu3, relu_3_out);
 
-    // Dense layer 2
-    weights_2.multiply(relu_3_out, dense_layer_2_out).unwrap();
+            // Dense layer 2
+            weights_2.multiply(relu_3_out, dense_layer_2_out).unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
 
-    // Activation (ReLU)
-    dense_layer_2_out.relu(relu_4_out).unwrap();
+            // Activation (ReLU)
+            dense_layer_2_out.relu(relu_4_out).unwrap();
+            inject(Layer::Relu4, relu_4_out);
 
-    // Softmax
-    relu_4_out.softmax(output).unwrap();
+            // Softmax
+            relu_4_out.softmax(output).unwrap();
+        }
+        Some((bias_1, bias_2)) => {
+            // Dense layer 1 with bias and fused activation (ReLU)
+            weights_1
+                .dense(
+                    pool_2_out,
+                    bias_1,
+                    dense_layer_1_out,
+                    Some(ActivationFunction::Relu),
+                )
+                .unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
+
+            // Dense layer 2 with bias and fused activation (ReLU)
+            weights_2
+                .dense(
+                    dense_layer_1_out,
+                    bias_2,
+                    dense_layer_2_out,
+                    Some(ActivationFunction::Relu),
+                )
+                .unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
+
+            // Softmax of each sample, the maximum is subtracted as the biased dense outputs
+            // overflow the exponential
+            dense_layer_2_out
+                .stable_softmax(output, SoftmaxAxis::Columns)
+                .unwrap();
+        }
+    }
+
+    inject(Layer::Output, output);
 
     // Reshape for next iteration
     // TODO: this is very unoptimal for 2d matrices, it might be better to
@@ -429,6 +720,7 @@ fn cifar_10_rayon(
     kernel_2: &Matrix,
     weights_1: &Matrix,
     weights_2: &Matrix,
+    bias: Option<(&[Number], &[Number])>,
     output: &mut Matrix,
     conv_1_out: &mut Matrix,
     relu_1_out: &mut Matrix,
@@ -444,38 +736,47 @@ fn cifar_10_rayon(
     relu_4_out: &mut Matrix,
     stride_1_size: usize,
     stride_2_size: usize,
+    inject: &mut dyn FnMut(Layer, &mut Matrix),
 ) {
     // 1-1 Convolution
     input
         .rayon_convolute(&kernel_1, Padding::Zeroes, conv_1_out)
         .unwrap();
+    inject(Layer::Conv1, conv_1_out);
 
     // 1-2 Activation (ReLU)
     conv_1_out.rayon_relu(relu_1_out).unwrap();
+    inject(Layer::Relu1, relu_1_out);
 
     // 1-3 Max pooling
     relu_1_out
         .rayon_max_pooling(pool_1_out, stride_1_size, stride_1_size)
         .unwrap();
+    inject(Layer::Pool1, pool_1_out);
 
     // 1-4 Normalization (LRN)
     pool_1_out.rayon_lrn(lrn_1_out, ALPHA, BETA, K).unwrap();
+    inject(Layer::Lrn1, lrn_1_out);
 
     // 2-1 Convolution
     lrn_1_out
         .rayon_convolute(kernel_2, Padding::Zeroes, conv_2_out)
         .unwrap();
+    inject(Layer::Conv2, conv_2_out);
 
     // 2-2 Activation (ReLU)
     conv_2_out.rayon_relu(relu_2_out).unwrap();
+    inject(Layer::Relu2, relu_2_out);
 
     // 2-3 Normalization (LRN)
     relu_2_out.rayon_lrn(lrn_2_out, ALPHA, BETA, K).unwrap();
+    inject(Layer::Lrn2, lrn_2_out);
 
     // 2-4 Max pooling
     lrn_2_out
         .rayon_max_pooling(pool_2_out, stride_2_size, stride_2_size)
         .unwrap();
+    inject(Layer::Pool2, pool_2_out);
 
     // Reshape
     pool_2_out
@@ -485,24 +786,63 @@ fn cifar_10_rayon(
         )
         .unwrap();
 
-    // Dense layer 1
-    weights_1
-        .rayon_multiply(pool_2_out, dense_layer_1_out)
-        .unwrap();
-
-    // Activation (ReLU)
-    dense_layer_1_out.rayon_relu(relu_3_out).unwrap();
-
-    // Dense layer 2
-    weights_2
-        .rayon_multiply(relu_3_out, dense_layer_2_out)
-        .unwrap();
-
-    // Activation (ReLU)
-    dense_layer_2_out.rayon_relu(relu_4_out).unwrap();
+    match bias {
+        None => {
+            // Dense layer 1
+            weights_1
+                .rayon_multiply(pool_2_out, dense_layer_1_out)
+                .unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
+
+            // Activation (ReLU)
+            dense_layer_1_out.rayon_relu(relu_3_out).unwrap();
+            inject(Layer::Relu3, relu_3_out);
+
+            // Dense layer 2
+            weights_2
+                .rayon_multiply(relu_3_out, dense_layer_2_out)
+                .unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
+
+            // Activation (ReLU)
+            dense_layer_2_out.rayon_relu(relu_4_out).unwrap();
+            inject(Layer::Relu4, relu_4_out);
+
+            // Softmax
+            relu_4_out.rayon_softmax(output).unwrap();
+        }
+        Some((bias_1, bias_2)) => {
+            // Dense layer 1 with bias and fused activation (ReLU)
+            weights_1
+                .rayon_dense(
+                    pool_2_out,
+                    bias_1,
+                    dense_layer_1_out,
+                    Some(ActivationFunction::Relu),
+                )
+                .unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
+
+            // Dense layer 2 with bias and fused activation (ReLU)
+            weights_2
+                .rayon_dense(
+                    dense_layer_1_out,
+                    bias_2,
+                    dense_layer_2_out,
+                    Some(ActivationFunction::Relu),
+                )
+                .unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
+
+            // Softmax of each sample, the maximum is subtracted as the biased dense outputs
+            // overflow the exponential
+            dense_layer_2_out
+                .rayon_stable_softmax(output, SoftmaxAxis::Columns)
+                .unwrap();
+        }
+    }
 
-    // Softmax
-    relu_4_out.rayon_softmax(output).unwrap();
+    inject(Layer::Output, output);
 
     // Reshape for next iteration
     // TODO: this is very unoptimal for 2d matrices, it might be better to
@@ -521,6 +861,7 @@ fn cifar_10_parallel(
     kernel_2: &Matrix,
     weights_1: &Matrix,
     weights_2: &Matrix,
+    bias: Option<(&[Number], &[Number])>,
     output: &mut Matrix,
     conv_1_out: &mut Matrix,
     relu_1_out: &mut Matrix,
@@ -537,42 +878,51 @@ fn cifar_10_parallel(
     stride_1_size: usize,
     stride_2_size: usize,
     n_threads: usize,
+    inject: &mut dyn FnMut(Layer, &mut Matrix),
 ) {
     // 1-1 Convolution
     input
         .parallel_convolute(&kernel_1, Padding::Zeroes, conv_1_out, n_threads)
         .unwrap();
+    inject(Layer::Conv1, conv_1_out);
 
     // 1-2 Activation (ReLU)
     conv_1_out.parallel_relu(relu_1_out, n_threads).unwrap();
+    inject(Layer::Relu1, relu_1_out);
 
     // 1-3 Max pooling
     relu_1_out
         .parallel_max_pooling(pool_1_out, stride_1_size, stride_1_size, n_threads)
         .unwrap();
+    inject(Layer::Pool1, pool_1_out);
 
     // 1-4 Normalization (LRN)
     pool_1_out
         .parallel_lrn(lrn_1_out, ALPHA, BETA, K, n_threads)
         .unwrap();
+    inject(Layer::Lrn1, lrn_1_out);
 
     // 2-1 Convolution
     lrn_1_out
         .parallel_convolute(kernel_2, Padding::Zeroes, conv_2_out, n_threads)
         .unwrap();
+    inject(Layer::Conv2, conv_2_out);
 
     // 2-2 Activation (ReLU)
     conv_2_out.parallel_relu(relu_2_out, n_threads).unwrap();
+    inject(Layer::Relu2, relu_2_out);
 
     // 2-3 Normalization (LRN)
     relu_2_out
         .parallel_lrn(lrn_2_out, ALPHA, BETA, K, n_threads)
         .unwrap();
+    inject(Layer::Lrn2, lrn_2_out);
 
     // 2-4 Max pooling
     lrn_2_out
         .parallel_max_pooling(pool_2_out, stride_2_size, stride_2_size, n_threads)
         .unwrap();
+    inject(Layer::Pool2, pool_2_out);
 
     // Reshape
     pool_2_out
@@ -582,28 +932,69 @@ fn cifar_10_parallel(
         )
         .unwrap();
 
-    // Dense layer 1
-    weights_1
-        .parallel_multiply(pool_2_out, dense_layer_1_out, n_threads)
-        .unwrap();
-
-    // Activation (ReLU)
-    dense_layer_1_out
-        .parallel_relu(relu_3_out, n_threads)
-        .unwrap();
-
-    // Dense layer 2
-    weights_2
-        .parallel_multiply(relu_3_out, dense_layer_2_out, n_threads)
-        .unwrap();
+    match bias {
+        None => {
+            // Dense layer 1
+            weights_1
+                .parallel_multiply(pool_2_out, dense_layer_1_out, n_threads)
+                .unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
+
+            // Activation (ReLU)
+            dense_layer_1_out
+                .parallel_relu(relu_3_out, n_threads)
+                .unwrap();
+            inject(Layer::Relu3, relu_3_out);
+
+            // Dense layer 2
+            weights_2
+                .parallel_multiply(relu_3_out, dense_layer_2_out, n_threads)
+                .unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
+
+            // Activation (ReLU)
+            dense_layer_2_out
+                .parallel_relu(relu_4_out, n_threads)
+                .unwrap();
+            inject(Layer::Relu4, relu_4_out);
+
+            // Softmax
+            relu_4_out.parallel_softmax(output, n_threads).unwrap();
+        }
+        Some((bias_1, bias_2)) => {
+            // Dense layer 1 with bias and fused activation (ReLU)
+            weights_1
+                .parallel_dense(
+                    pool_2_out,
+                    bias_1,
+                    dense_layer_1_out,
+                    Some(ActivationFunction::Relu),
+                    n_threads,
+                )
+                .unwrap();
+            inject(Layer::Dense1, dense_layer_1_out);
 
-    // Activation (ReLU)
-    dense_layer_2_out
-        .parallel_relu(relu_4_out, n_threads)
-        .unwrap();
+            // Dense layer 2 with bias and fused activation (ReLU)
+            weights_2
+                .parallel_dense(
+                    dense_layer_1_out,
+                    bias_2,
+                    dense_layer_2_out,
+                    Some(ActivationFunction::Relu),
+                    n_threads,
+                )
+                .unwrap();
+            inject(Layer::Dense2, dense_layer_2_out);
+
+            // Softmax of each sample, the maximum is subtracted as the biased dense outputs
+            // overflow the exponential
+            dense_layer_2_out
+                .parallel_stable_softmax(output, SoftmaxAxis::Columns, n_threads)
+                .unwrap();
+        }
+    }
 
-    // Softmax
-    relu_4_out.parallel_softmax(output, n_threads).unwrap();
+    inject(Layer::Output, output);
 
     // Reshape for next iteration
     // TODO: this is very unoptimal for 2d matrices, it might be better to
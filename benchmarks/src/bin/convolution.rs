@@ -32,7 +32,7 @@ fn main() {
     let kernel;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 2 {
                 panic!("Expected 2 input files, got {}", v.len());
@@ -68,34 +68,28 @@ fn main() {
 
     B = Matrix::zeroes(args.common.size, args.common.size);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Rayon) => {
-            A.rayon_convolute(&kernel, Padding::Zeroes, &mut B).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Invalid parameter combination: sequential with nthreads != 1")
-        }
-        (_, Implementation::Sequential) => A.convolute(&kernel, Padding::Zeroes, &mut B).unwrap(),
-        (Some(n), Implementation::StdParallel) => A
-            .parallel_convolute(&kernel, Padding::Zeroes, &mut B, n)
-            .unwrap(),
-        (None, Implementation::StdParallel) => {
-            // TODO: change 8 to number of cores
-            A.parallel_convolute(&kernel, Padding::Zeroes, &mut B, 8)
-                .unwrap()
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (None, Implementation::Rayon) => {
+                A.rayon_convolute(&kernel, Padding::Zeroes, B).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Invalid parameter combination: sequential with nthreads != 1")
+            }
+            (_, Implementation::Sequential) => A.convolute(&kernel, Padding::Zeroes, B).unwrap(),
+            (Some(n), Implementation::StdParallel) => A
+                .parallel_convolute(&kernel, Padding::Zeroes, B, n)
+                .unwrap(),
+            (None, Implementation::StdParallel) => {
+                // TODO: change 8 to number of cores
+                A.parallel_convolute(&kernel, Padding::Zeroes, B, 8)
+                    .unwrap()
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Output:");
@@ -1,19 +1,20 @@
 #![allow(non_snake_case)]
 use clap::Parser;
 use core::panic;
+use obpmark_library::number_traits::FixedPoint;
 use obpmark_library::{
-    //parallel_traits::ParallelCorrelation,
-    rayon_traits::RayonCorrelation,
-    BaseMatrix,
-    Correlation,
+    parallel_traits::{ParallelCorrelation, ParallelCrossCorrelation, ParallelTemplateMatching},
+    random_matrix_data,
+    rayon_traits::{RayonCorrelation, RayonCrossCorrelation, RayonTemplateMatching},
+    BaseMatrix, Correlation, CorrelationPeak, CrossCorrelation, TemplateMatching,
 };
 use std::{path::Path, time::Instant};
 
-use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::benchmark_utils::{verify_toll, CommonArgs, Implementation, Matrix, Number};
 
 use reference_algorithms::correlation;
 
-use benchmarks::number;
+use benchmarks::{number, verify};
 
 #[cfg(feature = "float")]
 type Output = f32;
@@ -24,20 +25,61 @@ type Output = f32;
 #[cfg(not(any(feature = "float", feature = "double", feature = "int",)))]
 type Output = f32;
 
+// the integer cross-correlations are exact
+#[cfg(feature = "int")]
+const RELATIVE_TOLERANCE: f64 = 0.0;
+#[cfg(not(feature = "int"))]
+const RELATIVE_TOLERANCE: f64 = 1e-4;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Pearson coefficient of two matrices
+    Pearson,
+    /// Cross-correlation of two matrices over the lags from -max_lag to max_lag
+    Lags,
+    /// Normalized cross-correlation map of a template sliding over the image
+    Template,
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "2D correlation benchmark")]
 struct Args {
+    /// The inputs are size x size matrices, or 1 x size with --one-d
     #[clap(flatten)]
     common: CommonArgs,
+
+    #[arg(value_enum, long, default_value_t = Mode::Pearson)]
+    mode: Mode,
+
+    /// Correlates row vectors (lags and template modes)
+    #[arg(long, default_value_t = false)]
+    one_d: bool,
+
+    /// Maximum lag in both dimensions, size / 8 by default
+    #[arg(long)]
+    max_lag: Option<usize>,
+
+    /// The template is template_size x template_size (1 x template_size with --one-d), size / 8
+    /// by default
+    #[arg(long)]
+    template_size: Option<usize>,
 }
 
 fn main() {
     let args = Args::parse();
 
+    match args.mode {
+        Mode::Pearson => pearson(&args),
+        Mode::Lags => lags(&args),
+        Mode::Template => template(&args),
+    }
+}
+
+fn pearson(args: &Args) {
     let A;
     let B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 2 {
                 panic!("Expected 2 input files, got {}", v.len());
@@ -70,56 +112,44 @@ fn main() {
         println!("{}", B);
     }
 
-    let t0 = Instant::now();
-    let res;
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Sequential) => res = A.correlation(&B).unwrap(),
-        (Some(_), Implementation::Sequential) => {
-            panic!("Cannot specify number of threads for sequential implementation")
-        }
-        (None, Implementation::Rayon) => res = A.rayon_correlate(&B).unwrap(),
-        (Some(_), Implementation::Rayon) => {
-            /*
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(nthreads)
-                .build_global()
-                .unwrap();
-            res = A.rayon_correlate(&B).unwrap();
-            */
-            panic!("Specifying number of threads for rayon is not supported");
-        }
-        (Some(_n_threads), Implementation::StdParallel) => {
-            // res = A.parallel_correlate(&B, n_threads).unwrap()
-            panic!("CHANGE THIS")
-        }
-        (None, Implementation::StdParallel) => {
-            panic!("Must specify number of threads for std parallel");
+    let mut res: Output = 0.0;
+    args.common.run_kernel(&mut res, |res| {
+        *res = match (args.common.nthreads, &args.common.implementation) {
+            (None, Implementation::Sequential) => A.correlation(&B).unwrap(),
+            (Some(_), Implementation::Sequential) => {
+                panic!("Cannot specify number of threads for sequential implementation")
+            }
+            (None, Implementation::Rayon) => A.rayon_correlate(&B).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                /*
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(nthreads)
+                    .build_global()
+                    .unwrap();
+                res = A.rayon_correlate(&B).unwrap();
+                */
+                panic!("Specifying number of threads for rayon is not supported");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_correlate(&B, n.unwrap_or(8)).unwrap()
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Correlation = {}", res);
     }
 
-    match args.common.export {
-        Some(filename) => {
-            // export output
-            // TODO: this is a very hacky way to do this, make it better
-            obpmark_library::matrix_1d::Matrix1d::<Output>::new(vec![vec![res; 1]; 1], 1, 1)
-                .to_file(Path::new(&filename))
-                .unwrap();
-        }
-        None => (),
+    if let Some(filename) = &args.common.export {
+        // export output
+        // TODO: this is a very hacky way to do this, make it better
+        obpmark_library::matrix_1d::Matrix1d::<Output>::new(vec![vec![res; 1]; 1], 1, 1)
+            .to_file(Path::new(filename))
+            .unwrap();
     }
 
-    match args.common.verify {
+    match &args.common.verify {
         Some(Some(_filename)) => {
             /* verify against file TODO: how do we deal with this?
             let C_ref = Matrix::from_file(Path::new(&filename), args.common.size, args.common.size)
@@ -161,3 +191,340 @@ fn get_ref_result(A: Matrix, B: Matrix, size: usize) -> Output {
     println!("C code: {:.2?}", t1 - t0);
     res_ref
 }
+
+/// Rows of the inputs of the lags and template modes
+fn rows(args: &Args) -> usize {
+    if args.one_d {
+        1
+    } else {
+        args.common.size
+    }
+}
+
+fn print_peak<T: std::fmt::Display>(
+    label: &str,
+    peak: &CorrelationPeak<T>,
+    offsets: (usize, usize),
+) {
+    println!(
+        "{}: ({}, {}), value {}",
+        label,
+        peak.row as isize - offsets.0 as isize,
+        peak.col as isize - offsets.1 as isize,
+        peak.value
+    );
+}
+
+fn lags(args: &Args) {
+    let (rows, size) = (rows(args), args.common.size);
+    let max_lag = args.max_lag.unwrap_or(size / 8);
+    let max_lags = (if args.one_d { 0 } else { max_lag }, max_lag);
+
+    let (A, B) = match &args.common.input {
+        Some(v) => {
+            if v.len() != 2 {
+                panic!("Expected 2 input files, got {}", v.len());
+            }
+            (
+                Matrix::from_file(Path::new(&v[0]), rows, size).unwrap(),
+                Matrix::from_file(Path::new(&v[1]), rows, size).unwrap(),
+            )
+        }
+        None => {
+            // B is A shifted by a random lag, the gaps are filled with noise
+            let a = random_matrix_data(args.common.seed, rows, size, number!("-10"), number!("10"));
+            let mut b = random_matrix_data(
+                args.common.seed + 10,
+                rows,
+                size,
+                number!("-10"),
+                number!("10"),
+            );
+            let lag = random_matrix_data(
+                args.common.seed + 20,
+                1,
+                2,
+                -(max_lag as i32),
+                max_lag as i32 + 1,
+            );
+            let (dy, dx) = (if args.one_d { 0 } else { lag[0][0] }, lag[0][1]);
+            for (i, row) in a.iter().enumerate() {
+                for (j, el) in row.iter().enumerate() {
+                    let (y, x) = (i as i32 + dy, j as i32 + dx);
+                    if (0..rows as i32).contains(&y) && (0..size as i32).contains(&x) {
+                        b[y as usize][x as usize] = *el;
+                    }
+                }
+            }
+            (Matrix::new(a, rows, size), Matrix::new(b, rows, size))
+        }
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+        println!("B:");
+        println!("{}", B);
+    }
+
+    // the map and its peak are both voted with --tmr
+    let peak = CorrelationPeak {
+        row: 0,
+        col: 0,
+        value: number!("0"),
+    };
+    let mut result = (Matrix::zeroes(2 * max_lags.0 + 1, 2 * max_lags.1 + 1), peak);
+    args.common.run_kernel(&mut result, |(C, peak)| {
+        *peak = match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.cross_correlation(&B, max_lags, C),
+            (None, Implementation::Rayon) => A.rayon_cross_correlation(&B, max_lags, C),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_cross_correlation(&B, max_lags, C, n.unwrap_or(8))
+            }
+        }
+        .unwrap();
+    });
+    let (C, peak) = result;
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", C);
+    }
+    // the peak is the lag of the best alignment of B to A
+    print_peak("Peak at lag", &peak, max_lags);
+
+    if let Some(filename) = &args.common.export {
+        C.to_file(Path::new(filename)).unwrap();
+    }
+
+    match &args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported map
+            let C_ref =
+                Matrix::from_file(Path::new(filename), 2 * max_lags.0 + 1, 2 * max_lags.1 + 1)
+                    .unwrap();
+            verify!(C.get_data(), C_ref.get_data());
+        }
+        Some(None) => {
+            // sums over the overlaps in double precision
+            let (a, b) = (to_f64(&A.get_data()), to_f64(&B.get_data()));
+            let C_ref: Vec<Vec<f64>> = (0..2 * max_lags.0 + 1)
+                .map(|r| {
+                    (0..2 * max_lags.1 + 1)
+                        .map(|c| {
+                            let (dy, dx) = (
+                                r as isize - max_lags.0 as isize,
+                                c as isize - max_lags.1 as isize,
+                            );
+                            let mut sum = 0.0;
+                            for (i, row) in a.iter().enumerate() {
+                                for (j, el) in row.iter().enumerate() {
+                                    let (y, x) = (i as isize + dy, j as isize + dx);
+                                    if (0..rows as isize).contains(&y)
+                                        && (0..size as isize).contains(&x)
+                                    {
+                                        sum += el * b[y as usize][x as usize];
+                                    }
+                                }
+                            }
+                            sum
+                        })
+                        .collect()
+                })
+                .collect();
+            if reference_peak(&C_ref) != (peak.row, peak.col) {
+                println!("Verification failed: peak at a different lag");
+                return;
+            }
+            let toll = C_ref
+                .iter()
+                .flatten()
+                .fold(0.0f64, |max, el| max.max(el.abs()))
+                * RELATIVE_TOLERANCE;
+            let C_ref: Vec<Vec<Number>> = C_ref
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|el| Number::fixed_from_f64(el, 0))
+                        .collect()
+                })
+                .collect();
+            verify_toll(&C.get_data(), &C_ref, Number::fixed_from_f64(toll, 0));
+        }
+        None => (),
+    }
+}
+
+fn template(args: &Args) {
+    let (rows, size) = (rows(args), args.common.size);
+    let template_size = args.template_size.unwrap_or(size / 8);
+    let template_rows = if args.one_d { 1 } else { template_size };
+
+    let (A, template) = match &args.common.input {
+        Some(v) => {
+            if v.len() != 2 {
+                panic!("Expected 2 input files, got {}", v.len());
+            }
+            (
+                Matrix::from_file(Path::new(&v[0]), rows, size).unwrap(),
+                Matrix::from_file(Path::new(&v[1]), template_rows, template_size).unwrap(),
+            )
+        }
+        None => {
+            // the template is a patch of the image at a random position
+            let a = random_matrix_data(args.common.seed, rows, size, number!("-10"), number!("10"));
+            let position = random_matrix_data(
+                args.common.seed + 20,
+                1,
+                2,
+                0,
+                (size - template_size + 1) as i32,
+            );
+            let (r0, c0) = (
+                (position[0][0] as usize).min(rows - template_rows),
+                position[0][1] as usize,
+            );
+            let patch = a[r0..r0 + template_rows]
+                .iter()
+                .map(|row| row[c0..c0 + template_size].to_vec())
+                .collect();
+            (
+                Matrix::new(a, rows, size),
+                Matrix::new(patch, template_rows, template_size),
+            )
+        }
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+        println!("Template:");
+        println!("{}", template);
+    }
+
+    let (map_rows, map_cols) = (rows - template_rows + 1, size - template_size + 1);
+    // the map and its peak are both voted with --tmr
+    let peak = CorrelationPeak {
+        row: 0,
+        col: 0,
+        value: Default::default(),
+    };
+    let mut result = (
+        <Matrix as TemplateMatching>::Map::zeroes(map_rows, map_cols),
+        peak,
+    );
+    args.common.run_kernel(&mut result, |(map, peak)| {
+        *peak = match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.template_matching(&template, map),
+            (None, Implementation::Rayon) => A.rayon_template_matching(&template, map),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_template_matching(&template, map, n.unwrap_or(8))
+            }
+        }
+        .unwrap();
+    });
+    let (map, peak) = result;
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", map);
+    }
+    print_peak("Best match at", &peak, (0, 0));
+
+    if let Some(filename) = &args.common.export {
+        map.to_file(Path::new(filename)).unwrap();
+    }
+
+    match &args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported map
+            let map_ref = <Matrix as TemplateMatching>::Map::from_file(
+                Path::new(filename),
+                map_rows,
+                map_cols,
+            )
+            .unwrap();
+            verify!(map.get_data(), map_ref.get_data());
+        }
+        Some(None) => {
+            // zero-normalized cross-correlation in double precision
+            let (image, template) = (to_f64(&A.get_data()), to_f64(&template.get_data()));
+            let n = (template_rows * template_size) as f64;
+            let template_mean = template.iter().flatten().sum::<f64>() / n;
+            let map_ref: Vec<Vec<f64>> = (0..map_rows)
+                .map(|r| {
+                    (0..map_cols)
+                        .map(|c| {
+                            let window = || {
+                                image[r..r + template_rows]
+                                    .iter()
+                                    .flat_map(|row| &row[c..c + template_size])
+                            };
+                            let mean = window().sum::<f64>() / n;
+                            let (mut product, mut energy, mut template_energy) = (0.0, 0.0, 0.0);
+                            for (el, t) in window().zip(template.iter().flatten()) {
+                                product += (el - mean) * (t - template_mean);
+                                energy += (el - mean) * (el - mean);
+                                template_energy += (t - template_mean) * (t - template_mean);
+                            }
+                            let norm = (energy * template_energy).sqrt();
+                            if norm > 0.0 {
+                                product / norm
+                            } else {
+                                0.0
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            let error = map
+                .get_data()
+                .iter()
+                .flatten()
+                .zip(map_ref.iter().flatten())
+                .fold(0.0f64, |max, (el, el_ref)| {
+                    max.max((*el as f64 - el_ref).abs())
+                });
+            if reference_peak(&map_ref) == (peak.row, peak.col) && error <= 1e-4 {
+                println!("Verification passed");
+            } else {
+                println!("Verification failed");
+            }
+        }
+        None => (),
+    }
+}
+
+fn to_f64(matrix: &[Vec<Number>]) -> Vec<Vec<f64>> {
+    matrix
+        .iter()
+        .map(|row| row.iter().map(|el| el.fixed_to_f64(0)).collect())
+        .collect()
+}
+
+/// Position of the first maximum of a reference map
+fn reference_peak(map: &[Vec<f64>]) -> (usize, usize) {
+    let mut peak = (0, 0);
+    for (i, row) in map.iter().enumerate() {
+        for (j, el) in row.iter().enumerate() {
+            if *el > map[peak.0][peak.1] {
+                peak = (i, j);
+            }
+        }
+    }
+    peak
+}
@@ -1,13 +1,16 @@
 #![allow(non_snake_case)]
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use core::panic;
+use obpmark_library::fft_plan::FftPlan;
+use obpmark_library::parallel_traits::ParallelFastFourierTransform;
+use obpmark_library::rayon_traits::RayonFastFourierTransform;
 use obpmark_library::BaseMatrix;
 use std::{path::Path, time::Instant};
 
 use obpmark_library::matrix_1d::Matrix1d as RefMatrix;
 use reference_algorithms::fft_function;
 
-use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::benchmark_utils::{verify_toll, CommonArgs, Implementation, Matrix, Number};
 
 use benchmarks::number;
 
@@ -16,96 +19,204 @@ compile_error!(
     "This benchmark is not supported for 2d matrices, since the underlying data is always 1d"
 );
 #[cfg(not(feature = "2d"))]
-use obpmark_library::FastFourierTransform;
+use obpmark_library::{
+    FastFourierTransform, InverseFastFourierTransform, MixedRadixFastFourierTransform,
+    RealFastFourierTransform,
+};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Transform {
+    /// Complex FFT of size / 2 interleaved complex numbers
+    Forward,
+    /// Inverse complex FFT of size / 2 interleaved complex numbers
+    Inverse,
+    /// FFT of size real samples
+    Real,
+    /// Inverse FFT of the spectrum of size real samples
+    InverseReal,
+}
 
 #[derive(Parser, Debug)]
 #[command(about = "FFT benchmark")]
 struct Args {
     #[clap(flatten)]
     common: CommonArgs,
+
+    /// Transform to compute, the parallel implementations only support the forward one
+    #[arg(value_enum, long, default_value_t = Transform::Forward)]
+    transform: Transform,
+
+    /// Use the mixed radix FFT, which supports any even size, instead of the radix-2 one, only
+    /// in the sequential implementation
+    #[arg(long)]
+    mixed_radix: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
-    if args.common.size.count_ones() != 1 {
-        // checks that size is power of 2
-        panic!("Size must be a power of 2");
+    let parallel = !matches!(args.common.implementation, Implementation::Sequential);
+    if parallel && (args.mixed_radix || !matches!(args.transform, Transform::Forward)) {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "the parallel implementations only support the radix-2 forward transform",
+            )
+            .exit();
     }
 
-    let mut A;
+    let size = args.common.size;
 
-    match args.common.input {
+    if args.mixed_radix {
+        if size < 2 || size & 1 == 1 {
+            panic!("Size must be even, the input is made of size / 2 complex numbers");
+        }
+        if let Transform::Real | Transform::InverseReal = args.transform {
+            panic!("The mixed radix FFT only supports complex transforms");
+        }
+    } else if size.count_ones() != 1 {
+        // checks that size is power of 2
+        panic!("Size must be a power of 2, use --mixed-radix for other sizes");
+    }
+
+    let A = match &args.common.input {
         Some(v) => {
             if v.len() != 1 {
                 panic!("Expected 1 input files, got {}", v.len());
             }
-            A = Matrix::from_file(Path::new(&v[0]), 1, args.common.size).unwrap();
+            Matrix::from_file(Path::new(&v[0]), 1, size).unwrap()
         }
-        None => {
-            A = Matrix::from_random_seed(
-                args.common.seed,
-                1,
-                args.common.size,
-                number!("-10"),
-                number!("10"),
-            );
-        }
-    }
-
-    // this is for validation, since in this case the original matrix is not preserved
-    let A_ref = Matrix::new(A.get_data(), 1, args.common.size);
+        None => Matrix::from_random_seed(args.common.seed, 1, size, number!("-10"), number!("10")),
+    };
 
     if args.common.print_input {
         println!("A:");
         println!("{}", A);
     }
 
-    let t0 = Instant::now();
+    // the inverse real transform needs the spectrum of A as input, it is not timed
+    let spectrum = match args.transform {
+        Transform::InverseReal => {
+            let mut S = Matrix::zeroes(1, size + 2);
+            A.rfft(&mut S).unwrap();
+            Some(S)
+        }
+        _ => None,
+    };
 
-    match args.common.implementation {
-        Implementation::Sequential => A.fft(args.common.size >> 1).unwrap(), // the >> 1 is to keep it consistent with the reference implementation
-        _ => unimplemented!("Parallel versions not yet implemented"),
-    }
+    // the complex transforms work in place, so B starts as a copy of A
+    let mut B = match args.transform {
+        Transform::Forward | Transform::Inverse => Matrix::new(A.get_data(), 1, size),
+        Transform::Real => Matrix::zeroes(1, size + 2),
+        Transform::InverseReal => Matrix::zeroes(1, size),
+    };
 
-    let t1 = Instant::now();
+    // the twiddle factors of the mixed radix FFT are computed once, outside of the timing
+    let plan = match args.mixed_radix {
+        true => Some(FftPlan::new(size >> 1).unwrap()),
+        false => None,
+    };
 
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    // with --tmr each execution starts from a copy of B
+    args.common.run_kernel(&mut B, |B| {
+        // the >> 1 is to keep it consistent with the reference implementation
+        match (
+            args.common.nthreads,
+            &args.common.implementation,
+            args.transform,
+            &plan,
+        ) {
+            (Some(n), Implementation::Sequential, _, _) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (Some(_), Implementation::Rayon, _, _) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (_, Implementation::Sequential, Transform::Forward, Some(plan)) => {
+                B.mixed_radix_fft(plan).unwrap()
+            }
+            (_, Implementation::Sequential, Transform::Inverse, Some(plan)) => {
+                B.mixed_radix_ifft(plan).unwrap()
+            }
+            (_, Implementation::Sequential, Transform::Forward, _) => B.fft(size >> 1).unwrap(),
+            (_, Implementation::Sequential, Transform::Inverse, _) => B.ifft(size >> 1).unwrap(),
+            (_, Implementation::Sequential, Transform::Real, _) => A.rfft(B).unwrap(),
+            (_, Implementation::Sequential, Transform::InverseReal, _) => {
+                spectrum.as_ref().unwrap().irfft(B).unwrap()
+            }
+            (None, Implementation::Rayon, Transform::Forward, None) => {
+                B.rayon_fft(size >> 1).unwrap()
+            }
+            (Some(n), Implementation::StdParallel, Transform::Forward, None) => {
+                B.parallel_fft(size >> 1, n).unwrap()
+            }
+            (None, Implementation::StdParallel, Transform::Forward, None) => {
+                // TODO: change n_threads
+                B.parallel_fft(size >> 1, 8).unwrap()
+            }
+            // rejected after the parsing of the arguments
+            _ => unreachable!(),
+        }
+    });
 
     if args.common.output {
         println!("Output:");
-        println!("{}", A);
+        println!("{}", B);
     }
 
     match args.common.export {
         Some(filename) => {
             // export output
-            A.to_file(Path::new(&filename)).unwrap();
+            B.to_file(Path::new(&filename)).unwrap();
         }
         None => (),
     }
 
+    // rounding errors of the transforms (and of the reference) grow with the size
+    let toll = number!("5e-5") * (size as Number).sqrt() * (size as Number).log2();
+
     match args.common.verify {
         Some(Some(filename)) => {
             // verify against file
-            let A_ref = Matrix::from_file(Path::new(&filename), 1, args.common.size).unwrap();
-            if A.get_data() == A_ref.get_data() {
+            let B_ref = Matrix::from_file(Path::new(&filename), 1, B.get_data()[0].len()).unwrap();
+            if B.get_data() == B_ref.get_data() {
                 println!("Verification passed");
             } else {
                 println!("Verification failed");
             }
         }
-        Some(None) => {
-            // verify against reference implementation
-            let A_ref = get_ref_result(A_ref, args.common.size);
-            if A.get_data() == A_ref.get_data() {
-                println!("Verification passed");
-            } else {
-                println!("Verification failed");
+        Some(None) => match args.transform {
+            Transform::Forward | Transform::Inverse if size.count_ones() != 1 => {
+                // the reference only supports powers of 2, use a direct DFT
+                let B_ref = get_dft_ref_result(A, size, args.transform);
+                verify_toll(&B.get_data(), &B_ref.get_data(), toll);
             }
-        }
+            Transform::Forward if args.mixed_radix => {
+                let B_ref = get_ref_result(A, size);
+                verify_toll(&B.get_data(), &B_ref.get_data(), toll);
+            }
+            Transform::Forward => {
+                // verify against reference implementation
+                let B_ref = get_ref_result(A, size);
+                if B.get_data() == B_ref.get_data() {
+                    println!("Verification passed");
+                } else {
+                    println!("Verification failed");
+                }
+            }
+            Transform::Inverse => {
+                let B_ref = get_inverse_ref_result(A, size);
+                verify_toll(&B.get_data(), &B_ref.get_data(), toll);
+            }
+            Transform::Real => {
+                let B_ref = get_real_ref_result(A, size);
+                verify_toll(&B.get_data(), &B_ref.get_data(), toll);
+            }
+            Transform::InverseReal => {
+                // the round trip has to give back the original signal
+                verify_toll(&B.get_data(), &A.get_data(), toll);
+            }
+        },
         None => (),
     }
 }
@@ -122,3 +233,69 @@ fn get_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
 
     RefMatrix::new(vec![A_ref], 1, size)
 }
+
+/// The inverse transform is computed with the forward reference as `conj(fft(conj(x))) / nn`
+fn get_inverse_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
+    let mut A_ref = A.to_c_format();
+    A_ref
+        .iter_mut()
+        .skip(1)
+        .step_by(2)
+        .for_each(|el| *el = -*el);
+
+    unsafe {
+        fft_function(A_ref.as_mut_ptr(), size >> 1);
+    }
+
+    let scale = number!("1") / (size >> 1) as Number;
+    A_ref.iter_mut().enumerate().for_each(|(i, el)| {
+        *el *= if i % 2 == 1 { -scale } else { scale };
+    });
+
+    RefMatrix::new(vec![A_ref], 1, size)
+}
+
+/// The real transform is the first half of the complex transform of the signal with zero
+/// imaginary parts
+fn get_real_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
+    let mut A_ref = vec![number!("0"); 2 * size];
+    for (i, el) in A.to_c_format().into_iter().enumerate() {
+        A_ref[2 * i] = el;
+    }
+
+    unsafe {
+        fft_function(A_ref.as_mut_ptr(), size);
+    }
+
+    A_ref.truncate(size + 2);
+    RefMatrix::new(vec![A_ref], 1, size + 2)
+}
+
+/// Direct O(n^2) DFT computed in double precision, for the sizes the reference does not support
+fn get_dft_ref_result(A: Matrix, size: usize, transform: Transform) -> RefMatrix<Number> {
+    let n = size >> 1;
+    let data = A.to_c_format();
+    let sign = match transform {
+        Transform::Inverse => 1.0,
+        _ => -1.0,
+    };
+
+    let mut B_ref = vec![number!("0"); size];
+    for k in 0..n {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for j in 0..n {
+            let angle = sign * 2.0 * std::f64::consts::PI * ((j * k) % n) as f64 / n as f64;
+            let (x_re, x_im) = (data[2 * j] as f64, data[2 * j + 1] as f64);
+            re += x_re * angle.cos() - x_im * angle.sin();
+            im += x_re * angle.sin() + x_im * angle.cos();
+        }
+        if let Transform::Inverse = transform {
+            re /= n as f64;
+            im /= n as f64;
+        }
+        B_ref[2 * k] = re as Number;
+        B_ref[2 * k + 1] = im as Number;
+    }
+
+    RefMatrix::new(vec![B_ref], 1, size)
+}
@@ -0,0 +1,127 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::parallel_traits::ParallelFastFourierTransform2d;
+use obpmark_library::rayon_traits::RayonFastFourierTransform2d;
+use obpmark_library::{BaseMatrix, FastFourierTransform2d};
+use std::{path::Path, time::Instant};
+
+use obpmark_library::matrix_2d::Matrix2d as RefMatrix;
+use reference_algorithms::fft_function;
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+
+use benchmarks::{number, verify};
+
+#[cfg(any(feature = "int", feature = "half"))]
+compile_error!(
+    "This benchmark is not supported for int and half, the FFT is only implemented for f32 and f64"
+);
+
+#[derive(Parser, Debug)]
+#[command(about = "FFT 2D benchmark")]
+struct Args {
+    /// The input is a size x size matrix of complex numbers, stored as size x (2 * size) values
+    #[clap(flatten)]
+    common: CommonArgs,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if args.common.size.count_ones() != 1 {
+        // checks that size is power of 2
+        panic!("Size must be a power of 2");
+    }
+
+    let rows = args.common.size;
+    let cols = args.common.size << 1;
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), rows, cols).unwrap()
+        }
+        None => {
+            Matrix::from_random_seed(args.common.seed, rows, cols, number!("-10"), number!("10"))
+        }
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let mut B = Matrix::zeroes(rows, cols);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.fft_2d(B).unwrap(),
+            (None, Implementation::Rayon) => A.rayon_fft_2d(B).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::StdParallel) => A.parallel_fft_2d(B, n).unwrap(),
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_fft_2d(B, 8).unwrap()
+            }
+        }
+    });
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = args.common.export {
+        B.to_file(Path::new(&filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let B_ref = Matrix::from_file(Path::new(&filename), rows, cols).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // verify against the 1d reference implementation applied to rows and columns
+            let B_ref = get_ref_result(A, rows);
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        None => (),
+    }
+}
+
+fn get_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
+    let mut rows = A.get_data();
+
+    // TODO: this is for testing, remove
+    let t = Instant::now();
+    for row in rows.iter_mut() {
+        unsafe {
+            fft_function(row.as_mut_ptr(), size);
+        }
+    }
+    for j in 0..size {
+        let mut column: Vec<Number> = rows
+            .iter()
+            .flat_map(|row| [row[2 * j], row[2 * j + 1]])
+            .collect();
+        unsafe {
+            fft_function(column.as_mut_ptr(), size);
+        }
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[2 * j] = column[2 * i];
+            row[2 * j + 1] = column[2 * i + 1];
+        }
+    }
+    println!("C code: {:.2?}", t.elapsed());
+
+    RefMatrix::new(rows, size, size << 1)
+}
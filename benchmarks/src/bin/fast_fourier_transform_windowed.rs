@@ -1,24 +1,37 @@
 #![allow(non_snake_case)]
 use clap::Parser;
 use core::panic;
-use obpmark_library::parallel_traits::ParallelFastFourierTransformWindowed;
-use obpmark_library::rayon_traits::RayonFastFourierTransformWindowed;
-use obpmark_library::BaseMatrix;
+use obpmark_library::parallel_traits::{
+    ParallelFastFourierTransformWindowed, ParallelShortTimeFourierTransform,
+};
+use obpmark_library::rayon_traits::{
+    RayonFastFourierTransformWindowed, RayonShortTimeFourierTransform,
+};
+use obpmark_library::{BaseMatrix, SpectrumOutput, StftParameters, WindowFunction};
 use std::{path::Path, time::Instant};
 
 use obpmark_library::matrix_1d::Matrix1d as RefMatrix;
-use reference_algorithms::fft_windowed_function;
+use reference_algorithms::{fft_function, fft_windowed_function};
 
 use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
 
-use benchmarks::number;
+use benchmarks::{number, verify};
 
 #[cfg(feature = "2d")]
 compile_error!(
     "This benchmark is not supported for 2d matrices, since the underlying data is always 1d"
 );
 #[cfg(not(feature = "2d"))]
-use obpmark_library::FastFourierTransformWindowed;
+use obpmark_library::{FastFourierTransformWindowed, ShortTimeFourierTransform};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Function {
+    Rectangular,
+    Hann,
+    Hamming,
+    BlackmanHarris,
+    Kaiser,
+}
 
 #[derive(Parser, Debug)]
 #[command(about = "FFT windowed benchmark")]
@@ -26,9 +39,25 @@ struct Args {
     #[clap(flatten)]
     common: CommonArgs,
 
-    /// Size of the window
+    /// Size of the window, in values (window / 2 complex numbers)
     #[arg(short, long)]
     window: usize,
+
+    /// Window function applied to each frame, enables the short time FFT
+    #[arg(value_enum, long)]
+    function: Option<Function>,
+
+    /// Beta of the Kaiser window
+    #[arg(long, default_value = "8.6")]
+    kaiser_beta: Number,
+
+    /// Distance in complex numbers between two frames, enables the short time FFT
+    #[arg(long)]
+    hop: Option<usize>,
+
+    /// Output the power spectrum of each frame (spectrogram), enables the short time FFT
+    #[arg(long)]
+    power: bool,
 }
 
 fn main() {
@@ -37,7 +66,7 @@ fn main() {
     let A;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 1 {
                 panic!("Expected 1 input files, got {}", v.len());
@@ -60,46 +89,41 @@ fn main() {
         println!("{}", A);
     }
 
+    if args.function.is_some() || args.hop.is_some() || args.power {
+        short_time_fft(&args, A);
+        return;
+    }
+
     let n_elements_B = ((args.common.size - args.window) + 1) * args.window;
     B = Matrix::zeroes(1, n_elements_B);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        // Note that this call will modify A as well as B
-        (_, Implementation::Sequential) => A.fftw(args.window, &mut B).unwrap(),
-        (None, Implementation::Rayon) => {
-            A.rayon_fft_windowed(args.window, &mut B).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n_threads), Implementation::StdParallel) => A
-            .parallel_fft_windowed(args.window, &mut B, n_threads)
-            .unwrap(),
-        (None, Implementation::StdParallel) => {
-            // TODO: change 8 to number of cores
-            A.parallel_fft_windowed(args.window, &mut B, 8).unwrap()
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            // Note that this call will modify A as well as B
+            (_, Implementation::Sequential) => A.fftw(args.window, B).unwrap(),
+            (None, Implementation::Rayon) => {
+                A.rayon_fft_windowed(args.window, B).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n_threads), Implementation::StdParallel) => {
+                A.parallel_fft_windowed(args.window, B, n_threads).unwrap()
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: change 8 to number of cores
+                A.parallel_fft_windowed(args.window, B, 8).unwrap()
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Output:");
         println!("{}", B);
     }
 
-    match args.common.export {
-        Some(filename) => {
-            // export output
-            A.to_file(Path::new(&filename)).unwrap();
-        }
-        None => (),
+    if let Some(filename) = args.common.export {
+        B.to_file(Path::new(&filename)).unwrap();
     }
 
     match args.common.verify {
@@ -138,3 +162,98 @@ fn get_ref_result(A: Matrix, size: usize, window: usize, B_size: usize) -> RefMa
 
     RefMatrix::new(vec![B_ref], 1, B_size)
 }
+
+fn short_time_fft(args: &Args, A: Matrix) {
+    let parameters = StftParameters {
+        window: args.window >> 1,
+        hop: args.hop.unwrap_or(1),
+        function: match args.function.unwrap_or(Function::Rectangular) {
+            Function::Rectangular => WindowFunction::Rectangular,
+            Function::Hann => WindowFunction::Hann,
+            Function::Hamming => WindowFunction::Hamming,
+            Function::BlackmanHarris => WindowFunction::BlackmanHarris,
+            Function::Kaiser => WindowFunction::Kaiser(args.kaiser_beta),
+        },
+        output: match args.power {
+            true => SpectrumOutput::Power,
+            false => SpectrumOutput::Complex,
+        },
+    };
+
+    let frames = parameters.frames(args.common.size >> 1).unwrap();
+    let cols = parameters.output_cols();
+    let mut B = Matrix::zeroes(frames, cols);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.stft(B, &parameters).unwrap(),
+            (None, Implementation::Rayon) => A.rayon_stft(B, &parameters).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::StdParallel) => A.parallel_stft(B, &parameters, n).unwrap(),
+            (None, Implementation::StdParallel) => {
+                // TODO: change 8 to number of cores
+                A.parallel_stft(B, &parameters, 8).unwrap()
+            }
+        }
+    });
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = &args.common.export {
+        B.to_file(Path::new(filename)).unwrap();
+    }
+
+    match &args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let B_ref = Matrix::from_file(Path::new(filename), frames, cols).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // verify against the reference fft applied to each windowed frame
+            let B_ref = get_stft_ref_result(A, &parameters, frames);
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        None => (),
+    }
+}
+
+fn get_stft_ref_result(
+    A: Matrix,
+    parameters: &StftParameters<Number>,
+    frames: usize,
+) -> RefMatrix<Number> {
+    let A_ref = A.to_c_format();
+    let coefficients = parameters.function.coefficients(parameters.window);
+
+    let B_ref = (0..frames)
+        .map(|i| {
+            let start = i * parameters.hop * 2;
+            let mut frame: Vec<Number> = A_ref[start..start + parameters.window * 2]
+                .iter()
+                .enumerate()
+                .map(|(j, el)| *el * coefficients[j / 2])
+                .collect();
+            unsafe {
+                fft_function(frame.as_mut_ptr(), parameters.window);
+            }
+            match parameters.output {
+                SpectrumOutput::Complex => frame,
+                SpectrumOutput::Power => frame
+                    .chunks_exact(2)
+                    .map(|el| el[0] * el[0] + el[1] * el[1])
+                    .collect(),
+            }
+        })
+        .collect();
+
+    RefMatrix::new(B_ref, frames, parameters.output_cols())
+}
@@ -6,8 +6,9 @@
 use clap::Parser;
 use core::panic;
 use obpmark_library::{
-    parallel_traits::ParallelFiniteImpulseResponseFilter,
-    rayon_traits::RayonFiniteImpulseResponseFilter, BaseMatrix, FirFilter,
+    parallel_traits::{ParallelFiniteImpulseResponseFilter, ParallelMultirateFirFilter},
+    rayon_traits::{RayonFiniteImpulseResponseFilter, RayonMultirateFirFilter},
+    BaseMatrix, FirFilter, MultirateFirFilter, PolyphaseFilter,
 };
 use std::path::Path;
 use std::time::Instant;
@@ -28,6 +29,22 @@ struct Args {
     /// Kernel size
     #[clap(short, long)]
     kernel_size: usize,
+
+    /// Decimation factor: only one sample every `decimate` of the filtered signal is computed
+    #[arg(long, conflicts_with = "interpolate")]
+    decimate: Option<usize>,
+
+    /// Interpolation factor: the signal is filtered with `interpolate - 1` zeros inserted
+    /// between the samples
+    #[arg(long)]
+    interpolate: Option<usize>,
+}
+
+/// Sample rate of the output
+enum Rate {
+    Input,
+    Decimated(PolyphaseFilter<Number>),
+    Interpolated(PolyphaseFilter<Number>),
 }
 
 fn main() {
@@ -37,7 +54,7 @@ fn main() {
     let kernel;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 2 {
                 panic!("Expected 2 input files, got {}", v.len());
@@ -70,36 +87,56 @@ fn main() {
         println!("{}", kernel);
     }
 
-    // B size = A size + kernel size - 1
-    B = Matrix::zeroes(1, args.common.size + args.kernel_size - 1);
-
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Rayon) => {
-            A.rayon_fir_filter(&kernel, &mut B).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Invalid parameter combination: sequential with nthreads != 1")
-        }
-        (_, Implementation::Sequential) => A.fir_filter(&kernel, &mut B).unwrap(),
-        (Some(n), Implementation::StdParallel) => {
-            A.parallel_fir_filter(&kernel, &mut B, n).unwrap()
-        }
-        (None, Implementation::StdParallel) => {
+    // the polyphase decomposition of the kernel is computed once, before the filtering
+    let polyphase = |factor| PolyphaseFilter::new(&kernel.get_data()[0], factor).unwrap();
+    let rate = match (args.decimate, args.interpolate) {
+        (Some(factor), _) => Rate::Decimated(polyphase(factor)),
+        (_, Some(factor)) => Rate::Interpolated(polyphase(factor)),
+        (None, None) => Rate::Input,
+    };
+
+    // B size = A size + kernel size - 1 at the input rate
+    let output_size = match &rate {
+        Rate::Input => args.common.size + args.kernel_size - 1,
+        Rate::Decimated(filter) => filter.decimated_len(args.common.size).unwrap(),
+        Rate::Interpolated(filter) => filter.interpolated_len(args.common.size).unwrap(),
+    };
+    B = Matrix::zeroes(1, output_size);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation, &rate) {
+            (Some(_), Implementation::Rayon, _) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::Sequential, _) if n != 1 => {
+                panic!("Invalid parameter combination: sequential with nthreads != 1")
+            }
+            (None, Implementation::Rayon, Rate::Input) => A.rayon_fir_filter(&kernel, B).unwrap(),
+            (None, Implementation::Rayon, Rate::Decimated(filter)) => {
+                A.rayon_fir_decimate(filter, B).unwrap()
+            }
+            (None, Implementation::Rayon, Rate::Interpolated(filter)) => {
+                A.rayon_fir_interpolate(filter, B).unwrap()
+            }
+            (_, Implementation::Sequential, Rate::Input) => A.fir_filter(&kernel, B).unwrap(),
+            (_, Implementation::Sequential, Rate::Decimated(filter)) => {
+                A.fir_decimate(filter, B).unwrap()
+            }
+            (_, Implementation::Sequential, Rate::Interpolated(filter)) => {
+                A.fir_interpolate(filter, B).unwrap()
+            }
             // TODO: use number of cores
-            A.parallel_fir_filter(&kernel, &mut B, 8).unwrap()
+            (n, Implementation::StdParallel, Rate::Input) => {
+                A.parallel_fir_filter(&kernel, B, n.unwrap_or(8)).unwrap()
+            }
+            (n, Implementation::StdParallel, Rate::Decimated(filter)) => {
+                A.parallel_fir_decimate(filter, B, n.unwrap_or(8)).unwrap()
+            }
+            (n, Implementation::StdParallel, Rate::Interpolated(filter)) => A
+                .parallel_fir_interpolate(filter, B, n.unwrap_or(8))
+                .unwrap(),
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Output:");
@@ -117,14 +154,26 @@ fn main() {
     match args.common.verify {
         Some(Some(filename)) => {
             // verify against file
-            let B_ref = Matrix::from_file(Path::new(&filename), 1, args.common.size).unwrap();
-            verify!(B.get_data(), B_ref.get_data());
-        }
-        Some(None) => {
-            // verify against cpu implementation
-            let B_ref = get_ref_result(A, args.common.size, kernel, args.kernel_size);
+            let B_ref = Matrix::from_file(Path::new(&filename), 1, output_size).unwrap();
             verify!(B.get_data(), B_ref.get_data());
         }
+        Some(None) => match rate {
+            Rate::Input => {
+                // verify against cpu implementation
+                let B_ref = get_ref_result(A, args.common.size, kernel, args.kernel_size);
+                verify!(B.get_data(), B_ref.get_data());
+            }
+            Rate::Decimated(filter) => {
+                // verify against the naive filter-then-downsample
+                let B_ref = get_multirate_ref_result(&A, &kernel, 1, filter.factor());
+                verify!(B.get_data(), B_ref);
+            }
+            Rate::Interpolated(filter) => {
+                // verify against the naive upsample-then-filter
+                let B_ref = get_multirate_ref_result(&A, &kernel, filter.factor(), 1);
+                verify!(B.get_data(), B_ref);
+            }
+        },
         None => (),
     }
 }
@@ -149,3 +198,34 @@ fn get_ref_result(A: Matrix, size: usize, kernel: Matrix, kernel_size: usize) ->
 
     RefMatrix::new(vec![B_ref], 1, size)
 }
+
+/// Naive multirate filter: `upsample - 1` zeros are inserted between the input samples, the
+/// result is filtered at that rate and then one sample every `downsample` is kept  
+/// The taps are accumulated in order, as the polyphase filters do, so the results are the same
+fn get_multirate_ref_result(
+    A: &Matrix,
+    kernel: &Matrix,
+    upsample: usize,
+    downsample: usize,
+) -> Vec<Vec<Number>> {
+    let input = A.get_data().swap_remove(0);
+    let kernel = kernel.get_data().swap_remove(0);
+
+    let mut upsampled = vec![number!("0"); (input.len() - 1) * upsample + 1];
+    for (i, el) in input.into_iter().enumerate() {
+        upsampled[i * upsample] = el;
+    }
+
+    let filtered: Vec<Number> = (0..upsampled.len() + kernel.len() - 1)
+        .map(|i| {
+            let mut sum = number!("0");
+            for (j, h) in kernel.iter().enumerate() {
+                if j <= i && i - j < upsampled.len() {
+                    sum += *h * upsampled[i - j];
+                }
+            }
+            sum
+        })
+        .collect();
+    vec![filtered.into_iter().step_by(downsample).collect()]
+}
@@ -0,0 +1,239 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+#[cfg(not(feature = "half"))]
+use obpmark_library::number_traits::FixedPoint;
+use obpmark_library::{
+    parallel_traits::ParallelIirFilter, rayon_traits::RayonIirFilter, BaseMatrix, BiquadCascade,
+    BiquadForm, IirFilter,
+};
+use std::path::Path;
+
+#[cfg(not(feature = "half"))]
+use benchmarks::benchmark_utils::verify_toll;
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::{number, verify};
+
+// relative to the peak of the output (the tolerance of the integers comes from the noise gain)
+#[cfg(not(any(feature = "int", feature = "half")))]
+const RELATIVE_TOLERANCE: f64 = 1e-3;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Form {
+    /// Direct form I
+    Df1,
+    /// Transposed direct form II
+    Tdf2,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Cascaded biquad IIR filter benchmark")]
+struct Args {
+    /// The input has a row of size samples per channel
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Number of independent channels
+    #[arg(long, default_value_t = 16)]
+    channels: usize,
+
+    /// Second order sections of the Butterworth low pass filter
+    #[arg(long, default_value_t = 4)]
+    sections: usize,
+
+    /// Cutoff frequency as a fraction of the sampling frequency
+    #[arg(long, default_value_t = 0.1)]
+    cutoff: f64,
+
+    /// Structure of the sections
+    #[arg(value_enum, long, default_value_t = Form::Tdf2)]
+    form: Form,
+
+    /// Fractional bits of the fixed point coefficients (int only)
+    #[arg(long, default_value_t = 14)]
+    fraction_bits: u32,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let form = match args.form {
+        Form::Df1 => BiquadForm::DirectFormI,
+        Form::Tdf2 => BiquadForm::TransposedDirectFormII,
+    };
+    let cascade =
+        BiquadCascade::butterworth_low_pass(args.sections, args.cutoff, form, args.fraction_bits)
+            .unwrap();
+    // the rounding of the coefficients can move the poles outside the unit circle
+    if let Some(section) = cascade.unstable_section() {
+        panic!(
+            "The filter is not stable: section {} has a pole on or outside the unit circle",
+            section
+        );
+    }
+
+    let (channels, size) = (args.channels, args.common.size);
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input file, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), channels, size).unwrap()
+        }
+        None => Matrix::from_random_seed(
+            args.common.seed,
+            channels,
+            size,
+            number!("-1000"),
+            number!("1000"),
+        ),
+    };
+
+    // the samples are scaled by the fraction bits inside the filter, 2 bits are left for the
+    // gain of the sections
+    #[cfg(feature = "int")]
+    {
+        let peak = A
+            .get_data()
+            .into_iter()
+            .flatten()
+            .map(i32::unsigned_abs)
+            .max();
+        if peak.unwrap_or(0) as u64 * (4 << args.fraction_bits) > i32::MAX as u64 {
+            panic!(
+                "The input scaled by {} fraction bits does not fit in the samples",
+                args.fraction_bits
+            );
+        }
+    }
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let mut B = Matrix::zeroes(channels, size);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => A.iir_filter(&cascade, B).unwrap(),
+            (None, Implementation::Rayon) => A.rayon_iir_filter(&cascade, B).unwrap(),
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_iir_filter(&cascade, B, n.unwrap_or(8)).unwrap()
+            }
+        }
+    });
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = &args.common.export {
+        B.to_file(Path::new(filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported output
+            let B_ref = Matrix::from_file(Path::new(&filename), channels, size).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        #[cfg(feature = "half")]
+        Some(None) => panic!("Verification against the reference is not supported for half"),
+        #[cfg(not(feature = "half"))]
+        Some(None) => {
+            // difference equations of the rounded coefficients in double precision, the
+            // rounding errors are fed back by the recursion
+            let B_ref = get_ref_result(&A.get_data(), &cascade);
+            #[cfg(not(feature = "int"))]
+            let toll = {
+                let peak = B_ref
+                    .iter()
+                    .flatten()
+                    .fold(0.0f64, |max, el| max.max(el.abs()));
+                peak * RELATIVE_TOLERANCE
+            };
+            #[cfg(feature = "int")]
+            let toll = rounding_error_bound(&cascade, size).ceil();
+            let B_ref: Vec<Vec<Number>> = B_ref
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|el| Number::fixed_from_f64(el, 0))
+                        .collect()
+                })
+                .collect();
+            verify_toll(&B.get_data(), &B_ref, Number::fixed_from_f64(toll, 0));
+        }
+        None => (),
+    }
+}
+
+#[cfg(not(feature = "half"))]
+fn get_ref_result(channels: &[Vec<Number>], cascade: &BiquadCascade<Number>) -> Vec<Vec<f64>> {
+    let coefficient = |c: Number| c.fixed_to_f64(cascade.fraction_bits);
+    channels
+        .iter()
+        .map(|channel| {
+            let mut x: Vec<f64> = channel.iter().map(|el| el.fixed_to_f64(0)).collect();
+            for section in &cascade.sections {
+                let ([b0, b1, b2], [a1, a2]) =
+                    (section.b.map(coefficient), section.a.map(coefficient));
+                let mut y = vec![0.0; x.len()];
+                for n in 0..x.len() {
+                    let past = |signal: &[f64], k: usize| if n >= k { signal[n - k] } else { 0.0 };
+                    y[n] = b0 * x[n] + b1 * past(&x, 1) + b2 * past(&x, 2)
+                        - a1 * past(&y, 1)
+                        - a2 * past(&y, 2);
+                }
+                x = y;
+            }
+            x
+        })
+        .collect()
+}
+
+/// Bound of the error of the fixed point filter on `size` samples: every rounding to the guard
+/// bits is at most half of their LSB and goes through the feedback of its section and the
+/// sections after it, the output is then rounded once more
+#[cfg(feature = "int")]
+fn rounding_error_bound(cascade: &BiquadCascade<Number>, size: usize) -> f64 {
+    let coefficient = |c: Number| c.fixed_to_f64(cascade.fraction_bits);
+    let roundings = match cascade.form {
+        BiquadForm::DirectFormI => 1.0,
+        BiquadForm::TransposedDirectFormII => 3.0,
+    };
+    let guard_lsb = 1.0 / (1u64 << cascade.fraction_bits) as f64;
+    (0..cascade.sections.len())
+        .map(|k| {
+            // impulse response of the feedback of section k and of the following sections
+            let mut impulse = vec![0.0; size];
+            impulse[0] = 1.0;
+            for (i, section) in cascade.sections.iter().enumerate().skip(k) {
+                let ([b0, b1, b2], [a1, a2]) =
+                    (section.b.map(coefficient), section.a.map(coefficient));
+                let mut y = vec![0.0; size];
+                for n in 0..size {
+                    let past = |signal: &[f64], k: usize| if n >= k { signal[n - k] } else { 0.0 };
+                    let x = if i == k {
+                        impulse[n]
+                    } else {
+                        b0 * impulse[n] + b1 * past(&impulse, 1) + b2 * past(&impulse, 2)
+                    };
+                    y[n] = x - a1 * past(&y, 1) - a2 * past(&y, 2);
+                }
+                impulse = y;
+            }
+            roundings * guard_lsb / 2.0 * impulse.iter().map(|el| el.abs()).sum::<f64>()
+        })
+        .sum::<f64>()
+        + 0.5
+}
@@ -0,0 +1,345 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::{
+    parallel_traits::ParallelImageCalibration, rayon_traits::RayonImageCalibration, BaseMatrix,
+    ImageCalibration,
+};
+use std::path::Path;
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::{number, verify};
+
+#[derive(Parser, Debug)]
+#[command(about = "Image calibration benchmark")]
+struct Args {
+    /// The input is `frames` size x size frames, read from the first input file (one frame
+    /// after the other), the second one has the offsets, the gains and the bad pixels maps
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Number of frames
+    #[arg(long, default_value_t = 8)]
+    frames: usize,
+
+    /// Side of the blocks of pixels summed by the spatial binning
+    #[arg(long, default_value_t = 2)]
+    binning: usize,
+
+    /// Difference from the mean of the neighbouring frames above which a pixel is a radiation
+    /// hit
+    #[arg(long, default_value_t = 50)]
+    threshold: u32,
+}
+
+/// Frames with `rows` rows of the rows of `data`, one after the other
+fn split_frames(data: Vec<Vec<Number>>, rows: usize) -> Vec<Matrix> {
+    data.chunks(rows)
+        .map(|frame| Matrix::new(frame.to_vec(), rows, frame[0].len()))
+        .collect()
+}
+
+/// A random scene with noise and some radiation hits in every frame
+fn random_frames(seed: u64, frames: usize, size: usize) -> Vec<Matrix> {
+    let scene = Matrix::from_random_seed(seed, size, size, number!("10"), number!("60"))
+        .get_data()
+        .concat();
+    (0..frames as u64)
+        .map(|f| {
+            let noise =
+                Matrix::from_random_seed(seed + 2 * f + 1, size, size, number!("0"), number!("10"));
+            let hits = Matrix::from_random_seed(
+                seed + 2 * f + 2,
+                size,
+                size,
+                number!("0"),
+                number!("1000"),
+            );
+            let data = scene
+                .iter()
+                .zip(
+                    noise
+                        .get_data()
+                        .concat()
+                        .into_iter()
+                        .zip(hits.get_data().concat()),
+                )
+                .map(|(value, (noise, hit))| {
+                    let hit = if hit < number!("2") {
+                        number!("200")
+                    } else {
+                        number!("0")
+                    };
+                    *value + noise + hit
+                })
+                .collect::<Vec<_>>();
+            Matrix::new(
+                data.chunks(size).map(|row| row.to_vec()).collect(),
+                size,
+                size,
+            )
+        })
+        .collect()
+}
+
+/// Offsets, gains and bad pixels maps
+fn random_maps(seed: u64, size: usize) -> (Matrix, Matrix, Matrix) {
+    let offsets = Matrix::from_random_seed(seed, size, size, number!("0"), number!("10"));
+    let gains = Matrix::from_random_seed(seed + 1, size, size, number!("1"), number!("2"));
+    let bad_pixels = Matrix::from_random_seed(seed + 2, size, size, number!("0"), number!("100"))
+        .get_data()
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| {
+                    if value < number!("1") {
+                        number!("1")
+                    } else {
+                        number!("0")
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    (offsets, gains, Matrix::new(bad_pixels, size, size))
+}
+
+/// Up to two frames before and after frame `f`, from the other side at the borders
+fn neighbours(f: usize, frames: usize) -> Vec<usize> {
+    let first = f.saturating_sub(2).min(frames.saturating_sub(5));
+    (first..(first + 5).min(frames))
+        .filter(|&n| n != f)
+        .collect()
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let (size, n_frames, binning) = (args.common.size, args.frames, args.binning);
+    if n_frames < 2 {
+        panic!("At least 2 frames are needed for the radiation scrubbing");
+    }
+    let threshold = number!(args.threshold.to_string());
+
+    let (A, (offsets, gains, bad_pixels)) = match &args.common.input {
+        Some(v) => {
+            let frames = Matrix::from_file(Path::new(&v[0]), n_frames * size, size).unwrap();
+            let maps = match v.get(1) {
+                Some(filename) => {
+                    let maps = Matrix::from_file(Path::new(filename), 3 * size, size).unwrap();
+                    let mut maps = split_frames(maps.get_data(), size).into_iter();
+                    let mut map = || maps.next().unwrap();
+                    (map(), map(), map())
+                }
+                None => random_maps(args.common.seed + 1, size),
+            };
+            (split_frames(frames.get_data(), size), maps)
+        }
+        None => (
+            random_frames(args.common.seed, n_frames, size),
+            random_maps(args.common.seed + 1, size),
+        ),
+    };
+
+    if args.common.print_input {
+        for (f, frame) in A.iter().enumerate() {
+            println!("Frame {}:", f);
+            println!("{}", frame);
+        }
+        println!("Offsets:");
+        println!("{}", offsets);
+        println!("Gains:");
+        println!("{}", gains);
+        println!("Bad pixels:");
+        println!("{}", bad_pixels);
+    }
+
+    // the frames are co-added in B, the scratch buffers are per execution for --tmr
+    let mut B = Matrix::zeroes(size / binning, size / binning);
+    let elapsed = args.common.run_kernel(&mut B, |B| {
+        let mut corrected: Vec<Matrix> =
+            (0..n_frames).map(|_| Matrix::zeroes(size, size)).collect();
+        let mut temp = Matrix::zeroes(size, size);
+        let mut scrubbed = Matrix::zeroes(size, size);
+        let mut binned = Matrix::zeroes(size / binning, size / binning);
+
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => {
+                for (frame, result) in A.iter().zip(&mut corrected) {
+                    frame.offset_correction(&offsets, &mut temp).unwrap();
+                    temp.bad_pixel_correction(&bad_pixels, result).unwrap();
+                }
+                for f in 0..n_frames {
+                    let neighbours: Vec<_> = neighbours(f, n_frames)
+                        .into_iter()
+                        .map(|n| &corrected[n])
+                        .collect();
+                    corrected[f]
+                        .radiation_scrubbing(&neighbours, threshold, &mut scrubbed)
+                        .unwrap();
+                    scrubbed.gain_correction(&gains, &mut temp).unwrap();
+                    temp.spatial_binning(binning, &mut binned).unwrap();
+                    binned.co_add(B).unwrap();
+                }
+            }
+            (None, Implementation::Rayon) => {
+                for (frame, result) in A.iter().zip(&mut corrected) {
+                    frame.rayon_offset_correction(&offsets, &mut temp).unwrap();
+                    temp.rayon_bad_pixel_correction(&bad_pixels, result)
+                        .unwrap();
+                }
+                for f in 0..n_frames {
+                    let neighbours: Vec<_> = neighbours(f, n_frames)
+                        .into_iter()
+                        .map(|n| &corrected[n])
+                        .collect();
+                    corrected[f]
+                        .rayon_radiation_scrubbing(&neighbours, threshold, &mut scrubbed)
+                        .unwrap();
+                    scrubbed.rayon_gain_correction(&gains, &mut temp).unwrap();
+                    temp.rayon_spatial_binning(binning, &mut binned).unwrap();
+                    binned.rayon_co_add(B).unwrap();
+                }
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                let n = n.unwrap_or(8);
+                for (frame, result) in A.iter().zip(&mut corrected) {
+                    frame
+                        .parallel_offset_correction(&offsets, &mut temp, n)
+                        .unwrap();
+                    temp.parallel_bad_pixel_correction(&bad_pixels, result, n)
+                        .unwrap();
+                }
+                for f in 0..n_frames {
+                    let neighbours: Vec<_> = neighbours(f, n_frames)
+                        .into_iter()
+                        .map(|n| &corrected[n])
+                        .collect();
+                    corrected[f]
+                        .parallel_radiation_scrubbing(&neighbours, threshold, &mut scrubbed, n)
+                        .unwrap();
+                    scrubbed
+                        .parallel_gain_correction(&gains, &mut temp, n)
+                        .unwrap();
+                    temp.parallel_spatial_binning(binning, &mut binned, n)
+                        .unwrap();
+                    binned.parallel_co_add(B, n).unwrap();
+                }
+            }
+        }
+    });
+
+    if args.common.timing {
+        println!("Elapsed per frame: {:.2?}", elapsed / n_frames as u32);
+    }
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = &args.common.export {
+        B.to_file(Path::new(filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported image
+            let B_ref =
+                Matrix::from_file(Path::new(&filename), size / binning, size / binning).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            let frames: Vec<_> = A.iter().map(|frame| frame.get_data()).collect();
+            let maps = (offsets.get_data(), gains.get_data(), bad_pixels.get_data());
+            let B_ref = get_ref_result(&frames, maps, threshold, binning);
+            verify!(B.get_data(), B_ref);
+        }
+        None => (),
+    }
+}
+
+type Frame = Vec<Vec<Number>>;
+
+/// Naive calibration of the frames, one step at a time over the whole sequence
+fn get_ref_result(
+    frames: &[Frame],
+    (offsets, gains, bad_pixels): (Frame, Frame, Frame),
+    threshold: Number,
+    binning: usize,
+) -> Frame {
+    let size = offsets.len();
+    let zero = number!("0");
+    let is_bad = |i: usize, j: usize| bad_pixels[i][j] != zero;
+
+    let mut corrected = Vec::new();
+    for frame in frames {
+        let mut frame = frame.clone();
+        for (frame_row, offsets_row) in frame.iter_mut().zip(&offsets) {
+            for (el, offset) in frame_row.iter_mut().zip(offsets_row) {
+                *el -= *offset;
+            }
+        }
+        let pixel = |i: usize, j: usize| {
+            if !is_bad(i, j) {
+                return frame[i][j];
+            }
+            let (mut sum, mut count) = (zero, 0);
+            let ys = i.saturating_sub(1)..(i + 2).min(size);
+            let xs = j.saturating_sub(1)..(j + 2).min(size);
+            for (frame_row, bad_row) in frame[ys.clone()].iter().zip(&bad_pixels[ys]) {
+                for (value, bad) in frame_row[xs.clone()].iter().zip(&bad_row[xs.clone()]) {
+                    if *bad == zero {
+                        sum += *value;
+                        count += 1;
+                    }
+                }
+            }
+            if count > 0 {
+                sum / number!(count.to_string())
+            } else {
+                frame[i][j]
+            }
+        };
+        let result: Frame = (0..size)
+            .map(|i| (0..size).map(|j| pixel(i, j)).collect())
+            .collect();
+        corrected.push(result);
+    }
+
+    let mut B = vec![vec![zero; size / binning]; size / binning];
+    for f in 0..frames.len() {
+        let neighbours = neighbours(f, frames.len());
+        let mut binned = vec![vec![zero; size / binning]; size / binning];
+        for i in 0..size {
+            for j in 0..size {
+                let mut mean = zero;
+                for &n in &neighbours {
+                    mean += corrected[n][i][j];
+                }
+                mean /= number!(neighbours.len().to_string());
+                let value = corrected[f][i][j];
+                let difference = if value > mean {
+                    value - mean
+                } else {
+                    mean - value
+                };
+                let value = if difference > threshold { mean } else { value };
+                binned[i / binning][j / binning] += value * gains[i][j];
+            }
+        }
+        for (B_row, binned_row) in B.iter_mut().zip(binned) {
+            for (el, value) in B_row.iter_mut().zip(binned_row) {
+                *el += value;
+            }
+        }
+    }
+    B
+}
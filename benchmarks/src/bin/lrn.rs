@@ -1,7 +1,10 @@
 #![allow(non_snake_case)]
 use clap::Parser;
 use core::panic;
-use obpmark_library::{parallel_traits::ParallelLRN, rayon_traits::RayonLRN, BaseMatrix, LRN};
+use obpmark_library::{
+    parallel_traits::ParallelGeneralLRN, rayon_traits::RayonGeneralLRN, BaseMatrix, GeneralLRN,
+    LRNParameters, LRNRegion,
+};
 use std::path::Path;
 use std::time::Instant;
 
@@ -12,35 +15,88 @@ use benchmarks::{number, verify};
 
 use reference_algorithms::lrn;
 
+// values used by the OBPMark benchmark (and by the C reference implementation)
 const ALPHA: Number = 10e-4;
 const BETA: Number = 0.75;
 const K: Number = 2.0;
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Region {
+    AcrossChannels,
+    WithinChannel,
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "LRN benchmark")]
 struct Args {
     #[clap(flatten)]
     common: CommonArgs,
+
+    /// Scaling parameter alpha
+    #[arg(long, default_value_t = ALPHA)]
+    alpha: Number,
+
+    /// Exponent beta
+    #[arg(long, default_value_t = BETA)]
+    beta: Number,
+
+    /// Bias k
+    #[arg(long, default_value_t = K)]
+    k: Number,
+
+    /// Size of the normalisation window (odd), 1 is the OBPMark benchmark
+    #[arg(long, default_value_t = 1)]
+    n: usize,
+
+    /// Neighbourhood the window spans
+    #[arg(value_enum, long, default_value_t = Region::AcrossChannels)]
+    region: Region,
+
+    /// Number of channels, each one a size x size image, by default each of the size rows of
+    /// the size x size input is a channel
+    #[arg(long)]
+    channels: Option<usize>,
 }
 
 fn main() {
     let args = Args::parse();
 
+    let parameters = LRNParameters {
+        alpha: args.alpha,
+        beta: args.beta,
+        k: args.k,
+        n: args.n,
+        region: match args.region {
+            Region::AcrossChannels => LRNRegion::AcrossChannels,
+            Region::WithinChannel => LRNRegion::WithinChannel,
+        },
+    };
+
+    let obpmark_configuration = is_obpmark_configuration(&args);
+
+    let (rows, cols) = match (args.channels, args.region) {
+        (None, _) => (args.common.size, args.common.size),
+        (Some(c), Region::AcrossChannels) => (c, args.common.size * args.common.size),
+        (Some(_), Region::WithinChannel) => {
+            panic!("Within channel normalization works on a single channel")
+        }
+    };
+
     let A;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 1 {
                 panic!("Expected 1 input files, got {}", v.len());
             }
-            A = Matrix::from_file(Path::new(&v[0]), args.common.size, args.common.size).unwrap();
+            A = Matrix::from_file(Path::new(&v[0]), rows, cols).unwrap();
         }
         None => {
             A = Matrix::from_random_seed(
                 args.common.seed,
-                args.common.size,
-                args.common.size,
+                rows,
+                cols,
                 number!("-10"),
                 number!("10"),
             );
@@ -52,35 +108,29 @@ fn main() {
         println!("{}", A);
     }
 
-    B = Matrix::zeroes(args.common.size, args.common.size);
+    B = Matrix::zeroes(rows, cols);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Rayon) => {
-            A.rayon_lrn(&mut B, ALPHA, BETA, K).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Invalid parameter combination: sequential with nthreads != 1")
-        }
-        (_, Implementation::Sequential) => A.lrn(&mut B, ALPHA, BETA, K).unwrap(),
-        (Some(n), Implementation::StdParallel) => {
-            A.parallel_lrn(&mut B, ALPHA, BETA, K, n).unwrap()
-        }
-        (None, Implementation::StdParallel) => {
-            // TODO: use number of cores
-            A.parallel_lrn(&mut B, ALPHA, BETA, K, 8).unwrap()
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (None, Implementation::Rayon) => {
+                A.rayon_general_lrn(B, &parameters).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Invalid parameter combination: sequential with nthreads != 1")
+            }
+            (_, Implementation::Sequential) => A.general_lrn(B, &parameters).unwrap(),
+            (Some(n), Implementation::StdParallel) => {
+                A.parallel_general_lrn(B, &parameters, n).unwrap()
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: use number of cores
+                A.parallel_general_lrn(B, &parameters, 8).unwrap()
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Output:");
@@ -98,19 +148,31 @@ fn main() {
     match args.common.verify {
         Some(Some(filename)) => {
             // verify against file
-            let B_ref = Matrix::from_file(Path::new(&filename), args.common.size, args.common.size)
-                .unwrap();
+            let B_ref = Matrix::from_file(Path::new(&filename), rows, cols).unwrap();
             verify!(B.get_data(), B_ref.get_data());
         }
-        Some(None) => {
-            // verify against cpu implementation
+        Some(None) if obpmark_configuration => {
+            // verify against cpu implementation, which only supports the OBPMark configuration
             let B_ref = get_ref_result(A, args.common.size);
             verify_toll(&B.get_data(), &B_ref.get_data(), 1e-6);
         }
+        Some(None) => {
+            // verify against naive loops over the input
+            let B_ref = get_general_ref_result(A, &parameters);
+            verify_toll(&B.get_data(), &B_ref, 1e-6);
+        }
         None => (),
     }
 }
 
+fn is_obpmark_configuration(args: &Args) -> bool {
+    args.n == 1
+        && args.alpha == ALPHA
+        && args.beta == BETA
+        && args.k == K
+        && args.channels.is_none()
+}
+
 fn get_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
     let A_ref = A.to_c_format();
     let mut B_ref = vec![number!("0"); size * size];
@@ -125,3 +187,33 @@ fn get_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
 
     RefMatrix::new(B_ref, size, size)
 }
+
+/// `x / (k + alpha * sum)^beta` where sum is the sum of the squares of the n elements of the
+/// adjacent rows (channels) in the same column, or of the n x n neighbourhood of x, clipped at
+/// the borders
+fn get_general_ref_result(A: Matrix, parameters: &LRNParameters<Number>) -> Vec<Vec<Number>> {
+    let A = A.get_data();
+    let (rows, cols) = (A.len() as isize, A[0].len() as isize);
+    let radius = (parameters.n / 2) as isize;
+    let col_radius = match parameters.region {
+        LRNRegion::AcrossChannels => 0,
+        LRNRegion::WithinChannel => radius,
+    };
+
+    (0..rows)
+        .map(|i| {
+            (0..cols)
+                .map(|j| {
+                    let mut sum = number!("0");
+                    for k in (i - radius).max(0)..(i + radius + 1).min(rows) {
+                        for l in (j - col_radius).max(0)..(j + col_radius + 1).min(cols) {
+                            sum += A[k as usize][l as usize] * A[k as usize][l as usize];
+                        }
+                    }
+                    A[i as usize][j as usize]
+                        / (parameters.k + parameters.alpha * sum).powf(parameters.beta)
+                })
+                .collect()
+        })
+        .collect()
+}
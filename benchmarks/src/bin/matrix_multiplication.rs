@@ -1,17 +1,22 @@
 /***
  * Matrix multiplication benchmark
  * It multiplies two square matrices with side length `size`
+ * With --abft the operands are checksummed and the product is checked (and corrected)
  */
 
 #![allow(non_snake_case)]
 use clap::Parser;
 use core::panic;
+use obpmark_library::fault_injection::{classify, FaultInjector, FaultReport};
 use obpmark_library::{
-    parallel_traits::ParallelMatMul, rayon_traits::RayonMatMul, BaseMatrix, MatMul,
+    parallel_traits::ParallelMatMul, rayon_traits::RayonMatMul, AbftMatMul, AbftReport, BaseMatrix,
+    MatMul,
 };
-use std::{path::Path, time::Instant};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::benchmark_utils::{CommonArgs, FaultArgs, Implementation, Matrix, Number};
 use benchmarks::{number, verify};
 
 use obpmark_library::matrix_1d::Matrix1d as RefMatrix;
@@ -21,6 +26,30 @@ use reference_algorithms::matrix_multiplication;
 struct Args {
     #[clap(flatten)]
     common: CommonArgs,
+
+    /// Algorithm based fault tolerance: checksummed operands, the product is checked and a
+    /// single wrong element is corrected
+    #[arg(long, default_value_t = false)]
+    abft: bool,
+
+    /// Relative tolerance of the ABFT checksums, sqrt(size) * epsilon by default (0 for integers)
+    #[arg(long)]
+    tolerance: Option<f64>,
+
+    /// The faults hit the product, before the ABFT checks
+    #[clap(flatten)]
+    faults: FaultArgs,
+}
+
+/// The rounding errors of the sums are random, they grow with the square root of the length
+#[cfg(not(feature = "int"))]
+fn default_tolerance(size: usize) -> f64 {
+    (size as f64).sqrt() * f64::from(Number::EPSILON)
+}
+
+#[cfg(feature = "int")]
+fn default_tolerance(_size: usize) -> f64 {
+    0.0
 }
 
 fn main() {
@@ -30,7 +59,7 @@ fn main() {
     let B;
     let mut C;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 2 {
                 panic!("Expected 2 input files, got {}", v.len());
@@ -65,11 +94,12 @@ fn main() {
 
     C = Matrix::zeroes(args.common.size, args.common.size);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
+    let multiply = |A: &Matrix, B: &Matrix, C: &mut Matrix| match (
+        args.common.nthreads,
+        &args.common.implementation,
+    ) {
         (None, Implementation::Rayon) => {
-            A.rayon_multiply(&B, &mut C).unwrap();
+            A.rayon_multiply(B, C).unwrap();
         }
         (Some(_), Implementation::Rayon) => {
             panic!("Cannot specify number of threads for Rayon implementation")
@@ -77,20 +107,62 @@ fn main() {
         (Some(n), Implementation::Sequential) if n != 1 => {
             panic!("Invalid parameter combination: sequential with nthreads != 1")
         }
-        (_, Implementation::Sequential) => A.multiply(&B, &mut C).unwrap(),
+        (_, Implementation::Sequential) => A.multiply(B, C).unwrap(),
         (Some(n_threads), Implementation::StdParallel) => {
-            A.parallel_multiply(&B, &mut C, n_threads).unwrap()
+            A.parallel_multiply(B, C, n_threads).unwrap()
         }
         (None, Implementation::StdParallel) => {
             // TODO: change 8 to number of cores
-            A.parallel_multiply(&B, &mut C, 8).unwrap()
+            A.parallel_multiply(B, C, 8).unwrap()
         }
-    }
+    };
+    let tolerance = args
+        .tolerance
+        .unwrap_or_else(|| default_tolerance(args.common.size));
+
+    // product of the checksummed operands, the faults are injected in a copy
+    let mut product = Matrix::zeroes(args.common.size + 1, args.common.size + 1);
 
-    let t1 = Instant::now();
+    if args.abft {
+        // with --tmr the checked multiplication is the voted kernel, the stage times are only
+        // printed without it
+        let stages = Mutex::new([Duration::ZERO; 3]);
+        let mut result = (product, C, AbftReport::default());
+        let elapsed = args.common.run_kernel(&mut result, |(product, C, report)| {
+            let t0 = Instant::now();
+            let (A_checksums, B_checksums) = (A.column_checksums(), B.row_checksums());
+            let t1 = Instant::now();
+            multiply(&A_checksums, &B_checksums, product);
+            let t2 = Instant::now();
+            *report = product.abft_verify(tolerance, C).unwrap();
+            *stages.lock().unwrap() = [t1 - t0, t2 - t1, t2.elapsed()];
+        });
+        let report;
+        (product, C, report) = result;
 
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
+        println!(
+            "ABFT: {} errors detected, {} corrected",
+            report.detected, report.corrected
+        );
+        if args.common.timing && args.common.tmr.is_none() {
+            // the plain multiplication, to measure the overhead
+            let mut C_plain = Matrix::zeroes(args.common.size, args.common.size);
+            let t = Instant::now();
+            multiply(&A, &B, &mut C_plain);
+            let plain = t.elapsed();
+
+            let [checksums, multiplication, verification] = stages.into_inner().unwrap();
+            println!("Checksums: {:.2?}", checksums);
+            println!("Multiplication: {:.2?}", multiplication);
+            println!("Verification: {:.2?}", verification);
+            println!("Plain multiplication: {:.2?}", plain);
+            println!(
+                "ABFT overhead: {:.1}%",
+                100.0 * (elapsed.as_secs_f64() / plain.as_secs_f64() - 1.0)
+            );
+        }
+    } else {
+        args.common.run_kernel(&mut C, |C| multiply(&A, &B, C));
     }
 
     if args.common.output {
@@ -106,6 +178,49 @@ fn main() {
         None => (),
     }
 
+    if args.faults.injections > 0 {
+        // the faults hit the product, without ABFT nothing can detect them
+        let fault = args.faults.fault();
+        let mut injector = FaultInjector::new(args.faults.fault_seed);
+        let mut report = FaultReport::default();
+        let mut corrected = 0;
+        let golden = C.get_data();
+        let (rows, cols) = if args.abft {
+            (args.common.size + 1, args.common.size + 1)
+        } else {
+            (args.common.size, args.common.size)
+        };
+        for _ in 0..args.faults.injections {
+            let mut faulty = Matrix::new(
+                if args.abft {
+                    product.get_data()
+                } else {
+                    golden.clone()
+                },
+                rows,
+                cols,
+            );
+            injector
+                .inject(&mut faulty, fault, args.faults.faulty_elements)
+                .unwrap();
+            let abft = if args.abft {
+                let mut result = Matrix::zeroes(args.common.size, args.common.size);
+                let abft = faulty.abft_verify(tolerance, &mut result).unwrap();
+                faulty = result;
+                abft
+            } else {
+                AbftReport::default()
+            };
+            corrected += abft.corrected;
+            report.record(classify(&faulty.get_data(), &golden, abft.detected > 0));
+        }
+        println!("Fault injection in the product, {:?}:", fault);
+        println!("{}", report);
+        if args.abft {
+            println!("Corrected: {}", corrected);
+        }
+    }
+
     #[cfg(not(feature = "half"))]
     match args.common.verify {
         Some(Some(filename)) => {
@@ -1,16 +1,28 @@
 #![allow(non_snake_case)]
 use obpmark_library::{
-    parallel_traits::ParallelMaxPooling, rayon_traits::RayonMaxPooling, BaseMatrix, MaxPooling,
+    parallel_traits::{ParallelMaxPooling, ParallelPooling},
+    rayon_traits::{RayonMaxPooling, RayonPooling},
+    BaseMatrix, MaxPooling, Pooling, PoolingParameters, PoolingType,
 };
 use reference_algorithms::max_pooling;
-use std::time::Instant;
+use std::{path::Path, time::Instant};
 
 use clap::Parser;
 
+#[cfg(not(any(feature = "int", feature = "half")))]
+use benchmarks::benchmark_utils::verify_toll;
 use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
 use benchmarks::{number, verify};
 use obpmark_library::matrix_2d::Matrix2d as RefMatrix;
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Pool {
+    Max,
+    Average,
+    GlobalMax,
+    GlobalAverage,
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "Max pooling benchmark")]
 struct Args {
@@ -21,11 +33,32 @@ struct Args {
     /// Stride
     #[clap(long)]
     stride: usize,
+
+    /// Pooling to run, if not specified runs the OBPMark max pooling (window == stride)
+    #[arg(value_enum, long)]
+    pool: Option<Pool>,
+
+    /// Size of the pooling window (default: stride), only used with --pool
+    #[arg(long)]
+    window: Option<usize>,
+
+    /// Implicit padding on each side of the input, only used with --pool
+    #[arg(long, default_value_t = 0)]
+    padding: usize,
+
+    /// Also computes the argmax indices, only used with --pool max
+    #[arg(long, default_value_t = false)]
+    indices: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
+    if let Some(pool) = args.pool {
+        run_pooling(args, pool);
+        return;
+    }
+
     if args.common.size % args.stride != 0 {
         panic!("Size must be a multiple of stride");
     }
@@ -61,36 +94,28 @@ fn main() {
         println!("{}", A);
     }
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Rayon) => {
-            A.rayon_max_pooling(&mut B, args.stride, args.stride)
-                .unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Invalid parameter combination: sequential with nthreads != 1")
-        }
-        (_, Implementation::Sequential) => A.max_pooling(&mut B, args.stride, args.stride).unwrap(),
-        (Some(n), Implementation::StdParallel) => A
-            .parallel_max_pooling(&mut B, args.stride, args.stride, n)
-            .unwrap(),
-        (None, Implementation::StdParallel) => {
-            // TODO: use number of cores
-            A.parallel_max_pooling(&mut B, args.stride, args.stride, 8)
-                .unwrap();
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (None, Implementation::Rayon) => {
+                A.rayon_max_pooling(B, args.stride, args.stride).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Invalid parameter combination: sequential with nthreads != 1")
+            }
+            (_, Implementation::Sequential) => A.max_pooling(B, args.stride, args.stride).unwrap(),
+            (Some(n), Implementation::StdParallel) => A
+                .parallel_max_pooling(B, args.stride, args.stride, n)
+                .unwrap(),
+            (None, Implementation::StdParallel) => {
+                // TODO: use number of cores
+                A.parallel_max_pooling(B, args.stride, args.stride, 8)
+                    .unwrap();
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        // print timing
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         // print output
@@ -139,3 +164,220 @@ fn get_ref_result(A: Matrix, size: usize, stride: usize, B_size: usize) -> RefMa
 
     RefMatrix::new(B_ref, B_size, B_size)
 }
+
+fn run_pooling(args: Args, pool: Pool) {
+    let parameters = PoolingParameters::new(
+        args.window.unwrap_or(args.stride),
+        args.window.unwrap_or(args.stride),
+        args.stride,
+        args.stride,
+        args.padding,
+        args.padding,
+    );
+
+    let (B_rows, B_cols) = match pool {
+        Pool::Max | Pool::Average => parameters
+            .output_dimensions(args.common.size, args.common.size)
+            .unwrap(),
+        Pool::GlobalMax | Pool::GlobalAverage => (1, 1),
+    };
+
+    if args.indices && !matches!(pool, Pool::Max) {
+        panic!("Indices can only be computed for max pooling");
+    }
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), args.common.size, args.common.size).unwrap()
+        }
+        None => Matrix::from_random_seed(
+            args.common.seed,
+            args.common.size,
+            args.common.size,
+            number!("-10"),
+            number!("10"),
+        ),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let n_threads = match (args.common.nthreads, &args.common.implementation) {
+        (Some(_), Implementation::Rayon) => {
+            panic!("Cannot specify number of threads for Rayon implementation")
+        }
+        (Some(n), Implementation::Sequential) if n != 1 => {
+            panic!("Invalid parameter combination: sequential with nthreads != 1")
+        }
+        // TODO: use number of cores
+        (n, _) => n.unwrap_or(8),
+    };
+
+    // the indices are a second output, voted with the result by --tmr
+    let n_indices = if args.indices { B_rows * B_cols } else { 0 };
+    let mut result = (Matrix::zeroes(B_rows, B_cols), vec![0; n_indices]);
+    args.common.run_kernel(&mut result, |(B, indices)| {
+        match (pool, &args.common.implementation) {
+            (Pool::Max, Implementation::Sequential) if args.indices => {
+                A.max_pooling_indices(B, indices, &parameters).unwrap()
+            }
+            (Pool::Max, Implementation::StdParallel) if args.indices => A
+                .parallel_max_pooling_indices(B, indices, &parameters, n_threads)
+                .unwrap(),
+            (Pool::Max, Implementation::Rayon) if args.indices => A
+                .rayon_max_pooling_indices(B, indices, &parameters)
+                .unwrap(),
+            (Pool::Max | Pool::Average, Implementation::Sequential) => {
+                A.pooling(B, pooling_type(pool), &parameters).unwrap()
+            }
+            (Pool::Max | Pool::Average, Implementation::StdParallel) => A
+                .parallel_pooling(B, pooling_type(pool), &parameters, n_threads)
+                .unwrap(),
+            (Pool::Max | Pool::Average, Implementation::Rayon) => {
+                A.rayon_pooling(B, pooling_type(pool), &parameters).unwrap()
+            }
+            (Pool::GlobalMax | Pool::GlobalAverage, Implementation::Sequential) => {
+                *B = Matrix::new(
+                    vec![vec![A.global_pooling(pooling_type(pool)).unwrap()]],
+                    1,
+                    1,
+                )
+            }
+            (Pool::GlobalMax | Pool::GlobalAverage, Implementation::StdParallel) => {
+                *B = Matrix::new(
+                    vec![vec![A
+                        .parallel_global_pooling(pooling_type(pool), n_threads)
+                        .unwrap()]],
+                    1,
+                    1,
+                )
+            }
+            (Pool::GlobalMax | Pool::GlobalAverage, Implementation::Rayon) => {
+                *B = Matrix::new(
+                    vec![vec![A.rayon_global_pooling(pooling_type(pool)).unwrap()]],
+                    1,
+                    1,
+                )
+            }
+        }
+    });
+    let (B, indices) = result;
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+        if args.indices {
+            println!("Indices:");
+            for row in indices.chunks(B_cols) {
+                println!("{:?}", row);
+            }
+        }
+    }
+
+    if let Some(filename) = args.common.export {
+        B.to_file(Path::new(&filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let B_ref = Matrix::from_file(Path::new(&filename), B_rows, B_cols).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // verify against naive loops over the input, the C reference only supports non
+            // overlapping max pooling
+            let (B_ref, indices_ref) =
+                get_pooling_ref_result(&A.get_data(), pool, &parameters, B_rows, B_cols);
+            if args.indices && indices != indices_ref {
+                println!("Verification failed");
+            } else if matches!(pool, Pool::Average | Pool::GlobalAverage) {
+                verify_average(&B, &B_ref);
+            } else {
+                verify!(B.get_data(), B_ref);
+            }
+        }
+        None => (),
+    }
+}
+
+/// Pooling as naive loops over the input, padded positions are skipped, the average is taken
+/// over the input elements of the window and the index of a maximum is the first one in row
+/// major order  
+/// Returns the result and the indices of the maxima
+fn get_pooling_ref_result<T: obpmark_library::number_traits::Number>(
+    A: &[Vec<T>],
+    pool: Pool,
+    parameters: &PoolingParameters,
+    B_rows: usize,
+    B_cols: usize,
+) -> (Vec<Vec<T>>, Vec<usize>) {
+    let (rows, cols) = (A.len(), A[0].len());
+
+    let reduce = |positions: Vec<(usize, usize)>| -> (T, usize) {
+        let (k, l) = positions[0];
+        let (mut max, mut max_idx) = (A[k][l], k * cols + l);
+        let mut sum = T::zero();
+        for (k, l) in positions.iter().copied() {
+            if A[k][l] > max {
+                max = A[k][l];
+                max_idx = k * cols + l;
+            }
+            sum += A[k][l];
+        }
+        match pool {
+            Pool::Max | Pool::GlobalMax => (max, max_idx),
+            Pool::Average | Pool::GlobalAverage => {
+                (sum / T::from_usize(positions.len()).unwrap(), max_idx)
+            }
+        }
+    };
+
+    if let Pool::GlobalMax | Pool::GlobalAverage = pool {
+        let positions = (0..rows)
+            .flat_map(|k| (0..cols).map(move |l| (k, l)))
+            .collect();
+        return (vec![vec![reduce(positions).0]], vec![0]);
+    }
+
+    let mut B = vec![vec![T::zero(); B_cols]; B_rows];
+    let mut indices = vec![0; B_rows * B_cols];
+    for i in 0..B_rows {
+        for j in 0..B_cols {
+            // positions of the window in the padded input, moved back to the input
+            let positions = (0..parameters.window_rows)
+                .flat_map(|k| (0..parameters.window_cols).map(move |l| (k, l)))
+                .filter_map(|(k, l)| {
+                    let k = (i * parameters.row_stride + k).checked_sub(parameters.row_padding)?;
+                    let l = (j * parameters.col_stride + l).checked_sub(parameters.col_padding)?;
+                    (k < rows && l < cols).then_some((k, l))
+                })
+                .collect();
+            (B[i][j], indices[i * B_cols + j]) = reduce(positions);
+        }
+    }
+    (B, indices)
+}
+
+/// The parallel implementations sum in a different order, so floating point averages can differ
+#[cfg(not(any(feature = "int", feature = "half")))]
+fn verify_average(B: &Matrix, B_ref: &Vec<Vec<Number>>) {
+    verify_toll(&B.get_data(), B_ref, 1e-4);
+}
+
+#[cfg(any(feature = "int", feature = "half"))]
+fn verify_average(B: &Matrix, B_ref: &Vec<Vec<Number>>) {
+    verify!(B.get_data(), *B_ref);
+}
+
+fn pooling_type(pool: Pool) -> PoolingType {
+    match pool {
+        Pool::Max | Pool::GlobalMax => PoolingType::Max,
+        Pool::Average | Pool::GlobalAverage => PoolingType::Average,
+    }
+}
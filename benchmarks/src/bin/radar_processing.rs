@@ -0,0 +1,257 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::parallel_traits::ParallelRadarProcessing;
+use obpmark_library::rayon_traits::RayonRadarProcessing;
+use obpmark_library::{random_matrix_data, BaseMatrix, Chirp, RadarProcessing};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use benchmarks::benchmark_utils::{verify_toll, CommonArgs, Implementation, Matrix, Number};
+use benchmarks::{number, verify};
+
+#[cfg(any(feature = "int", feature = "half"))]
+compile_error!(
+    "This benchmark is not supported for int and half, the processing is done on complex f32 or f64 numbers"
+);
+
+#[derive(Parser, Debug)]
+#[command(about = "SAR range-Doppler processing benchmark")]
+struct Args {
+    /// The input is size x size complex raw samples (a row per azimuth position), stored as
+    /// size x (2 * size) values
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Samples of the range chirp, size / 4 by default
+    #[arg(long)]
+    range_samples: Option<usize>,
+
+    /// Bandwidth of the range chirp as a fraction of the sampling frequency
+    #[arg(long, default_value_t = 0.8)]
+    range_bandwidth: f64,
+
+    /// Samples of the azimuth chirp, size / 8 by default
+    #[arg(long)]
+    azimuth_samples: Option<usize>,
+
+    /// Bandwidth of the azimuth chirp as a fraction of the pulse repetition frequency
+    #[arg(long, default_value_t = 0.5)]
+    azimuth_bandwidth: f64,
+
+    /// Number of point targets of the synthetic scene
+    #[arg(long, default_value_t = 8)]
+    targets: usize,
+}
+
+/// Raw data of point targets at random positions with random amplitudes, plus noise: the echo of
+/// each target is the product of the range and the azimuth chirps starting at its position
+fn synthetic_raw_data(
+    seed: u64,
+    size: usize,
+    targets: usize,
+    range_chirp: &[f64],
+    azimuth_chirp: &[f64],
+) -> Matrix {
+    let mut data = random_matrix_data(seed, size, size << 1, -0.1, 0.1);
+    let positions = random_matrix_data(seed + 1, targets, 2, 0, size as i32);
+    let amplitudes = random_matrix_data(seed + 2, targets, 1, 0.5, 2.0);
+    for (position, amplitude) in positions.iter().zip(amplitudes) {
+        let (a0, r0) = (position[0] as usize, position[1] as usize);
+        for (a, azimuth) in azimuth_chirp.chunks_exact(2).enumerate() {
+            let row = &mut data[(a0 + a) % size];
+            for (r, range) in range_chirp.chunks_exact(2).enumerate() {
+                let r = (r0 + r) % size;
+                row[2 * r] += amplitude[0] * (azimuth[0] * range[0] - azimuth[1] * range[1]);
+                row[2 * r + 1] += amplitude[0] * (azimuth[0] * range[1] + azimuth[1] * range[0]);
+            }
+        }
+    }
+    let data = data
+        .into_iter()
+        .map(|row| row.into_iter().map(|el| number!(el.to_string())).collect())
+        .collect();
+    Matrix::new(data, size, size << 1)
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let size = args.common.size;
+    if size.count_ones() != 1 {
+        panic!("Size must be a power of 2");
+    }
+
+    let range_chirp = Chirp {
+        samples: args.range_samples.unwrap_or(size / 4),
+        bandwidth: args.range_bandwidth,
+    };
+    let azimuth_chirp = Chirp {
+        samples: args.azimuth_samples.unwrap_or(size / 8),
+        bandwidth: args.azimuth_bandwidth,
+    };
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), size, size << 1).unwrap()
+        }
+        None => synthetic_raw_data(
+            args.common.seed,
+            size,
+            args.targets,
+            &range_chirp.signal(),
+            &azimuth_chirp.signal(),
+        ),
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    // the matched filters are computed once, before the processing
+    let to_number = |chirp: Chirp<f64>| Chirp {
+        samples: chirp.samples,
+        bandwidth: number!(chirp.bandwidth.to_string()),
+    };
+    let range_filter = to_number(range_chirp).matched_filter(size).unwrap();
+    let azimuth_filter = to_number(azimuth_chirp).matched_filter(size).unwrap();
+
+    // the intermediate matrices are per execution for --tmr, the stage times are only printed
+    // without it
+    let stages = Mutex::new([Duration::ZERO; 3]);
+    let mut B = Matrix::zeroes(size, size << 1);
+    args.common.run_kernel(&mut B, |B| {
+        let mut range_compressed = Matrix::zeroes(size, size << 1);
+        let mut turned = Matrix::zeroes(size, size << 1);
+        let t0 = Instant::now();
+        let (t1, t2);
+
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => {
+                A.compression(&range_filter, &mut range_compressed).unwrap();
+                t1 = Instant::now();
+                range_compressed.corner_turn(&mut turned).unwrap();
+                t2 = Instant::now();
+                turned.compression(&azimuth_filter, B).unwrap();
+            }
+            (None, Implementation::Rayon) => {
+                A.rayon_compression(&range_filter, &mut range_compressed)
+                    .unwrap();
+                t1 = Instant::now();
+                range_compressed.rayon_corner_turn(&mut turned).unwrap();
+                t2 = Instant::now();
+                turned.rayon_compression(&azimuth_filter, B).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                let n = n.unwrap_or(8);
+                A.parallel_compression(&range_filter, &mut range_compressed, n)
+                    .unwrap();
+                t1 = Instant::now();
+                range_compressed
+                    .parallel_corner_turn(&mut turned, n)
+                    .unwrap();
+                t2 = Instant::now();
+                turned.parallel_compression(&azimuth_filter, B, n).unwrap();
+            }
+        }
+
+        *stages.lock().unwrap() = [t1 - t0, t2 - t1, t2.elapsed()];
+    });
+
+    if args.common.timing && args.common.tmr.is_none() {
+        let [range, turn, azimuth] = stages.into_inner().unwrap();
+        println!("Range compression: {:.2?}", range);
+        println!("Corner turn: {:.2?}", turn);
+        println!("Azimuth compression: {:.2?}", azimuth);
+    }
+
+    if args.common.output {
+        // a row per range sample
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = &args.common.export {
+        B.to_file(Path::new(filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against a previously exported image
+            let B_ref = Matrix::from_file(Path::new(&filename), size, size << 1).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // time domain correlations in double precision
+            let B_ref = get_ref_result(
+                &A.get_data(),
+                &range_chirp.signal(),
+                &azimuth_chirp.signal(),
+            );
+            let peak = B_ref
+                .iter()
+                .flatten()
+                .fold(0.0f64, |max, el| max.max(el.abs()));
+            let B_ref: Vec<Vec<Number>> = B_ref
+                .into_iter()
+                .map(|row| row.into_iter().map(|el| number!(el.to_string())).collect())
+                .collect();
+            verify_toll(&B.get_data(), &B_ref, number!((peak * 1e-4).to_string()));
+        }
+        None => (),
+    }
+}
+
+/// Circular correlation of `line` with `chirp`, both interleaved complex numbers
+fn correlate(line: &[f64], chirp: &[f64]) -> Vec<f64> {
+    let n = line.len() >> 1;
+    let mut result = vec![0.0; line.len()];
+    for (k, result_el) in result.chunks_exact_mut(2).enumerate() {
+        for (m, c) in chirp.chunks_exact(2).enumerate() {
+            let x = &line[2 * ((k + m) % n)..2 * ((k + m) % n) + 2];
+            // x * conj(c)
+            result_el[0] += x[0] * c[0] + x[1] * c[1];
+            result_el[1] += x[1] * c[0] - x[0] * c[1];
+        }
+    }
+    result
+}
+
+fn get_ref_result(
+    raw: &[Vec<Number>],
+    range_chirp: &[f64],
+    azimuth_chirp: &[f64],
+) -> Vec<Vec<f64>> {
+    let size = raw.len();
+    let range_compressed: Vec<Vec<f64>> = raw
+        .iter()
+        .map(|row| {
+            let row: Vec<f64> = row
+                .iter()
+                .map(|el| el.to_string().parse().unwrap())
+                .collect();
+            correlate(&row, range_chirp)
+        })
+        .collect();
+    (0..size)
+        .map(|r| {
+            let column: Vec<f64> = range_compressed
+                .iter()
+                .flat_map(|row| [row[2 * r], row[2 * r + 1]])
+                .collect();
+            correlate(&column, azimuth_chirp)
+        })
+        .collect()
+}
@@ -22,7 +22,7 @@ fn main() {
 
     let A;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             // read input from file
             if v.len() != 1 {
@@ -50,35 +50,29 @@ fn main() {
 
     let mut B = Matrix::zeroes(args.common.size, args.common.size);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Cannot run sequential implementation with more than 1 thread");
-        }
-        (_, Implementation::Sequential) => {
-            A.relu(&mut B).unwrap();
-        }
-        (None, Implementation::Rayon) => {
-            A.rayon_relu(&mut B).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation");
-        }
-        (Some(n), Implementation::StdParallel) => {
-            A.parallel_relu(&mut B, n).unwrap();
-        }
-        (None, Implementation::StdParallel) => {
-            // TODO: change n_threads
-            A.parallel_relu(&mut B, 8).unwrap();
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => {
+                A.relu(B).unwrap();
+            }
+            (None, Implementation::Rayon) => {
+                A.rayon_relu(B).unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation");
+            }
+            (Some(n), Implementation::StdParallel) => {
+                A.parallel_relu(B, n).unwrap();
+            }
+            (None, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                A.parallel_relu(B, 8).unwrap();
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         // print output
@@ -1,8 +1,9 @@
 #![allow(non_snake_case)]
 use clap::Parser;
 use core::panic;
-use obpmark_library::parallel_traits::ParallelSoftmax;
-use obpmark_library::{rayon_traits::RayonSoftmax, BaseMatrix, Softmax};
+use obpmark_library::parallel_traits::{ParallelSoftmax, ParallelStableSoftmax};
+use obpmark_library::rayon_traits::{RayonSoftmax, RayonStableSoftmax};
+use obpmark_library::{BaseMatrix, Softmax, SoftmaxAxis, StableSoftmax};
 use reference_algorithms::softmax;
 use std::time::Instant;
 
@@ -10,20 +11,55 @@ use benchmarks::benchmark_utils::{verify_toll, CommonArgs, Implementation, Matri
 use benchmarks::number;
 use obpmark_library::matrix_2d::Matrix2d as RefMatrix;
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum Mode {
+    /// Raw exponentials normalised over the whole matrix, as in OBPMark
+    Obpmark,
+    /// Max subtracted, normalised over the whole matrix
+    Global,
+    /// Max subtracted, normalised over each row
+    Rows,
+    /// Max subtracted, normalised over each column
+    Columns,
+}
+
+impl Mode {
+    fn axis(self) -> Option<SoftmaxAxis> {
+        match self {
+            Mode::Obpmark => None,
+            Mode::Global => Some(SoftmaxAxis::Global),
+            Mode::Rows => Some(SoftmaxAxis::Rows),
+            Mode::Columns => Some(SoftmaxAxis::Columns),
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(about = "Softmax function benchmark")]
 struct Args {
     #[clap(flatten)]
     common: CommonArgs,
+
+    /// Softmax variant to run
+    #[arg(value_enum, long, default_value_t = Mode::Obpmark)]
+    mode: Mode,
+
+    /// Computes the log-softmax, not available in obpmark mode
+    #[arg(long, default_value_t = false)]
+    log: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
+    if args.log && args.mode.axis().is_none() {
+        panic!("Log-softmax is not available in obpmark mode");
+    }
+
     let A;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 1 {
                 panic!("Expected 1 input files, got {}", v.len());
@@ -48,31 +84,42 @@ fn main() {
 
     B = Matrix::zeroes(args.common.size, args.common.size);
 
-    let t0 = Instant::now();
-
-    match (args.common.nthreads, args.common.implementation) {
-        (None, Implementation::Rayon) => {
-            A.rayon_softmax(&mut B).unwrap();
-        }
-        (Some(_), Implementation::Rayon) => {
-            panic!("Cannot specify number of threads for Rayon implementation")
-        }
-        (Some(n), Implementation::Sequential) if n != 1 => {
-            panic!("Invalid parameter combination: sequential with nthreads != 1")
-        }
-        (_, Implementation::Sequential) => A.softmax(&mut B).unwrap(),
-        (Some(n), Implementation::StdParallel) => A.parallel_softmax(&mut B, n).unwrap(),
-        (None, Implementation::StdParallel) => {
+    args.common.run_kernel(&mut B, |B| {
+        match (
+            args.common.nthreads,
+            &args.common.implementation,
+            args.mode.axis(),
+        ) {
+            (Some(_), Implementation::Rayon, _) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (Some(n), Implementation::Sequential, _) if n != 1 => {
+                panic!("Invalid parameter combination: sequential with nthreads != 1")
+            }
+            (None, Implementation::Rayon, None) => {
+                A.rayon_softmax(B).unwrap();
+            }
+            (None, Implementation::Rayon, Some(axis)) if args.log => {
+                A.rayon_log_softmax(B, axis).unwrap()
+            }
+            (None, Implementation::Rayon, Some(axis)) => A.rayon_stable_softmax(B, axis).unwrap(),
+            (_, Implementation::Sequential, None) => A.softmax(B).unwrap(),
+            (_, Implementation::Sequential, Some(axis)) if args.log => {
+                A.log_softmax(B, axis).unwrap()
+            }
+            (_, Implementation::Sequential, Some(axis)) => A.stable_softmax(B, axis).unwrap(),
             // TODO: use number of cores
-            A.parallel_softmax(&mut B, 8).unwrap();
+            (n, Implementation::StdParallel, None) => {
+                A.parallel_softmax(B, n.unwrap_or(8)).unwrap()
+            }
+            (n, Implementation::StdParallel, Some(axis)) if args.log => {
+                A.parallel_log_softmax(B, axis, n.unwrap_or(8)).unwrap()
+            }
+            (n, Implementation::StdParallel, Some(axis)) => {
+                A.parallel_stable_softmax(B, axis, n.unwrap_or(8)).unwrap()
+            }
         }
-    }
-
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    });
 
     if args.common.output {
         println!("Output:");
@@ -95,11 +142,19 @@ fn main() {
                 filename
             );
         }
-        Some(None) => {
-            // verify against cpu implementation
-            let B_ref = get_ref_result(A, args.common.size);
-            verify_toll(&B.get_data(), &B_ref.get_data(), 1e-5);
-        }
+        Some(None) => match args.mode {
+            Mode::Obpmark => {
+                // verify against cpu implementation
+                let B_ref = get_ref_result(A, args.common.size);
+                verify_toll(&B.get_data(), &B_ref.get_data(), 1e-5);
+            }
+            mode => {
+                // the C reference only has the obpmark softmax, verify against naive loops over
+                // the input instead
+                let B_ref = get_stable_ref_result(A, mode, args.log);
+                verify_toll(&B.get_data(), &B_ref, 1e-5);
+            }
+        },
         None => (),
     }
 }
@@ -120,3 +175,46 @@ fn get_ref_result(A: Matrix, size: usize) -> RefMatrix<Number> {
 
     RefMatrix::new(B_ref, size, size)
 }
+
+/// `exp(x - max) / sum` (or `x - max - ln(sum)` for the log-softmax) where max and sum are
+/// taken over the elements normalised together with x
+fn get_stable_ref_result(A: Matrix, mode: Mode, log: bool) -> Vec<Vec<Number>> {
+    let A = A.get_data();
+    let (rows, cols) = (A.len(), A[0].len());
+
+    // the group of the element (i, j), one max and one sum per group
+    let (n_groups, group): (usize, fn(usize, usize) -> usize) = match mode {
+        Mode::Global => (1, |_, _| 0),
+        Mode::Rows => (rows, |i, _| i),
+        Mode::Columns => (cols, |_, j| j),
+        Mode::Obpmark => unreachable!(),
+    };
+
+    let mut max = vec![Number::NEG_INFINITY; n_groups];
+    for i in 0..rows {
+        for j in 0..cols {
+            max[group(i, j)] = max[group(i, j)].max(A[i][j]);
+        }
+    }
+    let mut sum = vec![number!("0"); n_groups];
+    for i in 0..rows {
+        for j in 0..cols {
+            sum[group(i, j)] += (A[i][j] - max[group(i, j)]).exp();
+        }
+    }
+
+    (0..rows)
+        .map(|i| {
+            (0..cols)
+                .map(|j| {
+                    let g = group(i, j);
+                    if log {
+                        A[i][j] - max[g] - sum[g].ln()
+                    } else {
+                        (A[i][j] - max[g]).exp() / sum[g]
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -3,9 +3,14 @@ use clap::Parser;
 use core::panic;
 use obpmark_library::BaseMatrix;
 #[cfg(not(feature = "int"))]
-use obpmark_library::WaveletTransformFloating;
+use obpmark_library::{
+    parallel_traits::{ParallelInverseWaveletTransformFloating, ParallelWaveletTransformFloating},
+    rayon_traits::{RayonInverseWaveletTransformFloating, RayonWaveletTransformFloating},
+    InverseWaveletTransformFloating, WaveletTransformFloating,
+};
 use reference_algorithms::ccsds_wavelet_transform;
 #[cfg(not(feature = "int"))]
+#[allow(clippy::excessive_precision)]
 mod constants {
     use benchmarks::benchmark_utils::Number;
     pub const LOW_PASS_FILTER_SIZE: usize = 9;
@@ -30,12 +35,39 @@ mod constants {
         0.040689417609,
         -0.064538882629,
     ];
+    /// Synthesis filters of the inverse transform, the analysis ones modulated by (-1)^(n + 1)
+    pub const SYNTHESIS_LOW_PASS_FILTER: [Number; HIGH_PASS_FILTER_SIZE] = [
+        -0.064538882629,
+        -0.040689417609,
+        0.418092273222,
+        0.788485616406,
+        0.418092273222,
+        -0.040689417609,
+        -0.064538882629,
+    ];
+    pub const SYNTHESIS_HIGH_PASS_FILTER: [Number; LOW_PASS_FILTER_SIZE] = [
+        -0.037828455507,
+        -0.023849465020,
+        0.110624404418,
+        0.377402855613,
+        -0.852698679009,
+        0.377402855613,
+        0.110624404418,
+        -0.023849465020,
+        -0.037828455507,
+    ];
 }
 #[cfg(feature = "int")]
-use obpmark_library::WaveletTransformInteger;
+use obpmark_library::{
+    parallel_traits::{ParallelInverseWaveletTransformInteger, ParallelWaveletTransformInteger},
+    rayon_traits::{RayonInverseWaveletTransformInteger, RayonWaveletTransformInteger},
+    InverseWaveletTransformInteger, WaveletTransformInteger,
+};
 use std::{path::Path, time::Instant};
 
-use benchmarks::benchmark_utils::{CommonArgs, Matrix, Number};
+#[cfg(not(feature = "int"))]
+use benchmarks::benchmark_utils::verify_toll;
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
 use benchmarks::{number, verify};
 use obpmark_library::matrix_1d::Matrix1d as RefMatrix;
 
@@ -44,6 +76,11 @@ use obpmark_library::matrix_1d::Matrix1d as RefMatrix;
 struct Args {
     #[clap(flatten)]
     common: CommonArgs,
+
+    /// Computes the inverse transform of the coefficients of the input, the forward transform
+    /// is not timed and the result is verified against the input
+    #[arg(long)]
+    inverse: bool,
 }
 
 fn main() {
@@ -52,7 +89,7 @@ fn main() {
     let A;
     let mut B;
 
-    match args.common.input {
+    match &args.common.input {
         Some(v) => {
             if v.len() != 1 {
                 panic!("Expected 1 input files, got {}", v.len());
@@ -77,25 +114,131 @@ fn main() {
 
     B = Matrix::zeroes(1, args.common.size);
 
-    let t0 = Instant::now();
-
-    #[cfg(feature = "int")]
-    A.wavelet_transform(&mut B, args.common.size / 2).unwrap();
-    #[cfg(not(feature = "int"))]
-    A.wavelet_transform(
-        &mut B,
-        args.common.size / 2,
-        &constants::LOW_PASS_FILTER,
-        constants::LOW_PASS_FILTER_SIZE,
-        &constants::HIGH_PASS_FILTER,
-        constants::HIGH_PASS_FILTER_SIZE,
-    )
-    .unwrap();
-    let t1 = Instant::now();
-
-    if args.common.timing {
-        println!("Elapsed: {:.2?}", t1 - t0);
-    }
+    let forward = |input: &Matrix, output: &mut Matrix| {
+        #[cfg(feature = "int")]
+        input
+            .wavelet_transform(output, args.common.size / 2)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        input
+            .wavelet_transform(
+                output,
+                args.common.size / 2,
+                &constants::LOW_PASS_FILTER,
+                constants::LOW_PASS_FILTER_SIZE,
+                &constants::HIGH_PASS_FILTER,
+                constants::HIGH_PASS_FILTER_SIZE,
+            )
+            .unwrap();
+    };
+
+    let parallel_forward = |output: &mut Matrix, n_threads: usize| {
+        #[cfg(feature = "int")]
+        A.parallel_wavelet_transform(output, args.common.size / 2, n_threads)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        A.parallel_wavelet_transform(
+            output,
+            args.common.size / 2,
+            &constants::LOW_PASS_FILTER,
+            &constants::HIGH_PASS_FILTER,
+            n_threads,
+        )
+        .unwrap();
+    };
+
+    let rayon_forward = |output: &mut Matrix| {
+        #[cfg(feature = "int")]
+        A.rayon_wavelet_transform(output, args.common.size / 2)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        A.rayon_wavelet_transform(
+            output,
+            args.common.size / 2,
+            &constants::LOW_PASS_FILTER,
+            &constants::HIGH_PASS_FILTER,
+        )
+        .unwrap();
+    };
+
+    let inverse = |C: &Matrix, output: &mut Matrix| {
+        #[cfg(feature = "int")]
+        C.inverse_wavelet_transform(output, args.common.size / 2)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        C.inverse_wavelet_transform(
+            output,
+            args.common.size / 2,
+            &constants::SYNTHESIS_LOW_PASS_FILTER,
+            &constants::SYNTHESIS_HIGH_PASS_FILTER,
+        )
+        .unwrap();
+    };
+
+    let parallel_inverse = |C: &Matrix, output: &mut Matrix, n_threads: usize| {
+        #[cfg(feature = "int")]
+        C.parallel_inverse_wavelet_transform(output, args.common.size / 2, n_threads)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        C.parallel_inverse_wavelet_transform(
+            output,
+            args.common.size / 2,
+            &constants::SYNTHESIS_LOW_PASS_FILTER,
+            &constants::SYNTHESIS_HIGH_PASS_FILTER,
+            n_threads,
+        )
+        .unwrap();
+    };
+
+    let rayon_inverse = |C: &Matrix, output: &mut Matrix| {
+        #[cfg(feature = "int")]
+        C.rayon_inverse_wavelet_transform(output, args.common.size / 2)
+            .unwrap();
+        #[cfg(not(feature = "int"))]
+        C.rayon_inverse_wavelet_transform(
+            output,
+            args.common.size / 2,
+            &constants::SYNTHESIS_LOW_PASS_FILTER,
+            &constants::SYNTHESIS_HIGH_PASS_FILTER,
+        )
+        .unwrap();
+    };
+
+    let coefficients = match args.inverse {
+        true => {
+            let mut C = Matrix::zeroes(1, args.common.size);
+            forward(&A, &mut C);
+            Some(C)
+        }
+        false => None,
+    };
+
+    args.common.run_kernel(&mut B, |B| {
+        match (
+            &coefficients,
+            args.common.nthreads,
+            &args.common.implementation,
+        ) {
+            (_, Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (None, _, Implementation::Sequential) => forward(&A, B),
+            (None, None, Implementation::Rayon) => rayon_forward(B),
+            (Some(C), _, Implementation::Sequential) => inverse(C, B),
+            (Some(C), None, Implementation::Rayon) => rayon_inverse(C, B),
+            (coefficients, n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                let n = n.unwrap_or(8);
+                match coefficients {
+                    None => parallel_forward(B, n),
+                    Some(C) => parallel_inverse(C, B, n),
+                }
+            }
+        }
+    });
 
     if args.common.output {
         println!("Output:");
@@ -121,6 +264,13 @@ fn main() {
                 println!("Verification failed");
             }
         }
+        Some(None) if args.inverse => {
+            // the integer transform is lossless, the floating one only has rounding errors
+            #[cfg(feature = "int")]
+            verify!(B.get_data(), A.get_data());
+            #[cfg(not(feature = "int"))]
+            verify_toll(&B.get_data(), &A.get_data(), number!("1e-4"));
+        }
         Some(None) => {
             // verify against cpu implementation
             let B_ref = get_ref_result(A, args.common.size);
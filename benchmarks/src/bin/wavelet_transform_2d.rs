@@ -0,0 +1,186 @@
+#![allow(non_snake_case)]
+use clap::Parser;
+use core::panic;
+use obpmark_library::BaseMatrix;
+#[cfg(not(feature = "int"))]
+use obpmark_library::{
+    parallel_traits::ParallelWaveletTransform2dFloating,
+    rayon_traits::RayonWaveletTransform2dFloating, WaveletTransform2dFloating,
+};
+#[cfg(feature = "int")]
+use obpmark_library::{
+    parallel_traits::ParallelWaveletTransform2dInteger,
+    rayon_traits::RayonWaveletTransform2dInteger, WaveletTransform2dInteger,
+};
+use reference_algorithms::ccsds_wavelet_transform;
+use std::{path::Path, time::Instant};
+
+use benchmarks::benchmark_utils::{CommonArgs, Implementation, Matrix, Number};
+use benchmarks::{number, verify};
+use obpmark_library::matrix_2d::Matrix2d as RefMatrix;
+
+#[cfg(feature = "half")]
+compile_error!("This benchmark is not supported for half, the reference implementation is in f32");
+
+#[cfg(not(feature = "int"))]
+#[allow(clippy::excessive_precision)]
+mod constants {
+    use benchmarks::benchmark_utils::Number;
+    pub const LOW_PASS_FILTER: [Number; 9] = [
+        0.037828455507,
+        -0.023849465020,
+        -0.110624404418,
+        0.377402855613,
+        0.852698679009,
+        0.377402855613,
+        -0.110624404418,
+        -0.023849465020,
+        0.037828455507,
+    ];
+    pub const HIGH_PASS_FILTER: [Number; 7] = [
+        -0.064538882629,
+        0.040689417609,
+        0.418092273222,
+        -0.788485616406,
+        0.418092273222,
+        0.040689417609,
+        -0.064538882629,
+    ];
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "2D multi level wavelet transform benchmark")]
+struct Args {
+    /// The input is a size x size image
+    #[clap(flatten)]
+    common: CommonArgs,
+
+    /// Number of levels of the decomposition, CCSDS 122.0 uses 3
+    #[arg(long, default_value_t = 3)]
+    levels: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let size = args.common.size;
+
+    let A = match &args.common.input {
+        Some(v) => {
+            if v.len() != 1 {
+                panic!("Expected 1 input files, got {}", v.len());
+            }
+            Matrix::from_file(Path::new(&v[0]), size, size).unwrap()
+        }
+        // 12 bit pixels
+        None => {
+            Matrix::from_random_seed(args.common.seed, size, size, number!("0"), number!("4096"))
+        }
+    };
+
+    if args.common.print_input {
+        println!("A:");
+        println!("{}", A);
+    }
+
+    let mut B = Matrix::zeroes(size, size);
+
+    args.common.run_kernel(&mut B, |B| {
+        match (args.common.nthreads, &args.common.implementation) {
+            (Some(n), Implementation::Sequential) if n != 1 => {
+                panic!("Cannot run sequential implementation with more than 1 thread");
+            }
+            (_, Implementation::Sequential) => {
+                #[cfg(feature = "int")]
+                A.wavelet_transform_2d(B, args.levels).unwrap();
+                #[cfg(not(feature = "int"))]
+                A.wavelet_transform_2d(
+                    B,
+                    args.levels,
+                    &constants::LOW_PASS_FILTER,
+                    &constants::HIGH_PASS_FILTER,
+                )
+                .unwrap();
+            }
+            (None, Implementation::Rayon) => {
+                #[cfg(feature = "int")]
+                A.rayon_wavelet_transform_2d(B, args.levels).unwrap();
+                #[cfg(not(feature = "int"))]
+                A.rayon_wavelet_transform_2d(
+                    B,
+                    args.levels,
+                    &constants::LOW_PASS_FILTER,
+                    &constants::HIGH_PASS_FILTER,
+                )
+                .unwrap();
+            }
+            (Some(_), Implementation::Rayon) => {
+                panic!("Cannot specify number of threads for Rayon implementation")
+            }
+            (n, Implementation::StdParallel) => {
+                // TODO: change n_threads
+                let n_threads = n.unwrap_or(8);
+                #[cfg(feature = "int")]
+                A.parallel_wavelet_transform_2d(B, args.levels, n_threads)
+                    .unwrap();
+                #[cfg(not(feature = "int"))]
+                A.parallel_wavelet_transform_2d(
+                    B,
+                    args.levels,
+                    &constants::LOW_PASS_FILTER,
+                    &constants::HIGH_PASS_FILTER,
+                    n_threads,
+                )
+                .unwrap();
+            }
+        }
+    });
+
+    if args.common.output {
+        println!("Output:");
+        println!("{}", B);
+    }
+
+    if let Some(filename) = args.common.export {
+        B.to_file(Path::new(&filename)).unwrap();
+    }
+
+    match args.common.verify {
+        Some(Some(filename)) => {
+            // verify against file
+            let B_ref = Matrix::from_file(Path::new(&filename), size, size).unwrap();
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        Some(None) => {
+            // verify against the 1d reference implementation applied to rows and columns
+            let B_ref = get_ref_result(A, size, args.levels);
+            verify!(B.get_data(), B_ref.get_data());
+        }
+        None => (),
+    }
+}
+
+fn get_ref_result(A: Matrix, size: usize, levels: usize) -> RefMatrix<Number> {
+    let mut rows = A.get_data();
+
+    // TODO: this is for testing, remove
+    let t = Instant::now();
+    for level in 0..levels {
+        let n = size >> level;
+        let mut transformed = vec![number!("0"); n];
+        for row in rows.iter_mut().take(n) {
+            unsafe { ccsds_wavelet_transform(row.as_ptr(), transformed.as_mut_ptr(), n / 2) }
+            row[..n].copy_from_slice(&transformed);
+        }
+        for j in 0..n {
+            let column: Vec<Number> = rows.iter().take(n).map(|row| row[j]).collect();
+            unsafe { ccsds_wavelet_transform(column.as_ptr(), transformed.as_mut_ptr(), n / 2) }
+            for (row, el) in rows.iter_mut().zip(&transformed) {
+                row[j] = *el;
+            }
+        }
+    }
+    println!("C code: {:.2?}", t.elapsed());
+
+    RefMatrix::new(rows, size, size)
+}
@@ -1,5 +1,7 @@
 pub mod benchmark_utils {
     use clap::Parser;
+    use obpmark_library::Voting;
+    use std::time::{Duration, Instant};
 
     #[cfg(feature = "float")]
     pub type Number = f32;
@@ -53,8 +55,9 @@ pub mod benchmark_utils {
         #[arg(short, long, default_value_t = false)]
         pub timing: bool,
 
-        /// Uses "mat_A.in" ["mat_B.in"] for input data, can take 1 or 2 files
-        #[arg(short, long, num_args = 1..=2)]
+        /// Uses "mat_A.in" ["mat_B.in" ...] for input data, the number of files depends on the
+        /// benchmark
+        #[arg(short, long, num_args = 1..)]
         pub input: Option<Vec<String>>,
 
         /// Print the input matrix (or matrices) to stdout
@@ -72,6 +75,139 @@ pub mod benchmark_utils {
         /// Parallel implementation to use
         #[arg(value_enum, long, default_value_t = Implementation::Sequential)]
         pub implementation: Implementation,
+
+        /// Triple modular redundancy: runs the kernel three times and votes the results
+        #[arg(value_enum, long)]
+        pub tmr: Option<TmrExecution>,
+    }
+
+    #[derive(clap::ValueEnum, Clone, Copy, Debug)]
+    pub enum TmrExecution {
+        /// One execution after the other
+        Sequential,
+        /// Three concurrent threads
+        Concurrent,
+    }
+
+    impl CommonArgs {
+        /// Runs `kernel`, that writes in `result`, prints the elapsed time if timing and returns
+        /// it  
+        /// With --tmr the kernel is run three times, each on a copy of `result`, and the results
+        /// are voted, the voting statistics are printed and, if timing, the overhead versus a
+        /// plain execution  
+        /// `result` can be anything that is voted, e.g. a tuple of the outputs of the kernel or a
+        /// byte stream, scratch buffers belong inside the kernel
+        pub fn run_kernel<O: Voting>(
+            &self,
+            result: &mut O,
+            kernel: impl Fn(&mut O) + Sync,
+        ) -> Duration {
+            use obpmark_library::tmr::{tmr, TmrMode};
+
+            // the plain execution runs first, the inputs are in cache for both
+            let plain = match (self.tmr, self.timing) {
+                (Some(_), true) => {
+                    let mut plain = result.clone();
+                    let t = Instant::now();
+                    kernel(&mut plain);
+                    Some(t.elapsed())
+                }
+                _ => None,
+            };
+
+            let t0 = Instant::now();
+            let report = match self.tmr {
+                Some(execution) => {
+                    let mode = match execution {
+                        TmrExecution::Sequential => TmrMode::Sequential,
+                        TmrExecution::Concurrent => TmrMode::Concurrent,
+                    };
+                    let kernel = |replica: &mut O| {
+                        kernel(replica);
+                        Ok(())
+                    };
+                    Some(tmr(mode, result, kernel).unwrap())
+                }
+                None => {
+                    kernel(result);
+                    None
+                }
+            };
+            let elapsed = t0.elapsed();
+
+            if self.timing {
+                println!("Elapsed: {:.2?}", elapsed);
+            }
+            if let Some(report) = report {
+                println!("TMR: {}", report);
+            }
+            if let Some(plain) = plain {
+                println!("Plain execution: {:.2?}", plain);
+                println!(
+                    "TMR overhead: {:.1}%",
+                    100.0 * (elapsed.as_secs_f64() / plain.as_secs_f64() - 1.0)
+                );
+            }
+            elapsed
+        }
+    }
+
+    /// Bits of the number type, the range of the stuck-at bit
+    const STUCK_BITS: i64 = <Number as obpmark_library::number_traits::Bits>::BITS as i64;
+
+    #[derive(clap::ValueEnum, Clone, Copy, Debug)]
+    pub enum FaultModel {
+        BitFlip,
+        StuckAt0,
+        StuckAt1,
+    }
+
+    /// Options of the fault injection campaigns
+    #[derive(Parser, Debug)]
+    pub struct FaultArgs {
+        /// Number of fault injection runs, each one is compared against a fault free run
+        #[arg(long, default_value_t = 0)]
+        pub injections: usize,
+
+        /// Fault model
+        #[arg(value_enum, long, default_value_t = FaultModel::BitFlip)]
+        pub fault: FaultModel,
+
+        /// Bits flipped in each faulty element
+        #[arg(long, default_value_t = 1)]
+        pub flipped_bits: u32,
+
+        /// Bit forced by the stuck-at faults, a random one if not given, it has to be a bit of
+        /// the number type
+        #[arg(long, value_parser = clap::value_parser!(u32).range(..STUCK_BITS))]
+        pub stuck_bit: Option<u32>,
+
+        /// Faulty elements of each injection
+        #[arg(long, default_value_t = 1)]
+        pub faulty_elements: usize,
+
+        /// Random seed of the fault locations
+        #[arg(long, default_value_t = 1)]
+        pub fault_seed: u64,
+    }
+
+    impl FaultArgs {
+        pub fn fault(&self) -> obpmark_library::fault_injection::Fault {
+            use obpmark_library::fault_injection::Fault;
+            match self.fault {
+                FaultModel::BitFlip => Fault::BitFlip {
+                    bits: self.flipped_bits,
+                },
+                FaultModel::StuckAt0 => Fault::StuckAt {
+                    bit: self.stuck_bit,
+                    value: false,
+                },
+                FaultModel::StuckAt1 => Fault::StuckAt {
+                    bit: self.stuck_bit,
+                    value: true,
+                },
+            }
+        }
     }
 
     #[macro_export]
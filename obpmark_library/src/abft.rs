@@ -0,0 +1,81 @@
+//! Checks of the products of checksummed operands (see `AbftMatMul`)
+//! The product is passed as a slice of rows, the last row and the last column are the checksums
+//! The checks are O(n^2), they are done sequentially after the (parallel) multiplication
+use crate::number_traits::Number;
+use crate::AbftReport;
+
+/// Sums of the columns of a matrix given by its rows
+pub(crate) fn column_sums<T: Number>(rows: &[&[T]]) -> Vec<T> {
+    let mut sums = vec![T::zero(); rows.first().map_or(0, |row| row.len())];
+    for row in rows {
+        for (sum, el) in sums.iter_mut().zip(*row) {
+            *sum += *el;
+        }
+    }
+    sums
+}
+
+/// The sum of `values` matches `checksum` if they differ by at most `tolerance` times the sum of
+/// their absolute values, a value that is not finite never matches
+fn matches<T: Number>(values: impl Iterator<Item = T>, checksum: T, tolerance: f64) -> bool {
+    let checksum: f64 = checksum.as_();
+    let (sum, magnitude) = values.fold((0.0, checksum.abs()), |(sum, magnitude), el| {
+        let el: f64 = el.as_();
+        (sum + el, magnitude + el.abs())
+    });
+    (sum - checksum).abs() <= tolerance * magnitude
+}
+
+/// Checks the rows and the columns of `product` against their checksums, a single wrong element
+/// (possibly a checksum) is at the crossing of the only row and the only column that do not
+/// match, it is recomputed from the checksum of its column
+pub(crate) fn verify<T: Number>(product: &mut [&mut [T]], tolerance: f64) -> AbftReport {
+    let (rows, cols) = (product.len() - 1, product[0].len() - 1);
+    let row_matches = |product: &[&mut [T]], i: usize| {
+        matches(
+            product[i][..cols].iter().copied(),
+            product[i][cols],
+            tolerance,
+        )
+    };
+    let column_matches = |product: &[&mut [T]], j: usize| {
+        matches(
+            product[..rows].iter().map(|row| row[j]),
+            product[rows][j],
+            tolerance,
+        )
+    };
+
+    let wrong_rows: Vec<usize> = (0..=rows).filter(|&i| !row_matches(product, i)).collect();
+    let wrong_cols: Vec<usize> = (0..=cols)
+        .filter(|&j| !column_matches(product, j))
+        .collect();
+    let mut report = AbftReport {
+        detected: wrong_rows.len().max(wrong_cols.len()),
+        corrected: 0,
+    };
+
+    if let ([i], [j]) = (wrong_rows.as_slice(), wrong_cols.as_slice()) {
+        let (i, j) = (*i, *j);
+        let sum: f64 = (0..rows)
+            .filter(|&r| r != i)
+            .map(|r| -> f64 { product[r][j].as_() })
+            .sum();
+        let value = if i == rows {
+            sum
+        } else {
+            let checksum: f64 = product[rows][j].as_();
+            checksum - sum
+        };
+        if let Some(value) = T::from_f64(value) {
+            let wrong = product[i][j];
+            product[i][j] = value;
+            if row_matches(product, i) && column_matches(product, j) {
+                report.corrected = 1;
+            } else {
+                product[i][j] = wrong;
+            }
+        }
+    }
+    report
+}
@@ -0,0 +1,412 @@
+//! AES (FIPS-197) with 128, 192 and 256 bit keys
+//! The S-boxes are computed at compile time from the inverses in GF(2^8) and the affine
+//! transformation, the state is stored column major like in the standard
+use crate::parallel_traits::ParallelBlockCipher;
+use crate::rayon_traits::RayonBlockCipher;
+use crate::{BlockCipher, Error};
+
+use rayon::prelude::*;
+use std::thread;
+
+pub const BLOCK_SIZE: usize = 16;
+
+/// Multiplication in GF(2^8) with the AES polynomial x^8 + x^4 + x^3 + x + 1
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplication by x
+const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+const fn sbox() -> [u8; 256] {
+    let mut sbox = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        // the inverse is a^254, 0 is mapped to itself
+        let mut inverse = 1;
+        let mut n = 0;
+        while n < 254 {
+            inverse = gf_mul(inverse, i as u8);
+            n += 1;
+        }
+        let b = if i == 0 { 0 } else { inverse };
+        sbox[i] =
+            b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+        i += 1;
+    }
+    sbox
+}
+
+const fn inverse_sbox(sbox: &[u8; 256]) -> [u8; 256] {
+    let mut inverse = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inverse[sbox[i] as usize] = i as u8;
+        i += 1;
+    }
+    inverse
+}
+
+const SBOX: [u8; 256] = sbox();
+const INVERSE_SBOX: [u8; 256] = inverse_sbox(&SBOX);
+
+/// AES cipher with the expanded key
+#[derive(Clone, Debug)]
+pub struct Aes {
+    round_keys: Vec<[u8; BLOCK_SIZE]>,
+}
+
+impl Aes {
+    /// Key expansion, the key has to be of 16, 24 or 32 bytes
+    pub fn new(key: &[u8]) -> Result<Self, Error> {
+        let nk = key.len() / 4;
+        if ![16, 24, 32].contains(&key.len()) {
+            return Err(Error::UnsupportedSize);
+        }
+        let rounds = nk + 6;
+
+        let mut words: Vec<[u8; 4]> = key
+            .chunks(4)
+            .map(|word| [word[0], word[1], word[2], word[3]])
+            .collect();
+        let mut rcon = 1;
+        for i in nk..4 * (rounds + 1) {
+            let mut temp = words[i - 1];
+            if i % nk == 0 {
+                temp.rotate_left(1);
+                temp = temp.map(|byte| SBOX[byte as usize]);
+                temp[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|byte| SBOX[byte as usize]);
+            }
+            let word = words[i - nk];
+            words.push([0, 1, 2, 3].map(|j| word[j] ^ temp[j]));
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|key| {
+                let mut round_key = [0; BLOCK_SIZE];
+                for (j, word) in key.iter().enumerate() {
+                    round_key[4 * j..4 * j + 4].copy_from_slice(word);
+                }
+                round_key
+            })
+            .collect();
+        Ok(Aes { round_keys })
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+}
+
+fn add_round_key(state: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    state
+        .iter_mut()
+        .zip(round_key)
+        .for_each(|(byte, key)| *byte ^= key);
+}
+
+fn sub_bytes(state: &mut [u8; BLOCK_SIZE], sbox: &[u8; 256]) {
+    state
+        .iter_mut()
+        .for_each(|byte| *byte = sbox[*byte as usize]);
+}
+
+/// Row `r` is rotated left by `r` positions
+fn shift_rows(state: &mut [u8; BLOCK_SIZE]) {
+    let old = *state;
+    for r in 1..4 {
+        for c in 0..4 {
+            state[r + 4 * c] = old[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+fn inverse_shift_rows(state: &mut [u8; BLOCK_SIZE]) {
+    let old = *state;
+    for r in 1..4 {
+        for c in 0..4 {
+            state[r + 4 * ((c + r) % 4)] = old[r + 4 * c];
+        }
+    }
+}
+
+/// Multiplies every column by the circulant matrix with first row 2, 3, 1, 1
+fn mix_columns(state: &mut [u8; BLOCK_SIZE]) {
+    for column in state.chunks_exact_mut(4) {
+        let a = [column[0], column[1], column[2], column[3]];
+        let sum = a[0] ^ a[1] ^ a[2] ^ a[3];
+        for (r, el) in column.iter_mut().enumerate() {
+            *el = a[r] ^ sum ^ xtime(a[r] ^ a[(r + 1) % 4]);
+        }
+    }
+}
+
+/// The inverse matrix (first row 14, 11, 13, 9) is the forward one times the one with first row
+/// 5, 0, 4, 0
+fn inverse_mix_columns(state: &mut [u8; BLOCK_SIZE]) {
+    for column in state.chunks_exact_mut(4) {
+        let u = xtime(xtime(column[0] ^ column[2]));
+        let v = xtime(xtime(column[1] ^ column[3]));
+        column[0] ^= u;
+        column[1] ^= v;
+        column[2] ^= u;
+        column[3] ^= v;
+    }
+    mix_columns(state);
+}
+
+impl BlockCipher for Aes {
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let rounds = self.rounds();
+        add_round_key(block, &self.round_keys[0]);
+        for round in 1..=rounds {
+            sub_bytes(block, &SBOX);
+            shift_rows(block);
+            if round != rounds {
+                mix_columns(block);
+            }
+            add_round_key(block, &self.round_keys[round]);
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        let rounds = self.rounds();
+        add_round_key(block, &self.round_keys[rounds]);
+        for round in (0..rounds).rev() {
+            inverse_shift_rows(block);
+            sub_bytes(block, &INVERSE_SBOX);
+            add_round_key(block, &self.round_keys[round]);
+            if round != 0 {
+                inverse_mix_columns(block);
+            }
+        }
+    }
+
+    fn ecb_encrypt(&self, data: &mut [u8]) -> Result<(), Error> {
+        if !data.len().is_multiple_of(BLOCK_SIZE) {
+            return Err(Error::InvalidDimensions);
+        }
+        data.chunks_exact_mut(BLOCK_SIZE)
+            .for_each(|block| self.encrypt_block(block.try_into().unwrap()));
+        Ok(())
+    }
+
+    fn ecb_decrypt(&self, data: &mut [u8]) -> Result<(), Error> {
+        if !data.len().is_multiple_of(BLOCK_SIZE) {
+            return Err(Error::InvalidDimensions);
+        }
+        data.chunks_exact_mut(BLOCK_SIZE)
+            .for_each(|block| self.decrypt_block(block.try_into().unwrap()));
+        Ok(())
+    }
+
+    fn ctr_block(&self, counter: &[u8; BLOCK_SIZE], block_idx: usize, data: &mut [u8]) {
+        let mut keystream =
+            (u128::from_be_bytes(*counter).wrapping_add(block_idx as u128)).to_be_bytes();
+        self.encrypt_block(&mut keystream);
+        data.iter_mut()
+            .zip(keystream)
+            .for_each(|(byte, key)| *byte ^= key);
+    }
+
+    fn ctr(&self, counter: &[u8; BLOCK_SIZE], data: &mut [u8]) {
+        data.chunks_mut(BLOCK_SIZE)
+            .enumerate()
+            .for_each(|(block_idx, block)| self.ctr_block(counter, block_idx, block));
+    }
+}
+
+/// Applies `cipher` to the blocks of `data`, split in `n_threads` chunks of whole blocks
+fn parallel_ecb(
+    data: &mut [u8],
+    n_threads: usize,
+    cipher: impl Fn(&mut [u8; BLOCK_SIZE]) + Sync,
+) -> Result<(), Error> {
+    if n_threads == 0 {
+        return Err(Error::InvalidNumberOfThreads);
+    }
+    if !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(Error::InvalidDimensions);
+    }
+    if data.is_empty() {
+        return Ok(());
+    }
+    let n_blocks = data.len() / BLOCK_SIZE;
+    let blocks_per_thread = (n_blocks - 1) / n_threads + 1;
+    let cipher = &cipher;
+    thread::scope(|s| {
+        data.chunks_mut(blocks_per_thread * BLOCK_SIZE)
+            .for_each(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .chunks_exact_mut(BLOCK_SIZE)
+                        .for_each(|block| cipher(block.try_into().unwrap()));
+                });
+            });
+    });
+    Ok(())
+}
+
+/// Rayon version of `parallel_ecb`, every block is a task
+fn rayon_ecb(
+    data: &mut [u8],
+    cipher: impl Fn(&mut [u8; BLOCK_SIZE]) + Sync + Send,
+) -> Result<(), Error> {
+    if !data.len().is_multiple_of(BLOCK_SIZE) {
+        return Err(Error::InvalidDimensions);
+    }
+    data.par_chunks_exact_mut(BLOCK_SIZE)
+        .for_each(|block| cipher(block.try_into().unwrap()));
+    Ok(())
+}
+
+impl ParallelBlockCipher for Aes {
+    fn parallel_ecb_encrypt(&self, data: &mut [u8], n_threads: usize) -> Result<(), Error> {
+        parallel_ecb(data, n_threads, |block| self.encrypt_block(block))
+    }
+
+    fn parallel_ecb_decrypt(&self, data: &mut [u8], n_threads: usize) -> Result<(), Error> {
+        parallel_ecb(data, n_threads, |block| self.decrypt_block(block))
+    }
+
+    fn parallel_ctr(
+        &self,
+        counter: &[u8; BLOCK_SIZE],
+        data: &mut [u8],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        let n_blocks = data.len().div_ceil(BLOCK_SIZE);
+        let blocks_per_thread = (n_blocks - 1) / n_threads + 1;
+        thread::scope(|s| {
+            data.chunks_mut(blocks_per_thread * BLOCK_SIZE)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let first_block = chunk_idx * blocks_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(BLOCK_SIZE)
+                            .enumerate()
+                            .for_each(|(i, block)| self.ctr_block(counter, first_block + i, block));
+                    });
+                });
+        });
+        Ok(())
+    }
+}
+
+impl RayonBlockCipher for Aes {
+    fn rayon_ecb_encrypt(&self, data: &mut [u8]) -> Result<(), Error> {
+        rayon_ecb(data, |block| self.encrypt_block(block))
+    }
+
+    fn rayon_ecb_decrypt(&self, data: &mut [u8]) -> Result<(), Error> {
+        rayon_ecb(data, |block| self.decrypt_block(block))
+    }
+
+    fn rayon_ctr(&self, counter: &[u8; BLOCK_SIZE], data: &mut [u8]) {
+        data.par_chunks_mut(BLOCK_SIZE)
+            .enumerate()
+            .for_each(|(block_idx, block)| self.ctr_block(counter, block_idx, block));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        let hex = |s: &str| -> Vec<u8> {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect()
+        };
+
+        // FIPS-197 appendix C
+        let plaintext = hex("00112233445566778899aabbccddeeff");
+        for (key_bytes, ciphertext) in [
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            let key: Vec<u8> = (0..key_bytes as u8).collect();
+            let aes = Aes::new(&key).unwrap();
+            assert_eq!(aes.rounds(), key_bytes / 4 + 6);
+            let mut data = plaintext.clone();
+            aes.ecb_encrypt(&mut data).unwrap();
+            assert_eq!(data, hex(ciphertext));
+            aes.ecb_decrypt(&mut data).unwrap();
+            assert_eq!(data, plaintext);
+        }
+
+        // SP 800-38A F.5.1, CTR-AES128.Encrypt
+        let aes = Aes::new(&hex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        let counter: [u8; 16] = hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").try_into().unwrap();
+        let plaintext = hex(concat!(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+            "30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710"
+        ));
+        let ciphertext = hex(concat!(
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff",
+            "5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+        ));
+        let mut data = plaintext.clone();
+        aes.ctr(&counter, &mut data);
+        assert_eq!(data, ciphertext);
+
+        // the parallel implementations split the data at block boundaries
+        let mut data = plaintext[..60].to_vec();
+        aes.parallel_ctr(&counter, &mut data, 3).unwrap();
+        assert_eq!(data, ciphertext[..60]);
+        let mut data = plaintext[..60].to_vec();
+        aes.rayon_ctr(&counter, &mut data);
+        assert_eq!(data, ciphertext[..60]);
+
+        // SP 800-38A F.1.1, ECB-AES128.Encrypt
+        let ciphertext = hex(concat!(
+            "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf",
+            "43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4"
+        ));
+        let mut data = plaintext.clone();
+        aes.ecb_encrypt(&mut data).unwrap();
+        assert_eq!(data, ciphertext);
+        for n_threads in [1, 3, 8] {
+            let mut data = plaintext.clone();
+            aes.parallel_ecb_encrypt(&mut data, n_threads).unwrap();
+            assert_eq!(data, ciphertext);
+            aes.parallel_ecb_decrypt(&mut data, n_threads).unwrap();
+            assert_eq!(data, plaintext);
+        }
+        let mut data = plaintext.clone();
+        aes.rayon_ecb_encrypt(&mut data).unwrap();
+        assert_eq!(data, ciphertext);
+        aes.rayon_ecb_decrypt(&mut data).unwrap();
+        assert_eq!(data, plaintext);
+        assert!(aes.parallel_ecb_encrypt(&mut data, 0).is_err());
+        assert!(aes.rayon_ecb_encrypt(&mut [0; 20]).is_err());
+
+        assert!(Aes::new(&[0; 20]).is_err());
+        assert!(aes.ecb_encrypt(&mut [0; 20]).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! Bit level writer and reader used by the CCSDS compressors, bits are packed MSB first
+
+/// Writes bits MSB first, an optional limit drops the bits past it
+#[derive(Debug, Default)]
+pub(crate) struct BitWriter {
+    data: Vec<u8>,
+    bits: usize,
+    limit: Option<usize>,
+    overflow: bool,
+}
+
+impl BitWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bits written
+    pub(crate) fn len(&self) -> usize {
+        self.bits
+    }
+
+    /// Bits past `limit` (absolute position in bits) are dropped, `None` removes the limit
+    pub(crate) fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.overflow = false;
+    }
+
+    /// Returns true if some bits were dropped since the last `set_limit`
+    pub(crate) fn overflow(&self) -> bool {
+        self.overflow
+    }
+
+    pub(crate) fn write_bit(&mut self, bit: bool) {
+        if self.limit.is_some_and(|limit| self.bits >= limit) {
+            self.overflow = true;
+            return;
+        }
+        if self.bits & 7 == 0 {
+            self.data.push(0);
+        }
+        if bit {
+            *self.data.last_mut().unwrap() |= 0x80 >> (self.bits & 7);
+        }
+        self.bits += 1;
+    }
+
+    /// Writes the `n` least significant bits of `value`
+    pub(crate) fn write_bits(&mut self, value: u64, n: u32) {
+        for i in (0..n).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    /// Fundamental sequence codeword of `value`: `value` zeroes followed by a one
+    pub(crate) fn write_unary(&mut self, value: u64) {
+        for _ in 0..value {
+            self.write_bit(false);
+        }
+        self.write_bit(true);
+    }
+
+    /// Writes the bits of `other`
+    pub(crate) fn append(&mut self, other: &BitWriter) {
+        let full_bytes = other.bits / 8;
+        other.data[..full_bytes]
+            .iter()
+            .for_each(|byte| self.write_bits(*byte as u64, 8));
+        if other.bits & 7 != 0 {
+            let rest = (other.bits & 7) as u32;
+            self.write_bits((other.data[full_bytes] >> (8 - rest)) as u64, rest);
+        }
+    }
+
+    /// Pads with zeroes up to the next byte
+    pub(crate) fn align(&mut self) {
+        while self.bits & 7 != 0 {
+            self.write_bit(false);
+        }
+    }
+
+    /// Pads with zeroes up to `bits`
+    pub(crate) fn fill(&mut self, bits: usize) {
+        while self.bits < bits {
+            self.write_bit(false);
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Reads bits MSB first, the reads past the end return `None`
+#[derive(Debug)]
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    end: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            position: 0,
+            end: data.len() * 8,
+        }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn set_position(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// Moves the end to `end` bits, without going past the end of the data
+    pub(crate) fn set_end(&mut self, end: usize) {
+        self.end = end.min(self.data.len() * 8);
+    }
+
+    pub(crate) fn read_bit(&mut self) -> Option<bool> {
+        if self.position >= self.end {
+            return None;
+        }
+        let bit = self.data[self.position >> 3] & (0x80 >> (self.position & 7)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    pub(crate) fn read_bits(&mut self, n: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..n {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Some(value)
+    }
+
+    /// Reads a fundamental sequence codeword, see `BitWriter::write_unary`
+    pub(crate) fn read_unary(&mut self) -> Option<u64> {
+        let mut value = 0;
+        while !self.read_bit()? {
+            value += 1;
+        }
+        Some(value)
+    }
+
+    /// Skips to the next byte
+    pub(crate) fn align(&mut self) {
+        self.position = (self.position + 7) & !7;
+    }
+}
+
+/// Range of the `n` bit values
+pub(crate) fn value_range(n: u32, signed: bool) -> (i64, i64) {
+    match signed {
+        true => (-(1 << (n - 1)), (1 << (n - 1)) - 1),
+        false => (0, (1 << n) - 1),
+    }
+}
+
+/// Maps the difference between `value` and its prediction `previous` to a non negative integer,
+/// the small differences of both signs are interleaved (CCSDS 121.0 and 122.0 mapper)
+pub(crate) fn map_difference(value: i64, previous: i64, (min, max): (i64, i64)) -> u64 {
+    let delta = value - previous;
+    let theta = (previous - min).min(max - previous);
+    if 0 <= delta && delta <= theta {
+        2 * delta as u64
+    } else if -theta <= delta && delta < 0 {
+        2 * delta.unsigned_abs() - 1
+    } else {
+        (theta + delta.abs()) as u64
+    }
+}
+
+/// Inverse of `map_difference`
+pub(crate) fn unmap_difference(mapped: u64, previous: i64, (min, max): (i64, i64)) -> i64 {
+    let theta = (previous - min).min(max - previous);
+    let mapped = mapped as i64;
+    if mapped <= 2 * theta {
+        match mapped & 1 {
+            0 => previous + mapped / 2,
+            _ => previous - (mapped + 1) / 2,
+        }
+    } else if previous - min < max - previous {
+        previous + mapped - theta
+    } else {
+        previous - (mapped - theta)
+    }
+}
@@ -0,0 +1,176 @@
+//! Calibration steps of the raw frames of an imager: offset, gain and bad pixel correction,
+//! radiation scrubbing against the neighbouring frames, spatial binning and co-adding
+use crate::number_traits::Number;
+use crate::Error;
+
+/// Rows and columns of a frame given by its rows
+fn dimensions<T: Number, R: AsRef<[T]>>(rows: &[R]) -> (usize, usize) {
+    (rows.len(), rows.first().map_or(0, |row| row.as_ref().len()))
+}
+
+fn check_dimensions<T: Number>(frame: &[&[T]], other: &[&[T]]) -> Result<(), Error> {
+    if dimensions(frame) != dimensions(other) {
+        return Err(Error::InvalidDimensions);
+    }
+    Ok(())
+}
+
+fn check_result<T: Number>(result: &[&mut [T]], (rows, cols): (usize, usize)) -> Result<(), Error> {
+    if dimensions(result) != (rows, cols) {
+        return Err(Error::InvalidDimensions);
+    }
+    Ok(())
+}
+
+fn absolute_difference<T: Number>(a: T, b: T) -> T {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Subtracts the dark frame `offsets`
+pub(crate) fn offset_correction<T: Number>(
+    frame: &[&[T]],
+    offsets: &[&[T]],
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    check_dimensions(frame, offsets)?;
+    check_result(result, dimensions(frame))?;
+    for_each_row(result, &|i, row| {
+        for (j, el) in row.iter_mut().enumerate() {
+            *el = frame[i][j] - offsets[i][j];
+        }
+    });
+    Ok(())
+}
+
+/// Multiplies by the flat field `gains`
+pub(crate) fn gain_correction<T: Number>(
+    frame: &[&[T]],
+    gains: &[&[T]],
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    check_dimensions(frame, gains)?;
+    check_result(result, dimensions(frame))?;
+    for_each_row(result, &|i, row| {
+        for (j, el) in row.iter_mut().enumerate() {
+            *el = frame[i][j] * gains[i][j];
+        }
+    });
+    Ok(())
+}
+
+/// The pixels with a non zero value in `bad_pixels` are replaced by the mean of the good ones
+/// of their 3x3 neighbourhood, they are kept if there are none
+pub(crate) fn bad_pixel_correction<T: Number>(
+    frame: &[&[T]],
+    bad_pixels: &[&[T]],
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    check_dimensions(frame, bad_pixels)?;
+    let (rows, cols) = dimensions(frame);
+    check_result(result, (rows, cols))?;
+    for_each_row(result, &|i, row| {
+        for (j, el) in row.iter_mut().enumerate() {
+            *el = frame[i][j];
+            if bad_pixels[i][j] == T::zero() {
+                continue;
+            }
+            let mut sum = T::zero();
+            let mut count = 0;
+            for y in i.saturating_sub(1)..(i + 2).min(rows) {
+                for x in j.saturating_sub(1)..(j + 2).min(cols) {
+                    if bad_pixels[y][x] == T::zero() {
+                        sum += frame[y][x];
+                        count += 1;
+                    }
+                }
+            }
+            if count > 0 {
+                *el = sum / T::from_usize(count).unwrap();
+            }
+        }
+    });
+    Ok(())
+}
+
+/// The pixels that differ by more than `threshold` from the mean of the same pixel in the
+/// `neighbours` frames are considered radiation hits and replaced by the mean
+pub(crate) fn radiation_scrubbing<T: Number>(
+    frame: &[&[T]],
+    neighbours: &[Vec<&[T]>],
+    threshold: T,
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    if neighbours.is_empty() {
+        return Err(Error::InvalidDimensions);
+    }
+    for neighbour in neighbours {
+        check_dimensions(frame, neighbour)?;
+    }
+    check_result(result, dimensions(frame))?;
+    let count = T::from_usize(neighbours.len()).unwrap();
+    for_each_row(result, &|i, row| {
+        for (j, el) in row.iter_mut().enumerate() {
+            let mut sum = T::zero();
+            for neighbour in neighbours {
+                sum += neighbour[i][j];
+            }
+            let mean = sum / count;
+            *el = if absolute_difference(frame[i][j], mean) > threshold {
+                mean
+            } else {
+                frame[i][j]
+            };
+        }
+    });
+    Ok(())
+}
+
+/// Sums the blocks of `factor` x `factor` pixels, the dimensions of the frame have to be
+/// multiples of `factor`
+pub(crate) fn spatial_binning<T: Number>(
+    frame: &[&[T]],
+    factor: usize,
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    let (rows, cols) = dimensions(frame);
+    if factor == 0 || !rows.is_multiple_of(factor) || !cols.is_multiple_of(factor) {
+        return Err(Error::InvalidKernelDimensions);
+    }
+    check_result(result, (rows / factor, cols / factor))?;
+    for_each_row(result, &|i, row| {
+        for (j, el) in row.iter_mut().enumerate() {
+            let mut sum = T::zero();
+            for frame_row in &frame[i * factor..(i + 1) * factor] {
+                for value in &frame_row[j * factor..(j + 1) * factor] {
+                    sum += *value;
+                }
+            }
+            *el = sum;
+        }
+    });
+    Ok(())
+}
+
+/// Adds the frame to `accumulator`
+pub(crate) fn co_add<T: Number>(
+    frame: &[&[T]],
+    accumulator: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    check_result(accumulator, dimensions(frame))?;
+    for_each_row(accumulator, &|i, row| {
+        for (el, value) in row.iter_mut().zip(frame[i]) {
+            *el += *value;
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! Lossless adaptive entropy coder (Rice coder) of CCSDS 121.0
+//! The samples are split in blocks of `block_size` samples, each block is coded with the option
+//! that gives the shortest output: zero block, second extension, fundamental sequence, sample
+//! splitting with `k` low bits or no compression
+//! With the preprocessor the samples are replaced by the mapped prediction errors of the unit
+//! delay predictor, the first sample of every reference interval is the reference sample and is
+//! written as it is
+//!
+//! Every reference interval starts a new byte, so that the intervals can be coded independently
+//! (this is what the parallel implementations split), and the runs of zero blocks do not cross
+//! the segments of 64 blocks counted from the start of the interval
+use crate::bitstream::{map_difference, unmap_difference, value_range, BitReader, BitWriter};
+use crate::{Error, RiceParameters};
+
+use rayon::prelude::*;
+use std::thread;
+
+/// Blocks of a segment, for the coding of the runs of zero blocks
+const SEGMENT_BLOCKS: usize = 64;
+/// Fundamental sequence codeword of a run of zero blocks reaching the end of the segment
+const REMAINDER_OF_SEGMENT: u64 = 4;
+
+impl Default for RiceParameters {
+    /// 16 bit samples, blocks of 16 samples and a reference sample every 64 blocks
+    fn default() -> Self {
+        RiceParameters {
+            sample_bits: 16,
+            block_size: 16,
+            reference_interval: 64,
+            preprocessor: true,
+            zero_block: true,
+            second_extension: true,
+        }
+    }
+}
+
+impl RiceParameters {
+    /// Number of samples of a reference interval
+    pub fn interval_samples(&self) -> usize {
+        self.block_size * self.reference_interval
+    }
+
+    /// Checks the parameters for `n_samples` samples, that have to be a whole number of blocks
+    pub(crate) fn check(&self, n_samples: usize) -> Result<(), Error> {
+        if !(1..=32).contains(&self.sample_bits)
+            || ![8, 16, 32, 64].contains(&self.block_size)
+            || !(1..=4096).contains(&self.reference_interval)
+            || n_samples == 0
+            || !n_samples.is_multiple_of(self.block_size)
+        {
+            return Err(Error::UnsupportedSize);
+        }
+        Ok(())
+    }
+
+    /// Length of the option identifier
+    fn id_bits(&self) -> u32 {
+        match self.sample_bits {
+            1..=8 => 3,
+            9..=16 => 4,
+            _ => 5,
+        }
+    }
+
+    /// Largest `k` of the sample splitting options
+    fn max_k(&self) -> u64 {
+        ((1 << self.id_bits()) - 3).min(self.sample_bits as u64 - 1)
+    }
+}
+
+/// Coding option of a block, the identifier of the sample splitting with `k` bits is `k + 1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CodeOption {
+    ZeroBlock,
+    SecondExtension,
+    Split(u64),
+    NoCompression,
+}
+
+/// Values of the second extension of a pair of mapped samples
+fn second_extension_value(a: u64, b: u64) -> u128 {
+    let sum = a as u128 + b as u128;
+    sum * (sum + 1) / 2 + b as u128
+}
+
+/// Pairs of the second extension, in the blocks with the reference sample the first value is 0
+fn second_extension_pairs(values: &[u64], reference: bool) -> Vec<u128> {
+    let padded: Vec<u64> = match reference {
+        true => std::iter::once(0).chain(values.iter().copied()).collect(),
+        false => values.to_vec(),
+    };
+    padded
+        .chunks(2)
+        .map(|pair| second_extension_value(pair[0], pair[1]))
+        .collect()
+}
+
+/// Coding option with the shortest output of the mapped `values` of a block
+fn select_option(values: &[u64], reference: bool, parameters: &RiceParameters) -> CodeOption {
+    let mut option = CodeOption::NoCompression;
+    let mut best = parameters.sample_bits as u128 * values.len() as u128;
+
+    for k in 0..=parameters.max_k() {
+        let length: u128 = values.iter().map(|v| ((v >> k) + 1 + k) as u128).sum();
+        if length < best {
+            (option, best) = (CodeOption::Split(k), length);
+        }
+    }
+    if parameters.second_extension {
+        // the longer identifier of the low entropy options
+        let length: u128 = 1 + second_extension_pairs(values, reference)
+            .iter()
+            .map(|gamma| gamma + 1)
+            .sum::<u128>();
+        if length < best {
+            option = CodeOption::SecondExtension;
+        }
+    }
+    option
+}
+
+/// Mapped prediction errors of the samples of a reference interval, the first one is the
+/// reference sample
+fn preprocess(samples: &[i32], parameters: &RiceParameters) -> Vec<u64> {
+    match parameters.preprocessor {
+        true => {
+            let range = value_range(parameters.sample_bits, false);
+            std::iter::once(samples[0] as u32 as u64)
+                .chain(samples.windows(2).map(|pair| {
+                    map_difference(pair[1] as u32 as i64, pair[0] as u32 as i64, range)
+                }))
+                .collect()
+        }
+        false => samples.iter().map(|sample| *sample as u32 as u64).collect(),
+    }
+}
+
+/// Checks the parameters and that the samples fit in `sample_bits` bits
+/// The samples are stored as `i32`, the ones of 32 bits are reinterpreted as unsigned
+fn check_samples(samples: &[i32], parameters: &RiceParameters) -> Result<(), Error> {
+    parameters.check(samples.len())?;
+    if parameters.sample_bits < 32
+        && samples
+            .iter()
+            .any(|sample| *sample < 0 || *sample >> parameters.sample_bits != 0)
+    {
+        return Err(Error::UnsupportedSize);
+    }
+    Ok(())
+}
+
+/// Encodes the samples of whole reference intervals, the last one can be shorter
+fn encode_intervals(samples: &[i32], parameters: &RiceParameters) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    for interval in samples.chunks(parameters.interval_samples()) {
+        encode_interval(&mut writer, interval, parameters);
+        writer.align();
+    }
+    writer.into_bytes()
+}
+
+pub(crate) fn encode(samples: &[i32], parameters: &RiceParameters) -> Result<Vec<u8>, Error> {
+    check_samples(samples, parameters)?;
+    Ok(encode_intervals(samples, parameters))
+}
+
+/// Std parallel `encode`, the reference intervals are split in `n_threads` chunks whose outputs
+/// are concatenated
+pub(crate) fn parallel_encode(
+    samples: &[i32],
+    parameters: &RiceParameters,
+    n_threads: usize,
+) -> Result<Vec<u8>, Error> {
+    check_samples(samples, parameters)?;
+    if n_threads == 0 {
+        return Err(Error::InvalidNumberOfThreads);
+    }
+
+    let n_intervals = samples.len().div_ceil(parameters.interval_samples());
+    let intervals_per_thread = (n_intervals - 1) / n_threads + 1;
+    let outputs: Vec<Vec<u8>> = thread::scope(|s| {
+        let handles: Vec<_> = samples
+            .chunks(intervals_per_thread * parameters.interval_samples())
+            .map(|chunk| s.spawn(move || encode_intervals(chunk, parameters)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    Ok(outputs.concat())
+}
+
+/// Rayon `encode`, every reference interval is a task
+pub(crate) fn rayon_encode(samples: &[i32], parameters: &RiceParameters) -> Result<Vec<u8>, Error> {
+    check_samples(samples, parameters)?;
+    let outputs: Vec<Vec<u8>> = samples
+        .par_chunks(parameters.interval_samples())
+        .map(|interval| encode_intervals(interval, parameters))
+        .collect();
+    Ok(outputs.concat())
+}
+
+fn encode_interval(writer: &mut BitWriter, samples: &[i32], parameters: &RiceParameters) {
+    let n = parameters.sample_bits;
+    let id_bits = parameters.id_bits();
+    let values = preprocess(samples, parameters);
+    let blocks: Vec<&[u64]> = values.chunks(parameters.block_size).collect();
+
+    let mut block_idx = 0;
+    while block_idx < blocks.len() {
+        let reference = parameters.preprocessor && block_idx == 0;
+        let (reference_sample, block) = match reference {
+            true => (Some(blocks[0][0]), &blocks[0][1..]),
+            false => (None, blocks[block_idx]),
+        };
+
+        if parameters.zero_block && block.iter().all(|v| *v == 0) {
+            // run of zero blocks up to the end of the segment
+            let segment_end = (block_idx / SEGMENT_BLOCKS + 1) * SEGMENT_BLOCKS;
+            let run = blocks[block_idx + 1..segment_end.min(blocks.len())]
+                .iter()
+                .take_while(|block| block.iter().all(|v| *v == 0))
+                .count()
+                + 1;
+            writer.write_bits(0, id_bits + 1);
+            if let Some(sample) = reference_sample {
+                writer.write_bits(sample, n);
+            }
+            let end_of_segment = block_idx + run == segment_end.min(blocks.len());
+            match (run, end_of_segment) {
+                (1..=4, _) => writer.write_unary(run as u64 - 1),
+                (_, true) => writer.write_unary(REMAINDER_OF_SEGMENT),
+                _ => writer.write_unary(run as u64),
+            }
+            block_idx += run;
+            continue;
+        }
+
+        let option = select_option(block, reference, parameters);
+        match option {
+            CodeOption::SecondExtension => writer.write_bits(1, id_bits + 1),
+            CodeOption::Split(k) => writer.write_bits(k + 1, id_bits),
+            CodeOption::NoCompression => writer.write_bits((1 << id_bits) - 1, id_bits),
+            CodeOption::ZeroBlock => unreachable!(),
+        }
+        if let Some(sample) = reference_sample {
+            writer.write_bits(sample, n);
+        }
+        match option {
+            CodeOption::SecondExtension => second_extension_pairs(block, reference)
+                .iter()
+                .for_each(|gamma| writer.write_unary(*gamma as u64)),
+            CodeOption::Split(k) => {
+                block.iter().for_each(|v| writer.write_unary(v >> k));
+                block
+                    .iter()
+                    .for_each(|v| writer.write_bits(v & ((1 << k) - 1), k as u32));
+            }
+            _ => block.iter().for_each(|v| writer.write_bits(*v, n)),
+        }
+        block_idx += 1;
+    }
+}
+
+/// Decodes the output of `encode` into `samples`, which has to have the number of the encoded
+/// samples
+pub(crate) fn decode(
+    data: &[u8],
+    samples: &mut [i32],
+    parameters: &RiceParameters,
+) -> Result<(), Error> {
+    parameters.check(samples.len())?;
+
+    let mut reader = BitReader::new(data);
+    for interval in samples.chunks_mut(parameters.interval_samples()) {
+        decode_interval(&mut reader, interval, parameters).ok_or(Error::InvalidBitstream)?;
+        reader.align();
+    }
+    Ok(())
+}
+
+fn decode_interval(
+    reader: &mut BitReader,
+    samples: &mut [i32],
+    parameters: &RiceParameters,
+) -> Option<()> {
+    let n = parameters.sample_bits;
+    let id_bits = parameters.id_bits();
+    let n_blocks = samples.len() / parameters.block_size;
+    let mut values = Vec::with_capacity(samples.len());
+
+    while values.len() < samples.len() {
+        let block_idx = values.len() / parameters.block_size;
+        let reference = parameters.preprocessor && block_idx == 0;
+        let block_size = parameters.block_size - reference as usize;
+
+        let id = reader.read_bits(id_bits)?;
+        let option = match id {
+            0 => match reader.read_bit()? {
+                false => CodeOption::ZeroBlock,
+                true => CodeOption::SecondExtension,
+            },
+            id if id == (1 << id_bits) - 1 => CodeOption::NoCompression,
+            id if id - 1 <= parameters.max_k() => CodeOption::Split(id - 1),
+            _ => return None,
+        };
+        if reference {
+            values.push(reader.read_bits(n)?);
+        }
+
+        match option {
+            CodeOption::ZeroBlock => {
+                let segment_end = ((block_idx / SEGMENT_BLOCKS + 1) * SEGMENT_BLOCKS).min(n_blocks);
+                let run = match reader.read_unary()? {
+                    fs @ 0..=3 => fs as usize + 1,
+                    REMAINDER_OF_SEGMENT => segment_end - block_idx,
+                    fs => fs as usize,
+                };
+                if block_idx + run > segment_end {
+                    return None;
+                }
+                values.resize(
+                    values.len() + run * parameters.block_size - reference as usize,
+                    0,
+                );
+            }
+            CodeOption::SecondExtension => {
+                let mut pairs = Vec::with_capacity(parameters.block_size);
+                while pairs.len() < parameters.block_size {
+                    // the sum of the pair is the largest s with s (s + 1) / 2 <= gamma
+                    let gamma = reader.read_unary()? as u128;
+                    let mut sum = ((8 * gamma + 1) as f64).sqrt() as u128 / 2;
+                    while sum * (sum + 1) / 2 > gamma {
+                        sum -= 1;
+                    }
+                    while (sum + 1) * (sum + 2) / 2 <= gamma {
+                        sum += 1;
+                    }
+                    let b = gamma - sum * (sum + 1) / 2;
+                    pairs.extend([(sum - b) as u64, b as u64]);
+                }
+                values.extend(&pairs[reference as usize..]);
+            }
+            CodeOption::Split(k) => {
+                let start = values.len();
+                for _ in 0..block_size {
+                    values.push(reader.read_unary()? << k);
+                }
+                for v in values[start..].iter_mut() {
+                    *v |= reader.read_bits(k as u32)?;
+                }
+            }
+            CodeOption::NoCompression => {
+                for _ in 0..block_size {
+                    values.push(reader.read_bits(n)?);
+                }
+            }
+        }
+    }
+    if values.len() != samples.len() {
+        return None;
+    }
+
+    match parameters.preprocessor {
+        true => {
+            let range = value_range(n, false);
+            let mut previous = values[0] as i64;
+            samples[0] = previous as u32 as i32;
+            for (sample, value) in samples[1..].iter_mut().zip(&values[1..]) {
+                previous = unmap_difference(*value, previous, range);
+                *sample = previous as u32 as i32;
+            }
+        }
+        false => samples
+            .iter_mut()
+            .zip(&values)
+            .for_each(|(sample, value)| *sample = *value as u32 as i32),
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_matrix_data;
+
+    #[test]
+    fn known_answer() {
+        // a block coded with the sample splitting option with k = 0 (3 bit identifier 001, the
+        // reference sample 10 and the codewords of the mapped errors 2 2 0 0 0 0 0), then a
+        // zero block (0000 and the run of 1 block)
+        let parameters = RiceParameters {
+            sample_bits: 8,
+            block_size: 8,
+            reference_interval: 2,
+            ..Default::default()
+        };
+        let mut samples = vec![12; 16];
+        samples[..2].copy_from_slice(&[10, 11]);
+        let data = encode(&samples, &parameters).unwrap();
+        assert_eq!(data, [0x21, 0x44, 0xfc, 0x20]);
+
+        let mut decoded = vec![0; 16];
+        decode(&data, &mut decoded, &parameters).unwrap();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn round_trip() {
+        // noise, a ramp with long runs of zero blocks and constant data
+        let noise = random_matrix_data::<i32>(4, 1, 64 * 64, 0, 1 << 12).concat();
+        let ramp: Vec<i32> = (0..64 * 64)
+            .map(|idx: i32| idx / 1000 + (idx % 7 == 0) as i32)
+            .collect();
+        let constant = vec![100; 64 * 64];
+
+        for samples in [&noise, &ramp, &constant] {
+            for parameters in [
+                RiceParameters::default(),
+                RiceParameters {
+                    sample_bits: 12,
+                    block_size: 8,
+                    reference_interval: 100,
+                    ..Default::default()
+                },
+                RiceParameters {
+                    block_size: 64,
+                    zero_block: false,
+                    second_extension: false,
+                    ..Default::default()
+                },
+                RiceParameters {
+                    sample_bits: 32,
+                    preprocessor: false,
+                    ..Default::default()
+                },
+            ] {
+                let data = encode(samples, &parameters).unwrap();
+                let mut decoded = vec![0; 64 * 64];
+                decode(&data, &mut decoded, &parameters).unwrap();
+                assert_eq!(&decoded, samples);
+
+                assert_eq!(parallel_encode(samples, &parameters, 3).unwrap(), data);
+                assert_eq!(rayon_encode(samples, &parameters).unwrap(), data);
+            }
+        }
+
+        // the constant data is mostly zero blocks
+        let data = encode(&constant, &RiceParameters::default()).unwrap();
+        assert!(data.len() < 64);
+
+        let parameters = RiceParameters {
+            sample_bits: 8,
+            ..Default::default()
+        };
+        assert!(encode(&noise, &parameters).is_err());
+        assert!(parallel_encode(&noise, &RiceParameters::default(), 0).is_err());
+    }
+}
@@ -0,0 +1,933 @@
+//! Bit plane encoder (BPE) of CCSDS 122.0, it codes the output of the 3 level integer 2d wavelet
+//! transform
+//! The image is split in blocks of 64 coefficients: the DC coefficient of the LL3 subband and
+//! the 63 AC coefficients of its family tree (3 parents in level 3, 12 children in level 2 and 48
+//! grandchildren in level 1). Consecutive blocks are grouped in segments, which are coded
+//! independently, each one with its header:
+//! - the DC coefficients, quantized by `q`, and the bit depths of the AC coefficients of every
+//!   block are coded with differential mapping and Rice coding of gaggles of 16 values
+//! - the remaining bit planes of the DC coefficients and the AC coefficients are coded from the
+//!   most significant one, each bit plane in the stages of the standard: 0 (DC refinement),
+//!   1 (parents), 2 (children), 3 (grandchildren) and 4 (refinement of AC coefficients)
+//!
+//! Every segment starts a new byte, so that the segments can be coded independently (this is
+//! what the parallel implementations split)
+//!
+//! The words of the stages 1 to 3 are written without the optional variable length coding
+use crate::bitstream::{map_difference, unmap_difference, value_range, BitReader, BitWriter};
+use crate::{BpeParameters, Error, Subband};
+
+use rayon::prelude::*;
+use std::thread;
+
+const BLOCK_SIZE: usize = 64;
+const AC_COEFFICIENTS: usize = BLOCK_SIZE - 1;
+const GAGGLE_SIZE: usize = 16;
+/// The coefficients have to be smaller than 2^30, so that the bit depths fit in the header
+const MAX_BIT_DEPTH_AC: u32 = 30;
+/// Size in bytes of the largest header, which is the one of the first segment
+const MAX_HEADER_BYTES: usize = 3 + 1 + 5 + 3 + 3;
+
+/// AC coefficient indexes inside a block
+fn children(family: usize) -> std::ops::Range<usize> {
+    3 + 4 * family..3 + 4 * family + 4
+}
+
+fn grandchildren(family: usize) -> std::ops::Range<usize> {
+    15 + 16 * family..15 + 16 * family + 16
+}
+
+/// Grandchildren of child `child` of `family`
+fn group(family: usize, child: usize) -> std::ops::Range<usize> {
+    15 + 16 * family + 4 * child..15 + 16 * family + 4 * child + 4
+}
+
+/// Positions of the DC coefficient and of the AC coefficients of block (`i`, `j`) of a
+/// `rows` x `cols` image
+fn block_positions(rows: usize, cols: usize, i: usize, j: usize) -> [(usize, usize); BLOCK_SIZE] {
+    let mut positions = [(0, 0); BLOCK_SIZE];
+    positions[0] = (i, j);
+    for (family, subband) in [Subband::HL, Subband::LH, Subband::HH]
+        .into_iter()
+        .enumerate()
+    {
+        let parent = subband.region(rows, cols, 3);
+        positions[1 + family] = (parent.row_start + i, parent.col_start + j);
+
+        let child = subband.region(rows, cols, 2);
+        let grandchild = subband.region(rows, cols, 1);
+        for a in 0..2 {
+            for b in 0..2 {
+                let c = 2 * a + b;
+                positions[1 + children(family).start + c] =
+                    (child.row_start + 2 * i + a, child.col_start + 2 * j + b);
+                for d in 0..2 {
+                    for e in 0..2 {
+                        positions[1 + group(family, c).start + 2 * d + e] = (
+                            grandchild.row_start + 4 * i + 2 * a + d,
+                            grandchild.col_start + 4 * j + 2 * b + e,
+                        );
+                    }
+                }
+            }
+        }
+    }
+    positions
+}
+
+/// AC coefficients of a block as sign and magnitude, with the state of the bit plane coding
+#[derive(Clone, Debug)]
+struct Block {
+    dc: i64,
+    magnitude: [u32; AC_COEFFICIENTS],
+    negative: [bool; AC_COEFFICIENTS],
+    /// Bit plane in which each coefficient became significant
+    significant: [Option<u32>; AC_COEFFICIENTS],
+    bit_depth_ac: u32,
+    tran_b: bool,
+    tran_d: [bool; 3],
+    tran_g: [bool; 3],
+    tran_h: [[bool; 4]; 3],
+}
+
+impl Block {
+    fn new(dc: i64) -> Self {
+        Block {
+            dc,
+            magnitude: [0; AC_COEFFICIENTS],
+            negative: [false; AC_COEFFICIENTS],
+            significant: [None; AC_COEFFICIENTS],
+            bit_depth_ac: 0,
+            tran_b: false,
+            tran_d: [false; 3],
+            tran_g: [false; 3],
+            tran_h: [[false; 4]; 3],
+        }
+    }
+
+    fn any_significant(&self, mut indexes: impl Iterator<Item = usize>, bit_plane: u32) -> bool {
+        indexes.any(|x| self.magnitude[x] >> bit_plane != 0)
+    }
+}
+
+/// Encoder and decoder share the bit plane coding: the encoder computes and writes each bit,
+/// the decoder reads it. `None` means that the segment ended, because of the byte limit
+trait Coder {
+    fn bit(&mut self, value: impl FnOnce() -> bool) -> Option<bool>;
+}
+
+struct Encoder<'a> {
+    writer: &'a mut BitWriter,
+}
+
+impl Coder for Encoder<'_> {
+    fn bit(&mut self, value: impl FnOnce() -> bool) -> Option<bool> {
+        let bit = value();
+        self.writer.write_bit(bit);
+        match self.writer.overflow() {
+            true => None,
+            false => Some(bit),
+        }
+    }
+}
+
+struct Decoder<'a, 'b> {
+    reader: &'a mut BitReader<'b>,
+}
+
+impl Coder for Decoder<'_, '_> {
+    fn bit(&mut self, _value: impl FnOnce() -> bool) -> Option<bool> {
+        self.reader.read_bit()
+    }
+}
+
+/// Header fields of a segment
+#[derive(Clone, Copy, Debug)]
+struct Header {
+    start: bool,
+    end: bool,
+    count: u8,
+    bit_depth_dc: u32,
+    bit_depth_ac: u32,
+}
+
+/// Number of bits of the two's complement representation of `value`
+fn signed_bits(value: i64) -> u32 {
+    match value >= 0 {
+        true => 65 - value.leading_zeros(),
+        false => 65 - (!value).leading_zeros(),
+    }
+}
+
+/// Quantization of the DC coefficients, from the bit depths of the segment
+fn quantization(bit_depth_dc: u32, bit_depth_ac: u32) -> u32 {
+    let half_ac = 1 + bit_depth_ac / 2;
+    if bit_depth_dc <= 3 {
+        0
+    } else if bit_depth_dc <= half_ac + 1 {
+        bit_depth_dc - 3
+    } else if bit_depth_dc > half_ac + 10 {
+        bit_depth_dc - 10
+    } else {
+        half_ac
+    }
+}
+
+/// Length of the code option identifier of a gaggle of `n` bit values
+fn code_id_bits(n: u32) -> u32 {
+    match n {
+        0..=2 => 1,
+        3..=4 => 2,
+        5..=8 => 3,
+        _ => 4,
+    }
+}
+
+/// Codes `values` of `n` bits: the first one as it is, the others as mapped differences with the
+/// previous one, Rice coded in gaggles with the best code option of each gaggle
+fn encode_values(writer: &mut BitWriter, values: &[i64], n: u32, signed: bool) {
+    if n == 0 {
+        return;
+    }
+    let mask = (1u64 << n) - 1;
+    writer.write_bits(values[0] as u64 & mask, n);
+    if n == 1 {
+        values[1..]
+            .iter()
+            .for_each(|value| writer.write_bits(*value as u64 & mask, n));
+        return;
+    }
+
+    let range = value_range(n, signed);
+    let mapped: Vec<u64> = values
+        .windows(2)
+        .map(|pair| map_difference(pair[1], pair[0], range))
+        .collect();
+
+    let id_bits = code_id_bits(n);
+    let uncoded = (1 << id_bits) - 1;
+    for gaggle in mapped.chunks(GAGGLE_SIZE) {
+        let mut option = uncoded;
+        let mut best = n as u64 * gaggle.len() as u64;
+        for k in 0..uncoded {
+            let length = gaggle.iter().map(|v| (v >> k) + 1 + k).sum::<u64>();
+            if length < best {
+                (option, best) = (k, length);
+            }
+        }
+
+        writer.write_bits(option, id_bits);
+        if option == uncoded {
+            gaggle.iter().for_each(|v| writer.write_bits(*v, n));
+        } else {
+            // first the unary parts of all the values, then their k low bits
+            gaggle.iter().for_each(|v| writer.write_unary(v >> option));
+            gaggle
+                .iter()
+                .for_each(|v| writer.write_bits(v & ((1 << option) - 1), option as u32));
+        }
+    }
+}
+
+fn decode_values(reader: &mut BitReader, count: usize, n: u32, signed: bool) -> Option<Vec<i64>> {
+    if n == 0 {
+        return Some(vec![0; count]);
+    }
+    let extend = |raw: u64| match signed && raw >> (n - 1) & 1 == 1 {
+        true => raw as i64 - (1 << n),
+        false => raw as i64,
+    };
+
+    let mut values = Vec::with_capacity(count);
+    values.push(extend(reader.read_bits(n)?));
+    if n == 1 {
+        for _ in 1..count {
+            values.push(extend(reader.read_bits(n)?));
+        }
+        return Some(values);
+    }
+
+    let range = value_range(n, signed);
+    let id_bits = code_id_bits(n);
+    let uncoded = (1 << id_bits) - 1;
+    let mut mapped = [0; GAGGLE_SIZE];
+    let mut remaining = count - 1;
+    while remaining > 0 {
+        let gaggle = &mut mapped[..remaining.min(GAGGLE_SIZE)];
+        let option = reader.read_bits(id_bits)?;
+        if option == uncoded {
+            for v in gaggle.iter_mut() {
+                *v = reader.read_bits(n)?;
+            }
+        } else {
+            for v in gaggle.iter_mut() {
+                *v = reader.read_unary()?;
+            }
+            for v in gaggle.iter_mut() {
+                *v = (*v << option) | reader.read_bits(option as u32)?;
+            }
+        }
+
+        for v in gaggle.iter() {
+            let previous = *values.last().unwrap();
+            values.push(unmap_difference(*v, previous, range));
+        }
+        remaining -= gaggle.len();
+    }
+    Some(values)
+}
+
+/// Bit planes of the segment after the quantized DC coefficients and the bit depths
+fn code_bit_planes<C: Coder>(
+    coder: &mut C,
+    blocks: &mut [Block],
+    q: u32,
+    bit_depth_ac: u32,
+    parameters: &BpeParameters,
+) -> Option<()> {
+    let code_dc_bit = |coder: &mut C, block: &mut Block, b: u32| -> Option<()> {
+        if coder.bit(|| block.dc >> b & 1 == 1)? {
+            block.dc |= 1 << b;
+        }
+        Some(())
+    };
+    let code_coefficient = |coder: &mut C, block: &mut Block, x: usize, b: u32| -> Option<()> {
+        if coder.bit(|| block.magnitude[x] >> b & 1 == 1)? {
+            block.magnitude[x] |= 1 << b;
+            block.significant[x] = Some(b);
+            block.negative[x] = coder.bit(|| block.negative[x])?;
+        }
+        Some(())
+    };
+
+    // the DC bit planes that are not coded by the AC bit planes
+    for b in (bit_depth_ac.max(parameters.bit_plane_stop)..q).rev() {
+        for block in blocks.iter_mut() {
+            code_dc_bit(coder, block, b)?;
+        }
+    }
+    if parameters.dc_stop {
+        return Some(());
+    }
+
+    for b in (parameters.bit_plane_stop..bit_depth_ac).rev() {
+        let stages = match b == parameters.bit_plane_stop {
+            true => parameters.stage_stop,
+            false => 4,
+        };
+
+        // stage 0
+        if b < q {
+            for block in blocks.iter_mut() {
+                code_dc_bit(coder, block, b)?;
+            }
+        }
+
+        // stage 1: parents
+        for block in blocks.iter_mut().filter(|block| block.bit_depth_ac > b) {
+            for x in 0..3 {
+                if block.significant[x].is_none() {
+                    code_coefficient(coder, block, x, b)?;
+                }
+            }
+        }
+
+        // stage 2: children
+        if stages >= 2 {
+            for block in blocks.iter_mut().filter(|block| block.bit_depth_ac > b) {
+                if !block.tran_b {
+                    block.tran_b = coder.bit(|| block.any_significant(3..AC_COEFFICIENTS, b))?;
+                }
+                if !block.tran_b {
+                    continue;
+                }
+                for family in 0..3 {
+                    if !block.tran_d[family] {
+                        block.tran_d[family] = coder.bit(|| {
+                            block.any_significant(children(family).chain(grandchildren(family)), b)
+                        })?;
+                    }
+                    if block.tran_d[family] {
+                        for x in children(family) {
+                            if block.significant[x].is_none() {
+                                code_coefficient(coder, block, x, b)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // stage 3: grandchildren
+        if stages >= 3 {
+            for block in blocks.iter_mut().filter(|block| block.bit_depth_ac > b) {
+                for family in 0..3 {
+                    if !block.tran_d[family] {
+                        continue;
+                    }
+                    if !block.tran_g[family] {
+                        block.tran_g[family] =
+                            coder.bit(|| block.any_significant(grandchildren(family), b))?;
+                    }
+                    if !block.tran_g[family] {
+                        continue;
+                    }
+                    for child in 0..4 {
+                        if !block.tran_h[family][child] {
+                            block.tran_h[family][child] =
+                                coder.bit(|| block.any_significant(group(family, child), b))?;
+                        }
+                        if block.tran_h[family][child] {
+                            for x in group(family, child) {
+                                if block.significant[x].is_none() {
+                                    code_coefficient(coder, block, x, b)?;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // stage 4: refinement of the coefficients that were already significant
+        if stages >= 4 {
+            for block in blocks.iter_mut() {
+                for x in 0..AC_COEFFICIENTS {
+                    if block.significant[x].is_some_and(|plane| plane > b)
+                        && coder.bit(|| block.magnitude[x] >> b & 1 == 1)?
+                    {
+                        block.magnitude[x] |= 1 << b;
+                    }
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+fn check_image(rows: usize, cols: usize) -> Result<(), Error> {
+    if rows == 0 || cols == 0 || rows & 7 != 0 || cols & 7 != 0 || cols >= 1 << 20 {
+        return Err(Error::UnsupportedSize);
+    }
+    Ok(())
+}
+
+/// Number of segments of a `rows` x `cols` image
+fn n_segments(rows: usize, cols: usize, parameters: &BpeParameters) -> usize {
+    (rows / 8 * (cols / 8)).div_ceil(parameters.segment_size)
+}
+
+/// Encodes the wavelet coefficients of a `rows` x `cols` image, `coefficient` returns the one
+/// in the given row and column
+pub(crate) fn encode(
+    rows: usize,
+    cols: usize,
+    coefficient: impl Fn(usize, usize) -> i32,
+    parameters: &BpeParameters,
+) -> Result<Vec<u8>, Error> {
+    check_image(rows, cols)?;
+    parameters.check()?;
+    let n_segments = n_segments(rows, cols, parameters);
+    encode_segments(rows, cols, &coefficient, parameters, 0..n_segments)
+}
+
+/// Std parallel `encode`, the segments are split in `n_threads` chunks whose outputs are
+/// concatenated
+pub(crate) fn parallel_encode(
+    rows: usize,
+    cols: usize,
+    coefficient: impl Fn(usize, usize) -> i32 + Sync,
+    parameters: &BpeParameters,
+    n_threads: usize,
+) -> Result<Vec<u8>, Error> {
+    check_image(rows, cols)?;
+    parameters.check()?;
+    if n_threads == 0 {
+        return Err(Error::InvalidNumberOfThreads);
+    }
+
+    let n_segments = n_segments(rows, cols, parameters);
+    let segments_per_thread = (n_segments - 1) / n_threads + 1;
+    let coefficient = &coefficient;
+    let outputs: Vec<Result<Vec<u8>, Error>> = thread::scope(|s| {
+        let handles: Vec<_> = (0..n_segments)
+            .step_by(segments_per_thread)
+            .map(|first| {
+                let segments = first..n_segments.min(first + segments_per_thread);
+                s.spawn(move || encode_segments(rows, cols, coefficient, parameters, segments))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    Ok(outputs.into_iter().collect::<Result<Vec<_>, _>>()?.concat())
+}
+
+/// Rayon `encode`, every segment is a task
+pub(crate) fn rayon_encode(
+    rows: usize,
+    cols: usize,
+    coefficient: impl Fn(usize, usize) -> i32 + Sync,
+    parameters: &BpeParameters,
+) -> Result<Vec<u8>, Error> {
+    check_image(rows, cols)?;
+    parameters.check()?;
+    let outputs: Result<Vec<Vec<u8>>, Error> = (0..n_segments(rows, cols, parameters))
+        .into_par_iter()
+        .map(|segment| encode_segments(rows, cols, &coefficient, parameters, segment..segment + 1))
+        .collect();
+    Ok(outputs?.concat())
+}
+
+/// Encodes the segments with indexes in `segments`
+fn encode_segments<F: Fn(usize, usize) -> i32>(
+    rows: usize,
+    cols: usize,
+    coefficient: &F,
+    parameters: &BpeParameters,
+    segments: std::ops::Range<usize>,
+) -> Result<Vec<u8>, Error> {
+    let blocks_per_row = cols / 8;
+    let n_blocks = rows / 8 * blocks_per_row;
+    let mut writer = BitWriter::new();
+
+    for count in segments {
+        let first = count * parameters.segment_size;
+        let mut blocks: Vec<Block> = (first..n_blocks.min(first + parameters.segment_size))
+            .map(|idx| {
+                let positions =
+                    block_positions(rows, cols, idx / blocks_per_row, idx % blocks_per_row);
+                let mut block = Block::new(coefficient(positions[0].0, positions[0].1) as i64);
+                for (x, (r, c)) in positions[1..].iter().enumerate() {
+                    let value = coefficient(*r, *c);
+                    block.magnitude[x] = value.unsigned_abs();
+                    block.negative[x] = value < 0;
+                }
+                block.bit_depth_ac = 32 - block.magnitude.iter().max().unwrap().leading_zeros();
+                block
+            })
+            .collect();
+
+        let header = Header {
+            start: first == 0,
+            end: first + blocks.len() == n_blocks,
+            count: count as u8,
+            bit_depth_dc: blocks
+                .iter()
+                .map(|block| signed_bits(block.dc))
+                .max()
+                .unwrap(),
+            bit_depth_ac: blocks.iter().map(|block| block.bit_depth_ac).max().unwrap(),
+        };
+        if header.bit_depth_ac > MAX_BIT_DEPTH_AC || header.bit_depth_dc > MAX_BIT_DEPTH_AC + 1 {
+            return Err(Error::UnsupportedSize);
+        }
+
+        let start = writer.len();
+        writer.set_limit(parameters.byte_limit.map(|limit| start + limit * 8));
+        write_header(&mut writer, &header, parameters, cols);
+
+        let q = quantization(header.bit_depth_dc, header.bit_depth_ac);
+        let dc: Vec<i64> = blocks.iter().map(|block| block.dc >> q).collect();
+        encode_values(
+            &mut writer,
+            &dc,
+            header.bit_depth_dc.saturating_sub(q).max(1),
+            true,
+        );
+        let bit_depths: Vec<i64> = blocks
+            .iter()
+            .map(|block| block.bit_depth_ac as i64)
+            .collect();
+        encode_values(
+            &mut writer,
+            &bit_depths,
+            32 - header.bit_depth_ac.leading_zeros(),
+            false,
+        );
+        if writer.overflow() {
+            // the byte limit has to leave room at least for the DC coefficients
+            return Err(Error::UnsupportedSize);
+        }
+
+        let mut encoder = Encoder {
+            writer: &mut writer,
+        };
+        // the coding stops when it reaches the byte limit
+        let _ = code_bit_planes(
+            &mut encoder,
+            &mut blocks,
+            q,
+            header.bit_depth_ac,
+            parameters,
+        );
+
+        writer.set_limit(None);
+        writer.align();
+        if let (Some(limit), true) = (parameters.byte_limit, parameters.use_fill) {
+            writer.fill(start + limit * 8);
+        }
+    }
+
+    Ok(writer.into_bytes())
+}
+
+/// Part 1 is in every segment, parts 2, 3 and 4 only in the first one
+fn write_header(writer: &mut BitWriter, header: &Header, parameters: &BpeParameters, cols: usize) {
+    // part 1A
+    writer.write_bit(header.start);
+    writer.write_bit(header.end);
+    writer.write_bits(header.count as u64, 8);
+    writer.write_bits(header.bit_depth_dc as u64, 5);
+    writer.write_bits(header.bit_depth_ac as u64, 5);
+    writer.write_bit(false);
+    writer.write_bits(if header.start { 0b111 } else { 0 }, 3);
+    // part 1B, there are no padding rows
+    if header.end {
+        writer.write_bits(0, 8);
+    }
+    if header.start {
+        // part 2
+        writer.write_bits(parameters.byte_limit.unwrap_or(0) as u64, 27);
+        writer.write_bit(parameters.dc_stop);
+        writer.write_bits(parameters.bit_plane_stop as u64, 5);
+        writer.write_bits(parameters.stage_stop as u64 - 1, 2);
+        writer.write_bit(parameters.use_fill);
+        writer.write_bits(0, 4);
+        // part 3
+        writer.write_bits(parameters.segment_size as u64, 20);
+        writer.write_bits(0, 4);
+        // part 4: integer transform and image width
+        writer.write_bit(true);
+        writer.write_bits(0, 2);
+        writer.write_bits(cols as u64, 20);
+        writer.write_bit(false);
+    }
+}
+
+/// Reads the header of a segment, updating `parameters` with parts 2 and 3
+fn read_header(
+    reader: &mut BitReader,
+    parameters: &mut BpeParameters,
+    cols: usize,
+) -> Option<Header> {
+    let header = Header {
+        start: reader.read_bit()?,
+        end: reader.read_bit()?,
+        count: reader.read_bits(8)? as u8,
+        bit_depth_dc: reader.read_bits(5)? as u32,
+        bit_depth_ac: reader.read_bits(5)? as u32,
+    };
+    reader.read_bit()?;
+    let parts = reader.read_bits(3)?;
+    if parts != if header.start { 0b111 } else { 0 } {
+        return None;
+    }
+    if header.end {
+        reader.read_bits(8)?;
+    }
+    if header.start {
+        let byte_limit = reader.read_bits(27)? as usize;
+        parameters.byte_limit = (byte_limit != 0).then_some(byte_limit);
+        parameters.dc_stop = reader.read_bit()?;
+        parameters.bit_plane_stop = reader.read_bits(5)? as u32;
+        parameters.stage_stop = reader.read_bits(2)? as u32 + 1;
+        parameters.use_fill = reader.read_bit()?;
+        reader.read_bits(4)?;
+        parameters.segment_size = reader.read_bits(20)? as usize;
+        reader.read_bits(4)?;
+        let integer = reader.read_bit()?;
+        reader.read_bits(2)?;
+        if !integer || reader.read_bits(20)? as usize != cols {
+            return None;
+        }
+        reader.read_bit()?;
+    }
+    Some(header)
+}
+
+/// Decodes the output of `encode` into the `rows` x `cols` image, `set_coefficient` stores the
+/// coefficient of the given row and column
+/// If the segments were truncated by the byte limit the coefficients are only approximated
+pub(crate) fn decode(
+    data: &[u8],
+    rows: usize,
+    cols: usize,
+    mut set_coefficient: impl FnMut(usize, usize, i32),
+) -> Result<(), Error> {
+    check_image(rows, cols)?;
+
+    let blocks_per_row = cols / 8;
+    let n_blocks = rows / 8 * blocks_per_row;
+    let mut reader = BitReader::new(data);
+    let mut parameters = BpeParameters::default();
+    let mut first = 0;
+
+    for count in 0.. {
+        let start = reader.position();
+        reader.set_end(data.len() * 8);
+        let header = read_header(&mut reader, &mut parameters, cols)
+            .filter(|header| header.start == (count == 0) && header.count == count as u8)
+            .ok_or(Error::InvalidBitstream)?;
+        if parameters.check().is_err() || first >= n_blocks {
+            return Err(Error::InvalidBitstream);
+        }
+        if let Some(limit) = parameters.byte_limit {
+            reader.set_end(start + limit * 8);
+        }
+
+        let n_segment_blocks = parameters.segment_size.min(n_blocks - first);
+        let q = quantization(header.bit_depth_dc, header.bit_depth_ac);
+        let dc = decode_values(
+            &mut reader,
+            n_segment_blocks,
+            header.bit_depth_dc.saturating_sub(q).max(1),
+            true,
+        );
+        let bit_depths = decode_values(
+            &mut reader,
+            n_segment_blocks,
+            32 - header.bit_depth_ac.leading_zeros(),
+            false,
+        );
+        let (Some(dc), Some(bit_depths)) = (dc, bit_depths) else {
+            return Err(Error::InvalidBitstream);
+        };
+
+        let mut blocks: Vec<Block> = dc
+            .iter()
+            .zip(&bit_depths)
+            .map(|(dc, bit_depth)| {
+                let mut block = Block::new(dc << q);
+                block.bit_depth_ac = *bit_depth as u32;
+                block
+            })
+            .collect();
+
+        let mut decoder = Decoder {
+            reader: &mut reader,
+        };
+        let complete = code_bit_planes(
+            &mut decoder,
+            &mut blocks,
+            q,
+            header.bit_depth_ac,
+            &parameters,
+        );
+
+        for (idx, block) in (first..).zip(&blocks) {
+            let positions = block_positions(rows, cols, idx / blocks_per_row, idx % blocks_per_row);
+            set_coefficient(positions[0].0, positions[0].1, block.dc as i32);
+            for (x, (r, c)) in positions[1..].iter().enumerate() {
+                let magnitude = block.magnitude[x] as i32;
+                set_coefficient(
+                    *r,
+                    *c,
+                    if block.negative[x] {
+                        -magnitude
+                    } else {
+                        magnitude
+                    },
+                );
+            }
+        }
+        first += n_segment_blocks;
+
+        match (parameters.byte_limit, complete) {
+            // without byte limit the segments are never truncated
+            (None, None) => return Err(Error::InvalidBitstream),
+            (Some(limit), None) => reader.set_position(start + limit * 8),
+            (Some(limit), Some(())) if parameters.use_fill => {
+                reader.set_position(start + limit * 8)
+            }
+            _ => reader.align(),
+        }
+
+        if header.end {
+            break;
+        }
+    }
+
+    match first == n_blocks {
+        true => Ok(()),
+        false => Err(Error::InvalidBitstream),
+    }
+}
+
+impl Default for BpeParameters {
+    /// Lossless coding in segments of 256 blocks
+    fn default() -> Self {
+        BpeParameters {
+            segment_size: 256,
+            byte_limit: None,
+            dc_stop: false,
+            bit_plane_stop: 0,
+            stage_stop: 4,
+            use_fill: false,
+        }
+    }
+}
+
+impl BpeParameters {
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.segment_size == 0
+            || self.segment_size >= 1 << 20
+            || self
+                .byte_limit
+                .is_some_and(|limit| limit <= MAX_HEADER_BYTES || limit >= 1 << 27)
+            || self.bit_plane_stop >= 32
+            || !(1..=4).contains(&self.stage_stop)
+        {
+            return Err(Error::UnsupportedSize);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix_1d::Matrix1d;
+    use crate::matrix_2d::Matrix2d;
+    use crate::parallel_traits::ParallelBitPlaneEncoder;
+    use crate::rayon_traits::RayonBitPlaneEncoder;
+    use crate::{BaseMatrix, BitPlaneEncoder, WaveletTransform2dInteger};
+
+    #[test]
+    fn known_answer() {
+        // a single block with DC 5 and the HL3 parent -3, the bit depths are 4 and 2 so the DC
+        // is quantized by 2
+        let coefficient = |i, j| match (i, j) {
+            (0, 0) => 5,
+            (0, 1) => -3,
+            _ => 0,
+        };
+        let data = encode(8, 8, coefficient, &BpeParameters::default()).unwrap();
+        let header = [
+            // part 1A and 1B: start, end, count 0, bit depths 4 and 2, parts 2 to 4 present
+            [0xc0, 0x08, 0x27, 0x00].as_slice(),
+            // part 2: no byte limit, stage 4 of bit plane 0
+            &[0x00, 0x00, 0x00, 0x00, 0x60],
+            // part 3: segments of 256 blocks
+            &[0x00, 0x10, 0x00],
+            // part 4: integer transform, 8 columns
+            &[0x80, 0x00, 0x10],
+        ]
+        .concat();
+        assert_eq!(data[..15], header);
+        assert_eq!(data[15..], [0x66, 0x22]);
+
+        let mut decoded = vec![vec![0; 8]; 8];
+        decode(&data, 8, 8, |i, j, value| decoded[i][j] = value).unwrap();
+        for (i, row) in decoded.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                assert_eq!(*value, coefficient(i, j));
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let (rows, cols) = (48, 32);
+        let image = Matrix1d::<i32>::from_random_seed(3, rows, cols, 0, 4096);
+        let mut coefficients = Matrix1d::zeroes(rows, cols);
+        image.wavelet_transform_2d(&mut coefficients, 3).unwrap();
+
+        // lossless, with more than one segment
+        let parameters = BpeParameters {
+            segment_size: 5,
+            ..Default::default()
+        };
+        let data = coefficients.bit_plane_encode(&parameters).unwrap();
+        let mut decoded = Matrix1d::zeroes(rows, cols);
+        decoded.bit_plane_decode(&data).unwrap();
+        assert_eq!(decoded.get_data(), coefficients.get_data());
+
+        // the byte limit truncates every segment, the DC coefficients are still close
+        let parameters = BpeParameters {
+            segment_size: 6,
+            byte_limit: Some(40),
+            use_fill: true,
+            ..Default::default()
+        };
+        let data = coefficients.bit_plane_encode(&parameters).unwrap();
+        assert_eq!(data.len(), 40 * 4);
+        let mut decoded = Matrix1d::zeroes(rows, cols);
+        decoded.bit_plane_decode(&data).unwrap();
+        // the lower bit planes of the DC coefficients are lost
+        assert!((decoded.get_data()[0][0] - coefficients.get_data()[0][0]).abs() < 128);
+
+        // only the first stages of the most significant bit planes
+        let parameters = BpeParameters {
+            bit_plane_stop: 4,
+            stage_stop: 2,
+            ..Default::default()
+        };
+        let data = coefficients.bit_plane_encode(&parameters).unwrap();
+        decoded.bit_plane_decode(&data).unwrap();
+        for (decoded, coefficient) in decoded
+            .get_data()
+            .concat()
+            .iter()
+            .zip(&coefficients.get_data().concat())
+        {
+            assert!((decoded - coefficient).abs() < 32);
+        }
+
+        assert!(decoded.bit_plane_decode(&data[..data.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn parallel_encode() {
+        let (rows, cols) = (48, 32);
+        let image = Matrix2d::<i32>::from_random_seed(3, rows, cols, 0, 4096);
+        let mut coefficients = Matrix2d::zeroes(rows, cols);
+        image.wavelet_transform_2d(&mut coefficients, 3).unwrap();
+        let as_1d = Matrix1d::new(coefficients.get_data(), rows, cols);
+
+        // the parallel implementations split the segments
+        for parameters in [
+            BpeParameters {
+                segment_size: 5,
+                ..Default::default()
+            },
+            BpeParameters {
+                segment_size: 6,
+                byte_limit: Some(40),
+                use_fill: true,
+                ..Default::default()
+            },
+            BpeParameters {
+                bit_plane_stop: 4,
+                stage_stop: 2,
+                ..Default::default()
+            },
+        ] {
+            let data = coefficients.bit_plane_encode(&parameters).unwrap();
+            for n_threads in [1, 3, 8, 100] {
+                assert_eq!(
+                    coefficients
+                        .parallel_bit_plane_encode(&parameters, n_threads)
+                        .unwrap(),
+                    data
+                );
+                assert_eq!(
+                    as_1d
+                        .parallel_bit_plane_encode(&parameters, n_threads)
+                        .unwrap(),
+                    data
+                );
+            }
+            assert_eq!(
+                coefficients.rayon_bit_plane_encode(&parameters).unwrap(),
+                data
+            );
+            assert_eq!(as_1d.rayon_bit_plane_encode(&parameters).unwrap(), data);
+        }
+        assert!(coefficients
+            .parallel_bit_plane_encode(&BpeParameters::default(), 0)
+            .is_err());
+    }
+}
@@ -0,0 +1,548 @@
+//! Lossless predictive compressor of CCSDS 123.0-B-1 for band sequential cubes
+//! Every sample is predicted from the local sums of the neighbours in its band (neighbour
+//! oriented, full prediction mode) and from the central local differences of the previous
+//! `prediction_bands` bands, with weights adapted after each sample. The mapped prediction
+//! residuals are coded with the sample adaptive entropy coder
+//!
+//! The output is band sequential and has no header, the parameters are passed to the decoder
+//! The weights and the statistics of the entropy coder are per band, so the bands can be
+//! encoded independently: this is what the parallel implementations split
+use crate::bitstream::{BitReader, BitWriter};
+use crate::{Error, PredictorParameters};
+
+use rayon::prelude::*;
+use std::thread;
+
+impl Default for PredictorParameters {
+    fn default() -> Self {
+        PredictorParameters {
+            dynamic_range: 16,
+            prediction_bands: 3,
+            weight_resolution: 13,
+            weight_interval: 64,
+            weight_exponent_min: -1,
+            weight_exponent_max: 3,
+            unary_length_limit: 18,
+            initial_count_exponent: 1,
+            accumulator_initialization: 3,
+            rescaling_counter_size: 6,
+        }
+    }
+}
+
+impl PredictorParameters {
+    /// Checks the parameters against the ranges of the standard
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if !(2..=16).contains(&self.dynamic_range)
+            || self.prediction_bands > 15
+            || !(4..=19).contains(&self.weight_resolution)
+            || !(16..=2048).contains(&self.weight_interval)
+            || !self.weight_interval.is_power_of_two()
+            || !(-6..=9).contains(&self.weight_exponent_min)
+            || !(self.weight_exponent_min..=9).contains(&self.weight_exponent_max)
+            || !(8..=32).contains(&self.unary_length_limit)
+            || !(1..=8).contains(&self.initial_count_exponent)
+            || self.accumulator_initialization > self.dynamic_range - 2
+            || !(self.initial_count_exponent.max(3) + 1..=11).contains(&self.rescaling_counter_size)
+        {
+            return Err(Error::UnsupportedSize);
+        }
+        Ok(())
+    }
+}
+
+/// Dimensions of a cube: bands, rows and columns
+type Dimensions = (usize, usize, usize);
+
+/// Local sum of the neighbours of (`x`, `y`), which can't be the first sample of the band
+fn local_sum(band: &[i32], cols: usize, x: usize, y: usize) -> i64 {
+    let s = |x: usize, y: usize| band[y * cols + x] as i64;
+    if y == 0 {
+        4 * s(x - 1, y)
+    } else if x == 0 {
+        2 * (s(x, y - 1) + s(x + 1, y - 1))
+    } else if x == cols - 1 {
+        s(x - 1, y) + s(x - 1, y - 1) + 2 * s(x, y - 1)
+    } else {
+        s(x - 1, y) + s(x - 1, y - 1) + s(x, y - 1) + s(x + 1, y - 1)
+    }
+}
+
+fn central_difference(band: &[i32], cols: usize, x: usize, y: usize) -> i64 {
+    4 * band[y * cols + x] as i64 - local_sum(band, cols, x, y)
+}
+
+/// Adaptive predictor of a band
+struct Predictor<'a> {
+    parameters: &'a PredictorParameters,
+    cols: usize,
+    /// Weights of the north, west and north west local differences and of the central local
+    /// differences of the previous bands
+    weights: Vec<i64>,
+}
+
+/// Prediction of a sample: the predicted sample, the scaled predicted sample and the local
+/// difference vector
+struct Prediction {
+    predicted: i64,
+    scaled: i64,
+    differences: Vec<i64>,
+}
+
+impl<'a> Predictor<'a> {
+    fn new(parameters: &'a PredictorParameters, cols: usize, previous_bands: usize) -> Self {
+        // default initialization, 7 / 8 of the unit weight for the previous band and 1 / 8 of
+        // the previous weight for the others
+        let mut weights = vec![0; 3 + previous_bands];
+        let mut weight = 7 * (1 << parameters.weight_resolution) / 8;
+        for el in weights[3..].iter_mut() {
+            *el = weight;
+            weight /= 8;
+        }
+        Predictor {
+            parameters,
+            cols,
+            weights,
+        }
+    }
+
+    /// Predicts sample `t` of `band`, which is only read before `t`, `previous` holds the
+    /// previous bands, the closest first
+    fn predict(&self, band: &[i32], previous: &[&[i32]], t: usize) -> Prediction {
+        let d = self.parameters.dynamic_range;
+        let omega = self.parameters.weight_resolution;
+        let mid = 1i64 << (d - 1);
+        let (x, y) = (t % self.cols, t / self.cols);
+
+        if t == 0 {
+            let scaled = match previous.first() {
+                Some(previous) => 2 * previous[0] as i64,
+                None => 2 * mid,
+            };
+            return Prediction {
+                predicted: scaled >> 1,
+                scaled,
+                differences: vec![],
+            };
+        }
+
+        let sum = local_sum(band, self.cols, x, y);
+        let s = |x: usize, y: usize| 4 * band[y * self.cols + x] as i64 - sum;
+        let (north, west, north_west) = match (x, y) {
+            (_, 0) => (0, 0, 0),
+            (0, _) => (s(x, y - 1), s(x, y - 1), s(x, y - 1)),
+            _ => (s(x, y - 1), s(x - 1, y), s(x - 1, y - 1)),
+        };
+        let differences: Vec<i64> = [north, west, north_west]
+            .into_iter()
+            .chain(
+                previous
+                    .iter()
+                    .map(|band| central_difference(band, self.cols, x, y)),
+            )
+            .collect();
+
+        let predicted_difference: i64 = self
+            .weights
+            .iter()
+            .zip(&differences)
+            .map(|(weight, difference)| weight * difference)
+            .sum();
+        let scaled =
+            (((predicted_difference + ((sum - 4 * mid) << omega)) >> (omega + 1)) + 2 * mid + 1)
+                .clamp(0, (2 << d) - 1);
+
+        Prediction {
+            predicted: scaled >> 1,
+            scaled,
+            differences,
+        }
+    }
+
+    /// Updates the weights after sample `t`
+    fn update(&mut self, prediction: &Prediction, sample: i64, t: usize) {
+        if t == 0 {
+            return;
+        }
+        let p = self.parameters;
+        let error_sign = match 2 * sample - prediction.scaled >= 0 {
+            true => 1,
+            false => -1,
+        };
+        let exponent = (p.weight_exponent_min
+            + (t as i64 - self.cols as i64).div_euclid(p.weight_interval as i64) as i32)
+            .clamp(p.weight_exponent_min, p.weight_exponent_max)
+            + p.dynamic_range as i32
+            - p.weight_resolution as i32;
+        let limit = 1i64 << (p.weight_resolution + 2);
+
+        for (weight, difference) in self.weights.iter_mut().zip(&prediction.differences) {
+            let change = error_sign * difference;
+            let change = match exponent >= 0 {
+                true => (change + (1 << exponent)) >> (exponent + 1),
+                false => ((change << -exponent) + 1) >> 1,
+            };
+            *weight = (*weight + change).clamp(-limit, limit - 1);
+        }
+    }
+}
+
+/// Maps the prediction residual to a non negative integer, the sign of the small residuals is
+/// interleaved depending on the parity of the scaled predicted sample
+fn map_residual(sample: i64, prediction: &Prediction, max: i64) -> u64 {
+    let residual = sample - prediction.predicted;
+    let theta = prediction.predicted.min(max - prediction.predicted);
+    let sign = 1 - 2 * (prediction.scaled & 1);
+    if residual.abs() > theta {
+        (residual.abs() + theta) as u64
+    } else if sign * residual >= 0 {
+        2 * residual.unsigned_abs()
+    } else {
+        2 * residual.unsigned_abs() - 1
+    }
+}
+
+fn unmap_residual(mapped: u64, prediction: &Prediction, max: i64) -> i64 {
+    let mapped = mapped as i64;
+    let theta = prediction.predicted.min(max - prediction.predicted);
+    let sign = 1 - 2 * (prediction.scaled & 1);
+    let residual = if mapped > 2 * theta {
+        match theta == prediction.predicted {
+            true => mapped - theta,
+            false => theta - mapped,
+        }
+    } else if mapped & 1 == 0 {
+        sign * mapped / 2
+    } else {
+        -sign * (mapped + 1) / 2
+    };
+    prediction.predicted + residual
+}
+
+/// Sample adaptive entropy coder of a band
+struct EntropyCoder<'a> {
+    parameters: &'a PredictorParameters,
+    counter: u64,
+    accumulator: u64,
+}
+
+impl<'a> EntropyCoder<'a> {
+    fn new(parameters: &'a PredictorParameters) -> Self {
+        let counter = 1 << parameters.initial_count_exponent;
+        let accumulator =
+            (((3 << (parameters.accumulator_initialization + 6)) - 49) * counter) >> 7;
+        EntropyCoder {
+            parameters,
+            counter,
+            accumulator,
+        }
+    }
+
+    /// Number of low bits of the codeword
+    fn k(&self) -> u32 {
+        let bound = self.accumulator + ((49 * self.counter) >> 7);
+        (1..=self.parameters.dynamic_range - 2)
+            .rev()
+            .find(|k| self.counter << k <= bound)
+            .unwrap_or(0)
+    }
+
+    fn update(&mut self, mapped: u64) {
+        if self.counter < (1 << self.parameters.rescaling_counter_size) - 1 {
+            self.accumulator += mapped;
+            self.counter += 1;
+        } else {
+            self.accumulator = (self.accumulator + mapped).div_ceil(2);
+            self.counter = self.counter.div_ceil(2);
+        }
+    }
+
+    fn write(&mut self, writer: &mut BitWriter, mapped: u64, t: usize) {
+        let d = self.parameters.dynamic_range;
+        if t == 0 {
+            writer.write_bits(mapped, d);
+            return;
+        }
+        let k = self.k();
+        let limit = self.parameters.unary_length_limit as u64;
+        match mapped >> k {
+            high if high < limit => {
+                writer.write_unary(high);
+                writer.write_bits(mapped, k);
+            }
+            _ => {
+                writer.write_bits(0, limit as u32);
+                writer.write_bits(mapped, d);
+            }
+        }
+        self.update(mapped);
+    }
+
+    fn read(&mut self, reader: &mut BitReader, t: usize) -> Option<u64> {
+        let d = self.parameters.dynamic_range;
+        if t == 0 {
+            return reader.read_bits(d);
+        }
+        let k = self.k();
+        let limit = self.parameters.unary_length_limit as u64;
+        let mut high = 0;
+        while high < limit && !reader.read_bit()? {
+            high += 1;
+        }
+        let mapped = match high == limit {
+            true => reader.read_bits(d)?,
+            false => (high << k) | reader.read_bits(k)?,
+        };
+        self.update(mapped);
+        Some(mapped)
+    }
+}
+
+/// Previous bands used by the prediction of band `z`, the closest first
+fn previous_bands<'a>(
+    samples: &'a [i32],
+    z: usize,
+    band_size: usize,
+    parameters: &PredictorParameters,
+) -> Vec<&'a [i32]> {
+    (1..=parameters.prediction_bands.min(z))
+        .map(|i| &samples[(z - i) * band_size..(z - i + 1) * band_size])
+        .collect()
+}
+
+fn encode_band(
+    samples: &[i32],
+    z: usize,
+    (_, rows, cols): Dimensions,
+    parameters: &PredictorParameters,
+) -> BitWriter {
+    let band_size = rows * cols;
+    let band = &samples[z * band_size..(z + 1) * band_size];
+    let previous = previous_bands(samples, z, band_size, parameters);
+    let max = (1 << parameters.dynamic_range) - 1;
+
+    let mut predictor = Predictor::new(parameters, cols, previous.len());
+    let mut coder = EntropyCoder::new(parameters);
+    let mut writer = BitWriter::new();
+    for (t, sample) in band.iter().enumerate() {
+        let prediction = predictor.predict(band, &previous, t);
+        coder.write(
+            &mut writer,
+            map_residual(*sample as i64, &prediction, max),
+            t,
+        );
+        predictor.update(&prediction, *sample as i64, t);
+    }
+    writer
+}
+
+fn check(
+    samples: &[i32],
+    (bands, rows, cols): Dimensions,
+    parameters: &PredictorParameters,
+) -> Result<(), Error> {
+    parameters.check()?;
+    if bands == 0 || rows == 0 || cols < 2 || samples.len() != bands * rows * cols {
+        return Err(Error::InvalidDimensions);
+    }
+    if samples
+        .iter()
+        .any(|sample| *sample < 0 || *sample >> parameters.dynamic_range != 0)
+    {
+        return Err(Error::UnsupportedSize);
+    }
+    Ok(())
+}
+
+/// Concatenates the outputs of the bands
+fn concatenate(bands: impl IntoIterator<Item = BitWriter>) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    bands.into_iter().for_each(|band| writer.append(&band));
+    writer.align();
+    writer.into_bytes()
+}
+
+/// Encodes the band sequential `samples` of a cube of `dimensions`
+pub(crate) fn encode(
+    samples: &[i32],
+    dimensions: Dimensions,
+    parameters: &PredictorParameters,
+) -> Result<Vec<u8>, Error> {
+    check(samples, dimensions, parameters)?;
+    Ok(concatenate(
+        (0..dimensions.0).map(|z| encode_band(samples, z, dimensions, parameters)),
+    ))
+}
+
+/// Std parallel `encode`, the bands are split in `n_threads` chunks
+pub(crate) fn parallel_encode(
+    samples: &[i32],
+    dimensions: Dimensions,
+    parameters: &PredictorParameters,
+    n_threads: usize,
+) -> Result<Vec<u8>, Error> {
+    check(samples, dimensions, parameters)?;
+    if n_threads == 0 {
+        return Err(Error::InvalidNumberOfThreads);
+    }
+
+    let bands: Vec<usize> = (0..dimensions.0).collect();
+    let bands_per_thread = (dimensions.0 - 1) / n_threads + 1;
+    let outputs: Vec<Vec<BitWriter>> = thread::scope(|s| {
+        let handles: Vec<_> = bands
+            .chunks(bands_per_thread)
+            .map(|chunk| {
+                s.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|z| encode_band(samples, *z, dimensions, parameters))
+                        .collect()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    Ok(concatenate(outputs.into_iter().flatten()))
+}
+
+/// Rayon `encode`, every band is a task
+pub(crate) fn rayon_encode(
+    samples: &[i32],
+    dimensions: Dimensions,
+    parameters: &PredictorParameters,
+) -> Result<Vec<u8>, Error> {
+    check(samples, dimensions, parameters)?;
+    let outputs: Vec<BitWriter> = (0..dimensions.0)
+        .into_par_iter()
+        .map(|z| encode_band(samples, z, dimensions, parameters))
+        .collect();
+    Ok(concatenate(outputs))
+}
+
+/// Decodes the output of `encode` into the band sequential `samples`, the bands are decoded
+/// sequentially because each one is predicted from the previous ones
+pub(crate) fn decode(
+    data: &[u8],
+    samples: &mut [i32],
+    (bands, rows, cols): Dimensions,
+    parameters: &PredictorParameters,
+) -> Result<(), Error> {
+    parameters.check()?;
+    if bands == 0 || rows == 0 || cols < 2 || samples.len() != bands * rows * cols {
+        return Err(Error::InvalidDimensions);
+    }
+
+    let band_size = rows * cols;
+    let max = (1 << parameters.dynamic_range) - 1;
+    let mut reader = BitReader::new(data);
+    for z in 0..bands {
+        let (decoded, rest) = samples.split_at_mut(z * band_size);
+        let band = &mut rest[..band_size];
+        let previous = previous_bands(decoded, z, band_size, parameters);
+
+        let mut predictor = Predictor::new(parameters, cols, previous.len());
+        let mut coder = EntropyCoder::new(parameters);
+        for t in 0..band_size {
+            let prediction = predictor.predict(band, &previous, t);
+            let mapped = coder.read(&mut reader, t).ok_or(Error::InvalidBitstream)?;
+            let sample = unmap_residual(mapped, &prediction, max);
+            if !(0..=max).contains(&sample) {
+                return Err(Error::InvalidBitstream);
+            }
+            band[t] = sample as i32;
+            predictor.update(&prediction, sample, t);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::Cube;
+    use crate::parallel_traits::ParallelPredictiveEncoder;
+    use crate::rayon_traits::RayonPredictiveEncoder;
+    use crate::PredictiveEncoder;
+
+    #[test]
+    fn known_answer() {
+        let parameters = PredictorParameters {
+            dynamic_range: 8,
+            ..Default::default()
+        };
+        // band 0: 130 is predicted as the mid value 128 (mapped 4, written in 8 bits), 131 from
+        // the local sum as 130 (mapped 1, k = 3: 1 001)
+        // band 1: 132 is predicted from the previous band as 130 (mapped 4), 133 from the local
+        // sum and the central difference of band 0 as 133 (mapped 0: 1 000)
+        let samples = [130, 131, 132, 133];
+        let data = encode(&samples, (2, 1, 2), &parameters).unwrap();
+        assert_eq!(data, [0x04, 0x90, 0x48]);
+
+        let mut decoded = [0; 4];
+        decode(&data, &mut decoded, (2, 1, 2), &parameters).unwrap();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn round_trip() {
+        // bands correlated with each other, with some noise
+        let (bands, rows, cols) = (6, 12, 10);
+        let noise = Cube::<i32>::from_random_seed(5, bands, rows, cols, 0, 64);
+        let data: Vec<i32> = noise
+            .get_data()
+            .iter()
+            .enumerate()
+            .map(|(idx, noise)| {
+                let (z, t) = (idx / (rows * cols), idx % (rows * cols));
+                (1000 + 50 * (t % cols) as i32 + 30 * (t / cols) as i32) * (z as i32 + 4) / 4
+                    + noise
+            })
+            .collect();
+        let cube = Cube::new(data, bands, rows, cols);
+
+        for parameters in [
+            PredictorParameters::default(),
+            PredictorParameters {
+                prediction_bands: 0,
+                weight_exponent_min: -6,
+                weight_exponent_max: 9,
+                ..Default::default()
+            },
+            PredictorParameters {
+                dynamic_range: 14,
+                prediction_bands: 15,
+                weight_resolution: 4,
+                unary_length_limit: 8,
+                initial_count_exponent: 8,
+                rescaling_counter_size: 11,
+                ..Default::default()
+            },
+        ] {
+            let data = cube.predictive_encode(&parameters).unwrap();
+            let mut decoded = Cube::zeroes(bands, rows, cols);
+            decoded.predictive_decode(&data, &parameters).unwrap();
+            assert_eq!(decoded, cube);
+
+            assert_eq!(
+                cube.parallel_predictive_encode(&parameters, 4).unwrap(),
+                data
+            );
+            assert_eq!(cube.rayon_predictive_encode(&parameters).unwrap(), data);
+        }
+
+        let data = cube
+            .predictive_encode(&PredictorParameters::default())
+            .unwrap();
+        assert!(data.len() * 8 < bands * rows * cols * 10);
+
+        let parameters = PredictorParameters {
+            dynamic_range: 8,
+            accumulator_initialization: 2,
+            ..Default::default()
+        };
+        assert!(cube.predictive_encode(&parameters).is_err());
+    }
+}
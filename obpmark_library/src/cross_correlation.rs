@@ -0,0 +1,157 @@
+//! Cross-correlation over lags and normalized template matching
+//! The rows of the maps are split in segments, so that also the maps of a single row (1-D) are
+//! split among the threads
+use crate::number_traits::{Float, Number};
+use crate::{CorrelationPeak, Error};
+
+/// Maximum length of the segments of the rows of the result
+const SEGMENT: usize = 64;
+
+/// Rows and columns of a matrix given by its rows
+fn dimensions<T, R: AsRef<[T]>>(rows: &[R]) -> (usize, usize) {
+    (rows.len(), rows.first().map_or(0, |row| row.as_ref().len()))
+}
+
+/// Computes every element of `result` as `value(row, col)`
+fn fill<T: Number>(
+    result: &mut [&mut [T]],
+    value: impl Fn(usize, usize) -> T + Sync,
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) {
+    let mut starts = Vec::new();
+    let mut segments: Vec<&mut [T]> = Vec::new();
+    for (i, row) in result.iter_mut().enumerate() {
+        for (k, segment) in row.chunks_mut(SEGMENT).enumerate() {
+            starts.push((i, k * SEGMENT));
+            segments.push(segment);
+        }
+    }
+    for_each_row(&mut segments, &|k, segment| {
+        let (i, start) = starts[k];
+        for (j, el) in segment.iter_mut().enumerate() {
+            *el = value(i, start + j);
+        }
+    });
+}
+
+/// Maximum of `map`, the first one in row-major order
+pub(crate) fn peak<T: Number>(map: &[&mut [T]]) -> CorrelationPeak<T> {
+    let mut peak = CorrelationPeak {
+        row: 0,
+        col: 0,
+        value: map[0][0],
+    };
+    for (i, row) in map.iter().enumerate() {
+        for (j, el) in row.iter().enumerate() {
+            if *el > peak.value {
+                peak = CorrelationPeak {
+                    row: i,
+                    col: j,
+                    value: *el,
+                };
+            }
+        }
+    }
+    peak
+}
+
+/// Cross-correlation of `a` and `b` at the lags from `-max_lags` to `max_lags`, see
+/// `CrossCorrelation`
+pub(crate) fn cross_correlation<T: Number>(
+    a: &[&[T]],
+    b: &[&[T]],
+    max_lags: (usize, usize),
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<CorrelationPeak<T>, Error> {
+    let ((a_rows, a_cols), (b_rows, b_cols)) = (dimensions(a), dimensions(b));
+    if a_rows == 0 || a_cols == 0 || b_rows == 0 || b_cols == 0 {
+        return Err(Error::InvalidDimensions);
+    }
+    if dimensions(result) != (2 * max_lags.0 + 1, 2 * max_lags.1 + 1) {
+        return Err(Error::InvalidDimensions);
+    }
+
+    // the elements i of a and i + lag of b overlap for i in [max(0, -lag), min(a, b - lag))
+    let overlap = |lag: isize, a_len: usize, b_len: usize| {
+        let first = (-lag).max(0) as usize;
+        let last = (b_len as isize - lag).clamp(0, a_len as isize) as usize;
+        first..last.max(first)
+    };
+    fill(
+        result,
+        |r, c| {
+            let dy = r as isize - max_lags.0 as isize;
+            let dx = c as isize - max_lags.1 as isize;
+            let mut sum = T::zero();
+            for i in overlap(dy, a_rows, b_rows) {
+                let b_row = &b[(i as isize + dy) as usize];
+                for j in overlap(dx, a_cols, b_cols) {
+                    sum += a[i][j] * b_row[(j as isize + dx) as usize];
+                }
+            }
+            sum
+        },
+        for_each_row,
+    );
+    Ok(peak(result))
+}
+
+/// Zero-normalized cross-correlation of `template` with every window of `image`, see
+/// `TemplateMatching`
+pub(crate) fn template_matching<T: Number, O: Float>(
+    image: &[&[T]],
+    template: &[&[T]],
+    result: &mut [&mut [O]],
+    for_each_row: impl Fn(&mut [&mut [O]], &(dyn Fn(usize, &mut [O]) + Sync)),
+) -> Result<CorrelationPeak<O>, Error> {
+    let ((rows, cols), (t_rows, t_cols)) = (dimensions(image), dimensions(template));
+    if t_rows == 0 || t_cols == 0 || t_rows > rows || t_cols > cols {
+        return Err(Error::InvalidKernelDimensions);
+    }
+    if dimensions(result) != (rows - t_rows + 1, cols - t_cols + 1) {
+        return Err(Error::InvalidDimensions);
+    }
+
+    let convert = |rows: &[&[T]]| -> Vec<Vec<O>> {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|el| O::from_f64(el.as_()).unwrap())
+                    .collect()
+            })
+            .collect()
+    };
+    let (image, mut template) = (convert(image), convert(template));
+    let n = O::from_usize(t_rows * t_cols).unwrap();
+    let template_mean = template.iter().flatten().copied().sum::<O>() / n;
+    for el in template.iter_mut().flatten() {
+        *el -= template_mean;
+    }
+    let template_energy: O = template.iter().flatten().map(|el| *el * *el).sum();
+
+    fill(
+        result,
+        |r, c| {
+            let window = || image[r..r + t_rows].iter().map(|row| &row[c..c + t_cols]);
+            let mean = window().flatten().copied().sum::<O>() / n;
+            let (mut product, mut energy) = (O::zero(), O::zero());
+            for (window_row, template_row) in window().zip(&template) {
+                for (el, t) in window_row.iter().zip(template_row) {
+                    let el = *el - mean;
+                    product += el * *t;
+                    energy += el * el;
+                }
+            }
+            let norm = (energy * template_energy).sqrt();
+            // a flat window (or template) is not correlated
+            if norm > O::zero() {
+                product / norm
+            } else {
+                O::zero()
+            }
+        },
+        for_each_row,
+    );
+    Ok(peak(result))
+}
@@ -0,0 +1,182 @@
+//! 3d data, like the images of hyperspectral payloads, made of `bands` images of `rows` x `cols`
+//! samples
+use crate::ccsds_123;
+use crate::parallel_traits::ParallelPredictiveEncoder;
+use crate::rayon_traits::RayonPredictiveEncoder;
+use crate::{random_matrix_data, Error, Number, PredictiveEncoder, PredictorParameters};
+
+/// Order of the samples of a cube in memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interleaving {
+    /// Band sequential: one band after the other
+    Bsq,
+    /// Band interleaved by line: the rows of all the bands, one row after the other
+    Bil,
+    /// Band interleaved by pixel: the bands of all the pixels, one pixel after the other
+    Bip,
+}
+
+impl Interleaving {
+    /// Position of sample (`band`, `row`, `col`) of a `bands` x `rows` x `cols` cube
+    fn index(
+        &self,
+        (band, row, col): (usize, usize, usize),
+        (bands, rows, cols): (usize, usize, usize),
+    ) -> usize {
+        match self {
+            Interleaving::Bsq => (band * rows + row) * cols + col,
+            Interleaving::Bil => (row * bands + band) * cols + col,
+            Interleaving::Bip => (row * cols + col) * bands + band,
+        }
+    }
+}
+
+/// The samples are stored band sequential
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cube<T: Number> {
+    data: Vec<T>,
+    bands: usize,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T: Number> Cube<T> {
+    /// Creates a cube from band sequential data, panics if the dimensions are not correct
+    pub fn new(data: Vec<T>, bands: usize, rows: usize, cols: usize) -> Self {
+        assert_eq!(data.len(), bands * rows * cols);
+        Cube {
+            data,
+            bands,
+            rows,
+            cols,
+        }
+    }
+
+    /// Creates a cube from data stored with `interleaving`
+    pub fn from_interleaved(
+        data: &[T],
+        interleaving: Interleaving,
+        bands: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Self {
+        let mut cube = Self::zeroes(bands, rows, cols);
+        for (idx, el) in cube.data.iter_mut().enumerate() {
+            let position = (idx / (rows * cols), idx / cols % rows, idx % cols);
+            *el = data[interleaving.index(position, (bands, rows, cols))];
+        }
+        cube
+    }
+
+    /// Returns the samples stored with `interleaving`
+    pub fn to_interleaved(&self, interleaving: Interleaving) -> Vec<T> {
+        let (bands, rows, cols) = (self.bands, self.rows, self.cols);
+        let mut data = vec![T::zero(); self.data.len()];
+        for (idx, el) in self.data.iter().enumerate() {
+            let position = (idx / (rows * cols), idx / cols % rows, idx % cols);
+            data[interleaving.index(position, (bands, rows, cols))] = *el;
+        }
+        data
+    }
+
+    pub fn zeroes(bands: usize, rows: usize, cols: usize) -> Self {
+        Self::new(vec![T::zero(); bands * rows * cols], bands, rows, cols)
+    }
+
+    /// Creates a cube with random samples in `min..max`
+    pub fn from_random_seed(
+        seed: u64,
+        bands: usize,
+        rows: usize,
+        cols: usize,
+        min: T,
+        max: T,
+    ) -> Self {
+        let data = random_matrix_data(seed, bands, rows * cols, min, max);
+        Self::new(data.into_iter().flatten().collect(), bands, rows, cols)
+    }
+
+    pub fn bands(&self) -> usize {
+        self.bands
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Band sequential samples
+    pub fn get_data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Samples of band `band`, row major
+    pub fn band(&self, band: usize) -> &[T] {
+        &self.data[band * self.rows * self.cols..(band + 1) * self.rows * self.cols]
+    }
+
+    pub fn band_mut(&mut self, band: usize) -> &mut [T] {
+        &mut self.data[band * self.rows * self.cols..(band + 1) * self.rows * self.cols]
+    }
+}
+
+impl PredictiveEncoder for Cube<i32> {
+    fn predictive_encode(&self, parameters: &PredictorParameters) -> Result<Vec<u8>, Error> {
+        ccsds_123::encode(&self.data, (self.bands, self.rows, self.cols), parameters)
+    }
+
+    fn predictive_decode(
+        &mut self,
+        data: &[u8],
+        parameters: &PredictorParameters,
+    ) -> Result<(), Error> {
+        let dimensions = (self.bands, self.rows, self.cols);
+        ccsds_123::decode(data, &mut self.data, dimensions, parameters)
+    }
+}
+
+impl ParallelPredictiveEncoder for Cube<i32> {
+    fn parallel_predictive_encode(
+        &self,
+        parameters: &PredictorParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let dimensions = (self.bands, self.rows, self.cols);
+        ccsds_123::parallel_encode(&self.data, dimensions, parameters, n_threads)
+    }
+}
+
+impl RayonPredictiveEncoder for Cube<i32> {
+    fn rayon_predictive_encode(&self, parameters: &PredictorParameters) -> Result<Vec<u8>, Error> {
+        ccsds_123::rayon_encode(&self.data, (self.bands, self.rows, self.cols), parameters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interleaving() {
+        let (bands, rows, cols) = (3, 2, 4);
+        let cube = Cube::new((0..24).collect(), bands, rows, cols);
+        assert_eq!(cube.band(1)[0], 8);
+        assert_eq!(cube.to_interleaved(Interleaving::Bsq), cube.get_data());
+
+        let bil = cube.to_interleaved(Interleaving::Bil);
+        assert_eq!(bil[..2 * cols], [0, 1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(
+            Cube::from_interleaved(&bil, Interleaving::Bil, bands, rows, cols),
+            cube
+        );
+        let bip = cube.to_interleaved(Interleaving::Bip);
+        assert_eq!(bip[..2 * bands], [0, 8, 16, 1, 9, 17]);
+        assert_eq!(
+            Cube::from_interleaved(&bip, Interleaving::Bip, bands, rows, cols),
+            cube
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Injection of soft errors in the elements of the matrices, to study the effect of single event
+//! upsets on the kernels
+//! A fault injection campaign runs the kernel without faults (golden run) and then once per
+//! injection, every outcome is classified comparing the result with the golden one
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::number_traits::Number;
+use crate::{Error, FaultInjection};
+
+/// Fault model of an injection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// `bits` distinct random bits of the element are flipped, 1 is a single bit upset
+    BitFlip { bits: u32 },
+    /// A bit of the element is forced to `value`, a random one if `bit` is `None`
+    /// The bit is forced when the fault is injected, later writes overwrite it
+    StuckAt { bit: Option<u32>, value: bool },
+}
+
+/// An injected fault, `mask` has the bits of the element that changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InjectedFault {
+    pub row: usize,
+    pub col: usize,
+    pub mask: u64,
+}
+
+/// Injects faults at random locations, the same seed gives the same locations
+pub struct FaultInjector {
+    rng: StdRng,
+}
+
+impl FaultInjector {
+    pub fn new(seed: u64) -> Self {
+        FaultInjector {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Injects `fault` in `elements` distinct random elements of `matrix` (at most all of them)
+    /// `Error::InvalidFault` if the stuck bit is not a bit of the elements
+    pub fn inject<T: Number>(
+        &mut self,
+        matrix: &mut impl FaultInjection<T>,
+        fault: Fault,
+        elements: usize,
+    ) -> Result<Vec<InjectedFault>, Error> {
+        if let Fault::StuckAt { bit: Some(bit), .. } = fault {
+            if bit >= T::BITS {
+                return Err(Error::InvalidFault);
+            }
+        }
+        let (rows, cols) = matrix.dimensions();
+        let elements = elements.min(rows * cols);
+        let locations = index::sample(&mut self.rng, rows * cols, elements).into_vec();
+        Ok(locations
+            .into_iter()
+            .map(|location| {
+                let (row, col) = (location / cols, location % cols);
+                let mask = match fault {
+                    Fault::BitFlip { bits } => {
+                        let bits = (bits as usize).min(T::BITS as usize);
+                        let flipped = index::sample(&mut self.rng, T::BITS as usize, bits)
+                            .into_iter()
+                            .fold(0, |mask, bit| mask | 1 << bit);
+                        matrix.modify_bits(row, col, |value| value ^ flipped)
+                    }
+                    Fault::StuckAt { bit, value } => {
+                        let bit = bit.unwrap_or_else(|| self.rng.gen_range(0..T::BITS));
+                        matrix.modify_bits(row, col, |bits| {
+                            if value {
+                                bits | 1 << bit
+                            } else {
+                                bits & !(1 << bit)
+                            }
+                        })
+                    }
+                };
+                InjectedFault { row, col, mask }
+            })
+            .collect())
+    }
+}
+
+/// Effect of a fault on the result of a kernel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The result is the same of the golden run
+    Masked,
+    /// The result is wrong and nothing signaled it
+    SilentDataCorruption,
+    /// The kernel, a check or a mitigation signaled the error
+    Detected,
+}
+
+/// Classifies the outcome of a run: `detected` is set if the kernel failed or a check found the
+/// error, a result that is not finite (NaN or infinite) also counts as detected
+pub fn classify<T: Number>(result: &[Vec<T>], golden: &[Vec<T>], detected: bool) -> Outcome {
+    let values = || result.iter().flatten();
+    if detected || values().any(|el| !el.as_().is_finite()) {
+        Outcome::Detected
+    } else if values().zip(golden.iter().flatten()).all(|(a, b)| a == b) {
+        Outcome::Masked
+    } else {
+        Outcome::SilentDataCorruption
+    }
+}
+
+/// Outcomes of a fault injection campaign
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FaultReport {
+    pub masked: usize,
+    pub silent_data_corruption: usize,
+    pub detected: usize,
+}
+
+impl FaultReport {
+    pub fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Masked => self.masked += 1,
+            Outcome::SilentDataCorruption => self.silent_data_corruption += 1,
+            Outcome::Detected => self.detected += 1,
+        }
+    }
+
+    pub fn injections(&self) -> usize {
+        self.masked + self.silent_data_corruption + self.detected
+    }
+}
+
+impl fmt::Display for FaultReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percentage = |count: usize| 100.0 * count as f64 / self.injections().max(1) as f64;
+        writeln!(f, "Injections: {}", self.injections())?;
+        writeln!(
+            f,
+            "Masked: {} ({:.1}%)",
+            self.masked,
+            percentage(self.masked)
+        )?;
+        writeln!(
+            f,
+            "Silent data corruption: {} ({:.1}%)",
+            self.silent_data_corruption,
+            percentage(self.silent_data_corruption)
+        )?;
+        write!(
+            f,
+            "Detected: {} ({:.1}%)",
+            self.detected,
+            percentage(self.detected)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix_1d::Matrix1d;
+    use crate::{BaseMatrix, MatMul};
+
+    #[test]
+    fn injection_and_classification() {
+        // a non square product, the dense layers of the networks
+        let weights = Matrix1d::new(
+            vec![vec![1.0f32, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]],
+            3,
+            2,
+        );
+        let input = Matrix1d::new(vec![vec![1.0], vec![-1.0]], 2, 1);
+        let mut golden = Matrix1d::zeroes(3, 1);
+        weights.multiply(&input, &mut golden).unwrap();
+        let golden = golden.get_data();
+        assert_eq!(golden, vec![vec![-1.0], vec![-1.0], vec![-1.0]]);
+
+        // the same seed hits the same elements with the same bits
+        let mut m1 = Matrix1d::new(weights.get_data(), 3, 2);
+        let mut m2 = Matrix1d::new(weights.get_data(), 3, 2);
+        let fault = Fault::BitFlip { bits: 2 };
+        let faults = FaultInjector::new(7).inject(&mut m1, fault, 2).unwrap();
+        assert_eq!(
+            faults,
+            FaultInjector::new(7).inject(&mut m2, fault, 2).unwrap()
+        );
+        let bits = |m: &Matrix1d<f32>| -> Vec<u32> {
+            m.get_data()
+                .concat()
+                .iter()
+                .map(|el| el.to_bits())
+                .collect()
+        };
+        assert_eq!(bits(&m1), bits(&m2));
+        assert_eq!(faults.len(), 2);
+        assert_ne!(faults[0], faults[1]);
+        for fault in &faults {
+            assert_eq!(fault.mask.count_ones(), 2);
+            let original = weights.get_data()[fault.row][fault.col].to_bits();
+            let faulty = m1.get_data()[fault.row][fault.col].to_bits();
+            assert_eq!((original ^ faulty) as u64, fault.mask);
+        }
+        assert_eq!(
+            FaultInjector::new(7)
+                .inject(&mut m1, fault, 10)
+                .unwrap()
+                .len(),
+            6
+        );
+
+        // the sign bit of 1.0 is already 0, stuck at 0 does not change the element
+        let mut m = Matrix1d::new(vec![vec![1.0f32]], 1, 1);
+        let stuck = |value| Fault::StuckAt {
+            bit: Some(31),
+            value,
+        };
+        assert_eq!(
+            FaultInjector::new(0)
+                .inject(&mut m, stuck(false), 1)
+                .unwrap()[0]
+                .mask,
+            0
+        );
+        assert_eq!(
+            FaultInjector::new(0)
+                .inject(&mut m, stuck(true), 1)
+                .unwrap()[0]
+                .mask,
+            1 << 31
+        );
+        assert_eq!(m.get_data(), vec![vec![-1.0]]);
+        let mut m = Matrix1d::new(vec![vec![5i32]], 1, 1);
+        FaultInjector::new(0)
+            .inject(&mut m, Fault::BitFlip { bits: 1 }, 1)
+            .unwrap();
+        assert_eq!((m.get_data()[0][0] ^ 5).count_ones(), 1);
+
+        // a stuck bit past the bits of the elements is rejected, nothing is injected
+        for bit in [32, 64, 100] {
+            let fault = Fault::StuckAt {
+                bit: Some(bit),
+                value: true,
+            };
+            assert!(FaultInjector::new(0).inject(&mut m, fault, 1).is_err());
+        }
+        let mut m = Matrix1d::new(vec![vec![1.0f64]], 1, 1);
+        let fault = Fault::StuckAt {
+            bit: Some(63),
+            value: true,
+        };
+        FaultInjector::new(0).inject(&mut m, fault, 1).unwrap();
+        assert_eq!(m.get_data(), vec![vec![-1.0]]);
+
+        let mut report = FaultReport::default();
+        let mut result = Matrix1d::zeroes(3, 1);
+        for (row, value) in [(0, 1.0), (1, f32::NAN), (2, -1.0)] {
+            let mut weights = Matrix1d::new(weights.get_data(), 3, 2);
+            weights.modify_bits(row, 0, |_| value.to_bits() as u64);
+            weights.multiply(&input, &mut result).unwrap();
+            report.record(classify(&result.get_data(), &golden, false));
+        }
+        report.record(classify(&golden, &golden, true));
+        assert_eq!(
+            report,
+            FaultReport {
+                masked: 1,
+                silent_data_corruption: 1,
+                detected: 2
+            }
+        );
+        assert_eq!(classify(&golden, &golden, false), Outcome::Masked);
+        assert_eq!(report.to_string().lines().nth(1), Some("Masked: 1 (25.0%)"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Mixed radix FFT for sizes that are not a power of 2
+//! Sizes whose only prime factors are 2, 3 and 5 are decomposed in radix 4, 2, 3 and 5
+//! butterflies, any other size uses Bluestein's algorithm, which computes the transform as a
+//! convolution through a power of 2 transform
+//! The data is stored as interleaved real and imaginary parts, like for the radix-2 FFT
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+use crate::number_traits::Float;
+use crate::Error;
+
+#[derive(Clone, Copy, Debug)]
+struct Complex<T> {
+    re: T,
+    im: T,
+}
+
+impl<T: Float> Complex<T> {
+    fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+
+    fn zero() -> Self {
+        Complex::new(T::zero(), T::zero())
+    }
+
+    /// `e^(i * angle)`, computed in double precision
+    fn from_angle(angle: f64) -> Self {
+        Complex::new(
+            T::from_f64(angle.cos()).unwrap(),
+            T::from_f64(angle.sin()).unwrap(),
+        )
+    }
+
+    fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    fn scale(self, factor: T) -> Self {
+        Complex::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl<T: Float> Add for Complex<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl<T: Float> Sub for Complex<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl<T: Float> Mul for Complex<T> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+/// Precomputed factorization and twiddle factors for the FFT of `n` complex numbers
+/// A plan can be reused for any number of transforms of the same size
+#[derive(Debug)]
+pub struct FftPlan<T> {
+    n: usize,
+    /// (radix, remaining size) for each stage
+    factors: Vec<(usize, usize)>,
+    /// `e^(-2 pi i k / n)` for k in 0..n
+    twiddles: Vec<Complex<T>>,
+    bluestein: Option<Box<Bluestein<T>>>,
+}
+
+/// Bluestein's algorithm: `X[k] = w[k] * sum(x[j] * w[j] * conj(w[k - j]))` with
+/// `w[k] = e^(-pi i k^2 / n)`, the sum is a convolution computed with a power of 2 FFT
+#[derive(Debug)]
+struct Bluestein<T> {
+    chirp: Vec<Complex<T>>,
+    /// Transform of the conjugated chirp, padded to the size of `plan`
+    filter: Vec<Complex<T>>,
+    plan: FftPlan<T>,
+}
+
+impl<T: Float> FftPlan<T> {
+    /// Creates the plan for a transform of `n` complex numbers, `n` must be at least 1
+    pub fn new(n: usize) -> Result<Self, Error> {
+        if n == 0 {
+            return Err(Error::UnsupportedSize);
+        }
+
+        let twiddles = (0..n)
+            .map(|k| Complex::from_angle(-2.0 * PI * k as f64 / n as f64))
+            .collect();
+
+        match factorize(n) {
+            Some(factors) => Ok(FftPlan {
+                n,
+                factors,
+                twiddles,
+                bluestein: None,
+            }),
+            None => Ok(FftPlan {
+                n,
+                factors: Vec::new(),
+                twiddles: Vec::new(),
+                bluestein: Some(Box::new(Bluestein::new(n)?)),
+            }),
+        }
+    }
+
+    /// Number of complex numbers of the transform
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Returns true if the size has prime factors other than 2, 3 and 5
+    pub fn uses_bluestein(&self) -> bool {
+        self.bluestein.is_some()
+    }
+
+    /// Forward transform in place, `data` holds `len()` interleaved complex numbers
+    pub fn forward(&self, data: &mut [T]) -> Result<(), Error> {
+        let mut values = self.to_complex(data)?;
+        self.transform(&mut values);
+        Self::from_complex(&values, data);
+        Ok(())
+    }
+
+    /// Inverse transform in place, scaled by `1 / len()`
+    pub fn inverse(&self, data: &mut [T]) -> Result<(), Error> {
+        let mut values = self.to_complex(data)?;
+        self.inverse_transform(&mut values);
+        Self::from_complex(&values, data);
+        Ok(())
+    }
+
+    fn to_complex(&self, data: &[T]) -> Result<Vec<Complex<T>>, Error> {
+        if data.len() != self.n << 1 {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok(data
+            .chunks_exact(2)
+            .map(|el| Complex::new(el[0], el[1]))
+            .collect())
+    }
+
+    fn from_complex(values: &[Complex<T>], data: &mut [T]) {
+        for (el, value) in data.chunks_exact_mut(2).zip(values) {
+            el[0] = value.re;
+            el[1] = value.im;
+        }
+    }
+
+    fn transform(&self, data: &mut [Complex<T>]) {
+        if let Some(bluestein) = &self.bluestein {
+            bluestein.transform(data);
+        } else if self.n > 1 {
+            let input = data.to_vec();
+            self.stage(data, &input, 0, 1, &self.factors);
+        }
+    }
+
+    /// The inverse is computed with the forward transform as `conj(fft(conj(x))) / n`
+    fn inverse_transform(&self, data: &mut [Complex<T>]) {
+        data.iter_mut().for_each(|el| *el = el.conj());
+        self.transform(data);
+        let scale = T::one() / T::from_usize(self.n).unwrap();
+        data.iter_mut().for_each(|el| *el = el.conj().scale(scale));
+    }
+
+    /// Decimation in time: `output` (p * m elements) is the transform of the elements of
+    /// `input` starting at `offset` with distance `stride`
+    fn stage(
+        &self,
+        output: &mut [Complex<T>],
+        input: &[Complex<T>],
+        offset: usize,
+        stride: usize,
+        factors: &[(usize, usize)],
+    ) {
+        let (p, m) = factors[0];
+        if m == 1 {
+            for (q, el) in output.iter_mut().enumerate() {
+                *el = input[offset + q * stride];
+            }
+        } else {
+            for (q, sub_output) in output.chunks_exact_mut(m).enumerate() {
+                self.stage(
+                    sub_output,
+                    input,
+                    offset + q * stride,
+                    stride * p,
+                    &factors[1..],
+                );
+            }
+        }
+
+        match p {
+            2 => self.butterfly_2(output, stride, m),
+            3 => self.butterfly_3(output, stride, m),
+            4 => self.butterfly_4(output, stride, m),
+            5 => self.butterfly_5(output, stride, m),
+            _ => unreachable!("radix {} not supported", p),
+        }
+    }
+
+    fn butterfly_2(&self, output: &mut [Complex<T>], stride: usize, m: usize) {
+        let (first, second) = output.split_at_mut(m);
+        for (k, (a, b)) in first.iter_mut().zip(second).enumerate() {
+            let t = *b * self.twiddles[k * stride];
+            *b = *a - t;
+            *a = *a + t;
+        }
+    }
+
+    fn butterfly_3(&self, output: &mut [Complex<T>], stride: usize, m: usize) {
+        let half = T::from_f64(0.5).unwrap();
+        let epi3 = self.twiddles[stride * m];
+        for k in 0..m {
+            let s1 = output[k + m] * self.twiddles[k * stride];
+            let s2 = output[k + 2 * m] * self.twiddles[2 * k * stride];
+            let s3 = s1 + s2;
+            let s0 = (s1 - s2).scale(epi3.im);
+
+            let a = output[k] - s3.scale(half);
+            output[k] = output[k] + s3;
+            output[k + m] = Complex::new(a.re - s0.im, a.im + s0.re);
+            output[k + 2 * m] = Complex::new(a.re + s0.im, a.im - s0.re);
+        }
+    }
+
+    fn butterfly_4(&self, output: &mut [Complex<T>], stride: usize, m: usize) {
+        for k in 0..m {
+            let s0 = output[k + m] * self.twiddles[k * stride];
+            let s1 = output[k + 2 * m] * self.twiddles[2 * k * stride];
+            let s2 = output[k + 3 * m] * self.twiddles[3 * k * stride];
+
+            let s5 = output[k] - s1;
+            let s6 = output[k] + s1;
+            let s3 = s0 + s2;
+            let s4 = s0 - s2;
+
+            output[k] = s6 + s3;
+            output[k + 2 * m] = s6 - s3;
+            output[k + m] = Complex::new(s5.re + s4.im, s5.im - s4.re);
+            output[k + 3 * m] = Complex::new(s5.re - s4.im, s5.im + s4.re);
+        }
+    }
+
+    fn butterfly_5(&self, output: &mut [Complex<T>], stride: usize, m: usize) {
+        let ya = self.twiddles[stride * m];
+        let yb = self.twiddles[2 * stride * m];
+        for k in 0..m {
+            let s0 = output[k];
+            let s1 = output[k + m] * self.twiddles[k * stride];
+            let s2 = output[k + 2 * m] * self.twiddles[2 * k * stride];
+            let s3 = output[k + 3 * m] * self.twiddles[3 * k * stride];
+            let s4 = output[k + 4 * m] * self.twiddles[4 * k * stride];
+
+            let s7 = s1 + s4;
+            let s10 = s1 - s4;
+            let s8 = s2 + s3;
+            let s9 = s2 - s3;
+
+            output[k] = s0 + s7 + s8;
+
+            let s5 = Complex::new(
+                s0.re + s7.re * ya.re + s8.re * yb.re,
+                s0.im + s7.im * ya.re + s8.im * yb.re,
+            );
+            let s6 = Complex::new(
+                s10.im * ya.im + s9.im * yb.im,
+                -(s10.re * ya.im) - s9.re * yb.im,
+            );
+            output[k + m] = s5 - s6;
+            output[k + 4 * m] = s5 + s6;
+
+            let s11 = Complex::new(
+                s0.re + s7.re * yb.re + s8.re * ya.re,
+                s0.im + s7.im * yb.re + s8.im * ya.re,
+            );
+            let s12 = Complex::new(
+                -(s10.im * yb.im) + s9.im * ya.im,
+                s10.re * yb.im - s9.re * ya.im,
+            );
+            output[k + 2 * m] = s11 + s12;
+            output[k + 3 * m] = s11 - s12;
+        }
+    }
+}
+
+impl<T: Float> Bluestein<T> {
+    fn new(n: usize) -> Result<Self, Error> {
+        let size = (2 * n - 1).next_power_of_two();
+        let plan = FftPlan::new(size)?;
+
+        // k^2 is reduced modulo 2n to keep the angle small
+        let chirp: Vec<Complex<T>> = (0..n)
+            .map(|k| Complex::from_angle(-PI * ((k * k) % (2 * n)) as f64 / n as f64))
+            .collect();
+
+        let mut filter = vec![Complex::zero(); size];
+        filter[0] = chirp[0].conj();
+        for k in 1..n {
+            filter[k] = chirp[k].conj();
+            filter[size - k] = chirp[k].conj();
+        }
+        plan.transform(&mut filter);
+
+        Ok(Bluestein {
+            chirp,
+            filter,
+            plan,
+        })
+    }
+
+    fn transform(&self, data: &mut [Complex<T>]) {
+        let mut buffer = vec![Complex::zero(); self.filter.len()];
+        for ((el, x), w) in buffer.iter_mut().zip(data.iter()).zip(&self.chirp) {
+            *el = *x * *w;
+        }
+
+        self.plan.transform(&mut buffer);
+        for (el, f) in buffer.iter_mut().zip(&self.filter) {
+            *el = *el * *f;
+        }
+        self.plan.inverse_transform(&mut buffer);
+
+        for ((x, el), w) in data.iter_mut().zip(buffer).zip(&self.chirp) {
+            *x = el * *w;
+        }
+    }
+}
+
+/// Splits `n` in radix 4, 2, 3 and 5 stages, returns `None` if `n` has other prime factors
+fn factorize(n: usize) -> Option<Vec<(usize, usize)>> {
+    let mut factors = Vec::new();
+    let mut m = n;
+    for p in [4, 2, 3, 5] {
+        while m.is_multiple_of(p) {
+            m /= p;
+            factors.push((p, m));
+        }
+    }
+    if m == 1 {
+        Some(factors)
+    } else {
+        None
+    }
+}
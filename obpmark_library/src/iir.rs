@@ -0,0 +1,84 @@
+//! Cascaded biquad IIR filters, every row is an independent channel and the recursion runs
+//! along it
+use crate::number_traits::Number;
+use crate::{Biquad, BiquadCascade, BiquadForm, Error};
+
+/// Coefficients of a section with the feedback ones negated, so that every output is a sum of
+/// products (see `FixedPoint::fixed_dot`)
+fn coefficients<T: Number>(section: &Biquad<T>) -> ([T; 3], [T; 2]) {
+    (section.b, section.a.map(|a| T::zero() - a))
+}
+
+/// Filters `row` in place with a section in direct form I, each output is a single sum of
+/// products (rounded once to the guard bits in fixed point)
+fn direct_form_1<T: Number>(section: &Biquad<T>, fraction_bits: u32, row: &mut [T]) {
+    let ([b0, b1, b2], [a1, a2]) = coefficients(section);
+    let coefficients = [b0, b1, b2, a1, a2];
+    let (mut x1, mut x2, mut y1, mut y2) = (T::zero(), T::zero(), T::zero(), T::zero());
+    for el in row.iter_mut() {
+        let x = *el;
+        let y = T::fixed_dot([x, x1, x2, y1, y2], coefficients, fraction_bits);
+        (x2, x1, y2, y1) = (x1, x, y1, y);
+        *el = y;
+    }
+}
+
+/// Filters `row` in place with a section in transposed direct form II, the states hold the
+/// partial sums of the next two outputs (rounded to the guard bits in fixed point)
+fn transposed_direct_form_2<T: Number>(section: &Biquad<T>, fraction_bits: u32, row: &mut [T]) {
+    let ([b0, b1, b2], [a1, a2]) = coefficients(section);
+    let (mut s1, mut s2) = (T::zero(), T::zero());
+    for el in row.iter_mut() {
+        let x = *el;
+        let y = T::fixed_dot([x], [b0], fraction_bits) + s1;
+        s1 = T::fixed_dot([x, y], [b1, a1], fraction_bits) + s2;
+        s2 = T::fixed_dot([x, y], [b2, a2], fraction_bits);
+        *el = y;
+    }
+}
+
+/// Filters every row of `channels` into the same row of `result`, one section after the other
+pub(crate) fn filter<T: Number>(
+    channels: &[&[T]],
+    cascade: &BiquadCascade<T>,
+    result: &mut [&mut [T]],
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) -> Result<(), Error> {
+    if channels.len() != result.len()
+        || channels
+            .iter()
+            .zip(result.iter())
+            .any(|(channel, row)| channel.len() != row.len())
+    {
+        return Err(Error::InvalidDimensions);
+    }
+    if cascade.sections.is_empty() {
+        return Err(Error::InvalidKernelDimensions);
+    }
+    cascade.check_stability()?;
+
+    // in fixed point the samples between the sections and the states keep `fraction_bits` guard
+    // bits, otherwise the poles amplify the rounding to the integer samples, only the output is
+    // rounded
+    let (one, scale) = (
+        T::fixed_from_f64(1.0, 0),
+        T::fixed_from_f64(1.0, cascade.fraction_bits),
+    );
+    for_each_row(result, &|i, row| {
+        for (el, x) in row.iter_mut().zip(channels[i]) {
+            *el = T::fixed_dot([*x], [scale], 0);
+        }
+        for section in &cascade.sections {
+            match cascade.form {
+                BiquadForm::DirectFormI => direct_form_1(section, cascade.fraction_bits, row),
+                BiquadForm::TransposedDirectFormII => {
+                    transposed_direct_form_2(section, cascade.fraction_bits, row)
+                }
+            }
+        }
+        for el in row.iter_mut() {
+            *el = T::fixed_dot([*el], [one], cascade.fraction_bits);
+        }
+    });
+    Ok(())
+}
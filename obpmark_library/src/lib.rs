@@ -4,13 +4,19 @@ use std::fs::File;
 use std::io::{self, BufRead, Write};
 use std::path::Path;
 
+use fft_plan::FftPlan;
 use number_traits::*;
+use tmr::TmrReport;
 
 #[derive(Debug)]
 pub enum Error {
     InvalidDimensions,
     InvalidKernelDimensions,
     InvalidNumberOfThreads,
+    UnsupportedSize,
+    InvalidBitstream,
+    UnstableFilter,
+    InvalidFault,
 }
 
 #[derive(Debug)]
@@ -161,16 +167,238 @@ pub trait MatMul<T> {
     fn multiply(&self, other: &Self, result: &mut Self) -> Result<(), Error>;
 }
 
+/// Errors found by the checksums of an ABFT multiplication
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AbftReport {
+    /// Wrong elements, at least the number of rows or columns whose checksums do not match
+    pub detected: usize,
+    /// Wrong elements that were corrected
+    pub corrected: usize,
+}
+
+/// Algorithm based fault tolerant multiplication (Huang and Abraham): the left operand gets an
+/// extra row with the sums of its columns and the right one an extra column with the sums of its
+/// rows, their product carries the checksums of the rows and of the columns of the result
+/// A single wrong element of the product is located and corrected, more are only detected
+pub trait AbftMatMul<T>: MatMul<T> {
+    /// `self` with an extra row with the sums of the columns
+    fn column_checksums(&self) -> Self;
+    /// `self` with an extra column with the sums of the rows
+    fn row_checksums(&self) -> Self;
+    /// Checks and corrects `self`, a product of checksummed operands, then copies it without the
+    /// checksums in `result`
+    /// A checksum matches if it differs from the sum by at most `tolerance` times the sum of the
+    /// absolute values, 0 for exact integer arithmetic
+    fn abft_verify(&mut self, tolerance: f64, result: &mut Self) -> Result<AbftReport, Error>;
+    fn abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+    ) -> Result<AbftReport, Error>;
+}
+
 pub trait Relu<T> {
     fn relu_row(&self, result_row: &mut [T], row_idx: usize);
     fn relu(&self, result: &mut Self) -> Result<(), Error>;
 }
 
+/// Elementwise activation functions, see `Activation`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActivationFunction<T> {
+    /// `max(x, 0)`, the function of `Relu` (to fuse it in a layer)
+    Relu,
+    /// `x` if positive, `slope * x` otherwise
+    LeakyRelu(T),
+    /// ReLU clamped to 6
+    Relu6,
+    /// `1 / (1 + e^-x)`
+    Sigmoid,
+    Tanh,
+    /// Gaussian error linear unit, tanh approximation
+    Gelu,
+    /// Swish with beta = 1 (SiLU), `x * sigmoid(x)`
+    Swish,
+    /// Piecewise linear sigmoid, `clamp(x / 6 + 1/2, 0, 1)`
+    HardSigmoid,
+}
+
+impl<T: Float> ActivationFunction<T> {
+    /// Applies the function to a single value
+    pub fn apply(&self, x: T) -> T {
+        let constant = |c: f64| T::from_f64(c).unwrap();
+        match *self {
+            ActivationFunction::Relu => x.max(T::zero()),
+            ActivationFunction::LeakyRelu(slope) => {
+                if x < T::zero() {
+                    slope * x
+                } else {
+                    x
+                }
+            }
+            ActivationFunction::Relu6 => x.max(T::zero()).min(constant(6.0)),
+            ActivationFunction::Sigmoid => T::one() / (T::one() + (-x).exp()),
+            ActivationFunction::Tanh => x.tanh(),
+            ActivationFunction::Gelu => {
+                let inner = constant((2.0 / std::f64::consts::PI).sqrt())
+                    * (x + constant(0.044715) * x * x * x);
+                constant(0.5) * x * (T::one() + inner.tanh())
+            }
+            ActivationFunction::Swish => x / (T::one() + (-x).exp()),
+            ActivationFunction::HardSigmoid => (x / constant(6.0) + constant(0.5))
+                .max(T::zero())
+                .min(T::one()),
+        }
+    }
+}
+
+pub trait Activation<T: Float> {
+    fn activation_row(&self, result_row: &mut [T], row_idx: usize, function: ActivationFunction<T>);
+    fn activation(&self, result: &mut Self, function: ActivationFunction<T>) -> Result<(), Error>;
+}
+
+/// Fully connected layer, `self` holds the weights (outputs x inputs)  
+/// `result = activation(self * input + bias)`, each column of `input` is a sample and the
+/// bias has a value per output
+pub trait Dense<T: Float> {
+    /// Computes row `row_idx` of the result, `input_transposed` is the transposed input
+    fn dense_row(
+        &self,
+        input_transposed: &Self,
+        bias: &[T],
+        result_row: &mut [T],
+        row_idx: usize,
+        activation: Option<ActivationFunction<T>>,
+    );
+    fn dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+    ) -> Result<(), Error>;
+}
+
+/// Inference mode batch normalization parameters, with a value per feature (row of the matrix)  
+/// `y = scale * (x - mean) / sqrt(variance + epsilon) + shift`
+#[derive(Clone, Debug)]
+pub struct BatchNormParameters<T> {
+    pub mean: Vec<T>,
+    pub variance: Vec<T>,
+    pub scale: Vec<T>,
+    pub shift: Vec<T>,
+    pub epsilon: T,
+}
+
+impl<T: Float> BatchNormParameters<T> {
+    /// Parameters of a batch normalization that does not change its input
+    pub fn identity(features: usize) -> Self {
+        BatchNormParameters {
+            mean: vec![T::zero(); features],
+            variance: vec![T::one(); features],
+            scale: vec![T::one(); features],
+            shift: vec![T::zero(); features],
+            epsilon: T::zero(),
+        }
+    }
+
+    pub(crate) fn check_features(&self, features: usize) -> Result<(), Error> {
+        if self.mean.len() != features
+            || self.variance.len() != features
+            || self.scale.len() != features
+            || self.shift.len() != features
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok(())
+    }
+}
+
+pub trait BatchNorm<T: Float> {
+    fn batch_norm_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        parameters: &BatchNormParameters<T>,
+    );
+    fn batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+    ) -> Result<(), Error>;
+}
+
+/// Softmax as implemented in OBPMark: exponentiates the raw inputs and normalises by the sum
+/// over the whole matrix, overflows for large inputs, see `StableSoftmax` for the general case
 pub trait Softmax<T> {
     fn softmax_row(&self, result_row: &mut [T], row_idx: usize) -> T;
     fn softmax(&self, result: &mut Self) -> Result<(), Error>;
 }
 
+/// Set of elements over which the softmax is normalised
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoftmaxAxis {
+    /// The whole matrix
+    Global,
+    /// Each row separately, the classifier softmax
+    Rows,
+    /// Each column separately
+    Columns,
+}
+
+impl SoftmaxAxis {
+    /// Number of max/sum statistics the softmax needs for a matrix with `cols` columns  
+    /// For `Rows` each row has its own statistic, computed and consumed row by row
+    pub(crate) fn n_statistics(&self, cols: usize) -> usize {
+        match self {
+            SoftmaxAxis::Global | SoftmaxAxis::Rows => 1,
+            SoftmaxAxis::Columns => cols,
+        }
+    }
+
+    /// Index of the statistic used by the element in column `col`
+    pub(crate) fn statistic_idx(&self, col: usize) -> usize {
+        match self {
+            SoftmaxAxis::Global | SoftmaxAxis::Rows => 0,
+            SoftmaxAxis::Columns => col,
+        }
+    }
+}
+
+/// Numerically stable softmax, the maximum of each normalisation group is subtracted before
+/// exponentiating  
+/// The row functions take the statistics as slices with one element per group (see
+/// `SoftmaxAxis::n_statistics`), so that the parallel implementations can combine partial ones
+pub trait StableSoftmax<T> {
+    /// Updates `max` with the elements of row `row_idx`
+    fn softmax_max_row(&self, max: &mut [T], row_idx: usize, axis: SoftmaxAxis);
+
+    /// Writes `exp(x - max)` for row `row_idx` in `result_row` and adds it to `sum`
+    fn softmax_exp_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &mut [T],
+    );
+
+    /// Normalises `result_row`, which holds the output of `softmax_exp_row`  
+    /// If `log` is true it writes the log-softmax `x - max - ln(sum)` instead
+    fn softmax_normalize_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &[T],
+        log: bool,
+    );
+
+    fn stable_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error>;
+    fn log_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error>;
+}
+
 pub trait MaxPooling<T> {
     fn max_pooling_row(
         &self,
@@ -187,6 +415,172 @@ pub trait MaxPooling<T> {
     ) -> Result<(), Error>;
 }
 
+/// Reduction applied to each window of a pooling operation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoolingType {
+    Max,
+    /// Padded positions are not counted when computing the average
+    Average,
+}
+
+impl PoolingType {
+    /// Combines two partial results of the pooling reduction  
+    /// For `Average` the partial results are sums, the division happens in `finalize`
+    pub(crate) fn combine<T: Number>(&self, acc: T, next: T) -> T {
+        match self {
+            PoolingType::Max => {
+                if next > acc {
+                    next
+                } else {
+                    acc
+                }
+            }
+            PoolingType::Average => acc + next,
+        }
+    }
+
+    /// Turns the combined partial results of `count` elements into the pooled value
+    pub(crate) fn finalize<T: Number>(&self, acc: T, count: usize) -> T {
+        match self {
+            PoolingType::Max => acc,
+            PoolingType::Average => acc / T::from_usize(count).unwrap(),
+        }
+    }
+}
+
+/// Window size, stride and padding of a pooling operation  
+/// Padding is implicit, i.e. padded positions are skipped instead of being filled with a value
+#[derive(Clone, Copy, Debug)]
+pub struct PoolingParameters {
+    pub window_rows: usize,
+    pub window_cols: usize,
+    pub row_stride: usize,
+    pub col_stride: usize,
+    pub row_padding: usize,
+    pub col_padding: usize,
+}
+
+impl PoolingParameters {
+    pub fn new(
+        window_rows: usize,
+        window_cols: usize,
+        row_stride: usize,
+        col_stride: usize,
+        row_padding: usize,
+        col_padding: usize,
+    ) -> Self {
+        PoolingParameters {
+            window_rows,
+            window_cols,
+            row_stride,
+            col_stride,
+            row_padding,
+            col_padding,
+        }
+    }
+
+    /// Non overlapping windows without padding, the configuration used by `MaxPooling`
+    pub fn non_overlapping(row_stride: usize, col_stride: usize) -> Self {
+        Self::new(row_stride, col_stride, row_stride, col_stride, 0, 0)
+    }
+
+    /// Returns the dimensions of the pooling result for an input of `rows` x `cols`  
+    /// Windows that do not fit entirely in the padded input are discarded  
+    /// Returns an error if the window or the stride are zero, if the padding is not smaller
+    /// than the window or if the window does not fit in the padded input
+    pub fn output_dimensions(&self, rows: usize, cols: usize) -> Result<(usize, usize), Error> {
+        if self.window_rows == 0
+            || self.window_cols == 0
+            || self.row_stride == 0
+            || self.col_stride == 0
+            || self.row_padding >= self.window_rows
+            || self.col_padding >= self.window_cols
+        {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        if rows + 2 * self.row_padding < self.window_rows
+            || cols + 2 * self.col_padding < self.window_cols
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok((
+            (rows + 2 * self.row_padding - self.window_rows) / self.row_stride + 1,
+            (cols + 2 * self.col_padding - self.window_cols) / self.col_stride + 1,
+        ))
+    }
+
+    /// Range of input rows covered by the window of output row `row_idx`, padding excluded
+    pub(crate) fn row_window(&self, row_idx: usize, rows: usize) -> std::ops::Range<usize> {
+        Self::window(
+            row_idx,
+            self.row_stride,
+            self.row_padding,
+            self.window_rows,
+            rows,
+        )
+    }
+
+    /// Range of input columns covered by the window of output column `col_idx`, padding excluded
+    pub(crate) fn col_window(&self, col_idx: usize, cols: usize) -> std::ops::Range<usize> {
+        Self::window(
+            col_idx,
+            self.col_stride,
+            self.col_padding,
+            self.window_cols,
+            cols,
+        )
+    }
+
+    fn window(
+        idx: usize,
+        stride: usize,
+        padding: usize,
+        window: usize,
+        size: usize,
+    ) -> std::ops::Range<usize> {
+        // since padding < window every window contains at least one input element
+        let start = (idx * stride).saturating_sub(padding);
+        let end = (idx * stride + window - padding).min(size);
+        start..end
+    }
+}
+
+/// General pooling with independent window size, stride and padding
+pub trait Pooling<T> {
+    /// Computes one row of the result, if `indices_row` is passed the (flat) index in the input
+    /// of the selected element is stored for each window, only meaningful for `PoolingType::Max`
+    fn pooling_row(
+        &self,
+        result_row: &mut [T],
+        indices_row: Option<&mut [usize]>,
+        row_idx: usize,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    );
+    fn pooling(
+        &self,
+        result: &mut Self,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error>;
+
+    /// Max pooling that also returns the argmax of each window  
+    /// `indices` has one element per element of the result, each is the index of the maximum
+    /// in the row major representation of the input (i.e. `row * cols + col`)
+    fn max_pooling_indices(
+        &self,
+        result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error>;
+
+    /// Returns the max or the sum of row `row_idx`, to be combined with `PoolingType::combine`
+    fn global_pooling_row(&self, row_idx: usize, pooling_type: PoolingType) -> T;
+
+    /// Pools the whole matrix into a single value
+    fn global_pooling(&self, pooling_type: PoolingType) -> Result<T, Error>;
+}
+
 // TODO: check with Leonidas the expected precision of this
 // in the C version it is Num: i32 -> f32, Num: f32 -> f32, Num: f64 -> f64
 // for semplicity I am going to use f64 for all for now
@@ -202,28 +596,308 @@ pub trait Correlation {
     fn correlation(&self, other: &Self) -> Result<Self::Output, Error>;
 }
 
+/// Position and value of the maximum of a correlation map, the first one in row-major order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CorrelationPeak<T> {
+    pub row: usize,
+    pub col: usize,
+    pub value: T,
+}
+
+/// Cross-correlation of two matrices over a range of lags, the 1-D cross-correlation is the one
+/// of two row vectors with no lags between the rows
+pub trait CrossCorrelation<T> {
+    /// The element `(r, c)` of `result` is the correlation at the lag
+    /// `(dy, dx) = (r - max_lags.0, c - max_lags.1)`, the sum of
+    /// `self[i][j] * other[i + dy][j + dx]` over the overlapping elements  
+    /// `result` is `(2 * max_lags.0 + 1) x (2 * max_lags.1 + 1)`, the peak is the lag of the
+    /// best alignment of `other` to `self`
+    fn cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+    ) -> Result<CorrelationPeak<T>, Error>;
+}
+
+/// Template matching with the zero-normalized cross-correlation, computed in floating point
+/// (single precision for the integer types)
+pub trait TemplateMatching {
+    type Output: Number;
+    type Map: BaseMatrix<Self::Output>;
+    /// The element `(r, c)` of `result` is the Pearson coefficient of `template` and the window
+    /// of `self` with the top left corner in `(r, c)`, 0 for flat windows  
+    /// `result` is `(rows - template.rows + 1) x (cols - template.cols + 1)`, the peak is the
+    /// best match
+    fn template_matching(
+        &self,
+        template: &Self,
+        result: &mut Self::Map,
+    ) -> Result<CorrelationPeak<Self::Output>, Error>;
+}
+
 pub trait Convolution<T> {
     fn convolute_row(&self, kernel: &Self, result_row: &mut [T], row_idx: usize);
     fn convolute(&self, kernel: &Self, padding: Padding, result: &mut Self) -> Result<(), Error>;
 }
 
+/// Local response normalization with n = 1, i.e. each element is normalised only by itself  
+/// This is what the OBPMark benchmark computes, see `GeneralLRN` for the general case
 pub trait LRN<T: Float> {
     fn lrn_row(&self, result_row: &mut [T], row_idx: usize, alpha: T, beta: T, k: T);
     fn lrn(&self, result: &mut Self, alpha: T, beta: T, k: T) -> Result<(), Error>;
 }
 
+/// Neighbourhood over which the squares are summed in `GeneralLRN`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LRNRegion {
+    /// `n` adjacent channels at the same position, each row of the matrix is a channel
+    /// (with its spatial dimensions flattened)
+    AcrossChannels,
+    /// `n` x `n` spatial neighbourhood of the element, the matrix is a single channel
+    WithinChannel,
+}
+
+/// Parameters of `GeneralLRN`, the normalised value is  
+/// `x / (k + alpha * sum(x_i^2))^beta` where the sum is over the region of size `n` around x
+#[derive(Clone, Copy, Debug)]
+pub struct LRNParameters<T> {
+    pub alpha: T,
+    pub beta: T,
+    pub k: T,
+    /// Size of the normalisation window, must be odd
+    pub n: usize,
+    pub region: LRNRegion,
+}
+
+/// Local response normalization with a window of size `n`, with `n = 1` it is the same as `LRN`
+pub trait GeneralLRN<T: Float> {
+    fn general_lrn_row(&self, result_row: &mut [T], row_idx: usize, parameters: &LRNParameters<T>);
+    fn general_lrn(&self, result: &mut Self, parameters: &LRNParameters<T>) -> Result<(), Error>;
+}
+
 pub trait FastFourierTransformHelper<T> {
     fn fft_helper(result: &mut [T], nn: usize);
+    /// Inverse transform without the `1 / nn` scaling
+    fn ifft_helper(result: &mut [T], nn: usize);
 }
 
+/// Radix-2 FFT of `nn` interleaved complex numbers, `nn` must be a power of 2
 pub trait FastFourierTransform<T>: FastFourierTransformHelper<T> {
     fn fft(&mut self, nn: usize) -> Result<(), Error>;
 }
 
+/// FFT of any size, mixed radix for sizes with prime factors 2, 3 and 5 and Bluestein's
+/// algorithm otherwise, the size is the one of the `FftPlan`
+pub trait MixedRadixFastFourierTransform<T: Float> {
+    fn mixed_radix_fft(&mut self, plan: &FftPlan<T>) -> Result<(), Error>;
+    /// Inverse transform, scaled by `1 / plan.len()`
+    fn mixed_radix_ifft(&mut self, plan: &FftPlan<T>) -> Result<(), Error>;
+}
+
+/// Inverse of `FastFourierTransform`, scaled by `1 / nn` so that `ifft(fft(x)) = x`
+pub trait InverseFastFourierTransform<T>: FastFourierTransformHelper<T> {
+    fn ifft(&mut self, nn: usize) -> Result<(), Error>;
+}
+
+/// FFT of a real signal of `n` samples (`n` power of 2), exploiting the Hermitian symmetry of
+/// its spectrum it only needs a complex FFT of `n / 2` elements  
+/// The spectrum holds the `n / 2 + 1` non redundant frequencies as interleaved real and
+/// imaginary parts, so `n + 2` values
+pub trait RealFastFourierTransform<T>: FastFourierTransformHelper<T> {
+    /// `self` is a 1 x n signal, `result` a 1 x (n + 2) spectrum
+    fn rfft(&self, result: &mut Self) -> Result<(), Error>;
+    /// Inverse of `rfft`, `self` is a 1 x (n + 2) spectrum, `result` a 1 x n signal
+    fn irfft(&self, result: &mut Self) -> Result<(), Error>;
+}
+
+/// 2D FFT of a matrix of complex numbers, each row holds `cols / 2` interleaved complex numbers  
+/// The rows are transformed first, then the matrix is transposed so that the columns can be
+/// transformed as rows, and finally transposed back  
+/// Both `rows` and `cols / 2` must be powers of 2
+pub trait FastFourierTransform2d<T>: FastFourierTransformHelper<T> {
+    /// Copies into `result_row` the column `row_idx` of complex numbers of `self`
+    fn complex_transpose_row(&self, result_row: &mut [T], row_idx: usize);
+    fn fft_2d(&self, result: &mut Self) -> Result<(), Error>;
+}
+
+/// Linear frequency modulated pulse, the signal transmitted by a radar (range chirp) or the
+/// Doppler history of a point target (azimuth chirp)
+#[derive(Clone, Copy, Debug)]
+pub struct Chirp<T> {
+    /// Length in complex samples
+    pub samples: usize,
+    /// Swept bandwidth as a fraction of the sampling frequency, at most 1
+    pub bandwidth: T,
+}
+
+impl<T: Float> Chirp<T> {
+    /// The `samples` interleaved complex samples, the frequency sweeps from `-bandwidth / 2`
+    /// to `bandwidth / 2`, computed in double precision
+    pub fn signal(&self) -> Vec<T> {
+        let n = self.samples as f64;
+        let rate = num_traits::AsPrimitive::<f64>::as_(self.bandwidth) / n;
+        (0..self.samples)
+            .flat_map(|k| {
+                let t = k as f64 - n / 2.0;
+                let phase = std::f64::consts::PI * rate * t * t;
+                [phase.cos(), phase.sin()]
+            })
+            .map(|value| T::from_f64(value).unwrap())
+            .collect()
+    }
+
+    /// Frequency domain matched filter of lines of `nn` complex samples (power of 2, at least
+    /// `samples`): the conjugate of the spectrum of the chirp, scaled by `1 / nn` so that the
+    /// inverse transform of the filtered line needs no scaling
+    pub fn matched_filter(&self, nn: usize) -> Result<Vec<T>, Error> {
+        if nn.count_ones() != 1 {
+            return Err(Error::UnsupportedSize);
+        }
+        if self.samples == 0 || self.samples > nn {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        let mut filter = self.signal();
+        filter.resize(nn << 1, T::zero());
+        FftPlan::new(nn)?.forward(&mut filter)?;
+        let scale = T::one() / T::from_usize(nn).unwrap();
+        for el in filter.chunks_exact_mut(2) {
+            el[0] *= scale;
+            el[1] *= -scale;
+        }
+        Ok(filter)
+    }
+}
+
+/// Range-Doppler focusing of the raw data of a synthetic aperture radar: each row is an echo
+/// of `cols / 2` interleaved complex range samples and there is a row per azimuth position  
+/// The range compression correlates every row with the range chirp, then the corner turn
+/// transposes the data so that the azimuth compression correlates every column with the
+/// azimuth chirp, the focused image has a row per range sample  
+/// Both `rows` and `cols / 2` must be powers of 2
+pub trait RadarProcessing<T>: FastFourierTransform2d<T> {
+    /// Circular correlation of row `row_idx` with the chirp of `matched_filter` (see
+    /// `Chirp::matched_filter`), through the FFT of the row
+    fn compression_row(&self, result_row: &mut [T], row_idx: usize, matched_filter: &[T]);
+    /// Compression of every row, range or azimuth depending on the orientation of the data
+    fn compression(&self, matched_filter: &[T], result: &mut Self) -> Result<(), Error>;
+    /// Transpose of the complex numbers, `result` is (cols / 2) x (2 * rows)
+    fn corner_turn(&self, result: &mut Self) -> Result<(), Error>;
+}
+
 pub trait FastFourierTransformWindowed<T>: FastFourierTransformHelper<T> {
     fn fftw(&self, window: usize, result: &mut Self) -> Result<(), Error>;
 }
 
+/// Window applied to each frame of a `ShortTimeFourierTransform`  
+/// The windows are periodic (DFT-even), as used for spectral analysis
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowFunction<T> {
+    /// No window, the OBPMark windowed FFT
+    Rectangular,
+    Hann,
+    Hamming,
+    /// 4-term Blackman-Harris
+    BlackmanHarris,
+    /// Kaiser window with the given beta
+    Kaiser(T),
+}
+
+impl<T: Float> WindowFunction<T> {
+    /// Returns the `length` coefficients of the window, computed in double precision
+    pub fn coefficients(&self, length: usize) -> Vec<T> {
+        let n = length as f64;
+        (0..length)
+            .map(|k| {
+                let phase = 2.0 * std::f64::consts::PI * k as f64 / n;
+                let coefficient = match *self {
+                    WindowFunction::Rectangular => 1.0,
+                    WindowFunction::Hann => 0.5 - 0.5 * phase.cos(),
+                    WindowFunction::Hamming => 0.54 - 0.46 * phase.cos(),
+                    WindowFunction::BlackmanHarris => {
+                        0.35875 - 0.48829 * phase.cos() + 0.14128 * (2.0 * phase).cos()
+                            - 0.01168 * (3.0 * phase).cos()
+                    }
+                    WindowFunction::Kaiser(beta) => {
+                        let beta: f64 = num_traits::AsPrimitive::as_(beta);
+                        let x = 2.0 * k as f64 / n - 1.0;
+                        bessel_i0(beta * (1.0 - x * x).sqrt()) / bessel_i0(beta)
+                    }
+                };
+                T::from_f64(coefficient).unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Modified Bessel function of the first kind of order 0, from its power series
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > 1e-16 * sum {
+        term *= (x / (2.0 * k)) * (x / (2.0 * k));
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// Output of each frame of a `ShortTimeFourierTransform`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpectrumOutput {
+    /// `window` interleaved complex numbers
+    Complex,
+    /// `window` squared magnitudes
+    Power,
+}
+
+/// Parameters of `ShortTimeFourierTransform`, sizes are in complex samples
+#[derive(Clone, Copy, Debug)]
+pub struct StftParameters<T> {
+    /// Length of each frame, must be a power of 2
+    pub window: usize,
+    /// Distance between the start of two consecutive frames, `window - hop` is the overlap
+    pub hop: usize,
+    pub function: WindowFunction<T>,
+    pub output: SpectrumOutput,
+}
+
+impl<T> StftParameters<T> {
+    /// Number of frames of a signal of `samples` complex samples
+    pub fn frames(&self, samples: usize) -> Result<usize, Error> {
+        if self.window.count_ones() != 1 {
+            return Err(Error::UnsupportedSize);
+        }
+        if self.hop == 0 || self.window > samples {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok((samples - self.window) / self.hop + 1)
+    }
+
+    /// Number of values of the spectrum of each frame
+    pub fn output_cols(&self) -> usize {
+        match self.output {
+            SpectrumOutput::Complex => self.window << 1,
+            SpectrumOutput::Power => self.window,
+        }
+    }
+}
+
+/// Windowed FFT of overlapping frames of a 1 x (2 * samples) signal of interleaved complex
+/// numbers, the result has a row per frame (spectrogram)
+pub trait ShortTimeFourierTransform<T>: FastFourierTransformHelper<T> {
+    /// Computes the spectrum of frame `row_idx`, `coefficients` are the ones of the window
+    fn stft_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        parameters: &StftParameters<T>,
+        coefficients: &[T],
+    );
+    fn stft(&self, result: &mut Self, parameters: &StftParameters<T>) -> Result<(), Error>;
+}
+
 pub trait WaveletTransformInteger<T: Integer> {
     fn wavelet_transform_bottom_half_element(&self, result_top_half: &[T], element_idx: usize)
         -> T;
@@ -243,6 +917,233 @@ pub trait WaveletTransformFloating<T: Float> {
     ) -> Result<(), Error>;
 }
 
+/// Inverse of `WaveletTransformInteger`, the round trip is lossless
+pub trait InverseWaveletTransformInteger<T: Integer> {
+    fn inverse_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error>;
+}
+
+/// Inverse of `WaveletTransformFloating`, the filters are the synthesis ones
+pub trait InverseWaveletTransformFloating<T: Float> {
+    fn inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error>;
+}
+
+/// Subbands of a level of the 2d wavelet decomposition, the first letter is the filter applied
+/// to the rows and the second the one applied to the columns
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subband {
+    LL,
+    HL,
+    LH,
+    HH,
+}
+
+/// Position of a subband in the decomposed image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubbandRegion {
+    pub row_start: usize,
+    pub col_start: usize,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Subband {
+    /// Region of the subband of `level` (1 is the finest) in a `rows` x `cols` image
+    /// The LL subband of a level is further decomposed by the next one
+    pub fn region(&self, rows: usize, cols: usize, level: usize) -> SubbandRegion {
+        let (sub_rows, sub_cols) = (rows >> level, cols >> level);
+        let (row_start, col_start) = match self {
+            Subband::LL => (0, 0),
+            Subband::HL => (0, sub_cols),
+            Subband::LH => (sub_rows, 0),
+            Subband::HH => (sub_rows, sub_cols),
+        };
+        SubbandRegion {
+            row_start,
+            col_start,
+            rows: sub_rows,
+            cols: sub_cols,
+        }
+    }
+
+    /// All the subbands of a decomposition in `levels` levels, from the coarsest (LL of the
+    /// last level) to the finest, which is the order used by CCSDS 122.0
+    pub fn layout(levels: usize) -> Vec<(usize, Subband)> {
+        let mut layout = vec![(levels, Subband::LL)];
+        for level in (1..levels + 1).rev() {
+            layout.extend([Subband::HL, Subband::LH, Subband::HH].map(|subband| (level, subband)));
+        }
+        layout
+    }
+}
+
+/// Multi level 2d version of `WaveletTransformInteger` on a whole image: every level transforms
+/// the rows and then the columns of the LL subband of the previous one
+pub trait WaveletTransform2dInteger<T: Integer> {
+    fn wavelet_transform_2d(&self, result: &mut Self, levels: usize) -> Result<(), Error>;
+}
+
+/// Multi level 2d version of `WaveletTransformFloating`, the filters are passed as slices
+pub trait WaveletTransform2dFloating<T: Float> {
+    fn wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error>;
+}
+
+/// Parameters of the CCSDS 122.0 bit plane encoder, stored in the header of the first segment
+/// The default is lossless coding in segments of 256 blocks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BpeParameters {
+    /// Number of 8x8 blocks of each segment
+    pub segment_size: usize,
+    /// Maximum number of bytes of each segment, the coding stops when it is reached
+    pub byte_limit: Option<usize>,
+    /// Only the DC coefficients are coded
+    pub dc_stop: bool,
+    /// Last bit plane coded
+    pub bit_plane_stop: u32,
+    /// Last stage (1 to 4) coded in the last bit plane
+    pub stage_stop: u32,
+    /// Segments shorter than the byte limit are padded up to it
+    pub use_fill: bool,
+}
+
+/// CCSDS 122.0 bit plane coding of the coefficients of the 3 level 2d integer wavelet
+/// transform, both dimensions of the image have to be multiples of 8
+pub trait BitPlaneEncoder {
+    fn bit_plane_encode(&self, parameters: &BpeParameters) -> Result<Vec<u8>, Error>;
+    /// Decodes `data` into `self`, which has to have the dimensions of the encoded image
+    fn bit_plane_decode(&mut self, data: &[u8]) -> Result<(), Error>;
+}
+
+/// Parameters of the CCSDS 121.0 Rice coder, they are not stored in the output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiceParameters {
+    /// Bits of the samples, from 1 to 32
+    pub sample_bits: u32,
+    /// Samples of each block: 8, 16, 32 or 64
+    pub block_size: usize,
+    /// Blocks between two reference samples, at most 4096
+    pub reference_interval: usize,
+    /// Codes the mapped prediction errors of the unit delay predictor instead of the samples
+    pub preprocessor: bool,
+    /// Enables the zero block option
+    pub zero_block: bool,
+    /// Enables the second extension option
+    pub second_extension: bool,
+}
+
+/// CCSDS 121.0 lossless coding of the samples of the matrix, taken in row major order
+/// The number of samples has to be a multiple of the block size
+pub trait RiceEncoder {
+    fn rice_encode(&self, parameters: &RiceParameters) -> Result<Vec<u8>, Error>;
+    /// Decodes `data` into `self`, which has to have the number of the encoded samples
+    fn rice_decode(&mut self, data: &[u8], parameters: &RiceParameters) -> Result<(), Error>;
+}
+
+/// Parameters of the CCSDS 123.0 predictor and sample adaptive entropy coder, they are not
+/// stored in the output
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PredictorParameters {
+    /// Bits of the samples, from 2 to 16
+    pub dynamic_range: u32,
+    /// Number of previous bands used for the prediction, at most 15
+    pub prediction_bands: usize,
+    /// Bits of the fractional part of the weights, from 4 to 19
+    pub weight_resolution: u32,
+    /// Samples after which the weight update scaling exponent grows, a power of 2 from 16 to
+    /// 2048
+    pub weight_interval: u32,
+    /// Initial weight update scaling exponent, from -6 to 9
+    pub weight_exponent_min: i32,
+    /// Final weight update scaling exponent, from `weight_exponent_min` to 9
+    pub weight_exponent_max: i32,
+    /// Longest unary part of the codewords, from 8 to 32
+    pub unary_length_limit: u32,
+    /// The counter of the entropy coder starts from 2^initial_count_exponent, from 1 to 8
+    pub initial_count_exponent: u32,
+    /// Initialization constant of the accumulator, at most `dynamic_range` - 2
+    pub accumulator_initialization: u32,
+    /// The statistics are halved when the counter reaches 2^rescaling_counter_size - 1, at most
+    /// 11
+    pub rescaling_counter_size: u32,
+}
+
+/// CCSDS 123.0 lossless predictive coding of 3d data
+pub trait PredictiveEncoder {
+    fn predictive_encode(&self, parameters: &PredictorParameters) -> Result<Vec<u8>, Error>;
+    /// Decodes `data` into `self`, which has to have the dimensions of the encoded data
+    fn predictive_decode(
+        &mut self,
+        data: &[u8],
+        parameters: &PredictorParameters,
+    ) -> Result<(), Error>;
+}
+
+/// Block cipher with blocks of 16 bytes and its modes of operation
+pub trait BlockCipher {
+    fn encrypt_block(&self, block: &mut [u8; 16]);
+    fn decrypt_block(&self, block: &mut [u8; 16]);
+    /// Electronic codebook mode, the length of `data` has to be a multiple of 16
+    fn ecb_encrypt(&self, data: &mut [u8]) -> Result<(), Error>;
+    fn ecb_decrypt(&self, data: &mut [u8]) -> Result<(), Error>;
+    /// Counter mode on block `block_idx` of the data, `counter` is the initial counter block
+    /// and is incremented as a 128 bit big endian integer
+    fn ctr_block(&self, counter: &[u8; 16], block_idx: usize, data: &mut [u8]);
+    /// Counter mode, it both encrypts and decrypts `data` of any length
+    fn ctr(&self, counter: &[u8; 16], data: &mut [u8]);
+}
+
+/// Steps of the calibration of the frames of an image sensor, every frame is a matrix
+pub trait ImageCalibration<T: Number> {
+    /// Subtracts the dark frame `offsets`
+    fn offset_correction(&self, offsets: &Self, result: &mut Self) -> Result<(), Error>;
+    /// The pixels with a non zero value in `bad_pixels` are replaced by the mean of the good
+    /// ones of their 3x3 neighbourhood
+    fn bad_pixel_correction(&self, bad_pixels: &Self, result: &mut Self) -> Result<(), Error>;
+    /// The pixels that differ by more than `threshold` from the mean of the same pixel in the
+    /// `neighbours` frames (e.g. the previous and the next ones) are replaced by the mean
+    fn radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+    ) -> Result<(), Error>;
+    /// Multiplies by the flat field `gains`
+    fn gain_correction(&self, gains: &Self, result: &mut Self) -> Result<(), Error>;
+    /// Sums the blocks of `factor` x `factor` pixels, the result is `factor` times smaller in
+    /// both dimensions
+    fn spatial_binning(&self, factor: usize, result: &mut Self) -> Result<(), Error>;
+    /// Temporal co-adding, adds the frame to `accumulator`
+    fn co_add(&self, accumulator: &mut Self) -> Result<(), Error>;
+}
+
+/// Bit level access to the elements of a matrix, used by `fault_injection::FaultInjector`
+pub trait FaultInjection<T: Number> {
+    /// Rows and columns of the matrix
+    fn dimensions(&self) -> (usize, usize);
+    /// Replaces the bits of element (`row`, `col`) with `function` of them, returns the mask of
+    /// the bits that changed
+    fn modify_bits(&mut self, row: usize, col: usize, function: impl FnOnce(u64) -> u64) -> u64;
+}
+
+/// Element-wise majority voting of redundant results, see `tmr::tmr`  
+/// Implemented by the matrices, the numbers (compared bit by bit), bytes, indices, vectors and
+/// tuples of them, so that kernels with several outputs or a byte stream can be voted
+pub trait Voting: Clone + Send {
+    /// Writes in `self` the majority of the three `replicas`
+    fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error>;
+}
+
 pub fn random_matrix_data<T: Number>(
     seed: u64,
     rows: usize,
@@ -266,6 +1167,198 @@ pub trait FirFilter<T> {
     fn fir_filter(&self, kernel: &Self, result: &mut Self) -> Result<(), Error>;
 }
 
+/// FIR filter split in `factor` phases for a sample rate change: phase `p` has the taps `p`,
+/// `p + factor`, `p + 2 * factor`, ... of the kernel (zero past the last tap)
+/// The decimator and the interpolator compute only the output samples: the decimator runs the
+/// phases on interleaved input samples, the interpolator one phase per output sample, in both
+/// cases the taps are accumulated in order
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolyphaseFilter<T> {
+    factor: usize,
+    taps: usize,
+    phases: Vec<Vec<T>>,
+}
+
+impl<T: Number> PolyphaseFilter<T> {
+    /// `factor` must be at least 1 and `kernel` not empty
+    pub fn new(kernel: &[T], factor: usize) -> Result<Self, Error> {
+        if factor == 0 || kernel.is_empty() {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        let length = (kernel.len() - 1) / factor + 1;
+        let phases = (0..factor)
+            .map(|p| {
+                (0..length)
+                    .map(|k| kernel.get(k * factor + p).copied().unwrap_or(T::zero()))
+                    .collect()
+            })
+            .collect();
+        Ok(PolyphaseFilter {
+            factor,
+            taps: kernel.len(),
+            phases,
+        })
+    }
+
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    pub fn phases(&self) -> &[Vec<T>] {
+        &self.phases
+    }
+
+    /// Length of the decimated full convolution of `input` samples, `input` must not be 0
+    pub fn decimated_len(&self, input: usize) -> Result<usize, Error> {
+        if input == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok((input + self.taps - 2) / self.factor + 1)
+    }
+
+    /// Length of the full convolution of `input` samples with `factor - 1` zeros inserted
+    /// between each other, `input` must not be 0
+    pub fn interpolated_len(&self, input: usize) -> Result<usize, Error> {
+        if input == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+        Ok((input - 1) * self.factor + self.taps)
+    }
+
+    /// Sample `idx` of the decimated output, the sample `idx * factor` of the full convolution
+    pub(crate) fn decimate_element(&self, input: &[T], idx: usize) -> T {
+        let position = idx * self.factor;
+        let first = (position + 1).saturating_sub(input.len());
+        let last = position.min(self.taps - 1);
+        let (mut k, mut p) = (first / self.factor, first % self.factor);
+        let mut sum = T::zero();
+        for j in first..=last {
+            sum += self.phases[p][k] * input[position - j];
+            p += 1;
+            if p == self.factor {
+                p = 0;
+                k += 1;
+            }
+        }
+        sum
+    }
+
+    /// Sample `idx` of the interpolated output, only the phase `idx % factor` contributes
+    pub(crate) fn interpolate_element(&self, input: &[T], idx: usize) -> T {
+        let (n, p) = (idx / self.factor, idx % self.factor);
+        let phase = &self.phases[p];
+        // the taps past the end of the kernel are zero
+        let length = (self.taps + self.factor - 1 - p) / self.factor;
+        let first = (n + 1).saturating_sub(input.len());
+        let mut sum = T::zero();
+        for (k, h) in phase.iter().enumerate().take(length.min(n + 1)).skip(first) {
+            sum += *h * input[n - k];
+        }
+        sum
+    }
+}
+
+/// Decimating and interpolating FIR filters of a row vector computed at the output rate with a
+/// `PolyphaseFilter`
+pub trait MultirateFirFilter<T> {
+    /// Full convolution keeping one sample every `filter.factor()`, the result has
+    /// `filter.decimated_len(self.cols)` columns
+    fn fir_decimate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error>;
+
+    /// Full convolution of the input with `filter.factor() - 1` zeros inserted between the
+    /// samples, the result has `filter.interpolated_len(self.cols)` columns
+    fn fir_interpolate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error>;
+}
+
+/// Structure of the sections of a `BiquadCascade`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BiquadForm {
+    /// Separate delay lines for the inputs and the outputs, four states per section
+    DirectFormI,
+    /// Two states per section, each one accumulates the contributions to a future output
+    TransposedDirectFormII,
+}
+
+/// Second order section `(b0 + b1 z^-1 + b2 z^-2) / (1 + a1 z^-1 + a2 z^-2)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Biquad<T> {
+    pub b: [T; 3],
+    pub a: [T; 2],
+}
+
+/// Cascade of second order sections, the IIR filter of `IirFilter`  
+/// The coefficients of integer types are fixed point numbers with `fraction_bits` fractional
+/// bits (see `FixedPoint`), the samples are integers: inside the filter they are scaled by
+/// `2^fraction_bits` as well, so the samples times that scale must fit in `T`
+#[derive(Clone, Debug, PartialEq)]
+pub struct BiquadCascade<T> {
+    pub sections: Vec<Biquad<T>>,
+    pub form: BiquadForm,
+    pub fraction_bits: u32,
+}
+
+impl<T: Number> BiquadCascade<T> {
+    /// Butterworth low pass filter of order `2 * sections`, `cutoff` is the -3 dB frequency as a
+    /// fraction of the sampling frequency (between 0 and 0.5)  
+    /// The sections are designed in double precision with the bilinear transform and then
+    /// rounded to `T`
+    pub fn butterworth_low_pass(
+        sections: usize,
+        cutoff: f64,
+        form: BiquadForm,
+        fraction_bits: u32,
+    ) -> Result<Self, Error> {
+        if sections == 0 || cutoff <= 0.0 || cutoff >= 0.5 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        let w0 = 2.0 * std::f64::consts::PI * cutoff;
+        let order = 4.0 * sections as f64;
+        let coefficient = |value: f64| T::fixed_from_f64(value, fraction_bits);
+        let sections = (0..sections)
+            .map(|k| {
+                // quality factor of the k-th pair of poles
+                let q = 1.0 / (2.0 * ((2 * k + 1) as f64 * std::f64::consts::PI / order).cos());
+                let alpha = w0.sin() / (2.0 * q);
+                let a0 = 1.0 + alpha;
+                let b1 = (1.0 - w0.cos()) / a0;
+                Biquad {
+                    b: [b1 / 2.0, b1, b1 / 2.0].map(coefficient),
+                    a: [-2.0 * w0.cos() / a0, (1.0 - alpha) / a0].map(coefficient),
+                }
+            })
+            .collect();
+        Ok(BiquadCascade {
+            sections,
+            form,
+            fraction_bits,
+        })
+    }
+
+    /// Index of the first section with a pole on or outside the unit circle, the roots of
+    /// `z^2 + a1 z + a2` are inside if `|a2| < 1` and `|a1| < 1 + a2`
+    pub fn unstable_section(&self) -> Option<usize> {
+        self.sections.iter().position(|section| {
+            let [a1, a2] = section.a.map(|a| a.fixed_to_f64(self.fraction_bits));
+            a2.abs() >= 1.0 || a1.abs() >= 1.0 + a2
+        })
+    }
+
+    /// `Error::UnstableFilter` if a section is not stable
+    pub fn check_stability(&self) -> Result<(), Error> {
+        match self.unstable_section() {
+            Some(_) => Err(Error::UnstableFilter),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Cascaded biquad IIR filter of every row of the matrix, each row is an independent channel
+/// and the states of the sections start at zero  
+/// Unstable cascades are rejected, see `BiquadCascade::check_stability`
+pub trait IirFilter<T> {
+    fn iir_filter(&self, cascade: &BiquadCascade<T>, result: &mut Self) -> Result<(), Error>;
+}
+
 pub mod parallel_traits;
 pub mod rayon_traits;
 
@@ -273,3 +1366,20 @@ pub mod matrix_1d;
 pub mod matrix_2d;
 
 pub mod number_traits;
+
+pub mod aes;
+pub mod cube;
+pub mod fault_injection;
+pub mod fft_plan;
+pub mod tmr;
+mod wavelet;
+
+mod abft;
+mod bitstream;
+mod calibration;
+mod ccsds_121;
+mod ccsds_122;
+mod ccsds_123;
+mod cross_correlation;
+mod iir;
+mod rows;
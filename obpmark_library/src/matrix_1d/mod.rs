@@ -1,5 +1,6 @@
 use crate::{BaseMatrix, Error, Number};
 
+#[derive(Clone)]
 pub struct Matrix1d<T: Number> {
     data: Vec<T>,
     rows: usize,
@@ -47,6 +48,17 @@ impl<T: Number> BaseMatrix<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Number> Matrix1d<T> {
+    /// The rows of the matrix as slices
+    pub(crate) fn row_slices(&self) -> Vec<&[T]> {
+        self.data.chunks(self.cols.max(1)).collect()
+    }
+
+    pub(crate) fn row_slices_mut(&mut self) -> Vec<&mut [T]> {
+        self.data.chunks_mut(self.cols.max(1)).collect()
+    }
+}
+
 impl_display!(Matrix1d);
 
 mod parallel_implementations;
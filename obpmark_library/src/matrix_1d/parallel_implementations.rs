@@ -1,14 +1,28 @@
+use crate::calibration;
+use crate::ccsds_121;
+use crate::ccsds_122;
+use crate::cross_correlation;
+use crate::iir;
 use crate::matrix_1d::Matrix1d;
 use crate::number_traits::{Float, Number};
 use crate::parallel_traits::*;
-use crate::{Error, Padding};
+use crate::rows;
+use crate::wavelet;
+use crate::BpeParameters;
+use crate::{
+    ActivationFunction, BatchNormParameters, Error, LRNParameters, Padding, PolyphaseFilter,
+    PoolingParameters, PoolingType, RiceParameters, SoftmaxAxis,
+};
+use crate::{BiquadCascade, CorrelationPeak};
 
 use std::sync::Arc;
 use std::thread;
 
 use crate::{
-    BaseMatrix, Convolution, FastFourierTransformHelper, FirFilter, MatMul, MaxPooling, Relu,
-    Softmax, LRN,
+    AbftMatMul, AbftReport, Activation, BaseMatrix, BatchNorm, Convolution, Correlation, Dense,
+    FastFourierTransform2d, FastFourierTransformHelper, FirFilter, GeneralLRN, MatMul, MaxPooling,
+    Pooling, RadarProcessing, Relu, ShortTimeFourierTransform, Softmax, StableSoftmax,
+    StftParameters, WaveletTransformInteger, LRN,
 };
 
 impl<T: Number> ParallelMatMul for Matrix1d<T> {
@@ -38,7 +52,7 @@ impl<T: Number> ParallelMatMul for Matrix1d<T> {
                     let start_row = chunk_idx * rows_per_thread;
                     s.spawn(move || {
                         chunk
-                            .chunks_mut(shared_self.cols)
+                            .chunks_mut(shared_other.rows)
                             .enumerate()
                             .for_each(|(i, row)| {
                                 shared_self.multiply_row(&shared_other, row, start_row + i);
@@ -51,6 +65,24 @@ impl<T: Number> ParallelMatMul for Matrix1d<T> {
     }
 }
 
+impl<T: Number> ParallelAbftMatMul for Matrix1d<T> {
+    fn parallel_abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<AbftReport, Error> {
+        let mut product = Matrix1d::zeroes(result.rows + 1, result.cols + 1);
+        self.column_checksums().parallel_multiply(
+            &other.row_checksums(),
+            &mut product,
+            n_threads,
+        )?;
+        product.abft_verify(tolerance, result)
+    }
+}
+
 impl<T: Number> ParallelConvolution for Matrix1d<T> {
     fn parallel_convolute(
         &self,
@@ -129,6 +161,127 @@ impl<T: Number> ParallelRelu for Matrix1d<T> {
     }
 }
 
+impl<T: Float> ParallelActivation<T> for Matrix1d<T> {
+    fn parallel_activation(
+        &self,
+        result: &mut Self,
+        function: ActivationFunction<T>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(result.cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(self.cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.activation_row(result_row, start_row + i, function);
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelDense<T> for Matrix1d<T> {
+    fn parallel_dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.cols != input.rows
+            || self.rows != result.rows
+            || input.cols != result.cols
+            || bias.len() != self.rows
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let result_cols = result.cols;
+        let input_transposed = &input.transpose();
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(result_cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(result_cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.dense_row(
+                                    input_transposed,
+                                    bias,
+                                    result_row,
+                                    start_row + i,
+                                    activation,
+                                );
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelBatchNorm<T> for Matrix1d<T> {
+    fn parallel_batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        parameters.check_features(self.rows)?;
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let result_cols = result.cols;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(result_cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(result_cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.batch_norm_row(result_row, start_row + i, parameters);
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
 impl<T: Float> ParallelSoftmax for Matrix1d<T> {
     fn parallel_softmax(&self, result: &mut Self, n_threads: usize) -> Result<(), Error> {
         if self.rows != result.rows || self.cols != result.cols {
@@ -163,7 +316,7 @@ impl<T: Float> ParallelSoftmax for Matrix1d<T> {
         thread::scope(|s| {
             result
                 .data
-                .chunks_exact_mut(result.cols * rows_per_thread)
+                .chunks_mut(result.cols * rows_per_thread)
                 .for_each(|chunk| {
                     s.spawn(|| {
                         chunk.iter_mut().for_each(|el| {
@@ -176,6 +329,172 @@ impl<T: Float> ParallelSoftmax for Matrix1d<T> {
     }
 }
 
+impl<T: Float> ParallelStableSoftmax for Matrix1d<T> {
+    fn parallel_stable_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        self.parallel_stable_softmax_helper(result, axis, false, n_threads)
+    }
+
+    fn parallel_log_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        self.parallel_stable_softmax_helper(result, axis, true, n_threads)
+    }
+}
+
+impl<T: Float> Matrix1d<T> {
+    fn parallel_stable_softmax_helper(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        log: bool,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let cols = self.cols;
+
+        if axis == SoftmaxAxis::Rows {
+            // rows are independent, each thread does all the passes on its own rows
+            thread::scope(|s| {
+                result
+                    .data
+                    .chunks_mut(cols * rows_per_thread)
+                    .enumerate()
+                    .for_each(|(chunk_idx, chunk)| {
+                        let start_row = chunk_idx * rows_per_thread;
+                        s.spawn(move || {
+                            chunk
+                                .chunks_mut(cols)
+                                .enumerate()
+                                .for_each(|(i, result_row)| {
+                                    let mut max = [T::neg_infinity()];
+                                    let mut sum = [T::zero()];
+                                    self.softmax_max_row(&mut max, start_row + i, axis);
+                                    self.softmax_exp_row(
+                                        result_row,
+                                        start_row + i,
+                                        axis,
+                                        &max,
+                                        &mut sum,
+                                    );
+                                    self.softmax_normalize_row(
+                                        result_row,
+                                        start_row + i,
+                                        axis,
+                                        &max,
+                                        &sum,
+                                        log,
+                                    );
+                                });
+                        });
+                    });
+            });
+            return Ok(());
+        }
+
+        let n_statistics = axis.n_statistics(cols);
+
+        // each thread computes the maxima of its rows, then they are combined
+        let mut max = vec![T::neg_infinity(); n_statistics];
+        thread::scope(|s| {
+            (0..self.rows)
+                .step_by(rows_per_thread)
+                .map(|start_row| {
+                    s.spawn(move || {
+                        let mut max = vec![T::neg_infinity(); n_statistics];
+                        let end_row = (start_row + rows_per_thread).min(self.rows);
+                        (start_row..end_row).for_each(|i| self.softmax_max_row(&mut max, i, axis));
+                        max
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    for (acc, partial) in max.iter_mut().zip(handle.join().unwrap()) {
+                        if partial > *acc {
+                            *acc = partial;
+                        }
+                    }
+                });
+        });
+
+        // same for the sums, the exponentials are stored in result
+        let max = &max;
+        let mut sum = vec![T::zero(); n_statistics];
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(cols * rows_per_thread)
+                .enumerate()
+                .map(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        let mut sum = vec![T::zero(); n_statistics];
+                        chunk
+                            .chunks_mut(cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.softmax_exp_row(
+                                    result_row,
+                                    start_row + i,
+                                    axis,
+                                    max,
+                                    &mut sum,
+                                );
+                            });
+                        sum
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    for (acc, partial) in sum.iter_mut().zip(handle.join().unwrap()) {
+                        *acc += partial;
+                    }
+                });
+        });
+
+        let sum = &sum;
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.softmax_normalize_row(
+                                    result_row,
+                                    start_row + i,
+                                    axis,
+                                    max,
+                                    sum,
+                                    log,
+                                );
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
 impl<T: Number> ParallelMaxPooling for Matrix1d<T> {
     fn parallel_max_pooling(
         &self,
@@ -217,34 +536,40 @@ impl<T: Number> ParallelMaxPooling for Matrix1d<T> {
     }
 }
 
-impl<T: Float> ParallelLRN<T> for Matrix1d<T> {
-    fn parallel_lrn(
+impl<T: Number> ParallelPooling<T> for Matrix1d<T> {
+    fn parallel_pooling(
         &self,
         result: &mut Self,
-        alpha: T,
-        beta: T,
-        k: T,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
         n_threads: usize,
     ) -> Result<(), Error> {
-        if self.rows != result.rows || self.cols != result.cols {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols) {
             return Err(Error::InvalidDimensions);
         }
 
-        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let rows_per_thread = (result.rows - 1) / n_threads + 1;
+        let result_cols = result.cols;
 
         thread::scope(|s| {
             result
                 .data
-                .chunks_mut(result.cols * rows_per_thread)
+                .chunks_mut(result_cols * rows_per_thread)
                 .enumerate()
                 .for_each(|(chunk_idx, chunk)| {
                     let start_row = chunk_idx * rows_per_thread;
                     s.spawn(move || {
                         chunk
-                            .chunks_mut(self.cols)
+                            .chunks_mut(result_cols)
                             .enumerate()
                             .for_each(|(i, result_row)| {
-                                self.lrn_row(result_row, start_row + i, alpha, beta, k);
+                                self.pooling_row(
+                                    result_row,
+                                    None,
+                                    start_row + i,
+                                    pooling_type,
+                                    parameters,
+                                );
                             });
                     });
                 });
@@ -252,81 +577,313 @@ impl<T: Float> ParallelLRN<T> for Matrix1d<T> {
 
         Ok(())
     }
-}
 
-impl<T: Number> ParallelFiniteImpulseResponseFilter for Matrix1d<T> {
-    fn parallel_fir_filter(
+    fn parallel_max_pooling_indices(
         &self,
-        kernel: &Self,
         result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
         n_threads: usize,
     ) -> Result<(), Error> {
-        if self.rows != result.rows || result.cols != self.cols + kernel.cols - 1 || self.rows != 1
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols)
+            || indices.len() != result.data.len()
         {
             return Err(Error::InvalidDimensions);
         }
-        if kernel.rows != 1 {
-            return Err(Error::InvalidKernelDimensions);
-        }
-
-        // here the number of rows will always be one
-        let elements_per_thread = (result.cols - 1) / n_threads + 1;
 
-        let shared_kernel = Arc::new(kernel);
+        let rows_per_thread = (result.rows - 1) / n_threads + 1;
+        let result_cols = result.cols;
 
         thread::scope(|s| {
             result
                 .data
-                .chunks_mut(elements_per_thread)
+                .chunks_mut(result_cols * rows_per_thread)
+                .zip(indices.chunks_mut(result_cols * rows_per_thread))
                 .enumerate()
-                .for_each(|(chunk_idx, chunk)| {
-                    let shared_kernel = shared_kernel.clone();
-                    let start_idx = chunk_idx * elements_per_thread;
+                .for_each(|(chunk_idx, (chunk, indices_chunk))| {
+                    let start_row = chunk_idx * rows_per_thread;
                     s.spawn(move || {
-                        chunk.iter_mut().enumerate().for_each(|(i, result_el)| {
-                            *result_el = self.fir_filter_element(&shared_kernel, i + start_idx);
-                        });
+                        chunk
+                            .chunks_mut(result_cols)
+                            .zip(indices_chunk.chunks_mut(result_cols))
+                            .enumerate()
+                            .for_each(|(i, (result_row, indices_row))| {
+                                self.pooling_row(
+                                    result_row,
+                                    Some(indices_row),
+                                    start_row + i,
+                                    PoolingType::Max,
+                                    parameters,
+                                );
+                            });
                     });
                 });
         });
 
         Ok(())
     }
-}
 
-macro_rules! impl_parallel_fft_windowed {
-    ($t: tt) => {
-        impl ParallelFastFourierTransformWindowed<$t> for Matrix1d<$t> {
-            fn parallel_fft_windowed(
-                &self,
-                window: usize,
-                result: &mut Self,
-                n_threads: usize,
-            ) -> Result<(), Error> {
-                if self.rows != 1 || result.rows != 1 {
-                    return Err(Error::InvalidDimensions);
-                }
+    fn parallel_global_pooling(
+        &self,
+        pooling_type: PoolingType,
+        n_threads: usize,
+    ) -> Result<T, Error> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::InvalidDimensions);
+        }
 
-                // here the number of rows will always be one
-                let elements_per_thread = (result.cols - 1) / n_threads + 1;
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let mut acc = None;
 
-                thread::scope(|s| {
-                    result
-                        .data
-                        .chunks_mut(window * 2 * elements_per_thread)
-                        .enumerate()
-                        .for_each(|(i, result_chunk)| {
-                            s.spawn(move || {
-                                let start_idx = i * elements_per_thread;
-                                result_chunk.chunks_mut(window * 2).enumerate().for_each(
-                                    |(i, result_chunk)| {
-                                        for j in 0..window {
-                                            result_chunk[j] = self.data[(start_idx + i) * 2 + j];
-                                        }
-                                        Self::fft_helper(result_chunk, window >> 1);
-                                    },
-                                );
-                            });
+        thread::scope(|s| {
+            (0..self.rows)
+                .step_by(rows_per_thread)
+                .map(|start_row| {
+                    s.spawn(move || {
+                        let end_row = (start_row + rows_per_thread).min(self.rows);
+                        (start_row + 1..end_row).fold(
+                            self.global_pooling_row(start_row, pooling_type),
+                            |acc, i| {
+                                pooling_type.combine(acc, self.global_pooling_row(i, pooling_type))
+                            },
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    let partial = handle.join().unwrap();
+                    acc = Some(match acc {
+                        Some(acc) => pooling_type.combine(acc, partial),
+                        None => partial,
+                    });
+                });
+        });
+
+        Ok(pooling_type.finalize(acc.unwrap(), self.rows * self.cols))
+    }
+}
+
+impl<T: Float> ParallelLRN<T> for Matrix1d<T> {
+    fn parallel_lrn(
+        &self,
+        result: &mut Self,
+        alpha: T,
+        beta: T,
+        k: T,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(result.cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(self.cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.lrn_row(result_row, start_row + i, alpha, beta, k);
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelGeneralLRN<T> for Matrix1d<T> {
+    fn parallel_general_lrn(
+        &self,
+        result: &mut Self,
+        parameters: &LRNParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        if parameters.n % 2 != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(result.cols * rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk
+                            .chunks_mut(self.cols)
+                            .enumerate()
+                            .for_each(|(i, result_row)| {
+                                self.general_lrn_row(result_row, start_row + i, parameters);
+                            });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Number> ParallelFiniteImpulseResponseFilter for Matrix1d<T> {
+    fn parallel_fir_filter(
+        &self,
+        kernel: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || result.cols != self.cols + kernel.cols - 1 || self.rows != 1
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        if kernel.rows != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+
+        // here the number of rows will always be one
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        let shared_kernel = Arc::new(kernel);
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let shared_kernel = shared_kernel.clone();
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, result_el)| {
+                            *result_el = self.fir_filter_element(&shared_kernel, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Number> ParallelMultirateFirFilter<T> for Matrix1d<T> {
+    fn parallel_fir_decimate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != 1 || result.rows != 1 || result.cols != filter.decimated_len(self.cols)? {
+            return Err(Error::InvalidDimensions);
+        }
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let input = &self.data[..];
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = filter.decimate_element(input, i + start_idx);
+                        });
+                    });
+                });
+        });
+        Ok(())
+    }
+
+    fn parallel_fir_interpolate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != 1
+            || result.rows != 1
+            || result.cols != filter.interpolated_len(self.cols)?
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let input = &self.data[..];
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = filter.interpolate_element(input, i + start_idx);
+                        });
+                    });
+                });
+        });
+        Ok(())
+    }
+}
+
+macro_rules! impl_parallel_fft_windowed {
+    ($t: tt) => {
+        impl ParallelFastFourierTransformWindowed<$t> for Matrix1d<$t> {
+            fn parallel_fft_windowed(
+                &self,
+                window: usize,
+                result: &mut Self,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.rows != 1 || result.rows != 1 {
+                    return Err(Error::InvalidDimensions);
+                }
+                if window < 2 || window.count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+
+                // here the number of rows will always be one
+                let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(window * 2 * elements_per_thread)
+                        .enumerate()
+                        .for_each(|(i, result_chunk)| {
+                            s.spawn(move || {
+                                let start_idx = i * elements_per_thread;
+                                result_chunk.chunks_mut(window * 2).enumerate().for_each(
+                                    |(i, result_chunk)| {
+                                        for j in 0..window {
+                                            result_chunk[j] = self.data[(start_idx + i) * 2 + j];
+                                        }
+                                        Self::fft_helper(result_chunk, window >> 1);
+                                    },
+                                );
+                            });
                         });
                 });
                 Ok(())
@@ -337,3 +894,815 @@ macro_rules! impl_parallel_fft_windowed {
 
 impl_parallel_fft_windowed!(f32);
 impl_parallel_fft_windowed!(f64);
+
+macro_rules! impl_parallel_stft {
+    ($t: tt) => {
+        impl ParallelShortTimeFourierTransform<$t> for Matrix1d<$t> {
+            fn parallel_stft(
+                &self,
+                result: &mut Self,
+                parameters: &StftParameters<$t>,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.rows != 1 {
+                    return Err(Error::InvalidDimensions);
+                }
+                let frames = parameters.frames(self.cols >> 1)?;
+                if result.rows != frames || result.cols != parameters.output_cols() {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                let coefficients = &parameters.function.coefficients(parameters.window);
+                let rows_per_thread = (result.rows - 1) / n_threads + 1;
+                let result_cols = result.cols;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(result_cols * rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk.chunks_mut(result_cols).enumerate().for_each(
+                                    |(i, result_row)| {
+                                        self.stft_row(
+                                            result_row,
+                                            start_row + i,
+                                            parameters,
+                                            coefficients,
+                                        )
+                                    },
+                                );
+                            });
+                        });
+                });
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_stft!(f32);
+impl_parallel_stft!(f64);
+
+macro_rules! impl_parallel_fft {
+    ($t: tt) => {
+        impl ParallelFastFourierTransform<$t> for Matrix1d<$t> {
+            fn parallel_fft(&mut self, nn: usize, n_threads: usize) -> Result<(), Error> {
+                if self.rows != 1 || nn << 1 > self.cols {
+                    return Err(Error::InvalidDimensions);
+                }
+                if nn.count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+
+                let n = nn << 1;
+                let data = &mut self.data[..n];
+
+                // the bit reversal is done out of place, so that it can be split among the threads
+                let bits = nn.trailing_zeros();
+                let input = &data.to_vec();
+                let elements_per_thread = (nn - 1) / n_threads + 1;
+                thread::scope(|s| {
+                    data.chunks_mut(elements_per_thread << 1)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_idx = chunk_idx * elements_per_thread;
+                            s.spawn(move || {
+                                for (i, el) in chunk.chunks_exact_mut(2).enumerate() {
+                                    let k = Self::bit_reversed_index(start_idx + i, bits);
+                                    el[0] = input[2 * k];
+                                    el[1] = input[2 * k + 1];
+                                }
+                            });
+                        });
+                });
+
+                let mut mmax = 2;
+                while n > mmax {
+                    let istep = mmax << 1;
+                    let twiddles = &Self::fft_stage_twiddles(mmax, -1.0);
+                    let blocks = n / istep;
+                    if blocks >= n_threads {
+                        // each thread transforms whole blocks
+                        let blocks_per_thread = (blocks - 1) / n_threads + 1;
+                        thread::scope(|s| {
+                            data.chunks_mut(istep * blocks_per_thread)
+                                .for_each(|chunk| {
+                                    s.spawn(move || {
+                                        chunk.chunks_mut(istep).for_each(|block| {
+                                            let (top, bottom) = block.split_at_mut(mmax);
+                                            Self::fft_butterflies(top, bottom, twiddles);
+                                        });
+                                    });
+                                });
+                        });
+                    } else {
+                        // the last stages have few large blocks, which are split among the threads
+                        let threads_per_block = n_threads / blocks;
+                        let pairs_per_thread = ((mmax >> 1) - 1) / threads_per_block + 1;
+                        thread::scope(|s| {
+                            data.chunks_mut(istep).for_each(|block| {
+                                let (top, bottom) = block.split_at_mut(mmax);
+                                top.chunks_mut(pairs_per_thread << 1)
+                                    .zip(bottom.chunks_mut(pairs_per_thread << 1))
+                                    .zip(twiddles.chunks(pairs_per_thread))
+                                    .for_each(|((top, bottom), twiddles)| {
+                                        s.spawn(move || {
+                                            Self::fft_butterflies(top, bottom, twiddles)
+                                        });
+                                    });
+                            });
+                        });
+                    }
+                    mmax = istep;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_fft!(f32);
+impl_parallel_fft!(f64);
+
+macro_rules! impl_parallel_fft_2d {
+    ($t: tt) => {
+        impl ParallelFastFourierTransform2d<$t> for Matrix1d<$t> {
+            fn parallel_fft_2d(&self, result: &mut Self, n_threads: usize) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || self.rows.count_ones() != 1
+                    || self.cols.count_ones() != 1
+                    || self.cols < 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                let cols = self.cols;
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(cols * rows_per_thread)
+                        .zip(self.data.chunks(cols * rows_per_thread))
+                        .for_each(|(chunk, self_chunk)| {
+                            s.spawn(move || {
+                                chunk.copy_from_slice(self_chunk);
+                                chunk
+                                    .chunks_mut(cols)
+                                    .for_each(|result_row| Self::fft_helper(result_row, cols >> 1));
+                            });
+                        });
+                });
+
+                // each row of the transposed matrix is a column of complex numbers
+                let mut transposed = Self::zeroes(self.cols >> 1, self.rows << 1);
+                let transposed_cols = self.rows << 1;
+                let transposed_rows_per_thread = ((self.cols >> 1) - 1) / n_threads + 1;
+                let result_ref = &*result;
+
+                thread::scope(|s| {
+                    transposed
+                        .data
+                        .chunks_mut(transposed_cols * transposed_rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * transposed_rows_per_thread;
+                            s.spawn(move || {
+                                chunk.chunks_mut(transposed_cols).enumerate().for_each(
+                                    |(i, transposed_row)| {
+                                        result_ref
+                                            .complex_transpose_row(transposed_row, start_row + i);
+                                        Self::fft_helper(transposed_row, transposed_cols >> 1);
+                                    },
+                                );
+                            });
+                        });
+                });
+
+                let transposed = &transposed;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(cols * rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk
+                                    .chunks_mut(cols)
+                                    .enumerate()
+                                    .for_each(|(i, result_row)| {
+                                        transposed.complex_transpose_row(result_row, start_row + i)
+                                    });
+                            });
+                        });
+                });
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_fft_2d!(f32);
+impl_parallel_fft_2d!(f64);
+
+macro_rules! impl_parallel_radar_processing {
+    ($t: tt) => {
+        impl ParallelRadarProcessing<$t> for Matrix1d<$t> {
+            fn parallel_compression(
+                &self,
+                matched_filter: &[$t],
+                result: &mut Self,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || matched_filter.len() != self.cols
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if (self.cols >> 1).count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(self.cols * rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk.chunks_mut(self.cols).enumerate().for_each(
+                                    |(i, result_row)| {
+                                        self.compression_row(
+                                            result_row,
+                                            start_row + i,
+                                            matched_filter,
+                                        )
+                                    },
+                                );
+                            });
+                        });
+                });
+                Ok(())
+            }
+
+            fn parallel_corner_turn(
+                &self,
+                result: &mut Self,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.cols % 2 != 0
+                    || result.rows != self.cols >> 1
+                    || result.cols != self.rows << 1
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+
+                let rows_per_thread = ((self.cols >> 1) - 1) / n_threads + 1;
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut((self.rows << 1) * rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk.chunks_mut(self.rows << 1).enumerate().for_each(
+                                    |(i, result_row)| {
+                                        self.complex_transpose_row(result_row, start_row + i)
+                                    },
+                                );
+                            });
+                        });
+                });
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_radar_processing!(f32);
+impl_parallel_radar_processing!(f64);
+
+impl ParallelWaveletTransformInteger<i32> for Matrix1d<i32> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let (low, high) = result.data.split_at_mut(size);
+
+        // the low pass coefficients need all the high pass ones
+        thread::scope(|s| {
+            high.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = self.wavelet_transform_top_half_element(i + start_idx, size);
+                        });
+                    });
+                });
+        });
+
+        let high = &*high;
+        thread::scope(|s| {
+            low.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = self.wavelet_transform_bottom_half_element(high, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelWaveletTransformFloating<T> for Matrix1d<T> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let data = self.data.as_slice();
+        let (low, high) = result.data.split_at_mut(size);
+
+        thread::scope(|s| {
+            low.chunks_mut(elements_per_thread)
+                .zip(high.chunks_mut(elements_per_thread))
+                .enumerate()
+                .for_each(|(chunk_idx, (low_chunk, high_chunk))| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        for (i, (low_el, high_el)) in
+                            low_chunk.iter_mut().zip(high_chunk).enumerate()
+                        {
+                            let center = 2 * (i + start_idx);
+                            *low_el =
+                                wavelet::forward_floating_element(data, center, low_pass_filter);
+                            *high_el = wavelet::forward_floating_element(
+                                data,
+                                center + 1,
+                                high_pass_filter,
+                            );
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl ParallelInverseWaveletTransformInteger<i32> for Matrix1d<i32> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let coefficients = self.data.as_slice();
+        let mut even = vec![0; size];
+
+        // even samples first, the odd ones are predicted from them
+        thread::scope(|s| {
+            even.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el =
+                                wavelet::inverse_integer_even_element(coefficients, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        let even = even.as_slice();
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(2 * elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.chunks_exact_mut(2).enumerate().for_each(|(i, el)| {
+                            el[0] = even[i + start_idx];
+                            el[1] = wavelet::inverse_integer_odd_element(
+                                coefficients,
+                                even,
+                                i + start_idx,
+                            );
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelInverseWaveletTransformFloating<T> for Matrix1d<T> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (full_size - 1) / n_threads + 1;
+        let coefficients = self.data.as_slice();
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = wavelet::inverse_floating_element(
+                                coefficients,
+                                i + start_idx,
+                                low_pass_filter,
+                                high_pass_filter,
+                            );
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl ParallelWaveletTransform2dInteger<i32> for Matrix1d<i32> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.copy_from_slice(&self.data);
+        let mut rows: Vec<&mut [i32]> = result.data.chunks_mut(self.cols).collect();
+        wavelet::levels_2d(
+            &mut rows,
+            levels,
+            &wavelet::forward_integer,
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        );
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelWaveletTransform2dFloating<T> for Matrix1d<T> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.copy_from_slice(&self.data);
+        let mut rows: Vec<&mut [T]> = result.data.chunks_mut(self.cols).collect();
+        let transform = |data: &[T], transformed: &mut [T]| {
+            wavelet::forward_floating(data, transformed, low_pass_filter, high_pass_filter)
+        };
+        wavelet::levels_2d(&mut rows, levels, &transform, |rows, function| {
+            rows::parallel_for_each_row(rows, function, n_threads)
+        });
+        Ok(())
+    }
+}
+
+impl ParallelBitPlaneEncoder for Matrix1d<i32> {
+    fn parallel_bit_plane_encode(
+        &self,
+        parameters: &BpeParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error> {
+        ccsds_122::parallel_encode(
+            self.rows,
+            self.cols,
+            |i, j| self.data[i * self.cols + j],
+            parameters,
+            n_threads,
+        )
+    }
+}
+
+impl ParallelRiceEncoder for Matrix1d<i32> {
+    fn parallel_rice_encode(
+        &self,
+        parameters: &RiceParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error> {
+        ccsds_121::parallel_encode(&self.data, parameters, n_threads)
+    }
+}
+
+impl<T: Number> ParallelImageCalibration<T> for Matrix1d<T> {
+    fn parallel_offset_correction(
+        &self,
+        offsets: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::offset_correction(
+            &self.row_slices(),
+            &offsets.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_bad_pixel_correction(
+        &self,
+        bad_pixels: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::bad_pixel_correction(
+            &self.row_slices(),
+            &bad_pixels.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let neighbours: Vec<_> = neighbours.iter().map(|frame| frame.row_slices()).collect();
+        calibration::radiation_scrubbing(
+            &self.row_slices(),
+            &neighbours,
+            threshold,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_gain_correction(
+        &self,
+        gains: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::gain_correction(
+            &self.row_slices(),
+            &gains.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_spatial_binning(
+        &self,
+        factor: usize,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::spatial_binning(
+            &self.row_slices(),
+            factor,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_co_add(&self, accumulator: &mut Self, n_threads: usize) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::co_add(
+            &self.row_slices(),
+            &mut accumulator.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+impl<T: Number> ParallelIirFilter<T> for Matrix1d<T> {
+    fn parallel_iir_filter(
+        &self,
+        cascade: &BiquadCascade<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        iir::filter(
+            &self.row_slices(),
+            cascade,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+macro_rules! impl_parallel_corr {
+    ($self_type: tt, $output_type: tt) => {
+        impl ParallelCorrelation for Matrix1d<$self_type> {
+            type Output = $output_type;
+            fn parallel_correlate(
+                &self,
+                other: &Self,
+                n_threads: usize,
+            ) -> Result<Self::Output, Error> {
+                if self.rows != other.rows || self.cols != other.cols || self.rows == 0 {
+                    return Err(Error::InvalidDimensions);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+                let (self_rows, other_rows) = (self.row_slices(), other.row_slices());
+
+                // the sums of the rows of each thread, then the accumulators
+                let (self_sum, other_sum) = thread::scope(|s| {
+                    let handles: Vec<_> = self_rows
+                        .chunks(rows_per_thread)
+                        .zip(other_rows.chunks(rows_per_thread))
+                        .map(|(self_chunk, other_chunk)| {
+                            s.spawn(move || {
+                                let sum = |rows: &[&[$self_type]]| {
+                                    rows.iter().flat_map(|row| row.iter()).sum::<$self_type>()
+                                        as Self::Output
+                                };
+                                (sum(self_chunk), sum(other_chunk))
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .fold((0.0, 0.0), |(a, b), (c, d)| (a + c, b + d))
+                });
+                let elements = (self.rows * self.cols) as Self::Output;
+                let (self_mean, other_mean) = (self_sum / elements, other_sum / elements);
+
+                let (acc_self_sq, acc_other_sq, acc_self_other) = thread::scope(|s| {
+                    let handles: Vec<_> = (0..self.rows)
+                        .step_by(rows_per_thread)
+                        .map(|start| {
+                            s.spawn(move || {
+                                (start..(start + rows_per_thread).min(self.rows))
+                                    .map(|i| self.accumulate_row(other, self_mean, other_mean, i))
+                                    .fold((0.0, 0.0, 0.0), |(a, b, c), (d, e, f)| {
+                                        (a + d, b + e, c + f)
+                                    })
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .fold((0.0, 0.0, 0.0), |(a, b, c), (d, e, f)| {
+                            (a + d, b + e, c + f)
+                        })
+                });
+                Ok(acc_self_other / (acc_self_sq * acc_other_sq).sqrt())
+            }
+        }
+    };
+}
+
+impl_parallel_corr!(i32, f32);
+impl_parallel_corr!(f32, f32);
+impl_parallel_corr!(f64, f64);
+
+impl<T: Number> ParallelCrossCorrelation<T> for Matrix1d<T> {
+    fn parallel_cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<CorrelationPeak<T>, Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        cross_correlation::cross_correlation(
+            &self.row_slices(),
+            &other.row_slices(),
+            max_lags,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+macro_rules! impl_parallel_template_matching {
+    ($self_type: tt) => {
+        impl ParallelTemplateMatching for Matrix1d<$self_type> {
+            fn parallel_template_matching(
+                &self,
+                template: &Self,
+                result: &mut Self::Map,
+                n_threads: usize,
+            ) -> Result<CorrelationPeak<Self::Output>, Error> {
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+                cross_correlation::template_matching(
+                    &self.row_slices(),
+                    &template.row_slices(),
+                    &mut result.row_slices_mut(),
+                    |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+                )
+            }
+        }
+    };
+}
+
+impl_parallel_template_matching!(i32);
+impl_parallel_template_matching!(f32);
+impl_parallel_template_matching!(f64);
@@ -1,20 +1,40 @@
 use super::Matrix1d;
+use crate::abft;
+use crate::calibration;
+use crate::ccsds_121;
+use crate::ccsds_122;
+use crate::cross_correlation;
+use crate::fft_plan::FftPlan;
+use crate::iir;
+use crate::rows;
+use crate::tmr::{self, TmrReport};
+use crate::wavelet;
 use crate::BaseMatrix;
 use crate::{
-    Convolution, Correlation, Error, FastFourierTransform, FastFourierTransformHelper,
-    FastFourierTransformWindowed, FirFilter, Float, MatMul, MaxPooling, Number, Relu, Softmax,
-    WaveletTransformFloating, WaveletTransformInteger, LRN,
+    AbftMatMul, AbftReport, Activation, ActivationFunction, BatchNorm, BatchNormParameters,
+    BiquadCascade, BitPlaneEncoder, BpeParameters, Convolution, Correlation, CorrelationPeak,
+    CrossCorrelation, Dense, Error, FastFourierTransform, FastFourierTransform2d,
+    FastFourierTransformHelper, FastFourierTransformWindowed, FaultInjection, FirFilter, Float,
+    GeneralLRN, IirFilter, ImageCalibration, InverseFastFourierTransform,
+    InverseWaveletTransformFloating, InverseWaveletTransformInteger, LRNParameters, LRNRegion,
+    MatMul, MaxPooling, MixedRadixFastFourierTransform, MultirateFirFilter, Number,
+    PolyphaseFilter, Pooling, PoolingParameters, PoolingType, RadarProcessing,
+    RealFastFourierTransform, Relu, RiceEncoder, RiceParameters, ShortTimeFourierTransform,
+    Softmax, SoftmaxAxis, SpectrumOutput, StableSoftmax, StftParameters, TemplateMatching, Voting,
+    WaveletTransform2dFloating, WaveletTransform2dInteger, WaveletTransformFloating,
+    WaveletTransformInteger, LRN,
 };
 
 impl<T: Number> MatMul<T> for Matrix1d<T> {
     fn multiply_row(&self, other: &Matrix1d<T>, result_row: &mut [T], row_idx: usize) {
         let i = row_idx;
-        for j in 0..other.cols {
+        // other is transposed, the row has one element per row of other
+        for (j, el) in result_row.iter_mut().enumerate() {
             let mut sum = T::zero();
             for k in 0..self.cols {
                 sum += self.data[i * self.cols + k] * other.data[other.cols * j + k];
             }
-            result_row[j] = sum; // note that j is already the position in the chunk
+            *el = sum; // note that j is already the position in the chunk
         }
     }
 
@@ -27,13 +47,62 @@ impl<T: Number> MatMul<T> for Matrix1d<T> {
 
         result
             .data
-            .chunks_exact_mut(self.cols)
+            .chunks_exact_mut(result.cols)
             .enumerate()
             .for_each(|(i, result_row)| self.multiply_row(&other_transposed, result_row, i));
         Ok(())
     }
 }
 
+impl<T: Number> AbftMatMul<T> for Matrix1d<T> {
+    fn column_checksums(&self) -> Matrix1d<T> {
+        let rows = self.row_slices();
+        let mut data: Vec<Vec<T>> = rows.iter().map(|row| row.to_vec()).collect();
+        data.push(abft::column_sums(&rows));
+        Matrix1d::new(data, self.rows + 1, self.cols)
+    }
+
+    fn row_checksums(&self) -> Matrix1d<T> {
+        let data = self
+            .row_slices()
+            .into_iter()
+            .map(|row| {
+                let mut row = row.to_vec();
+                row.push(row.iter().copied().sum());
+                row
+            })
+            .collect();
+        Matrix1d::new(data, self.rows, self.cols + 1)
+    }
+
+    fn abft_verify(
+        &mut self,
+        tolerance: f64,
+        result: &mut Matrix1d<T>,
+    ) -> Result<AbftReport, Error> {
+        if self.rows != result.rows + 1 || self.cols != result.cols + 1 {
+            return Err(Error::InvalidDimensions);
+        }
+        let report = abft::verify(&mut self.row_slices_mut(), tolerance);
+        for (result_row, row) in result.row_slices_mut().into_iter().zip(self.row_slices()) {
+            result_row.copy_from_slice(&row[..result_row.len()]);
+        }
+        Ok(report)
+    }
+
+    fn abft_multiply(
+        &self,
+        other: &Matrix1d<T>,
+        tolerance: f64,
+        result: &mut Matrix1d<T>,
+    ) -> Result<AbftReport, Error> {
+        let mut product = Matrix1d::zeroes(result.rows + 1, result.cols + 1);
+        self.column_checksums()
+            .multiply(&other.row_checksums(), &mut product)?;
+        product.abft_verify(tolerance, result)
+    }
+}
+
 impl<T: Number> Relu<T> for Matrix1d<T> {
     fn relu_row(&self, result_row: &mut [T], row_idx: usize) {
         let i = row_idx;
@@ -59,6 +128,120 @@ impl<T: Number> Relu<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Float> Activation<T> for Matrix1d<T> {
+    fn activation_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        function: ActivationFunction<T>,
+    ) {
+        let row = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        for (result_el, el) in result_row.iter_mut().zip(row) {
+            *result_el = function.apply(*el);
+        }
+    }
+
+    fn activation(&self, result: &mut Self, function: ActivationFunction<T>) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .chunks_mut(self.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| self.activation_row(result_row, i, function));
+        Ok(())
+    }
+}
+
+impl<T: Float> Dense<T> for Matrix1d<T> {
+    fn dense_row(
+        &self,
+        input_transposed: &Self,
+        bias: &[T],
+        result_row: &mut [T],
+        row_idx: usize,
+        activation: Option<ActivationFunction<T>>,
+    ) {
+        let weights = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        for (j, result_el) in result_row.iter_mut().enumerate() {
+            let sample =
+                &input_transposed.data[j * input_transposed.cols..(j + 1) * input_transposed.cols];
+            let sum = weights
+                .iter()
+                .zip(sample)
+                .fold(bias[row_idx], |acc, (w, x)| acc + *w * *x);
+            *result_el = match activation {
+                Some(function) => function.apply(sum),
+                None => sum,
+            };
+        }
+    }
+
+    fn dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+    ) -> Result<(), Error> {
+        if self.cols != input.rows
+            || self.rows != result.rows
+            || input.cols != result.cols
+            || bias.len() != self.rows
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let input_transposed = input.transpose();
+
+        result
+            .data
+            .chunks_mut(result.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.dense_row(&input_transposed, bias, result_row, i, activation)
+            });
+        Ok(())
+    }
+}
+
+impl<T: Float> BatchNorm<T> for Matrix1d<T> {
+    fn batch_norm_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        parameters: &BatchNormParameters<T>,
+    ) {
+        let i = row_idx;
+        let factor = parameters.scale[i] / (parameters.variance[i] + parameters.epsilon).sqrt();
+        for (result_el, el) in result_row
+            .iter_mut()
+            .zip(&self.data[i * self.cols..(i + 1) * self.cols])
+        {
+            *result_el = (*el - parameters.mean[i]) * factor + parameters.shift[i];
+        }
+    }
+
+    fn batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        parameters.check_features(self.rows)?;
+
+        result
+            .data
+            .chunks_mut(self.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| self.batch_norm_row(result_row, i, parameters));
+        Ok(())
+    }
+}
+
 impl<T: Float> Softmax<T> for Matrix1d<T> {
     fn softmax_row(&self, result_row: &mut [T], row_idx: usize) -> T {
         let i = row_idx;
@@ -89,6 +272,109 @@ impl<T: Float> Softmax<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Float> StableSoftmax<T> for Matrix1d<T> {
+    fn softmax_max_row(&self, max: &mut [T], row_idx: usize, axis: SoftmaxAxis) {
+        let row = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        for (j, el) in row.iter().enumerate() {
+            let g = axis.statistic_idx(j);
+            if *el > max[g] {
+                max[g] = *el;
+            }
+        }
+    }
+
+    fn softmax_exp_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &mut [T],
+    ) {
+        let row = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        for (j, (result_el, el)) in result_row.iter_mut().zip(row).enumerate() {
+            let g = axis.statistic_idx(j);
+            *result_el = (*el - max[g]).exp();
+            sum[g] += *result_el;
+        }
+    }
+
+    fn softmax_normalize_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &[T],
+        log: bool,
+    ) {
+        let row = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        for (j, (result_el, el)) in result_row.iter_mut().zip(row).enumerate() {
+            let g = axis.statistic_idx(j);
+            if log {
+                *result_el = *el - max[g] - sum[g].ln();
+            } else {
+                *result_el /= sum[g];
+            }
+        }
+    }
+
+    fn stable_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.stable_softmax_helper(result, axis, false)
+    }
+
+    fn log_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.stable_softmax_helper(result, axis, true)
+    }
+}
+
+impl<T: Float> Matrix1d<T> {
+    fn stable_softmax_helper(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        log: bool,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        if axis == SoftmaxAxis::Rows {
+            // every row is normalised on its own, a single pass is enough
+            result
+                .data
+                .chunks_mut(self.cols)
+                .enumerate()
+                .for_each(|(i, result_row)| {
+                    let mut max = [T::neg_infinity()];
+                    let mut sum = [T::zero()];
+                    self.softmax_max_row(&mut max, i, axis);
+                    self.softmax_exp_row(result_row, i, axis, &max, &mut sum);
+                    self.softmax_normalize_row(result_row, i, axis, &max, &sum, log);
+                });
+            return Ok(());
+        }
+
+        let mut max = vec![T::neg_infinity(); axis.n_statistics(self.cols)];
+        let mut sum = vec![T::zero(); axis.n_statistics(self.cols)];
+
+        (0..self.rows).for_each(|i| self.softmax_max_row(&mut max, i, axis));
+        result
+            .data
+            .chunks_mut(self.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| self.softmax_exp_row(result_row, i, axis, &max, &mut sum));
+        result
+            .data
+            .chunks_mut(self.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.softmax_normalize_row(result_row, i, axis, &max, &sum, log)
+            });
+        Ok(())
+    }
+}
+
 impl<T: Number> MaxPooling<T> for Matrix1d<T> {
     fn max_pooling_row(
         &self,
@@ -134,6 +420,104 @@ impl<T: Number> MaxPooling<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Number> Pooling<T> for Matrix1d<T> {
+    fn pooling_row(
+        &self,
+        result_row: &mut [T],
+        mut indices_row: Option<&mut [usize]>,
+        row_idx: usize,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) {
+        let rows = parameters.row_window(row_idx, self.rows);
+        for j in 0..result_row.len() {
+            let cols = parameters.col_window(j, self.cols);
+            let mut selected_idx = rows.start * self.cols + cols.start;
+            let mut acc = match pooling_type {
+                PoolingType::Max => self.data[selected_idx],
+                PoolingType::Average => T::zero(),
+            };
+            for k in rows.clone() {
+                for l in cols.clone() {
+                    let idx = k * self.cols + l;
+                    if pooling_type == PoolingType::Max && acc < self.data[idx] {
+                        selected_idx = idx;
+                    }
+                    acc = pooling_type.combine(acc, self.data[idx]);
+                }
+            }
+            result_row[j] = pooling_type.finalize(acc, rows.len() * cols.len());
+            if let Some(indices_row) = indices_row.as_deref_mut() {
+                indices_row[j] = selected_idx;
+            }
+        }
+    }
+
+    fn pooling(
+        &self,
+        result: &mut Matrix1d<T>,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols) {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .chunks_mut(result.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.pooling_row(result_row, None, i, pooling_type, parameters)
+            });
+        Ok(())
+    }
+
+    fn max_pooling_indices(
+        &self,
+        result: &mut Matrix1d<T>,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols)
+            || indices.len() != result.data.len()
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .chunks_mut(result.cols)
+            .zip(indices.chunks_mut(result.cols))
+            .enumerate()
+            .for_each(|(i, (result_row, indices_row))| {
+                self.pooling_row(
+                    result_row,
+                    Some(indices_row),
+                    i,
+                    PoolingType::Max,
+                    parameters,
+                )
+            });
+        Ok(())
+    }
+
+    fn global_pooling_row(&self, row_idx: usize, pooling_type: PoolingType) -> T {
+        let row = &self.data[row_idx * self.cols..(row_idx + 1) * self.cols];
+        row.iter()
+            .skip(1)
+            .fold(row[0], |acc, el| pooling_type.combine(acc, *el))
+    }
+
+    fn global_pooling(&self, pooling_type: PoolingType) -> Result<T, Error> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+        let acc = (1..self.rows).fold(self.global_pooling_row(0, pooling_type), |acc, i| {
+            pooling_type.combine(acc, self.global_pooling_row(i, pooling_type))
+        });
+        Ok(pooling_type.finalize(acc, self.rows * self.cols))
+    }
+}
+
 macro_rules! impl_correlation {
     ($self_type: tt, $output_type: tt) => {
         impl Correlation for Matrix1d<$self_type> {
@@ -258,10 +642,109 @@ impl<T: Float> LRN<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Float> GeneralLRN<T> for Matrix1d<T> {
+    fn general_lrn_row(&self, result_row: &mut [T], row_idx: usize, parameters: &LRNParameters<T>) {
+        if parameters.n == 1 {
+            // same as the original benchmark
+            return self.lrn_row(
+                result_row,
+                row_idx,
+                parameters.alpha,
+                parameters.beta,
+                parameters.k,
+            );
+        }
+        let i = row_idx;
+        let radius = parameters.n / 2;
+        let rows = i.saturating_sub(radius)..(i + radius + 1).min(self.rows);
+        for (j, result_el) in result_row.iter_mut().enumerate() {
+            let cols = match parameters.region {
+                LRNRegion::AcrossChannels => j..j + 1,
+                LRNRegion::WithinChannel => {
+                    j.saturating_sub(radius)..(j + radius + 1).min(self.cols)
+                }
+            };
+            let mut sum = T::zero();
+            for k in rows.clone() {
+                for l in cols.clone() {
+                    sum += self.data[k * self.cols + l] * self.data[k * self.cols + l];
+                }
+            }
+            *result_el = self.data[i * self.cols + j]
+                / (parameters.k + parameters.alpha * sum).powf(parameters.beta);
+        }
+    }
+
+    fn general_lrn(&self, result: &mut Self, parameters: &LRNParameters<T>) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        if parameters.n % 2 != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        result
+            .data
+            .chunks_mut(result.cols)
+            .enumerate()
+            .for_each(|(i, result_row)| self.general_lrn_row(result_row, i, parameters));
+        Ok(())
+    }
+}
+
 macro_rules! impl_fft {
     ($t:tt) => {
-        impl FastFourierTransformHelper<$t> for Matrix1d<$t> {
-            fn fft_helper(result: &mut [$t], nn: usize) {
+        impl Matrix1d<$t> {
+            /// Position of the complex number `idx` after the bit reversal permutation of a
+            /// transform of `2^bits` elements
+            pub(crate) fn bit_reversed_index(idx: usize, bits: u32) -> usize {
+                if bits == 0 {
+                    return 0;
+                }
+                idx.reverse_bits() >> (usize::BITS - bits)
+            }
+
+            /// Twiddle factors of the butterflies of the stage with blocks of `mmax` values,
+            /// computed with the same recurrence as `fft_sign_helper`
+            pub(crate) fn fft_stage_twiddles(mmax: usize, sign: $t) -> Vec<($t, $t)> {
+                let theta = sign * (2.0 * std::$t::consts::PI / mmax as $t);
+                let wtemp = (theta / 2.0).sin();
+                let wpr = -2.0 * wtemp * wtemp;
+                let wpi = (theta).sin();
+                let mut wr = 1.0;
+                let mut wi = 0.0;
+                let mut twiddles = Vec::with_capacity(mmax >> 1);
+                for _ in 0..mmax >> 1 {
+                    twiddles.push((wr, wi));
+                    let wtemp = wr;
+                    wr += wr * wpr - wi * wpi;
+                    wi += wi * wpr + wtemp * wpi;
+                }
+                twiddles
+            }
+
+            /// Butterflies between the complex numbers of `top` and `bottom`, the k-th pair
+            /// uses the k-th twiddle factor
+            pub(crate) fn fft_butterflies(
+                top: &mut [$t],
+                bottom: &mut [$t],
+                twiddles: &[($t, $t)],
+            ) {
+                for ((t, b), (wr, wi)) in top
+                    .chunks_exact_mut(2)
+                    .zip(bottom.chunks_exact_mut(2))
+                    .zip(twiddles)
+                {
+                    let tempr = wr * b[0] - wi * b[1];
+                    let tempi = wr * b[1] + wi * b[0];
+                    b[0] = t[0] - tempr;
+                    b[1] = t[1] - tempi;
+                    t[0] += tempr;
+                    t[1] += tempi;
+                }
+            }
+
+            /// Radix-2 transform, `sign` is the sign of the exponent: -1 forward, 1 inverse
+            fn fft_sign_helper(result: &mut [$t], nn: usize, sign: $t) {
                 let n = nn << 1;
                 let mut j = 1;
                 for i in (1..n).step_by(2) {
@@ -280,7 +763,7 @@ macro_rules! impl_fft {
                 let mut mmax = 2;
                 while n > mmax {
                     let istep = mmax << 1;
-                    let theta = -(2.0 * std::$t::consts::PI / mmax as $t);
+                    let theta = sign * (2.0 * std::$t::consts::PI / mmax as $t);
                     let wtemp = (theta / 2.0).sin();
                     let wpr = -2.0 * wtemp * wtemp;
                     let wpi = (theta).sin();
@@ -305,15 +788,108 @@ macro_rules! impl_fft {
             }
         }
 
+        impl FastFourierTransformHelper<$t> for Matrix1d<$t> {
+            fn fft_helper(result: &mut [$t], nn: usize) {
+                Self::fft_sign_helper(result, nn, -1.0);
+            }
+
+            fn ifft_helper(result: &mut [$t], nn: usize) {
+                Self::fft_sign_helper(result, nn, 1.0);
+            }
+        }
+
         impl FastFourierTransform<$t> for Matrix1d<$t> {
             fn fft(&mut self, nn: usize) -> Result<(), Error> {
-                if self.rows != 1 {
+                if self.rows != 1 || nn << 1 > self.cols {
                     return Err(Error::InvalidDimensions);
                 }
+                if nn.count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
                 Self::fft_helper(self.data.as_mut_slice(), nn);
                 Ok(())
             }
         }
+
+        impl InverseFastFourierTransform<$t> for Matrix1d<$t> {
+            fn ifft(&mut self, nn: usize) -> Result<(), Error> {
+                if self.rows != 1 || nn << 1 > self.cols {
+                    return Err(Error::InvalidDimensions);
+                }
+                if nn.count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+                Self::ifft_helper(self.data.as_mut_slice(), nn);
+                let scale = 1.0 / nn as $t;
+                self.data[..nn << 1].iter_mut().for_each(|el| *el *= scale);
+                Ok(())
+            }
+        }
+
+        impl RealFastFourierTransform<$t> for Matrix1d<$t> {
+            fn rfft(&self, result: &mut Self) -> Result<(), Error> {
+                let n = self.cols;
+                if self.rows != 1
+                    || result.rows != 1
+                    || n < 2
+                    || n.count_ones() != 1
+                    || result.cols != n + 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                let half = n >> 1;
+
+                // the even samples are the real parts and the odd ones the imaginary parts of
+                // a complex signal of n / 2 elements
+                let mut z = self.data.clone();
+                Self::fft_helper(&mut z, half);
+
+                let theta = -(2.0 * std::$t::consts::PI / n as $t);
+                for k in 0..=half {
+                    let (zr, zi) = (z[2 * (k % half)], z[2 * (k % half) + 1]);
+                    // conjugate of Z[n / 2 - k]
+                    let (cr, ci) = (z[2 * ((half - k) % half)], -z[2 * ((half - k) % half) + 1]);
+                    // spectra of the even and odd samples
+                    let (er, ei) = ((zr + cr) / 2.0, (zi + ci) / 2.0);
+                    let (or, oi) = ((zi - ci) / 2.0, (cr - zr) / 2.0);
+                    let (wr, wi) = ((theta * k as $t).cos(), (theta * k as $t).sin());
+                    result.data[2 * k] = er + wr * or - wi * oi;
+                    result.data[2 * k + 1] = ei + wr * oi + wi * or;
+                }
+                Ok(())
+            }
+
+            fn irfft(&self, result: &mut Self) -> Result<(), Error> {
+                let n = result.cols;
+                if self.rows != 1
+                    || result.rows != 1
+                    || n < 2
+                    || n.count_ones() != 1
+                    || self.cols != n + 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                let half = n >> 1;
+
+                let theta = 2.0 * std::$t::consts::PI / n as $t;
+                for k in 0..half {
+                    let (xr, xi) = (self.data[2 * k], self.data[2 * k + 1]);
+                    // conjugate of X[n / 2 - k]
+                    let (cr, ci) = (self.data[2 * (half - k)], -self.data[2 * (half - k) + 1]);
+                    let (er, ei) = ((xr + cr) / 2.0, (xi + ci) / 2.0);
+                    let (dr, di) = ((xr - cr) / 2.0, (xi - ci) / 2.0);
+                    let (wr, wi) = ((theta * k as $t).cos(), (theta * k as $t).sin());
+                    let (or, oi) = (dr * wr - di * wi, dr * wi + di * wr);
+                    result.data[2 * k] = er - oi;
+                    result.data[2 * k + 1] = ei + or;
+                }
+
+                Self::ifft_helper(&mut result.data, half);
+                let scale = 1.0 / half as $t;
+                result.data.iter_mut().for_each(|el| *el *= scale);
+                Ok(())
+            }
+        }
     };
 }
 
@@ -327,6 +903,9 @@ macro_rules! impl_fft_windowed {
                 if self.rows != 1 {
                     return Err(Error::InvalidDimensions);
                 }
+                if window < 2 || window.count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
                 // the actual size of the result chunk is window * 2 (window complex numbers)
                 // this is the reason for the step_by(2) in the outer loop
                 // so for each "actual" window, it looks like only the bottom half is copied to the result
@@ -351,50 +930,219 @@ macro_rules! impl_fft_windowed {
 impl_fft_windowed!(f32);
 impl_fft_windowed!(f64);
 
-// TODO: note that right now the data has a minimum size for the algorithm to work
-//       should at least document this in the error
-impl WaveletTransformInteger<i32> for Matrix1d<i32> {
-    fn wavelet_transform_bottom_half_element(
-        &self,
-        result_top_half: &[i32],
-        element_idx: usize,
-    ) -> i32 {
-        let i = element_idx;
-        let data = &self.data;
-
-        if i == 0 {
-            data[0] - (-(result_top_half[0] as f32 / 2.0) + 0.5) as i32
-        } else {
-            data[2 * i]
-                - (-((result_top_half[i - 1] + result_top_half[i]) as f32 / 4.0) + 0.5) as i32
+macro_rules! impl_stft {
+    ($t: tt) => {
+        impl ShortTimeFourierTransform<$t> for Matrix1d<$t> {
+            fn stft_row(
+                &self,
+                result_row: &mut [$t],
+                row_idx: usize,
+                parameters: &StftParameters<$t>,
+                coefficients: &[$t],
+            ) {
+                let start = row_idx * parameters.hop * 2;
+                let frame = &self.data[start..start + parameters.window * 2];
+                let window_frame = |spectrum: &mut [$t]| {
+                    for ((spectrum_el, el), w) in spectrum
+                        .chunks_exact_mut(2)
+                        .zip(frame.chunks_exact(2))
+                        .zip(coefficients)
+                    {
+                        spectrum_el[0] = el[0] * w;
+                        spectrum_el[1] = el[1] * w;
+                    }
+                    Self::fft_helper(spectrum, parameters.window);
+                };
+
+                match parameters.output {
+                    SpectrumOutput::Complex => window_frame(result_row),
+                    SpectrumOutput::Power => {
+                        let mut spectrum = vec![0.0; parameters.window * 2];
+                        window_frame(&mut spectrum);
+                        for (result_el, el) in result_row.iter_mut().zip(spectrum.chunks_exact(2)) {
+                            *result_el = el[0] * el[0] + el[1] * el[1];
+                        }
+                    }
+                }
+            }
+
+            fn stft(
+                &self,
+                result: &mut Self,
+                parameters: &StftParameters<$t>,
+            ) -> Result<(), Error> {
+                if self.rows != 1 {
+                    return Err(Error::InvalidDimensions);
+                }
+                let frames = parameters.frames(self.cols >> 1)?;
+                if result.rows != frames || result.cols != parameters.output_cols() {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                let coefficients = parameters.function.coefficients(parameters.window);
+                result
+                    .data
+                    .chunks_mut(result.cols)
+                    .enumerate()
+                    .for_each(|(i, result_row)| {
+                        self.stft_row(result_row, i, parameters, &coefficients)
+                    });
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_stft!(f32);
+impl_stft!(f64);
+
+macro_rules! impl_fft_2d {
+    ($t: tt) => {
+        impl FastFourierTransform2d<$t> for Matrix1d<$t> {
+            fn complex_transpose_row(&self, result_row: &mut [$t], row_idx: usize) {
+                for (i, result_el) in result_row.chunks_exact_mut(2).enumerate() {
+                    result_el[0] = self.data[i * self.cols + 2 * row_idx];
+                    result_el[1] = self.data[i * self.cols + 2 * row_idx + 1];
+                }
+            }
+
+            fn fft_2d(&self, result: &mut Self) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || self.rows.count_ones() != 1
+                    || self.cols.count_ones() != 1
+                    || self.cols < 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                result.data.copy_from_slice(&self.data);
+                result
+                    .data
+                    .chunks_mut(self.cols)
+                    .for_each(|result_row| Self::fft_helper(result_row, self.cols >> 1));
+
+                // each row of the transposed matrix is a column of complex numbers
+                let mut transposed = Self::zeroes(self.cols >> 1, self.rows << 1);
+                transposed
+                    .data
+                    .chunks_mut(self.rows << 1)
+                    .enumerate()
+                    .for_each(|(i, transposed_row)| {
+                        result.complex_transpose_row(transposed_row, i);
+                        Self::fft_helper(transposed_row, self.rows);
+                    });
+
+                result
+                    .data
+                    .chunks_mut(self.cols)
+                    .enumerate()
+                    .for_each(|(i, result_row)| transposed.complex_transpose_row(result_row, i));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_fft_2d!(f32);
+impl_fft_2d!(f64);
+
+macro_rules! impl_radar_processing {
+    ($t: tt) => {
+        impl RadarProcessing<$t> for Matrix1d<$t> {
+            fn compression_row(
+                &self,
+                result_row: &mut [$t],
+                row_idx: usize,
+                matched_filter: &[$t],
+            ) {
+                let nn = self.cols >> 1;
+                result_row
+                    .copy_from_slice(&self.data[row_idx * self.cols..(row_idx + 1) * self.cols]);
+                Self::fft_helper(result_row, nn);
+                for (el, filter) in result_row
+                    .chunks_exact_mut(2)
+                    .zip(matched_filter.chunks_exact(2))
+                {
+                    let re = el[0] * filter[0] - el[1] * filter[1];
+                    el[1] = el[0] * filter[1] + el[1] * filter[0];
+                    el[0] = re;
+                }
+                Self::ifft_helper(result_row, nn);
+            }
+
+            fn compression(&self, matched_filter: &[$t], result: &mut Self) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || matched_filter.len() != self.cols
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if (self.cols >> 1).count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+
+                result
+                    .data
+                    .chunks_mut(self.cols)
+                    .enumerate()
+                    .for_each(|(i, result_row)| {
+                        self.compression_row(result_row, i, matched_filter)
+                    });
+                Ok(())
+            }
+
+            fn corner_turn(&self, result: &mut Self) -> Result<(), Error> {
+                if self.cols % 2 != 0
+                    || result.rows != self.cols >> 1
+                    || result.cols != self.rows << 1
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                result
+                    .data
+                    .chunks_mut(self.rows << 1)
+                    .enumerate()
+                    .for_each(|(i, result_row)| self.complex_transpose_row(result_row, i));
+                Ok(())
+            }
         }
+    };
+}
+
+impl_radar_processing!(f32);
+impl_radar_processing!(f64);
+
+impl<T: Float> MixedRadixFastFourierTransform<T> for Matrix1d<T> {
+    fn mixed_radix_fft(&mut self, plan: &FftPlan<T>) -> Result<(), Error> {
+        if self.rows != 1 {
+            return Err(Error::InvalidDimensions);
+        }
+        plan.forward(&mut self.data)
     }
 
-    fn wavelet_transform_top_half_element(&self, element_idx: usize, size: usize) -> i32 {
-        let i = element_idx;
-        let data = &self.data;
-
-        if i == 0 {
-            data[1]
-                - (((9.0 / 16.0) * (data[0] + data[2]) as f32)
-                    - ((1.0 / 16.0) * (data[2] + data[4]) as f32)
-                    + 0.5) as i32
-        } else if i == size - 2 {
-            data[2 * size - 3]
-                - (((9.0 / 16.0) * (data[2 * size - 4] + data[2 * size - 2]) as f32)
-                    - ((1.0 / 16.0) * (data[2 * size - 6] + data[2 * size - 2]) as f32)
-                    + (1.0 / 2.0)) as i32
-        } else if i == size - 1 {
-            data[2 * size - 1]
-                - (((9.0 / 8.0) * (data[2 * size - 2]) as f32)
-                    - ((1.0 / 8.0) * (data[2 * size - 4]) as f32)
-                    + 0.5) as i32
-        } else {
-            data[2 * i + 1]
-                - (((9.0 / 16.0) * (data[2 * i] + data[2 * i + 2]) as f32)
-                    - ((1.0 / 16.0) * (data[2 * i - 2] + data[2 * i + 4]) as f32)
-                    + (1.0 / 2.0)) as i32
+    fn mixed_radix_ifft(&mut self, plan: &FftPlan<T>) -> Result<(), Error> {
+        if self.rows != 1 {
+            return Err(Error::InvalidDimensions);
         }
+        plan.inverse(&mut self.data)
+    }
+}
+
+// TODO: note that right now the data has a minimum size for the algorithm to work
+//       should at least document this in the error
+impl WaveletTransformInteger<i32> for Matrix1d<i32> {
+    fn wavelet_transform_bottom_half_element(
+        &self,
+        result_top_half: &[i32],
+        element_idx: usize,
+    ) -> i32 {
+        wavelet::forward_integer_low_element(&self.data, result_top_half, element_idx)
+    }
+
+    fn wavelet_transform_top_half_element(&self, element_idx: usize, size: usize) -> i32 {
+        wavelet::forward_integer_high_element(&self.data[..2 * size], element_idx)
     }
 
     fn wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
@@ -446,44 +1194,108 @@ impl<T: Float> WaveletTransformFloating<T> for Matrix1d<T> {
             return Err(Error::InvalidDimensions);
         }
 
-        let hi_start = -(low_pass_filter_size as isize / 2);
-        let hi_end = (low_pass_filter_size / 2) as isize;
-        let gi_start = -(high_pass_filter_size as isize / 2);
-        let gi_end = (high_pass_filter_size / 2) as isize;
+        wavelet::forward_floating(
+            &self.data,
+            &mut result.data,
+            &low_pass_filter[..low_pass_filter_size],
+            &high_pass_filter[..high_pass_filter_size],
+        );
+        Ok(())
+    }
+}
+
+impl WaveletTransform2dInteger<i32> for Matrix1d<i32> {
+    fn wavelet_transform_2d(&self, result: &mut Self, levels: usize) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.copy_from_slice(&self.data);
+        let mut rows: Vec<&mut [i32]> = result.data.chunks_mut(self.cols).collect();
+        wavelet::levels_2d(
+            &mut rows,
+            levels,
+            &wavelet::forward_integer,
+            rows::for_each_row,
+        );
+        Ok(())
+    }
+}
 
-        for i in 0..size {
-            let mut sum_value_low = T::zero();
-            // process the lowpass filter
-            for hi in hi_start..hi_end + 1 {
-                let x_position = (2 * i) as isize + hi;
-                let x_position = if x_position < 0 {
-                    x_position * -1
-                } else if x_position > full_size as isize - 1 {
-                    full_size as isize - 1 - (x_position - (full_size as isize - 1))
-                } else {
-                    x_position
-                };
-                sum_value_low +=
-                    low_pass_filter[(hi + hi_end) as usize] * self.data[x_position as usize];
-            }
-            result.data[i] = sum_value_low;
-
-            let mut sum_value_high = T::zero();
-            // process the highpass filter
-            for gi in gi_start..gi_end + 1 {
-                let x_position = (2 * i) as isize + gi + 1;
-                let x_position = if x_position < 0 {
-                    x_position * -1
-                } else if x_position > full_size as isize - 1 {
-                    full_size as isize - 1 - (x_position - (full_size as isize - 1))
-                } else {
-                    x_position
-                };
-                sum_value_high +=
-                    high_pass_filter[(gi + gi_end) as usize] * self.data[x_position as usize];
-            }
-            result.data[i + size] = sum_value_high;
+impl<T: Float> WaveletTransform2dFloating<T> for Matrix1d<T> {
+    fn wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
         }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.copy_from_slice(&self.data);
+        let mut rows: Vec<&mut [T]> = result.data.chunks_mut(self.cols).collect();
+        let transform = |data: &[T], transformed: &mut [T]| {
+            wavelet::forward_floating(data, transformed, low_pass_filter, high_pass_filter)
+        };
+        wavelet::levels_2d(&mut rows, levels, &transform, rows::for_each_row);
+        Ok(())
+    }
+}
+
+impl InverseWaveletTransformInteger<i32> for Matrix1d<i32> {
+    fn inverse_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let coefficients = self.data.as_slice();
+
+        // even samples first, the odd ones are predicted from them
+        let even: Vec<i32> = (0..size)
+            .map(|idx| wavelet::inverse_integer_even_element(coefficients, idx))
+            .collect();
+
+        result
+            .data
+            .chunks_exact_mut(2)
+            .enumerate()
+            .for_each(|(idx, el)| {
+                el[0] = even[idx];
+                el[1] = wavelet::inverse_integer_odd_element(coefficients, &even, idx);
+            });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> InverseWaveletTransformFloating<T> for Matrix1d<T> {
+    fn inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let coefficients = self.data.as_slice();
+
+        result.data.iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = wavelet::inverse_floating_element(
+                coefficients,
+                idx,
+                low_pass_filter,
+                high_pass_filter,
+            );
+        });
         Ok(())
     }
 }
@@ -519,11 +1331,209 @@ impl<T: Number> FirFilter<T> for Matrix1d<T> {
     }
 }
 
+impl<T: Number> MultirateFirFilter<T> for Matrix1d<T> {
+    fn fir_decimate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error> {
+        if self.rows != 1 || result.rows != 1 || result.cols != filter.decimated_len(self.cols)? {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[..];
+        result.data.iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = filter.decimate_element(input, idx);
+        });
+        Ok(())
+    }
+
+    fn fir_interpolate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error> {
+        if self.rows != 1
+            || result.rows != 1
+            || result.cols != filter.interpolated_len(self.cols)?
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[..];
+        result.data.iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = filter.interpolate_element(input, idx);
+        });
+        Ok(())
+    }
+}
+
+impl BitPlaneEncoder for Matrix1d<i32> {
+    fn bit_plane_encode(&self, parameters: &BpeParameters) -> Result<Vec<u8>, Error> {
+        ccsds_122::encode(
+            self.rows,
+            self.cols,
+            |i, j| self.data[i * self.cols + j],
+            parameters,
+        )
+    }
+
+    fn bit_plane_decode(&mut self, data: &[u8]) -> Result<(), Error> {
+        let cols = self.cols;
+        ccsds_122::decode(data, self.rows, cols, |i, j, value| {
+            self.data[i * cols + j] = value
+        })
+    }
+}
+
+impl RiceEncoder for Matrix1d<i32> {
+    fn rice_encode(&self, parameters: &RiceParameters) -> Result<Vec<u8>, Error> {
+        ccsds_121::encode(&self.data, parameters)
+    }
+
+    fn rice_decode(&mut self, data: &[u8], parameters: &RiceParameters) -> Result<(), Error> {
+        ccsds_121::decode(data, &mut self.data, parameters)
+    }
+}
+
+impl<T: Number> ImageCalibration<T> for Matrix1d<T> {
+    fn offset_correction(&self, offsets: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::offset_correction(
+            &self.row_slices(),
+            &offsets.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn bad_pixel_correction(&self, bad_pixels: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::bad_pixel_correction(
+            &self.row_slices(),
+            &bad_pixels.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        let neighbours: Vec<_> = neighbours.iter().map(|frame| frame.row_slices()).collect();
+        calibration::radiation_scrubbing(
+            &self.row_slices(),
+            &neighbours,
+            threshold,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn gain_correction(&self, gains: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::gain_correction(
+            &self.row_slices(),
+            &gains.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn spatial_binning(&self, factor: usize, result: &mut Self) -> Result<(), Error> {
+        calibration::spatial_binning(
+            &self.row_slices(),
+            factor,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn co_add(&self, accumulator: &mut Self) -> Result<(), Error> {
+        calibration::co_add(
+            &self.row_slices(),
+            &mut accumulator.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+impl<T: Number> IirFilter<T> for Matrix1d<T> {
+    fn iir_filter(&self, cascade: &BiquadCascade<T>, result: &mut Self) -> Result<(), Error> {
+        iir::filter(
+            &self.row_slices(),
+            cascade,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+impl<T: Number> CrossCorrelation<T> for Matrix1d<T> {
+    fn cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+    ) -> Result<CorrelationPeak<T>, Error> {
+        cross_correlation::cross_correlation(
+            &self.row_slices(),
+            &other.row_slices(),
+            max_lags,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+macro_rules! impl_template_matching {
+    ($self_type: tt, $output_type: tt) => {
+        impl TemplateMatching for Matrix1d<$self_type> {
+            type Output = $output_type;
+            type Map = Matrix1d<$output_type>;
+            fn template_matching(
+                &self,
+                template: &Self,
+                result: &mut Self::Map,
+            ) -> Result<CorrelationPeak<Self::Output>, Error> {
+                cross_correlation::template_matching(
+                    &self.row_slices(),
+                    &template.row_slices(),
+                    &mut result.row_slices_mut(),
+                    rows::for_each_row,
+                )
+            }
+        }
+    };
+}
+
+impl_template_matching!(i32, f32);
+impl_template_matching!(f32, f32);
+impl_template_matching!(f64, f64);
+
+impl<T: Number> FaultInjection<T> for Matrix1d<T> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn modify_bits(&mut self, row: usize, col: usize, function: impl FnOnce(u64) -> u64) -> u64 {
+        let element = &mut self.data[row * self.cols + col];
+        let bits = element.to_raw_bits();
+        let mask = (u64::MAX >> (64 - T::BITS)) & (function(bits) ^ bits);
+        *element = T::from_raw_bits(bits ^ mask);
+        mask
+    }
+}
+
+impl<T: Number> Voting for Matrix1d<T> {
+    fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+        if replicas
+            .iter()
+            .any(|replica| (replica.rows, replica.cols) != (self.rows, self.cols))
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let [a, b, c] = replicas.map(|replica| replica.row_slices());
+        Ok(tmr::vote_rows(&mut self.row_slices_mut(), [&a, &b, &c]))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::BaseMatrix;
     #[allow(unused_imports)]
     use super::*;
+    use crate::{Chirp, Subband, SubbandRegion, WindowFunction};
 
     fn gen_matrix_data(size: usize) -> Vec<Vec<f32>> {
         let mut data = vec![vec![0.0; size]; size];
@@ -558,4 +1568,1186 @@ mod tests {
         let res = m1.correlation(&m2).unwrap();
         assert!(abs(res - 0.073827) < 10e-6, "res = {}", res);
     }
+
+    #[test]
+    fn pooling() {
+        let m = Matrix1d::new(
+            vec![
+                vec![1.0, 2.0, 3.0, 4.0],
+                vec![5.0, 6.0, 7.0, 8.0],
+                vec![9.0, 10.0, 11.0, 12.0],
+            ],
+            3,
+            4,
+        );
+        // 2x2 windows with stride 1 and padding 1 overlap and fall partially outside the input
+        let parameters = PoolingParameters::new(2, 2, 1, 2, 1, 1);
+        assert_eq!(parameters.output_dimensions(3, 4).unwrap(), (4, 3));
+
+        let mut res = Matrix1d::zeroes(4, 3);
+        let mut indices = vec![0; 12];
+        m.max_pooling_indices(&mut res, &mut indices, &parameters)
+            .unwrap();
+        assert_eq!(
+            res.get_data(),
+            vec![
+                vec![1.0, 3.0, 4.0],
+                vec![5.0, 7.0, 8.0],
+                vec![9.0, 11.0, 12.0],
+                vec![9.0, 11.0, 12.0]
+            ]
+        );
+        assert_eq!(indices, vec![0, 2, 3, 4, 6, 7, 8, 10, 11, 8, 10, 11]);
+
+        m.pooling(&mut res, PoolingType::Average, &parameters)
+            .unwrap();
+        assert_eq!(res.get_data()[1], vec![3.0, 4.5, 6.0]);
+
+        assert_eq!(m.global_pooling(PoolingType::Max).unwrap(), 12.0);
+        assert_eq!(m.global_pooling(PoolingType::Average).unwrap(), 6.5);
+    }
+
+    #[test]
+    fn parallel_softmax() {
+        use crate::parallel_traits::ParallelSoftmax;
+
+        // the 10 rows of the cifar10 classifier are not split evenly between the threads
+        let m = Matrix1d::<f64>::from_random_seed(0, 10, 1, -1.0, 1.0);
+        let mut expected = Matrix1d::zeroes(10, 1);
+        m.softmax(&mut expected).unwrap();
+        for n_threads in [1, 3, 4, 7] {
+            let mut res = Matrix1d::zeroes(10, 1);
+            m.parallel_softmax(&mut res, n_threads).unwrap();
+            for (a, b) in res.data.iter().zip(&expected.data) {
+                assert!((a - b).abs() < 1e-12, "{} threads", n_threads);
+            }
+        }
+    }
+
+    #[test]
+    fn stable_softmax() {
+        // exp(100.0) overflows f32, the stable softmax must not
+        let m = Matrix1d::new(vec![vec![100.0f32, 100.0], vec![0.0, 2.0f32.ln()]], 2, 2);
+        let mut res = Matrix1d::zeroes(2, 2);
+
+        m.stable_softmax(&mut res, SoftmaxAxis::Rows).unwrap();
+        let data = res.get_data();
+        assert!(abs(data[0][0] - 0.5) < 1e-6 && abs(data[0][1] - 0.5) < 1e-6);
+        assert!(abs(data[1][0] - 1.0 / 3.0) < 1e-6 && abs(data[1][1] - 2.0 / 3.0) < 1e-6);
+
+        m.stable_softmax(&mut res, SoftmaxAxis::Columns).unwrap();
+        assert!(res.get_data()[0].iter().all(|el| abs(el - 1.0) < 1e-6));
+
+        m.log_softmax(&mut res, SoftmaxAxis::Global).unwrap();
+        let data = res.get_data();
+        assert!(abs(data[0][0] - 0.5f32.ln()) < 1e-6, "res = {}", data[0][0]);
+        assert!(data[1][0] < -90.0);
+    }
+
+    #[test]
+    fn general_lrn() {
+        // 3 channels with 2 positions each
+        let m = Matrix1d::new(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]], 3, 2);
+        let mut res = Matrix1d::zeroes(3, 2);
+        let mut parameters = LRNParameters {
+            alpha: 1.0,
+            beta: 1.0,
+            k: 0.0,
+            n: 3,
+            region: LRNRegion::AcrossChannels,
+        };
+        m.general_lrn(&mut res, &parameters).unwrap();
+        assert_eq!(res.get_data()[0], vec![1.0 / 10.0, 2.0 / 20.0]);
+        assert_eq!(res.get_data()[1], vec![3.0 / 35.0, 4.0 / 56.0]);
+
+        parameters.region = LRNRegion::WithinChannel;
+        m.general_lrn(&mut res, &parameters).unwrap();
+        assert_eq!(res.get_data()[0][0], 1.0 / 30.0);
+
+        // n = 1 is the benchmark version
+        parameters.n = 1;
+        let mut res_ref = Matrix1d::zeroes(3, 2);
+        m.general_lrn(&mut res, &parameters).unwrap();
+        m.lrn(&mut res_ref, 1.0, 1.0, 0.0).unwrap();
+        assert_eq!(res.get_data(), res_ref.get_data());
+
+        parameters.n = 2;
+        assert!(m.general_lrn(&mut res, &parameters).is_err());
+    }
+
+    #[test]
+    fn activation() {
+        let m = Matrix1d::new(vec![vec![-2.0, 0.0, 1.0, 8.0]], 1, 4);
+        let mut res = Matrix1d::zeroes(1, 4);
+        let expected = [
+            (ActivationFunction::Relu, [0.0, 0.0, 1.0, 8.0]),
+            (ActivationFunction::LeakyRelu(0.1), [-0.2, 0.0, 1.0, 8.0]),
+            (ActivationFunction::Relu6, [0.0, 0.0, 1.0, 6.0]),
+            (
+                ActivationFunction::HardSigmoid,
+                [1.0 / 6.0, 0.5, 2.0 / 3.0, 1.0],
+            ),
+            (
+                ActivationFunction::Sigmoid,
+                [0.1192029, 0.5, 0.7310586, 0.9996646],
+            ),
+            (
+                ActivationFunction::Tanh,
+                [-0.9640276, 0.0, 0.7615942, 0.9999998],
+            ),
+            (
+                ActivationFunction::Swish,
+                [-0.2384058, 0.0, 0.7310586, 7.9973168],
+            ),
+            (ActivationFunction::Gelu, [-0.0454023, 0.0, 0.8411920, 8.0]),
+        ];
+        for (function, values) in expected {
+            m.activation(&mut res, function).unwrap();
+            for (a, b) in res.get_data()[0].iter().zip(values) {
+                assert!(abs(a - b) < 1e-5, "{:?}: {} != {}", function, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn dense_and_batch_norm() {
+        let weights = Matrix1d::new(vec![vec![1.0, 2.0], vec![-1.0, 0.5], vec![0.0, 3.0]], 3, 2);
+        let input = Matrix1d::new(vec![vec![1.0, -1.0], vec![2.0, 4.0]], 2, 2);
+        let bias = [0.5, -1.0, 0.0];
+        let mut res = Matrix1d::zeroes(3, 2);
+
+        weights.dense(&input, &bias, &mut res, None).unwrap();
+        assert_eq!(
+            res.get_data(),
+            vec![vec![5.5, 7.5], vec![-1.0, 2.0], vec![6.0, 12.0]]
+        );
+        weights
+            .dense(&input, &bias, &mut res, Some(ActivationFunction::Relu6))
+            .unwrap();
+        assert_eq!(
+            res.get_data(),
+            vec![vec![5.5, 6.0], vec![0.0, 2.0], vec![6.0, 6.0]]
+        );
+        assert!(weights.dense(&input, &bias[..2], &mut res, None).is_err());
+
+        let mut parameters = BatchNormParameters::identity(3);
+        let mut normalized = Matrix1d::zeroes(3, 2);
+        res.batch_norm(&mut normalized, &parameters).unwrap();
+        assert_eq!(normalized.get_data(), res.get_data());
+
+        parameters.mean = vec![1.0, 0.0, 6.0];
+        parameters.variance = vec![4.0, 1.0, 9.0];
+        parameters.scale = vec![2.0, 1.0, 3.0];
+        parameters.shift = vec![0.0, 1.0, -1.0];
+        res.batch_norm(&mut normalized, &parameters).unwrap();
+        assert_eq!(
+            normalized.get_data(),
+            vec![vec![4.5, 5.0], vec![1.0, 3.0], vec![-1.0, -1.0]]
+        );
+    }
+
+    #[test]
+    fn fft_round_trip() {
+        let size = 64;
+        let signal = Matrix1d::<f64>::from_random_seed(42, 1, size, -10.0, 10.0);
+
+        let mut transformed = Matrix1d::new(signal.get_data(), 1, size);
+        transformed.fft(size >> 1).unwrap();
+        transformed.ifft(size >> 1).unwrap();
+        for (a, b) in transformed.data.iter().zip(&signal.data) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        // the real transform is the first half of the complex transform of the same signal
+        let mut complex = Matrix1d::zeroes(1, 2 * size);
+        for (i, el) in signal.data.iter().enumerate() {
+            complex.data[2 * i] = *el;
+        }
+        complex.fft(size).unwrap();
+        let mut spectrum = Matrix1d::zeroes(1, size + 2);
+        signal.rfft(&mut spectrum).unwrap();
+        for (a, b) in spectrum.data.iter().zip(&complex.data) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        let mut inverse = Matrix1d::zeroes(1, size);
+        spectrum.irfft(&mut inverse).unwrap();
+        for (a, b) in inverse.data.iter().zip(&signal.data) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        let signal = Matrix1d::<f32>::from_random_seed(42, 1, size, -10.0, 10.0);
+        let mut spectrum = Matrix1d::zeroes(1, size + 2);
+        let mut inverse = Matrix1d::zeroes(1, size);
+        signal.rfft(&mut spectrum).unwrap();
+        spectrum.irfft(&mut inverse).unwrap();
+        for (a, b) in inverse.data.iter().zip(&signal.data) {
+            assert!(abs(a - b) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn mixed_radix_fft() {
+        // 7, 14 and 97 have prime factors other than 2, 3 and 5 and use Bluestein's algorithm
+        for n in [1, 2, 3, 5, 12, 15, 16, 60, 1000, 7, 14, 97] {
+            let signal = Matrix1d::<f64>::from_random_seed(n as u64, 1, 2 * n, -10.0, 10.0);
+            let plan = FftPlan::new(n).unwrap();
+
+            let mut transformed = Matrix1d::new(signal.get_data(), 1, 2 * n);
+            transformed.mixed_radix_fft(&plan).unwrap();
+            for k in 0..n {
+                let (mut re, mut im) = (0.0, 0.0);
+                for j in 0..n {
+                    let angle = -2.0 * std::f64::consts::PI * ((j * k) % n) as f64 / n as f64;
+                    let (x_re, x_im) = (signal.data[2 * j], signal.data[2 * j + 1]);
+                    re += x_re * angle.cos() - x_im * angle.sin();
+                    im += x_re * angle.sin() + x_im * angle.cos();
+                }
+                assert!((transformed.data[2 * k] - re).abs() < 1e-8, "n = {}", n);
+                assert!((transformed.data[2 * k + 1] - im).abs() < 1e-8, "n = {}", n);
+            }
+
+            transformed.mixed_radix_ifft(&plan).unwrap();
+            for (a, b) in transformed.data.iter().zip(&signal.data) {
+                assert!((a - b).abs() < 1e-10, "n = {}", n);
+            }
+        }
+
+        // the radix-2 transform rejects sizes that are not a power of 2
+        let mut signal = Matrix1d::<f64>::zeroes(1, 24);
+        assert!(signal.fft(12).is_err());
+        assert!(FftPlan::<f64>::new(0).is_err());
+    }
+
+    #[test]
+    fn short_time_fft() {
+        let hann = WindowFunction::<f64>::Hann.coefficients(8);
+        assert!(hann[0].abs() < 1e-12 && (hann[4] - 1.0).abs() < 1e-12);
+        assert!((hann[1] - hann[7]).abs() < 1e-12);
+        let kaiser = WindowFunction::<f64>::Kaiser(8.6).coefficients(8);
+        assert!((kaiser[4] - 1.0).abs() < 1e-12 && kaiser[0] < kaiser[1]);
+
+        // 20 complex samples, frames of 8 with hop 4 give 4 frames
+        let signal = Matrix1d::<f64>::from_random_seed(1, 1, 40, -10.0, 10.0);
+        let mut parameters = StftParameters {
+            window: 8,
+            hop: 4,
+            function: WindowFunction::Hamming,
+            output: SpectrumOutput::Complex,
+        };
+        let mut spectrum = Matrix1d::zeroes(4, 16);
+        signal.stft(&mut spectrum, &parameters).unwrap();
+
+        let coefficients = parameters.function.coefficients(8);
+        let mut frame = Matrix1d::zeroes(1, 16);
+        for (j, el) in frame.data.iter_mut().enumerate() {
+            *el = signal.data[2 * 4 + j] * coefficients[j / 2];
+        }
+        frame.fft(8).unwrap();
+        assert_eq!(frame.data, spectrum.data[16..32]);
+
+        parameters.output = SpectrumOutput::Power;
+        let mut power = Matrix1d::zeroes(4, 8);
+        signal.stft(&mut power, &parameters).unwrap();
+        for (p, el) in power.data.iter().zip(spectrum.data.chunks_exact(2)) {
+            assert!((p - (el[0] * el[0] + el[1] * el[1])).abs() < 1e-9);
+        }
+
+        parameters.hop = 0;
+        assert!(signal.stft(&mut power, &parameters).is_err());
+    }
+
+    #[test]
+    fn fft_2d() {
+        use crate::matrix_2d::Matrix2d;
+
+        // 4 rows of 8 complex numbers
+        let (rows, cols) = (4, 16);
+        let image = Matrix1d::<f64>::from_random_seed(3, rows, cols, -10.0, 10.0);
+        let mut result = Matrix1d::zeroes(rows, cols);
+        image.fft_2d(&mut result).unwrap();
+
+        // the 1d transform of the rows and then of the columns
+        let mut expected = Matrix1d::zeroes(rows, cols);
+        for (i, row) in image.data.chunks(cols).enumerate() {
+            let mut row = Matrix1d::new(vec![row.to_vec()], 1, cols);
+            row.fft(cols / 2).unwrap();
+            expected.data[i * cols..(i + 1) * cols].copy_from_slice(&row.data);
+        }
+        for j in 0..cols / 2 {
+            let column = (0..rows)
+                .flat_map(|i| expected.data[i * cols + 2 * j..i * cols + 2 * j + 2].to_vec())
+                .collect();
+            let mut column = Matrix1d::new(vec![column], 1, 2 * rows);
+            column.fft(rows).unwrap();
+            for i in 0..rows {
+                expected.data[i * cols + 2 * j..i * cols + 2 * j + 2]
+                    .copy_from_slice(&column.data[2 * i..2 * i + 2]);
+            }
+        }
+        for (a, b) in result.data.iter().zip(&expected.data) {
+            assert!((a - b).abs() < 1e-10);
+        }
+
+        let mut result_2d = Matrix2d::zeroes(rows, cols);
+        Matrix2d::new(image.get_data(), rows, cols)
+            .fft_2d(&mut result_2d)
+            .unwrap();
+        assert_eq!(result_2d.get_data(), result.get_data());
+
+        // the rows and the complex columns must be powers of 2
+        let image = Matrix1d::<f32>::from_random_seed(3, 3, cols, -10.0, 10.0);
+        assert!(matches!(
+            image.fft_2d(&mut Matrix1d::zeroes(3, cols)),
+            Err(Error::InvalidDimensions)
+        ));
+        let image = Matrix2d::<f32>::from_random_seed(3, rows, 12, -10.0, 10.0);
+        assert!(matches!(
+            image.fft_2d(&mut Matrix2d::zeroes(rows, 12)),
+            Err(Error::InvalidDimensions)
+        ));
+    }
+
+    #[test]
+    fn parallel_fft() {
+        use crate::parallel_traits::ParallelFastFourierTransform;
+        use crate::rayon_traits::RayonFastFourierTransform;
+
+        for nn in [2, 16, 1024] {
+            let signal = Matrix1d::<f32>::from_random_seed(nn as u64, 1, 2 * nn, -10.0, 10.0);
+            let mut expected = Matrix1d::new(signal.get_data(), 1, 2 * nn);
+            expected.fft(nn).unwrap();
+
+            // the threads split the butterflies, each one computes the same operations
+            for n_threads in [1, 2, 3, 4, 7, 8] {
+                let mut transformed = Matrix1d::new(signal.get_data(), 1, 2 * nn);
+                transformed.parallel_fft(nn, n_threads).unwrap();
+                assert_eq!(transformed.data, expected.data, "{} threads", n_threads);
+            }
+            let mut transformed = Matrix1d::new(signal.get_data(), 1, 2 * nn);
+            transformed.rayon_fft(nn).unwrap();
+            assert_eq!(transformed.data, expected.data);
+        }
+    }
+
+    #[test]
+    fn inverse_wavelet_transform() {
+        for size in [3, 4, 17, 64, 1000] {
+            let signal = Matrix1d::<i32>::from_random_seed(size as u64, 1, 2 * size, -32768, 32767);
+            let mut transformed = Matrix1d::zeroes(1, 2 * size);
+            signal.wavelet_transform(&mut transformed, size).unwrap();
+            let mut reconstructed = Matrix1d::zeroes(1, 2 * size);
+            transformed
+                .inverse_wavelet_transform(&mut reconstructed, size)
+                .unwrap();
+            assert_eq!(signal.data, reconstructed.data, "size = {}", size);
+        }
+
+        let low_pass = [
+            0.037828455507,
+            -0.023849465020,
+            -0.110624404418,
+            0.377402855613,
+            0.852698679009,
+            0.377402855613,
+            -0.110624404418,
+            -0.023849465020,
+            0.037828455507,
+        ];
+        let high_pass = [
+            -0.064538882629,
+            0.040689417609,
+            0.418092273222,
+            -0.788485616406,
+            0.418092273222,
+            0.040689417609,
+            -0.064538882629,
+        ];
+        // the synthesis filters are the analysis ones modulated by (-1)^(n + 1)
+        let synthesis_low_pass: Vec<f64> = high_pass
+            .iter()
+            .enumerate()
+            .map(|(n, el)| if n % 2 == 1 { -el } else { *el })
+            .collect();
+        let synthesis_high_pass: Vec<f64> = low_pass
+            .iter()
+            .enumerate()
+            .map(|(n, el)| if n % 2 == 1 { *el } else { -el })
+            .collect();
+
+        for size in [4, 5, 64, 1000] {
+            let signal = Matrix1d::<f64>::from_random_seed(size as u64, 1, 2 * size, -10.0, 10.0);
+            let mut transformed = Matrix1d::zeroes(1, 2 * size);
+            signal
+                .wavelet_transform(&mut transformed, size, &low_pass, 9, &high_pass, 7)
+                .unwrap();
+            let mut reconstructed = Matrix1d::zeroes(1, 2 * size);
+            transformed
+                .inverse_wavelet_transform(
+                    &mut reconstructed,
+                    size,
+                    &synthesis_low_pass,
+                    &synthesis_high_pass,
+                )
+                .unwrap();
+            // the error comes from the 12 digits of the filter coefficients
+            for (a, b) in reconstructed.data.iter().zip(&signal.data) {
+                assert!((a - b).abs() < 1e-9, "size = {}", size);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_wavelet_transform() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::{
+            ParallelWaveletTransformFloating, ParallelWaveletTransformInteger,
+        };
+        use crate::rayon_traits::{RayonWaveletTransformFloating, RayonWaveletTransformInteger};
+
+        let low_pass = [
+            0.037828455507,
+            -0.023849465020,
+            -0.110624404418,
+            0.377402855613,
+            0.852698679009,
+            0.377402855613,
+            -0.110624404418,
+            -0.023849465020,
+            0.037828455507,
+        ];
+        let high_pass = [
+            -0.064538882629,
+            0.040689417609,
+            0.418092273222,
+            -0.788485616406,
+            0.418092273222,
+            0.040689417609,
+            -0.064538882629,
+        ];
+
+        for size in [4, 17, 1000] {
+            let signal = Matrix1d::<i32>::from_random_seed(size as u64, 1, 2 * size, -32768, 32767);
+            let mut expected = Matrix1d::zeroes(1, 2 * size);
+            signal.wavelet_transform(&mut expected, size).unwrap();
+            let signal_2d = Matrix2d::new(signal.get_data(), 1, 2 * size);
+            for n_threads in [1, 3, 5, 7] {
+                let mut result = Matrix1d::zeroes(1, 2 * size);
+                signal
+                    .parallel_wavelet_transform(&mut result, size, n_threads)
+                    .unwrap();
+                assert_eq!(result.data, expected.data, "{} threads", n_threads);
+                let mut result = Matrix2d::zeroes(1, 2 * size);
+                signal_2d
+                    .parallel_wavelet_transform(&mut result, size, n_threads)
+                    .unwrap();
+                assert_eq!(result.get_data(), expected.get_data());
+            }
+            let mut result = Matrix1d::zeroes(1, 2 * size);
+            signal.rayon_wavelet_transform(&mut result, size).unwrap();
+            assert_eq!(result.data, expected.data);
+            let mut result = Matrix2d::zeroes(1, 2 * size);
+            signal_2d
+                .rayon_wavelet_transform(&mut result, size)
+                .unwrap();
+            assert_eq!(result.get_data(), expected.get_data());
+
+            let signal = Matrix1d::<f64>::from_random_seed(size as u64, 1, 2 * size, -10.0, 10.0);
+            let mut expected = Matrix1d::zeroes(1, 2 * size);
+            signal
+                .wavelet_transform(&mut expected, size, &low_pass, 9, &high_pass, 7)
+                .unwrap();
+            let signal_2d = Matrix2d::new(signal.get_data(), 1, 2 * size);
+            for n_threads in [1, 3, 5, 7] {
+                let mut result = Matrix1d::zeroes(1, 2 * size);
+                signal
+                    .parallel_wavelet_transform(&mut result, size, &low_pass, &high_pass, n_threads)
+                    .unwrap();
+                assert_eq!(result.data, expected.data, "{} threads", n_threads);
+                let mut result = Matrix2d::zeroes(1, 2 * size);
+                signal_2d
+                    .parallel_wavelet_transform(&mut result, size, &low_pass, &high_pass, n_threads)
+                    .unwrap();
+                assert_eq!(result.get_data(), expected.get_data());
+            }
+            let mut result = Matrix1d::zeroes(1, 2 * size);
+            signal
+                .rayon_wavelet_transform(&mut result, size, &low_pass, &high_pass)
+                .unwrap();
+            assert_eq!(result.data, expected.data);
+            let mut result = Matrix2d::zeroes(1, 2 * size);
+            signal_2d
+                .rayon_wavelet_transform(&mut result, size, &low_pass, &high_pass)
+                .unwrap();
+            assert_eq!(result.get_data(), expected.get_data());
+        }
+    }
+
+    #[test]
+    fn parallel_inverse_wavelet_transform() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::{
+            ParallelInverseWaveletTransformFloating, ParallelInverseWaveletTransformInteger,
+        };
+        use crate::rayon_traits::{
+            RayonInverseWaveletTransformFloating, RayonInverseWaveletTransformInteger,
+        };
+
+        // the synthesis filters of the benchmark, only their lengths matter for the comparison
+        let low_pass = [
+            -0.064538882629,
+            -0.040689417609,
+            0.418092273222,
+            0.788485616406,
+            0.418092273222,
+            -0.040689417609,
+            -0.064538882629,
+        ];
+        let high_pass = [
+            -0.037828455507,
+            -0.023849465020,
+            0.110624404418,
+            0.377402855613,
+            -0.852698679009,
+            0.377402855613,
+            0.110624404418,
+            -0.023849465020,
+            -0.037828455507,
+        ];
+
+        for size in [4, 17, 1000] {
+            let coefficients =
+                Matrix1d::<i32>::from_random_seed(size as u64, 1, 2 * size, -32768, 32767);
+            let mut expected = Matrix1d::zeroes(1, 2 * size);
+            coefficients
+                .inverse_wavelet_transform(&mut expected, size)
+                .unwrap();
+            let coefficients_2d = Matrix2d::new(coefficients.get_data(), 1, 2 * size);
+            for n_threads in [1, 3, 5, 7] {
+                let mut result = Matrix1d::zeroes(1, 2 * size);
+                coefficients
+                    .parallel_inverse_wavelet_transform(&mut result, size, n_threads)
+                    .unwrap();
+                assert_eq!(result.data, expected.data, "{} threads", n_threads);
+                let mut result = Matrix2d::zeroes(1, 2 * size);
+                coefficients_2d
+                    .parallel_inverse_wavelet_transform(&mut result, size, n_threads)
+                    .unwrap();
+                assert_eq!(result.get_data(), expected.get_data());
+            }
+            let mut result = Matrix1d::zeroes(1, 2 * size);
+            coefficients
+                .rayon_inverse_wavelet_transform(&mut result, size)
+                .unwrap();
+            assert_eq!(result.data, expected.data);
+            let mut result = Matrix2d::zeroes(1, 2 * size);
+            coefficients_2d
+                .rayon_inverse_wavelet_transform(&mut result, size)
+                .unwrap();
+            assert_eq!(result.get_data(), expected.get_data());
+
+            let coefficients =
+                Matrix1d::<f64>::from_random_seed(size as u64, 1, 2 * size, -10.0, 10.0);
+            let mut expected = Matrix1d::zeroes(1, 2 * size);
+            coefficients
+                .inverse_wavelet_transform(&mut expected, size, &low_pass, &high_pass)
+                .unwrap();
+            let coefficients_2d = Matrix2d::new(coefficients.get_data(), 1, 2 * size);
+            for n_threads in [1, 3, 5, 7] {
+                let mut result = Matrix1d::zeroes(1, 2 * size);
+                coefficients
+                    .parallel_inverse_wavelet_transform(
+                        &mut result,
+                        size,
+                        &low_pass,
+                        &high_pass,
+                        n_threads,
+                    )
+                    .unwrap();
+                assert_eq!(result.data, expected.data, "{} threads", n_threads);
+                let mut result = Matrix2d::zeroes(1, 2 * size);
+                coefficients_2d
+                    .parallel_inverse_wavelet_transform(
+                        &mut result,
+                        size,
+                        &low_pass,
+                        &high_pass,
+                        n_threads,
+                    )
+                    .unwrap();
+                assert_eq!(result.get_data(), expected.get_data());
+            }
+            let mut result = Matrix1d::zeroes(1, 2 * size);
+            coefficients
+                .rayon_inverse_wavelet_transform(&mut result, size, &low_pass, &high_pass)
+                .unwrap();
+            assert_eq!(result.data, expected.data);
+            let mut result = Matrix2d::zeroes(1, 2 * size);
+            coefficients_2d
+                .rayon_inverse_wavelet_transform(&mut result, size, &low_pass, &high_pass)
+                .unwrap();
+            assert_eq!(result.get_data(), expected.get_data());
+        }
+    }
+
+    #[test]
+    fn wavelet_transform_2d() {
+        let (rows, cols) = (48, 32);
+        let image = Matrix1d::<i32>::from_random_seed(2, rows, cols, 0, 4096);
+        let mut result = Matrix1d::zeroes(rows, cols);
+        image.wavelet_transform_2d(&mut result, 3).unwrap();
+
+        // the first level is the 1d transform of the rows and then of the columns
+        let mut expected = Matrix1d::zeroes(rows, cols);
+        let mut level = Matrix1d::zeroes(rows, cols);
+        image.wavelet_transform_2d(&mut level, 1).unwrap();
+        for (i, row) in image.data.chunks(cols).enumerate() {
+            let row = Matrix1d::new(vec![row.to_vec()], 1, cols);
+            let mut transformed = Matrix1d::zeroes(1, cols);
+            row.wavelet_transform(&mut transformed, cols / 2).unwrap();
+            expected.data[i * cols..(i + 1) * cols].copy_from_slice(&transformed.data);
+        }
+        for j in 0..cols {
+            let column: Vec<i32> = (0..rows).map(|i| expected.data[i * cols + j]).collect();
+            let column = Matrix1d::new(vec![column], 1, rows);
+            let mut transformed = Matrix1d::zeroes(1, rows);
+            column
+                .wavelet_transform(&mut transformed, rows / 2)
+                .unwrap();
+            for i in 0..rows {
+                expected.data[i * cols + j] = transformed.data[i];
+            }
+        }
+        assert_eq!(level.data, expected.data);
+
+        // the next levels only change the LL subband of the first one
+        let ll = Subband::LL.region(rows, cols, 1);
+        for i in 0..rows {
+            for j in 0..cols {
+                if i >= ll.rows || j >= ll.cols {
+                    assert_eq!(result.data[i * cols + j], level.data[i * cols + j]);
+                }
+            }
+        }
+
+        assert_eq!(
+            Subband::HL.region(rows, cols, 2),
+            SubbandRegion {
+                row_start: 0,
+                col_start: 8,
+                rows: 12,
+                cols: 8
+            }
+        );
+        assert_eq!(Subband::layout(3).len(), 10);
+        assert_eq!(Subband::layout(3)[1], (3, Subband::HL));
+
+        // 32 >> 4 leaves only 2 low pass coefficients in the last level
+        assert!(image.wavelet_transform_2d(&mut result, 4).is_err());
+    }
+
+    #[test]
+    fn image_calibration() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::ParallelImageCalibration;
+        use crate::rayon_traits::RayonImageCalibration;
+
+        let frame = Matrix1d::new(vec![vec![10, 12, 14, 16], vec![18, 20, 22, 90]], 2, 4);
+        let offsets = Matrix1d::new(vec![vec![2; 4]; 2], 2, 4);
+        let mut bad_pixels = Matrix1d::zeroes(2, 4);
+        bad_pixels.data[1] = 1;
+        let mut corrected = Matrix1d::zeroes(2, 4);
+        let mut result = Matrix1d::zeroes(2, 4);
+
+        frame.offset_correction(&offsets, &mut corrected).unwrap();
+        corrected
+            .bad_pixel_correction(&bad_pixels, &mut result)
+            .unwrap();
+        // (8 + 12 + 16 + 18 + 20) / 5
+        assert_eq!(
+            result.get_data(),
+            vec![vec![8, 14, 12, 14], vec![16, 18, 20, 88]]
+        );
+
+        let neighbours = [
+            Matrix1d::new(vec![vec![8, 14, 12, 14], vec![16, 18, 20, 22]], 2, 4),
+            Matrix1d::new(vec![vec![8, 14, 12, 14], vec![16, 18, 22, 20]], 2, 4),
+        ];
+        let neighbours: Vec<_> = neighbours.iter().collect();
+        let mut scrubbed = Matrix1d::zeroes(2, 4);
+        result
+            .radiation_scrubbing(&neighbours, 10, &mut scrubbed)
+            .unwrap();
+        assert_eq!(
+            scrubbed.get_data(),
+            vec![vec![8, 14, 12, 14], vec![16, 18, 20, 21]]
+        );
+
+        let gains = Matrix1d::new(vec![vec![1, 2, 1, 2], vec![2, 1, 2, 1]], 2, 4);
+        scrubbed.gain_correction(&gains, &mut result).unwrap();
+        let mut binned = Matrix1d::zeroes(1, 2);
+        result.spatial_binning(2, &mut binned).unwrap();
+        assert_eq!(
+            binned.get_data(),
+            vec![vec![8 + 28 + 32 + 18, 12 + 28 + 40 + 21]]
+        );
+        let mut accumulator = Matrix1d::new(vec![vec![1, 2]], 1, 2);
+        binned.co_add(&mut accumulator).unwrap();
+        assert_eq!(accumulator.get_data(), vec![vec![87, 103]]);
+        assert!(result.spatial_binning(3, &mut binned).is_err());
+        assert!(result.co_add(&mut binned).is_err());
+
+        // the strategies and the matrix types give the same result
+        let frame = Matrix1d::from_random_seed(1, 16, 12, 0.0f32, 100.0);
+        let neighbour = Matrix1d::from_random_seed(2, 16, 12, 0.0f32, 100.0);
+        let bad_pixels = Matrix1d::from_random_seed(3, 16, 12, 0.0f32, 1.2);
+        let bad_pixels = Matrix1d::new(
+            bad_pixels
+                .get_data()
+                .into_iter()
+                .map(|row| row.into_iter().map(|value| value.floor()).collect())
+                .collect(),
+            16,
+            12,
+        );
+        let mut expected = Matrix1d::zeroes(16, 12);
+        let mut result = Matrix1d::zeroes(16, 12);
+        frame
+            .bad_pixel_correction(&bad_pixels, &mut expected)
+            .unwrap();
+        frame
+            .parallel_bad_pixel_correction(&bad_pixels, &mut result, 3)
+            .unwrap();
+        assert_eq!(result.get_data(), expected.get_data());
+        frame
+            .rayon_bad_pixel_correction(&bad_pixels, &mut result)
+            .unwrap();
+        assert_eq!(result.get_data(), expected.get_data());
+        let frame_2d = Matrix2d::new(frame.get_data(), 16, 12);
+        let mut result_2d = Matrix2d::zeroes(16, 12);
+        frame_2d
+            .bad_pixel_correction(
+                &Matrix2d::new(bad_pixels.get_data(), 16, 12),
+                &mut result_2d,
+            )
+            .unwrap();
+        assert_eq!(result_2d.get_data(), expected.get_data());
+
+        frame
+            .radiation_scrubbing(&[&neighbour], 20.0, &mut expected)
+            .unwrap();
+        frame
+            .parallel_radiation_scrubbing(&[&neighbour], 20.0, &mut result, 5)
+            .unwrap();
+        assert_eq!(result.get_data(), expected.get_data());
+        let mut binned = Matrix1d::zeroes(4, 3);
+        let mut expected_binned = Matrix1d::zeroes(4, 3);
+        frame.spatial_binning(4, &mut expected_binned).unwrap();
+        frame.rayon_spatial_binning(4, &mut binned).unwrap();
+        assert_eq!(binned.get_data(), expected_binned.get_data());
+    }
+
+    #[test]
+    fn radar_processing() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::ParallelRadarProcessing;
+        use crate::rayon_traits::RayonRadarProcessing;
+
+        let (rows, cols) = (16, 32);
+        let range_chirp = Chirp {
+            samples: 8,
+            bandwidth: 0.8,
+        };
+        let azimuth_chirp = Chirp {
+            samples: 4,
+            bandwidth: 0.5,
+        };
+        // a point target at azimuth 5 and range 20
+        let (range, azimuth) = (range_chirp.signal(), azimuth_chirp.signal());
+        let mut raw = vec![vec![0.0; cols << 1]; rows];
+        for (a, az) in azimuth.chunks_exact(2).enumerate() {
+            for (r, rg) in range.chunks_exact(2).enumerate() {
+                raw[5 + a][2 * (20 + r)] = az[0] * rg[0] - az[1] * rg[1];
+                raw[5 + a][2 * (20 + r) + 1] = az[0] * rg[1] + az[1] * rg[0];
+            }
+        }
+        let raw = Matrix1d::new(raw, rows, cols << 1);
+
+        let range_filter = range_chirp.matched_filter(cols).unwrap();
+        let azimuth_filter = azimuth_chirp.matched_filter(rows).unwrap();
+        let mut range_compressed = Matrix1d::zeroes(rows, cols << 1);
+        raw.compression(&range_filter, &mut range_compressed)
+            .unwrap();
+        let mut turned = Matrix1d::zeroes(cols, rows << 1);
+        range_compressed.corner_turn(&mut turned).unwrap();
+        let mut image = Matrix1d::zeroes(cols, rows << 1);
+        turned.compression(&azimuth_filter, &mut image).unwrap();
+
+        // the energy of the echo is focused in a pixel
+        let magnitude = |el: &[f64]| (el[0] * el[0] + el[1] * el[1]).sqrt();
+        let data = image.get_data();
+        assert!(abs(magnitude(&data[20][10..12]) - 32.0) < 1e-9);
+        assert!(data[20]
+            .chunks_exact(2)
+            .enumerate()
+            .all(|(a, el)| a == 5 || magnitude(el) < 24.0));
+
+        let mut result = Matrix1d::zeroes(rows, cols << 1);
+        raw.parallel_compression(&range_filter, &mut result, 3)
+            .unwrap();
+        assert_eq!(result.get_data(), range_compressed.get_data());
+        let mut result = Matrix1d::zeroes(cols, rows << 1);
+        range_compressed
+            .parallel_corner_turn(&mut result, 3)
+            .unwrap();
+        assert_eq!(result.get_data(), turned.get_data());
+        turned
+            .rayon_compression(&azimuth_filter, &mut result)
+            .unwrap();
+        assert_eq!(result.get_data(), data);
+        let turned_2d = Matrix2d::new(turned.get_data(), cols, rows << 1);
+        let mut image_2d = Matrix2d::zeroes(cols, rows << 1);
+        turned_2d
+            .compression(&azimuth_filter, &mut image_2d)
+            .unwrap();
+        assert_eq!(image_2d.get_data(), data);
+
+        assert!(turned.corner_turn(&mut image).is_err());
+        assert!(raw.compression(&azimuth_filter, &mut image).is_err());
+        assert!(range_chirp.matched_filter(6).is_err());
+        assert!(range_chirp.matched_filter(4).is_err());
+    }
+
+    #[test]
+    fn abft() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::ParallelAbftMatMul;
+        use crate::rayon_traits::RayonAbftMatMul;
+
+        let a = Matrix1d::<i32>::from_random_seed(3, 5, 4, -10, 10);
+        let b = Matrix1d::<i32>::from_random_seed(4, 4, 3, -10, 10);
+        let mut golden = Matrix1d::zeroes(5, 3);
+        a.multiply(&b, &mut golden).unwrap();
+
+        let mut result = Matrix1d::zeroes(5, 3);
+        let report = a.abft_multiply(&b, 0.0, &mut result).unwrap();
+        assert_eq!(report, AbftReport::default());
+        assert_eq!(result.get_data(), golden.get_data());
+        a.parallel_abft_multiply(&b, 0.0, &mut result, 2).unwrap();
+        assert_eq!(result.get_data(), golden.get_data());
+        a.rayon_abft_multiply(&b, 0.0, &mut result).unwrap();
+        assert_eq!(result.get_data(), golden.get_data());
+
+        let mut product = Matrix1d::zeroes(6, 4);
+        a.column_checksums()
+            .multiply(&b.row_checksums(), &mut product)
+            .unwrap();
+        let checksummed = product.get_data();
+        let faulty = |faults: &[(usize, usize)]| {
+            let mut product = Matrix1d::new(checksummed.clone(), 6, 4);
+            for &(row, col) in faults {
+                product.modify_bits(row, col, |bits| bits ^ 1 << 20);
+            }
+            product
+        };
+        // a single error is corrected, also in the checksums
+        for fault in [(2, 1), (0, 3), (5, 0), (5, 3)] {
+            let mut result = Matrix1d::zeroes(5, 3);
+            let report = faulty(&[fault]).abft_verify(0.0, &mut result).unwrap();
+            assert_eq!(
+                report,
+                AbftReport {
+                    detected: 1,
+                    corrected: 1
+                }
+            );
+            assert_eq!(result.get_data(), golden.get_data());
+        }
+        // two errors are only detected
+        let mut result = Matrix1d::zeroes(5, 3);
+        let report = faulty(&[(1, 1), (3, 2)])
+            .abft_verify(0.0, &mut result)
+            .unwrap();
+        assert_eq!(
+            report,
+            AbftReport {
+                detected: 2,
+                corrected: 0
+            }
+        );
+
+        // rounding errors are within the tolerance
+        let a = Matrix2d::<f32>::from_random_seed(5, 16, 16, -10.0, 10.0);
+        let b = Matrix2d::<f32>::from_random_seed(6, 16, 16, -10.0, 10.0);
+        let mut result = Matrix2d::zeroes(16, 16);
+        let report = a.abft_multiply(&b, 1e-6, &mut result).unwrap();
+        assert_eq!(report, AbftReport::default());
+        assert!(a
+            .abft_multiply(&b, 0.0, &mut Matrix2d::zeroes(15, 16))
+            .is_err());
+    }
+
+    #[test]
+    fn polyphase_fir() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::ParallelMultirateFirFilter;
+        use crate::rayon_traits::RayonMultirateFirFilter;
+
+        let a = Matrix1d::<i32>::from_random_seed(9, 1, 11, -10, 10);
+        let kernel = Matrix1d::<i32>::from_random_seed(10, 1, 7, -10, 10);
+        let mut full = Matrix1d::zeroes(1, 17);
+        a.fir_filter(&kernel, &mut full).unwrap();
+        let full = full.get_data().swap_remove(0);
+
+        for factor in [1, 2, 3, 8] {
+            let filter = PolyphaseFilter::new(&kernel.data, factor).unwrap();
+            // the decimated output is one sample every `factor` of the full convolution
+            let decimated: Vec<i32> = full.iter().copied().step_by(factor).collect();
+            let mut result = Matrix1d::zeroes(1, filter.decimated_len(11).unwrap());
+            a.fir_decimate(&filter, &mut result).unwrap();
+            assert_eq!(result.data, decimated);
+            a.parallel_fir_decimate(&filter, &mut result, 3).unwrap();
+            assert_eq!(result.data, decimated);
+            a.rayon_fir_decimate(&filter, &mut result).unwrap();
+            assert_eq!(result.data, decimated);
+
+            // the interpolated output is the convolution of the zero-stuffed input
+            let mut upsampled = Matrix1d::zeroes(1, 10 * factor + 1);
+            for (i, el) in a.data.iter().enumerate() {
+                upsampled.data[i * factor] = *el;
+            }
+            let mut interpolated = Matrix1d::zeroes(1, 10 * factor + 7);
+            upsampled.fir_filter(&kernel, &mut interpolated).unwrap();
+            let mut result = Matrix1d::zeroes(1, filter.interpolated_len(11).unwrap());
+            a.fir_interpolate(&filter, &mut result).unwrap();
+            assert_eq!(result.data, interpolated.data);
+            a.parallel_fir_interpolate(&filter, &mut result, 4).unwrap();
+            assert_eq!(result.data, interpolated.data);
+            let a_2d = Matrix2d::new(a.get_data(), 1, 11);
+            let mut result_2d = Matrix2d::zeroes(1, filter.interpolated_len(11).unwrap());
+            a_2d.rayon_fir_interpolate(&filter, &mut result_2d).unwrap();
+            assert_eq!(result_2d.get_data()[0], interpolated.data);
+        }
+
+        assert!(PolyphaseFilter::new(&kernel.data, 0).is_err());
+        let filter = PolyphaseFilter::new(&kernel.data, 2).unwrap();
+        assert_eq!(
+            filter.phases(),
+            [
+                vec![
+                    kernel.data[0],
+                    kernel.data[2],
+                    kernel.data[4],
+                    kernel.data[6]
+                ],
+                vec![kernel.data[1], kernel.data[3], kernel.data[5], 0]
+            ]
+        );
+        assert!(a
+            .fir_decimate(&filter, &mut Matrix1d::zeroes(1, 17))
+            .is_err());
+
+        // an empty input has no output length, also with a single tap
+        let single_tap = PolyphaseFilter::new(&[1], 2).unwrap();
+        assert!(single_tap.decimated_len(0).is_err());
+        assert!(single_tap.interpolated_len(0).is_err());
+        assert!(filter.interpolated_len(0).is_err());
+        let empty = Matrix1d::<i32>::zeroes(1, 0);
+        assert!(empty
+            .fir_interpolate(&filter, &mut Matrix1d::zeroes(1, 7))
+            .is_err());
+        assert!(empty
+            .parallel_fir_decimate(&single_tap, &mut Matrix1d::zeroes(1, 0), 2)
+            .is_err());
+    }
+
+    #[test]
+    fn iir_filter() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::number_traits::FixedPoint;
+        use crate::parallel_traits::ParallelIirFilter;
+        use crate::rayon_traits::RayonIirFilter;
+        use crate::{Biquad, BiquadForm};
+
+        // a single pole at 0.5: the impulse response is 0.5^n, in both forms
+        let mut impulse = Matrix1d::<f64>::zeroes(2, 8);
+        impulse.data[0] = 1.0;
+        impulse.data[12] = 2.0;
+        for form in [BiquadForm::DirectFormI, BiquadForm::TransposedDirectFormII] {
+            let cascade = BiquadCascade {
+                sections: vec![Biquad {
+                    b: [1.0, 0.0, 0.0],
+                    a: [-0.5, 0.0],
+                }],
+                form,
+                fraction_bits: 0,
+            };
+            let mut result = Matrix1d::zeroes(2, 8);
+            impulse.iir_filter(&cascade, &mut result).unwrap();
+            let response: Vec<f64> = (0..8).map(|n| 0.5f64.powi(n)).collect();
+            assert_eq!(result.data[..8], response);
+            assert_eq!(result.data[12..], [2.0, 1.0, 0.5, 0.25]);
+        }
+
+        let a = Matrix1d::<f32>::from_random_seed(11, 5, 64, -100.0, 100.0);
+        let filter = |form| {
+            let cascade = BiquadCascade::butterworth_low_pass(3, 0.15, form, 0).unwrap();
+            let mut result = Matrix1d::zeroes(5, 64);
+            a.iir_filter(&cascade, &mut result).unwrap();
+            let mut parallel = Matrix1d::zeroes(5, 64);
+            a.parallel_iir_filter(&cascade, &mut parallel, 2).unwrap();
+            assert_eq!(parallel.data, result.data);
+            let mut rayon = Matrix2d::zeroes(5, 64);
+            Matrix2d::new(a.get_data(), 5, 64)
+                .rayon_iir_filter(&cascade, &mut rayon)
+                .unwrap();
+            assert_eq!(rayon.get_data(), result.get_data());
+            result.data
+        };
+        let direct_form_1 = filter(BiquadForm::DirectFormI);
+        let transposed = filter(BiquadForm::TransposedDirectFormII);
+        for (x, y) in direct_form_1.iter().zip(&transposed) {
+            assert!((x - y).abs() < 1e-3);
+        }
+
+        // fixed point: the DC gain of the low pass filter is 1
+        let cascade =
+            BiquadCascade::<i32>::butterworth_low_pass(2, 0.1, BiquadForm::DirectFormI, 14)
+                .unwrap();
+        let step = Matrix1d::new(vec![vec![1000; 200]], 1, 200);
+        let mut result = Matrix1d::zeroes(1, 200);
+        step.iir_filter(&cascade, &mut result).unwrap();
+        assert!((result.data[199] - 1000).abs() <= 2);
+
+        // fixed point at a low cutoff, the poles are close to the unit circle: the states keep
+        // the guard bits, so the result is the double precision one of the rounded coefficients
+        let a = Matrix1d::<i32>::from_random_seed(12, 2, 1000, -1000, 1000);
+        for form in [BiquadForm::DirectFormI, BiquadForm::TransposedDirectFormII] {
+            let cascade = BiquadCascade::<i32>::butterworth_low_pass(4, 0.01, form, 14).unwrap();
+            let mut result = Matrix1d::zeroes(2, 1000);
+            a.iir_filter(&cascade, &mut result).unwrap();
+            let coefficient = |c: i32| c.fixed_to_f64(14);
+            for (channel, filtered) in a.data.chunks(1000).zip(result.data.chunks(1000)) {
+                let mut x: Vec<f64> = channel.iter().map(|el| *el as f64).collect();
+                for section in &cascade.sections {
+                    let ([b0, b1, b2], [a1, a2]) =
+                        (section.b.map(coefficient), section.a.map(coefficient));
+                    // the signals are zero before the first sample
+                    let (input, mut y) = ([vec![0.0; 2], x].concat(), vec![0.0; 1002]);
+                    for n in 2..1002 {
+                        y[n] = b0 * input[n] + b1 * input[n - 1] + b2 * input[n - 2]
+                            - a1 * y[n - 1]
+                            - a2 * y[n - 2];
+                    }
+                    x = y.split_off(2);
+                }
+                for (el, reference) in filtered.iter().zip(&x) {
+                    assert!((*el as f64 - reference).abs() <= 2.0);
+                }
+            }
+        }
+
+        // a pole on the unit circle
+        let mut unstable = cascade.clone();
+        unstable.sections[1].a[1] = 1 << 14;
+        assert_eq!(unstable.unstable_section(), Some(1));
+        assert!(matches!(
+            step.iir_filter(&unstable, &mut result),
+            Err(Error::UnstableFilter)
+        ));
+        assert!(step
+            .iir_filter(&cascade, &mut Matrix1d::zeroes(1, 199))
+            .is_err());
+        assert!(
+            BiquadCascade::<f32>::butterworth_low_pass(2, 0.5, BiquadForm::DirectFormI, 0).is_err()
+        );
+    }
+
+    #[test]
+    fn cross_correlation() {
+        use crate::matrix_2d::Matrix2d;
+        use crate::parallel_traits::{
+            ParallelCorrelation, ParallelCrossCorrelation, ParallelTemplateMatching,
+        };
+        use crate::rayon_traits::{RayonCrossCorrelation, RayonTemplateMatching};
+
+        // 1-D: b is a shifted by 2, the map has more than a segment
+        let a = Matrix1d::<i32>::from_random_seed(12, 1, 200, -10, 10);
+        let mut b = Matrix1d::<i32>::from_random_seed(13, 1, 200, -10, 10);
+        b.data[2..].copy_from_slice(&a.data[..198]);
+        let mut result = Matrix1d::zeroes(1, 81);
+        let peak = a.cross_correlation(&b, (0, 40), &mut result).unwrap();
+        assert_eq!((peak.row, peak.col), (0, 42));
+        assert_eq!(
+            peak.value,
+            a.data[..198].iter().map(|el| el * el).sum::<i32>()
+        );
+        let lag = |dx: isize| -> i32 {
+            (0..200)
+                .filter(|&j| (0..200).contains(&(j + dx)))
+                .map(|j| a.data[j as usize] * b.data[(j + dx) as usize])
+                .sum()
+        };
+        for (c, el) in result.data.iter().enumerate() {
+            assert_eq!(*el, lag(c as isize - 40));
+        }
+        let mut parallel = Matrix1d::zeroes(1, 81);
+        assert_eq!(
+            a.parallel_cross_correlation(&b, (0, 40), &mut parallel, 3)
+                .unwrap(),
+            peak
+        );
+        assert_eq!(parallel.data, result.data);
+
+        // 2-D: lag (-1, 2) of a matrix with itself shifted
+        let a = Matrix2d::<f64>::from_random_seed(14, 9, 11, -1.0, 1.0);
+        let mut shifted = vec![vec![0.0; 11]; 9];
+        for (i, row) in a.get_data().into_iter().enumerate().skip(1) {
+            shifted[i - 1][2..].copy_from_slice(&row[..9]);
+        }
+        let b = Matrix2d::new(shifted, 9, 11);
+        let mut result = Matrix2d::zeroes(5, 7);
+        let peak = a.rayon_cross_correlation(&b, (2, 3), &mut result).unwrap();
+        assert_eq!((peak.row, peak.col), (1, 5));
+        assert!(a
+            .cross_correlation(&b, (2, 3), &mut Matrix2d::zeroes(5, 6))
+            .is_err());
+
+        // the template is a patch of the image, a flat window is not correlated
+        let mut image = Matrix1d::<i32>::from_random_seed(15, 12, 70, -50, 50);
+        for el in &mut image.data[..5] {
+            *el = 7;
+        }
+        let template_data: Vec<Vec<i32>> = (0..4)
+            .map(|i| image.data[(6 + i) * 70 + 30..(6 + i) * 70 + 35].to_vec())
+            .collect();
+        let template = Matrix1d::new(template_data, 4, 5);
+        let mut map = Matrix1d::<f32>::zeroes(9, 66);
+        let peak = image.template_matching(&template, &mut map).unwrap();
+        assert_eq!((peak.row, peak.col), (6, 30));
+        assert!((peak.value - 1.0).abs() < 1e-5);
+        assert!(map.data.iter().all(|el| el.abs() <= 1.0 + 1e-5));
+        let flat = Matrix1d::new(vec![vec![7; 5]], 1, 5);
+        let mut flat_map = Matrix1d::<f32>::zeroes(12, 66);
+        flat.template_matching(&flat, &mut Matrix1d::zeroes(1, 1))
+            .unwrap();
+        image.template_matching(&flat, &mut flat_map).unwrap();
+        assert!(flat_map.data.iter().all(|el| *el == 0.0));
+        let mut parallel = Matrix1d::<f32>::zeroes(9, 66);
+        image
+            .parallel_template_matching(&template, &mut parallel, 4)
+            .unwrap();
+        assert_eq!(parallel.data, map.data);
+        image
+            .rayon_template_matching(&template, &mut parallel)
+            .unwrap();
+        assert_eq!(parallel.data, map.data);
+        assert!(template.template_matching(&image, &mut map).is_err());
+
+        // the Pearson coefficient of the std parallel strategy
+        let a = Matrix1d::<f32>::from_random_seed(16, 7, 9, -10.0, 10.0);
+        let b = Matrix1d::<f32>::from_random_seed(17, 7, 9, -10.0, 10.0);
+        let coefficient = a.correlation(&b).unwrap();
+        assert!((a.parallel_correlate(&b, 3).unwrap() - coefficient).abs() < 1e-5);
+    }
 }
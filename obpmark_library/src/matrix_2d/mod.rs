@@ -1,5 +1,6 @@
 use crate::{BaseMatrix, Error, Number};
 
+#[derive(Clone)]
 pub struct Matrix2d<T: Number> {
     data: Vec<Vec<T>>,
     rows: usize,
@@ -46,6 +47,17 @@ impl<T: Number> BaseMatrix<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Number> Matrix2d<T> {
+    /// The rows of the matrix as slices
+    pub(crate) fn row_slices(&self) -> Vec<&[T]> {
+        self.data.iter().map(|row| row.as_slice()).collect()
+    }
+
+    pub(crate) fn row_slices_mut(&mut self) -> Vec<&mut [T]> {
+        self.data.iter_mut().map(|row| row.as_mut_slice()).collect()
+    }
+}
+
 impl_display!(Matrix2d);
 
 mod parallel_implementations;
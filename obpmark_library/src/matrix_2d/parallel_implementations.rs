@@ -1,12 +1,28 @@
+use crate::calibration;
+use crate::ccsds_121;
+use crate::ccsds_122;
+use crate::cross_correlation;
+use crate::iir;
 use crate::matrix_2d::Matrix2d;
 use crate::number_traits::{Float, Number};
+use crate::rows;
+use crate::wavelet;
+use crate::BpeParameters;
 use crate::{parallel_traits::*, BaseMatrix, FirFilter};
-use crate::{Error, Padding};
+use crate::{
+    ActivationFunction, BatchNormParameters, Error, LRNParameters, Padding, PolyphaseFilter,
+    PoolingParameters, PoolingType, RiceParameters, SoftmaxAxis,
+};
+use crate::{BiquadCascade, CorrelationPeak};
 
 use std::sync::Arc;
 use std::thread;
 
-use crate::{Convolution, MatMul, MaxPooling, Relu, Softmax, LRN};
+use crate::{
+    AbftMatMul, AbftReport, Activation, BatchNorm, Convolution, Correlation, Dense,
+    FastFourierTransform2d, FastFourierTransformHelper, GeneralLRN, MatMul, MaxPooling, Pooling,
+    RadarProcessing, Relu, Softmax, StableSoftmax, WaveletTransformInteger, LRN,
+};
 
 impl<T: Number> ParallelMatMul for Matrix2d<T> {
     fn parallel_multiply(
@@ -45,6 +61,24 @@ impl<T: Number> ParallelMatMul for Matrix2d<T> {
     }
 }
 
+impl<T: Number> ParallelAbftMatMul for Matrix2d<T> {
+    fn parallel_abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<AbftReport, Error> {
+        let mut product = Matrix2d::zeroes(result.rows + 1, result.cols + 1);
+        self.column_checksums().parallel_multiply(
+            &other.row_checksums(),
+            &mut product,
+            n_threads,
+        )?;
+        product.abft_verify(tolerance, result)
+    }
+}
+
 impl<T: Number> ParallelConvolution for Matrix2d<T> {
     fn parallel_convolute(
         &self,
@@ -120,6 +154,116 @@ impl<T: Number> ParallelRelu for Matrix2d<T> {
     }
 }
 
+impl<T: Float> ParallelActivation<T> for Matrix2d<T> {
+    fn parallel_activation(
+        &self,
+        result: &mut Self,
+        function: ActivationFunction<T>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        for (i, row) in chunk.iter_mut().enumerate() {
+                            self.activation_row(row, start_row + i, function);
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelDense<T> for Matrix2d<T> {
+    fn parallel_dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.cols != input.rows
+            || self.rows != result.rows
+            || input.cols != result.cols
+            || bias.len() != self.rows
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let input_transposed = &input.transpose();
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        for (i, result_row) in chunk.iter_mut().enumerate() {
+                            self.dense_row(
+                                input_transposed,
+                                bias,
+                                result_row,
+                                start_row + i,
+                                activation,
+                            );
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelBatchNorm<T> for Matrix2d<T> {
+    fn parallel_batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        parameters.check_features(self.rows)?;
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        for (i, result_row) in chunk.iter_mut().enumerate() {
+                            self.batch_norm_row(result_row, start_row + i, parameters);
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
 impl<T: Float> ParallelSoftmax for Matrix2d<T> {
     fn parallel_softmax(&self, result: &mut Self, n_threads: usize) -> Result<(), Error> {
         if self.rows != result.rows || self.cols != result.cols {
@@ -163,6 +307,157 @@ impl<T: Float> ParallelSoftmax for Matrix2d<T> {
     }
 }
 
+impl<T: Float> ParallelStableSoftmax for Matrix2d<T> {
+    fn parallel_stable_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        self.parallel_stable_softmax_helper(result, axis, false, n_threads)
+    }
+
+    fn parallel_log_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        self.parallel_stable_softmax_helper(result, axis, true, n_threads)
+    }
+}
+
+impl<T: Float> Matrix2d<T> {
+    fn parallel_stable_softmax_helper(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        log: bool,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let cols = self.cols;
+
+        if axis == SoftmaxAxis::Rows {
+            // rows are independent, each thread does all the passes on its own rows
+            thread::scope(|s| {
+                result
+                    .data
+                    .chunks_mut(rows_per_thread)
+                    .enumerate()
+                    .for_each(|(chunk_idx, chunk)| {
+                        let start_row = chunk_idx * rows_per_thread;
+                        s.spawn(move || {
+                            chunk.iter_mut().enumerate().for_each(|(i, result_row)| {
+                                let mut max = [T::neg_infinity()];
+                                let mut sum = [T::zero()];
+                                self.softmax_max_row(&mut max, start_row + i, axis);
+                                self.softmax_exp_row(
+                                    result_row,
+                                    start_row + i,
+                                    axis,
+                                    &max,
+                                    &mut sum,
+                                );
+                                self.softmax_normalize_row(
+                                    result_row,
+                                    start_row + i,
+                                    axis,
+                                    &max,
+                                    &sum,
+                                    log,
+                                );
+                            });
+                        });
+                    });
+            });
+            return Ok(());
+        }
+
+        let n_statistics = axis.n_statistics(cols);
+
+        // each thread computes the maxima of its rows, then they are combined
+        let mut max = vec![T::neg_infinity(); n_statistics];
+        thread::scope(|s| {
+            (0..self.rows)
+                .step_by(rows_per_thread)
+                .map(|start_row| {
+                    s.spawn(move || {
+                        let mut max = vec![T::neg_infinity(); n_statistics];
+                        let end_row = (start_row + rows_per_thread).min(self.rows);
+                        (start_row..end_row).for_each(|i| self.softmax_max_row(&mut max, i, axis));
+                        max
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    for (acc, partial) in max.iter_mut().zip(handle.join().unwrap()) {
+                        if partial > *acc {
+                            *acc = partial;
+                        }
+                    }
+                });
+        });
+
+        // same for the sums, the exponentials are stored in result
+        let max = &max;
+        let mut sum = vec![T::zero(); n_statistics];
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .map(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        let mut sum = vec![T::zero(); n_statistics];
+                        chunk.iter_mut().enumerate().for_each(|(i, result_row)| {
+                            self.softmax_exp_row(result_row, start_row + i, axis, max, &mut sum);
+                        });
+                        sum
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    for (acc, partial) in sum.iter_mut().zip(handle.join().unwrap()) {
+                        *acc += partial;
+                    }
+                });
+        });
+
+        let sum = &sum;
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, result_row)| {
+                            self.softmax_normalize_row(
+                                result_row,
+                                start_row + i,
+                                axis,
+                                max,
+                                sum,
+                                log,
+                            );
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
 impl<T: Number> ParallelMaxPooling for Matrix2d<T> {
     fn parallel_max_pooling(
         &self,
@@ -196,6 +491,124 @@ impl<T: Number> ParallelMaxPooling for Matrix2d<T> {
     }
 }
 
+impl<T: Number> ParallelPooling<T> for Matrix2d<T> {
+    fn parallel_pooling(
+        &self,
+        result: &mut Self,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols) {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (result.rows - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        for (i, row) in chunk.iter_mut().enumerate() {
+                            self.pooling_row(row, None, start_row + i, pooling_type, parameters);
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+
+    fn parallel_max_pooling_indices(
+        &self,
+        result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols)
+            || indices.len() != result.rows * result.cols
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (result.rows - 1) / n_threads + 1;
+        let result_cols = result.cols;
+
+        thread::scope(|s| {
+            result
+                .data
+                .chunks_mut(rows_per_thread)
+                .zip(indices.chunks_mut(result_cols * rows_per_thread))
+                .enumerate()
+                .for_each(|(chunk_idx, (chunk, indices_chunk))| {
+                    let start_row = chunk_idx * rows_per_thread;
+                    s.spawn(move || {
+                        for (i, (row, indices_row)) in chunk
+                            .iter_mut()
+                            .zip(indices_chunk.chunks_mut(result_cols))
+                            .enumerate()
+                        {
+                            self.pooling_row(
+                                row,
+                                Some(indices_row),
+                                start_row + i,
+                                PoolingType::Max,
+                                parameters,
+                            );
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+
+    fn parallel_global_pooling(
+        &self,
+        pooling_type: PoolingType,
+        n_threads: usize,
+    ) -> Result<T, Error> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
+        let mut acc = None;
+
+        thread::scope(|s| {
+            (0..self.rows)
+                .step_by(rows_per_thread)
+                .map(|start_row| {
+                    s.spawn(move || {
+                        let end_row = (start_row + rows_per_thread).min(self.rows);
+                        (start_row + 1..end_row).fold(
+                            self.global_pooling_row(start_row, pooling_type),
+                            |acc, i| {
+                                pooling_type.combine(acc, self.global_pooling_row(i, pooling_type))
+                            },
+                        )
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .for_each(|handle| {
+                    let partial = handle.join().unwrap();
+                    acc = Some(match acc {
+                        Some(acc) => pooling_type.combine(acc, partial),
+                        None => partial,
+                    });
+                });
+        });
+
+        Ok(pooling_type.finalize(acc.unwrap(), self.rows * self.cols))
+    }
+}
+
 impl<T: Float> ParallelLRN<T> for Matrix2d<T> {
     fn parallel_lrn(
         &self,
@@ -230,37 +643,33 @@ impl<T: Float> ParallelLRN<T> for Matrix2d<T> {
     }
 }
 
-impl<T: Number> ParallelFiniteImpulseResponseFilter for Matrix2d<T> {
-    fn parallel_fir_filter(
+impl<T: Float> ParallelGeneralLRN<T> for Matrix2d<T> {
+    fn parallel_general_lrn(
         &self,
-        kernel: &Self,
         result: &mut Self,
+        parameters: &LRNParameters<T>,
         n_threads: usize,
     ) -> Result<(), Error> {
-        if self.rows != result.rows || result.cols != self.cols + kernel.cols - 1 || self.rows != 1
-        {
+        if self.rows != result.rows || self.cols != result.cols {
             return Err(Error::InvalidDimensions);
         }
-        if kernel.rows != 1 {
+        if parameters.n % 2 != 1 {
             return Err(Error::InvalidKernelDimensions);
         }
 
-        // here the number of rows will always be one
-        let elements_per_thread = (result.cols - 1) / n_threads + 1;
-
-        let shared_kernel = Arc::new(kernel);
+        let rows_per_thread = (self.rows - 1) / n_threads + 1;
 
         thread::scope(|s| {
-            result.data[0] // we only have one row, so this is all the data
-                .chunks_mut(elements_per_thread)
+            result
+                .data
+                .chunks_mut(rows_per_thread)
                 .enumerate()
                 .for_each(|(chunk_idx, chunk)| {
-                    let shared_kernel = shared_kernel.clone();
-                    let start_idx = chunk_idx * elements_per_thread;
+                    let start_row = chunk_idx * rows_per_thread;
                     s.spawn(move || {
-                        chunk.iter_mut().enumerate().for_each(|(i, result_el)| {
-                            *result_el = self.fir_filter_element(&shared_kernel, i + start_idx);
-                        });
+                        for (i, row) in chunk.iter_mut().enumerate() {
+                            self.general_lrn_row(row, start_row + i, parameters);
+                        }
                     });
                 });
         });
@@ -268,3 +677,781 @@ impl<T: Number> ParallelFiniteImpulseResponseFilter for Matrix2d<T> {
         Ok(())
     }
 }
+
+impl<T: Number> ParallelFiniteImpulseResponseFilter for Matrix2d<T> {
+    fn parallel_fir_filter(
+        &self,
+        kernel: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || result.cols != self.cols + kernel.cols - 1 || self.rows != 1
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        if kernel.rows != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+
+        // here the number of rows will always be one
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        let shared_kernel = Arc::new(kernel);
+
+        thread::scope(|s| {
+            result.data[0] // we only have one row, so this is all the data
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let shared_kernel = shared_kernel.clone();
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, result_el)| {
+                            *result_el = self.fir_filter_element(&shared_kernel, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Number> ParallelMultirateFirFilter<T> for Matrix2d<T> {
+    fn parallel_fir_decimate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != 1 || result.rows != 1 || result.cols != filter.decimated_len(self.cols)? {
+            return Err(Error::InvalidDimensions);
+        }
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let input = &self.data[0][..];
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result.data[0]
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = filter.decimate_element(input, i + start_idx);
+                        });
+                    });
+                });
+        });
+        Ok(())
+    }
+
+    fn parallel_fir_interpolate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != 1
+            || result.rows != 1
+            || result.cols != filter.interpolated_len(self.cols)?
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let input = &self.data[0][..];
+        let elements_per_thread = (result.cols - 1) / n_threads + 1;
+
+        thread::scope(|s| {
+            result.data[0]
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = filter.interpolate_element(input, i + start_idx);
+                        });
+                    });
+                });
+        });
+        Ok(())
+    }
+}
+
+macro_rules! impl_parallel_fft_2d {
+    ($t: tt) => {
+        impl ParallelFastFourierTransform2d<$t> for Matrix2d<$t> {
+            fn parallel_fft_2d(&self, result: &mut Self, n_threads: usize) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || self.rows.count_ones() != 1
+                    || self.cols.count_ones() != 1
+                    || self.cols < 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                let cols = self.cols;
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(rows_per_thread)
+                        .zip(self.data.chunks(rows_per_thread))
+                        .for_each(|(chunk, self_chunk)| {
+                            s.spawn(move || {
+                                for (result_row, row) in chunk.iter_mut().zip(self_chunk) {
+                                    result_row.copy_from_slice(row);
+                                    Self::fft_helper(result_row, cols >> 1);
+                                }
+                            });
+                        });
+                });
+
+                // each row of the transposed matrix is a column of complex numbers
+                let mut transposed = Self::zeroes(self.cols >> 1, self.rows << 1);
+                let transposed_cols = self.rows << 1;
+                let transposed_rows_per_thread = ((self.cols >> 1) - 1) / n_threads + 1;
+                let result_ref = &*result;
+
+                thread::scope(|s| {
+                    transposed
+                        .data
+                        .chunks_mut(transposed_rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * transposed_rows_per_thread;
+                            s.spawn(move || {
+                                for (i, transposed_row) in chunk.iter_mut().enumerate() {
+                                    result_ref.complex_transpose_row(transposed_row, start_row + i);
+                                    Self::fft_helper(transposed_row, transposed_cols >> 1);
+                                }
+                            });
+                        });
+                });
+
+                let transposed = &transposed;
+
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                for (i, result_row) in chunk.iter_mut().enumerate() {
+                                    transposed.complex_transpose_row(result_row, start_row + i);
+                                }
+                            });
+                        });
+                });
+
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_fft_2d!(f32);
+impl_parallel_fft_2d!(f64);
+
+macro_rules! impl_parallel_radar_processing {
+    ($t: tt) => {
+        impl ParallelRadarProcessing<$t> for Matrix2d<$t> {
+            fn parallel_compression(
+                &self,
+                matched_filter: &[$t],
+                result: &mut Self,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || matched_filter.len() != self.cols
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if (self.cols >> 1).count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk.iter_mut().enumerate().for_each(|(i, result_row)| {
+                                    self.compression_row(result_row, start_row + i, matched_filter)
+                                });
+                            });
+                        });
+                });
+                Ok(())
+            }
+
+            fn parallel_corner_turn(
+                &self,
+                result: &mut Self,
+                n_threads: usize,
+            ) -> Result<(), Error> {
+                if self.cols % 2 != 0
+                    || result.rows != self.cols >> 1
+                    || result.cols != self.rows << 1
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+
+                let rows_per_thread = ((self.cols >> 1) - 1) / n_threads + 1;
+                thread::scope(|s| {
+                    result
+                        .data
+                        .chunks_mut(rows_per_thread)
+                        .enumerate()
+                        .for_each(|(chunk_idx, chunk)| {
+                            let start_row = chunk_idx * rows_per_thread;
+                            s.spawn(move || {
+                                chunk.iter_mut().enumerate().for_each(|(i, result_row)| {
+                                    self.complex_transpose_row(result_row, start_row + i)
+                                });
+                            });
+                        });
+                });
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_parallel_radar_processing!(f32);
+impl_parallel_radar_processing!(f64);
+
+impl ParallelWaveletTransformInteger<i32> for Matrix2d<i32> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let (low, high) = result.data[0].split_at_mut(size);
+
+        // the low pass coefficients need all the high pass ones
+        thread::scope(|s| {
+            high.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = self.wavelet_transform_top_half_element(i + start_idx, size);
+                        });
+                    });
+                });
+        });
+
+        let high = &*high;
+        thread::scope(|s| {
+            low.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = self.wavelet_transform_bottom_half_element(high, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelWaveletTransformFloating<T> for Matrix2d<T> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let data = self.data[0].as_slice();
+        let (low, high) = result.data[0].split_at_mut(size);
+
+        thread::scope(|s| {
+            low.chunks_mut(elements_per_thread)
+                .zip(high.chunks_mut(elements_per_thread))
+                .enumerate()
+                .for_each(|(chunk_idx, (low_chunk, high_chunk))| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        for (i, (low_el, high_el)) in
+                            low_chunk.iter_mut().zip(high_chunk).enumerate()
+                        {
+                            let center = 2 * (i + start_idx);
+                            *low_el =
+                                wavelet::forward_floating_element(data, center, low_pass_filter);
+                            *high_el = wavelet::forward_floating_element(
+                                data,
+                                center + 1,
+                                high_pass_filter,
+                            );
+                        }
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl ParallelInverseWaveletTransformInteger<i32> for Matrix2d<i32> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (size - 1) / n_threads + 1;
+        let coefficients = self.data[0].as_slice();
+        let mut even = vec![0; size];
+
+        // even samples first, the odd ones are predicted from them
+        thread::scope(|s| {
+            even.chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el =
+                                wavelet::inverse_integer_even_element(coefficients, i + start_idx);
+                        });
+                    });
+                });
+        });
+
+        let even = even.as_slice();
+        thread::scope(|s| {
+            result.data[0]
+                .chunks_mut(2 * elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.chunks_exact_mut(2).enumerate().for_each(|(i, el)| {
+                            el[0] = even[i + start_idx];
+                            el[1] = wavelet::inverse_integer_odd_element(
+                                coefficients,
+                                even,
+                                i + start_idx,
+                            );
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelInverseWaveletTransformFloating<T> for Matrix2d<T> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let elements_per_thread = (full_size - 1) / n_threads + 1;
+        let coefficients = self.data[0].as_slice();
+
+        thread::scope(|s| {
+            result.data[0]
+                .chunks_mut(elements_per_thread)
+                .enumerate()
+                .for_each(|(chunk_idx, chunk)| {
+                    let start_idx = chunk_idx * elements_per_thread;
+                    s.spawn(move || {
+                        chunk.iter_mut().enumerate().for_each(|(i, el)| {
+                            *el = wavelet::inverse_floating_element(
+                                coefficients,
+                                i + start_idx,
+                                low_pass_filter,
+                                high_pass_filter,
+                            );
+                        });
+                    });
+                });
+        });
+
+        Ok(())
+    }
+}
+
+impl ParallelWaveletTransform2dInteger<i32> for Matrix2d<i32> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [i32]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        wavelet::levels_2d(
+            &mut rows,
+            levels,
+            &wavelet::forward_integer,
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        );
+        Ok(())
+    }
+}
+
+impl<T: Float> ParallelWaveletTransform2dFloating<T> for Matrix2d<T> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [T]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        let transform = |data: &[T], transformed: &mut [T]| {
+            wavelet::forward_floating(data, transformed, low_pass_filter, high_pass_filter)
+        };
+        wavelet::levels_2d(&mut rows, levels, &transform, |rows, function| {
+            rows::parallel_for_each_row(rows, function, n_threads)
+        });
+        Ok(())
+    }
+}
+
+impl ParallelBitPlaneEncoder for Matrix2d<i32> {
+    fn parallel_bit_plane_encode(
+        &self,
+        parameters: &BpeParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error> {
+        ccsds_122::parallel_encode(
+            self.rows,
+            self.cols,
+            |i, j| self.data[i][j],
+            parameters,
+            n_threads,
+        )
+    }
+}
+
+impl ParallelRiceEncoder for Matrix2d<i32> {
+    fn parallel_rice_encode(
+        &self,
+        parameters: &RiceParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error> {
+        ccsds_121::parallel_encode(&self.data.concat(), parameters, n_threads)
+    }
+}
+
+impl<T: Number> ParallelImageCalibration<T> for Matrix2d<T> {
+    fn parallel_offset_correction(
+        &self,
+        offsets: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::offset_correction(
+            &self.row_slices(),
+            &offsets.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_bad_pixel_correction(
+        &self,
+        bad_pixels: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::bad_pixel_correction(
+            &self.row_slices(),
+            &bad_pixels.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        let neighbours: Vec<_> = neighbours.iter().map(|frame| frame.row_slices()).collect();
+        calibration::radiation_scrubbing(
+            &self.row_slices(),
+            &neighbours,
+            threshold,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_gain_correction(
+        &self,
+        gains: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::gain_correction(
+            &self.row_slices(),
+            &gains.row_slices(),
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_spatial_binning(
+        &self,
+        factor: usize,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::spatial_binning(
+            &self.row_slices(),
+            factor,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+
+    fn parallel_co_add(&self, accumulator: &mut Self, n_threads: usize) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        calibration::co_add(
+            &self.row_slices(),
+            &mut accumulator.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+impl<T: Number> ParallelIirFilter<T> for Matrix2d<T> {
+    fn parallel_iir_filter(
+        &self,
+        cascade: &BiquadCascade<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        iir::filter(
+            &self.row_slices(),
+            cascade,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+macro_rules! impl_parallel_corr {
+    ($self_type: tt, $output_type: tt) => {
+        impl ParallelCorrelation for Matrix2d<$self_type> {
+            type Output = $output_type;
+            fn parallel_correlate(
+                &self,
+                other: &Self,
+                n_threads: usize,
+            ) -> Result<Self::Output, Error> {
+                if self.rows != other.rows || self.cols != other.cols || self.rows == 0 {
+                    return Err(Error::InvalidDimensions);
+                }
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+                let rows_per_thread = (self.rows - 1) / n_threads + 1;
+                let (self_rows, other_rows) = (self.row_slices(), other.row_slices());
+
+                // the sums of the rows of each thread, then the accumulators
+                let (self_sum, other_sum) = thread::scope(|s| {
+                    let handles: Vec<_> = self_rows
+                        .chunks(rows_per_thread)
+                        .zip(other_rows.chunks(rows_per_thread))
+                        .map(|(self_chunk, other_chunk)| {
+                            s.spawn(move || {
+                                let sum = |rows: &[&[$self_type]]| {
+                                    rows.iter().flat_map(|row| row.iter()).sum::<$self_type>()
+                                        as Self::Output
+                                };
+                                (sum(self_chunk), sum(other_chunk))
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .fold((0.0, 0.0), |(a, b), (c, d)| (a + c, b + d))
+                });
+                let elements = (self.rows * self.cols) as Self::Output;
+                let (self_mean, other_mean) = (self_sum / elements, other_sum / elements);
+
+                let (acc_self_sq, acc_other_sq, acc_self_other) = thread::scope(|s| {
+                    let handles: Vec<_> = (0..self.rows)
+                        .step_by(rows_per_thread)
+                        .map(|start| {
+                            s.spawn(move || {
+                                (start..(start + rows_per_thread).min(self.rows))
+                                    .map(|i| self.accumulate_row(other, self_mean, other_mean, i))
+                                    .fold((0.0, 0.0, 0.0), |(a, b, c), (d, e, f)| {
+                                        (a + d, b + e, c + f)
+                                    })
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().unwrap())
+                        .fold((0.0, 0.0, 0.0), |(a, b, c), (d, e, f)| {
+                            (a + d, b + e, c + f)
+                        })
+                });
+                Ok(acc_self_other / (acc_self_sq * acc_other_sq).sqrt())
+            }
+        }
+    };
+}
+
+impl_parallel_corr!(i32, f32);
+impl_parallel_corr!(f32, f32);
+impl_parallel_corr!(f64, f64);
+
+impl<T: Number> ParallelCrossCorrelation<T> for Matrix2d<T> {
+    fn parallel_cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<CorrelationPeak<T>, Error> {
+        if n_threads == 0 {
+            return Err(Error::InvalidNumberOfThreads);
+        }
+        cross_correlation::cross_correlation(
+            &self.row_slices(),
+            &other.row_slices(),
+            max_lags,
+            &mut result.row_slices_mut(),
+            |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+        )
+    }
+}
+
+macro_rules! impl_parallel_template_matching {
+    ($self_type: tt) => {
+        impl ParallelTemplateMatching for Matrix2d<$self_type> {
+            fn parallel_template_matching(
+                &self,
+                template: &Self,
+                result: &mut Self::Map,
+                n_threads: usize,
+            ) -> Result<CorrelationPeak<Self::Output>, Error> {
+                if n_threads == 0 {
+                    return Err(Error::InvalidNumberOfThreads);
+                }
+                cross_correlation::template_matching(
+                    &self.row_slices(),
+                    &template.row_slices(),
+                    &mut result.row_slices_mut(),
+                    |rows, function| rows::parallel_for_each_row(rows, function, n_threads),
+                )
+            }
+        }
+    };
+}
+
+impl_parallel_template_matching!(i32);
+impl_parallel_template_matching!(f32);
+impl_parallel_template_matching!(f64);
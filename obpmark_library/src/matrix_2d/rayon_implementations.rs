@@ -1,11 +1,26 @@
+use crate::calibration;
+use crate::ccsds_121;
+use crate::ccsds_122;
+use crate::cross_correlation;
+use crate::iir;
 use crate::matrix_2d::Matrix2d;
 use crate::number_traits::{Float, Number};
-use crate::Error;
+use crate::rows;
+use crate::wavelet;
+use crate::BpeParameters;
+use crate::PolyphaseFilter;
 use crate::{rayon_traits::*, FirFilter};
+use crate::{ActivationFunction, BatchNormParameters, Error, LRNParameters, RiceParameters};
+use crate::{BiquadCascade, CorrelationPeak};
 
 use rayon::prelude::*;
 
-use crate::{BaseMatrix, Convolution, Correlation, MatMul, MaxPooling, Relu, Softmax, LRN};
+use crate::{
+    AbftMatMul, AbftReport, Activation, BaseMatrix, BatchNorm, Convolution, Correlation, Dense,
+    FastFourierTransform2d, FastFourierTransformHelper, GeneralLRN, MatMul, MaxPooling, Pooling,
+    PoolingParameters, PoolingType, RadarProcessing, Relu, Softmax, SoftmaxAxis, StableSoftmax,
+    WaveletTransformInteger, LRN,
+};
 
 impl<T: Number> RayonMatMul for Matrix2d<T> {
     fn rayon_multiply(&self, other: &Self, result: &mut Self) -> Result<(), Error> {
@@ -25,6 +40,20 @@ impl<T: Number> RayonMatMul for Matrix2d<T> {
     }
 }
 
+impl<T: Number> RayonAbftMatMul for Matrix2d<T> {
+    fn rayon_abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+    ) -> Result<AbftReport, Error> {
+        let mut product = Matrix2d::zeroes(result.rows + 1, result.cols + 1);
+        self.column_checksums()
+            .rayon_multiply(&other.row_checksums(), &mut product)?;
+        product.abft_verify(tolerance, result)
+    }
+}
+
 impl<T: Number> RayonMaxPooling for Matrix2d<T> {
     fn rayon_max_pooling(
         &self,
@@ -43,6 +72,145 @@ impl<T: Number> RayonMaxPooling for Matrix2d<T> {
     }
 }
 
+impl<T: Number> RayonPooling<T> for Matrix2d<T> {
+    fn rayon_pooling(
+        &self,
+        result: &mut Self,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols) {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| self.pooling_row(row, None, i, pooling_type, parameters));
+        Ok(())
+    }
+
+    fn rayon_max_pooling_indices(
+        &self,
+        result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols)
+            || indices.len() != result.rows * result.cols
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .par_iter_mut()
+            .zip(indices.par_chunks_mut(result.cols))
+            .enumerate()
+            .for_each(|(i, (row, indices_row))| {
+                self.pooling_row(row, Some(indices_row), i, PoolingType::Max, parameters)
+            });
+        Ok(())
+    }
+
+    fn rayon_global_pooling(&self, pooling_type: PoolingType) -> Result<T, Error> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+        let acc = (0..self.rows)
+            .into_par_iter()
+            .map(|i| self.global_pooling_row(i, pooling_type))
+            .reduce_with(|acc, next| pooling_type.combine(acc, next))
+            .unwrap();
+        Ok(pooling_type.finalize(acc, self.rows * self.cols))
+    }
+}
+
+impl<T: Float> RayonStableSoftmax for Matrix2d<T> {
+    fn rayon_stable_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.rayon_stable_softmax_helper(result, axis, false)
+    }
+
+    fn rayon_log_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.rayon_stable_softmax_helper(result, axis, true)
+    }
+}
+
+impl<T: Float> Matrix2d<T> {
+    fn rayon_stable_softmax_helper(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        log: bool,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let cols = self.cols;
+
+        if axis == SoftmaxAxis::Rows {
+            result.data.par_iter_mut().enumerate().for_each(|(i, row)| {
+                let mut max = [T::neg_infinity()];
+                let mut sum = [T::zero()];
+                self.softmax_max_row(&mut max, i, axis);
+                self.softmax_exp_row(row, i, axis, &max, &mut sum);
+                self.softmax_normalize_row(row, i, axis, &max, &sum, log);
+            });
+            return Ok(());
+        }
+
+        let n_statistics = axis.n_statistics(cols);
+
+        let max = (0..self.rows)
+            .into_par_iter()
+            .fold(
+                || vec![T::neg_infinity(); n_statistics],
+                |mut max, i| {
+                    self.softmax_max_row(&mut max, i, axis);
+                    max
+                },
+            )
+            .reduce(
+                || vec![T::neg_infinity(); n_statistics],
+                |mut max, partial| {
+                    for (acc, partial) in max.iter_mut().zip(partial) {
+                        if partial > *acc {
+                            *acc = partial;
+                        }
+                    }
+                    max
+                },
+            );
+
+        let sum = result
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .fold(
+                || vec![T::zero(); n_statistics],
+                |mut sum, (i, row)| {
+                    self.softmax_exp_row(row, i, axis, &max, &mut sum); // SIDE EFFECT ON ROW
+                    sum
+                },
+            )
+            .reduce(
+                || vec![T::zero(); n_statistics],
+                |mut sum, partial| {
+                    for (acc, partial) in sum.iter_mut().zip(partial) {
+                        *acc += partial;
+                    }
+                    sum
+                },
+            );
+
+        result.data.par_iter_mut().enumerate().for_each(|(i, row)| {
+            self.softmax_normalize_row(row, i, axis, &max, &sum, log);
+        });
+
+        Ok(())
+    }
+}
+
 impl<T: Float> RayonSoftmax for Matrix2d<T> {
     fn rayon_softmax(&self, result: &mut Matrix2d<T>) -> Result<(), Error> {
         if self.rows != result.rows || self.cols != result.cols {
@@ -108,6 +276,73 @@ impl<T: Number> RayonRelu for Matrix2d<T> {
     }
 }
 
+impl<T: Float> RayonActivation<T> for Matrix2d<T> {
+    fn rayon_activation(
+        &self,
+        result: &mut Self,
+        function: ActivationFunction<T>,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| self.activation_row(row, i, function));
+        Ok(())
+    }
+}
+
+impl<T: Float> RayonDense<T> for Matrix2d<T> {
+    fn rayon_dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+    ) -> Result<(), Error> {
+        if self.cols != input.rows
+            || self.rows != result.rows
+            || input.cols != result.cols
+            || bias.len() != self.rows
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let input_transposed = input.transpose();
+
+        result
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.dense_row(&input_transposed, bias, result_row, i, activation)
+            });
+        Ok(())
+    }
+}
+
+impl<T: Float> RayonBatchNorm<T> for Matrix2d<T> {
+    fn rayon_batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        parameters.check_features(self.rows)?;
+
+        result
+            .data
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| self.batch_norm_row(result_row, i, parameters));
+        Ok(())
+    }
+}
+
 impl<T: Float> RayonLRN<T> for Matrix2d<T> {
     fn rayon_lrn(&self, result: &mut Matrix2d<T>, alpha: T, beta: T, k: T) -> Result<(), Error> {
         if self.rows != result.rows || self.cols != result.cols {
@@ -120,6 +355,25 @@ impl<T: Float> RayonLRN<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Float> RayonGeneralLRN<T> for Matrix2d<T> {
+    fn rayon_general_lrn(
+        &self,
+        result: &mut Self,
+        parameters: &LRNParameters<T>,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        if parameters.n % 2 != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        result.data.par_iter_mut().enumerate().for_each(|(i, row)| {
+            self.general_lrn_row(row, i, parameters);
+        });
+        Ok(())
+    }
+}
+
 impl<T: Number> RayonFiniteImpulseResponseFilter for Matrix2d<T> {
     fn rayon_fir_filter(&self, kernel: &Self, result: &mut Self) -> Result<(), Error> {
         if self.rows != result.rows || result.cols != self.cols + kernel.cols - 1 || self.rows != 1
@@ -140,6 +394,47 @@ impl<T: Number> RayonFiniteImpulseResponseFilter for Matrix2d<T> {
     }
 }
 
+impl<T: Number> RayonMultirateFirFilter<T> for Matrix2d<T> {
+    fn rayon_fir_decimate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        if self.rows != 1 || result.rows != 1 || result.cols != filter.decimated_len(self.cols)? {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[0][..];
+        result.data[0]
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(idx, el)| {
+                *el = filter.decimate_element(input, idx);
+            });
+        Ok(())
+    }
+
+    fn rayon_fir_interpolate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        if self.rows != 1
+            || result.rows != 1
+            || result.cols != filter.interpolated_len(self.cols)?
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[0][..];
+        result.data[0]
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(idx, el)| {
+                *el = filter.interpolate_element(input, idx);
+            });
+        Ok(())
+    }
+}
+
 macro_rules! impl_rayon_corr {
     ($self_type: tt, $output_type: tt) => {
         impl RayonCorrelation for Matrix2d<$self_type> {
@@ -178,3 +473,390 @@ macro_rules! impl_rayon_corr {
 impl_rayon_corr!(i32, f32);
 impl_rayon_corr!(f32, f32);
 impl_rayon_corr!(f64, f64);
+
+macro_rules! impl_rayon_fft_2d {
+    ($t: tt) => {
+        impl RayonFastFourierTransform2d<$t> for Matrix2d<$t> {
+            fn rayon_fft_2d(&self, result: &mut Self) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || self.rows.count_ones() != 1
+                    || self.cols.count_ones() != 1
+                    || self.cols < 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                result
+                    .data
+                    .par_iter_mut()
+                    .zip(self.data.par_iter())
+                    .for_each(|(result_row, row)| {
+                        result_row.copy_from_slice(row);
+                        Self::fft_helper(result_row, self.cols >> 1);
+                    });
+
+                // each row of the transposed matrix is a column of complex numbers
+                let mut transposed = Self::zeroes(self.cols >> 1, self.rows << 1);
+                let result_ref = &*result;
+                transposed
+                    .data
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(|(i, transposed_row)| {
+                        result_ref.complex_transpose_row(transposed_row, i);
+                        Self::fft_helper(transposed_row, self.rows);
+                    });
+
+                result
+                    .data
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| transposed.complex_transpose_row(result_row, i));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_rayon_fft_2d!(f32);
+impl_rayon_fft_2d!(f64);
+
+macro_rules! impl_rayon_radar_processing {
+    ($t: tt) => {
+        impl RayonRadarProcessing<$t> for Matrix2d<$t> {
+            fn rayon_compression(
+                &self,
+                matched_filter: &[$t],
+                result: &mut Self,
+            ) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || matched_filter.len() != self.cols
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if (self.cols >> 1).count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+
+                result
+                    .data
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| {
+                        self.compression_row(result_row, i, matched_filter)
+                    });
+                Ok(())
+            }
+
+            fn rayon_corner_turn(&self, result: &mut Self) -> Result<(), Error> {
+                if self.cols % 2 != 0
+                    || result.rows != self.cols >> 1
+                    || result.cols != self.rows << 1
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                result
+                    .data
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| self.complex_transpose_row(result_row, i));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_rayon_radar_processing!(f32);
+impl_rayon_radar_processing!(f64);
+
+impl RayonWaveletTransformInteger<i32> for Matrix2d<i32> {
+    fn rayon_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let (low, high) = result.data[0].split_at_mut(size);
+
+        // the low pass coefficients need all the high pass ones
+        high.par_iter_mut().enumerate().for_each(|(i, el)| {
+            *el = self.wavelet_transform_top_half_element(i, size);
+        });
+        let high = &*high;
+        low.par_iter_mut().enumerate().for_each(|(i, el)| {
+            *el = self.wavelet_transform_bottom_half_element(high, i);
+        });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> RayonWaveletTransformFloating<T> for Matrix2d<T> {
+    fn rayon_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let data = self.data[0].as_slice();
+        let (low, high) = result.data[0].split_at_mut(size);
+
+        low.par_iter_mut()
+            .zip(high.par_iter_mut())
+            .enumerate()
+            .for_each(|(i, (low_el, high_el))| {
+                *low_el = wavelet::forward_floating_element(data, 2 * i, low_pass_filter);
+                *high_el = wavelet::forward_floating_element(data, 2 * i + 1, high_pass_filter);
+            });
+
+        Ok(())
+    }
+}
+
+impl RayonInverseWaveletTransformInteger<i32> for Matrix2d<i32> {
+    fn rayon_inverse_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let coefficients = self.data[0].as_slice();
+
+        // even samples first, the odd ones are predicted from them
+        let even: Vec<i32> = (0..size)
+            .into_par_iter()
+            .map(|idx| wavelet::inverse_integer_even_element(coefficients, idx))
+            .collect();
+
+        result.data[0]
+            .par_chunks_exact_mut(2)
+            .enumerate()
+            .for_each(|(idx, el)| {
+                el[0] = even[idx];
+                el[1] = wavelet::inverse_integer_odd_element(coefficients, &even, idx);
+            });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> RayonInverseWaveletTransformFloating<T> for Matrix2d<T> {
+    fn rayon_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let coefficients = self.data[0].as_slice();
+
+        result.data[0]
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(idx, el)| {
+                *el = wavelet::inverse_floating_element(
+                    coefficients,
+                    idx,
+                    low_pass_filter,
+                    high_pass_filter,
+                );
+            });
+
+        Ok(())
+    }
+}
+
+impl RayonWaveletTransform2dInteger<i32> for Matrix2d<i32> {
+    fn rayon_wavelet_transform_2d(&self, result: &mut Self, levels: usize) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [i32]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        wavelet::levels_2d(
+            &mut rows,
+            levels,
+            &wavelet::forward_integer,
+            rows::rayon_for_each_row,
+        );
+        Ok(())
+    }
+}
+
+impl<T: Float> RayonWaveletTransform2dFloating<T> for Matrix2d<T> {
+    fn rayon_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [T]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        let transform = |data: &[T], transformed: &mut [T]| {
+            wavelet::forward_floating(data, transformed, low_pass_filter, high_pass_filter)
+        };
+        wavelet::levels_2d(&mut rows, levels, &transform, rows::rayon_for_each_row);
+        Ok(())
+    }
+}
+
+impl RayonBitPlaneEncoder for Matrix2d<i32> {
+    fn rayon_bit_plane_encode(&self, parameters: &BpeParameters) -> Result<Vec<u8>, Error> {
+        ccsds_122::rayon_encode(self.rows, self.cols, |i, j| self.data[i][j], parameters)
+    }
+}
+
+impl RayonRiceEncoder for Matrix2d<i32> {
+    fn rayon_rice_encode(&self, parameters: &RiceParameters) -> Result<Vec<u8>, Error> {
+        ccsds_121::rayon_encode(&self.data.concat(), parameters)
+    }
+}
+
+impl<T: Number> RayonImageCalibration<T> for Matrix2d<T> {
+    fn rayon_offset_correction(&self, offsets: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::offset_correction(
+            &self.row_slices(),
+            &offsets.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+
+    fn rayon_bad_pixel_correction(
+        &self,
+        bad_pixels: &Self,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        calibration::bad_pixel_correction(
+            &self.row_slices(),
+            &bad_pixels.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+
+    fn rayon_radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        let neighbours: Vec<_> = neighbours.iter().map(|frame| frame.row_slices()).collect();
+        calibration::radiation_scrubbing(
+            &self.row_slices(),
+            &neighbours,
+            threshold,
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+
+    fn rayon_gain_correction(&self, gains: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::gain_correction(
+            &self.row_slices(),
+            &gains.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+
+    fn rayon_spatial_binning(&self, factor: usize, result: &mut Self) -> Result<(), Error> {
+        calibration::spatial_binning(
+            &self.row_slices(),
+            factor,
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+
+    fn rayon_co_add(&self, accumulator: &mut Self) -> Result<(), Error> {
+        calibration::co_add(
+            &self.row_slices(),
+            &mut accumulator.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+}
+
+impl<T: Number> RayonIirFilter<T> for Matrix2d<T> {
+    fn rayon_iir_filter(&self, cascade: &BiquadCascade<T>, result: &mut Self) -> Result<(), Error> {
+        iir::filter(
+            &self.row_slices(),
+            cascade,
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+}
+
+impl<T: Number> RayonCrossCorrelation<T> for Matrix2d<T> {
+    fn rayon_cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+    ) -> Result<CorrelationPeak<T>, Error> {
+        cross_correlation::cross_correlation(
+            &self.row_slices(),
+            &other.row_slices(),
+            max_lags,
+            &mut result.row_slices_mut(),
+            rows::rayon_for_each_row,
+        )
+    }
+}
+
+macro_rules! impl_rayon_template_matching {
+    ($self_type: tt) => {
+        impl RayonTemplateMatching for Matrix2d<$self_type> {
+            fn rayon_template_matching(
+                &self,
+                template: &Self,
+                result: &mut Self::Map,
+            ) -> Result<CorrelationPeak<Self::Output>, Error> {
+                cross_correlation::template_matching(
+                    &self.row_slices(),
+                    &template.row_slices(),
+                    &mut result.row_slices_mut(),
+                    rows::rayon_for_each_row,
+                )
+            }
+        }
+    };
+}
+
+impl_rayon_template_matching!(i32);
+impl_rayon_template_matching!(f32);
+impl_rayon_template_matching!(f64);
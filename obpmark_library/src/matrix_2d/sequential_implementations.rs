@@ -1,18 +1,36 @@
 use super::Matrix2d;
+use crate::abft;
+use crate::calibration;
+use crate::ccsds_121;
+use crate::ccsds_122;
+use crate::cross_correlation;
+use crate::iir;
+use crate::matrix_1d::Matrix1d;
+use crate::rows;
+use crate::tmr::{self, TmrReport};
+use crate::wavelet;
 use crate::{
-    BaseMatrix, Convolution, Correlation, Error, FirFilter, Float, MatMul, MaxPooling, Number,
-    Relu, Softmax, WaveletTransformFloating, WaveletTransformInteger, LRN,
+    AbftMatMul, AbftReport, Activation, ActivationFunction, BaseMatrix, BatchNorm,
+    BatchNormParameters, BiquadCascade, BitPlaneEncoder, BpeParameters, Convolution, Correlation,
+    CorrelationPeak, CrossCorrelation, Dense, Error, FastFourierTransform2d,
+    FastFourierTransformHelper, FaultInjection, FirFilter, Float, GeneralLRN, IirFilter,
+    ImageCalibration, InverseWaveletTransformFloating, InverseWaveletTransformInteger,
+    LRNParameters, LRNRegion, MatMul, MaxPooling, MultirateFirFilter, Number, PolyphaseFilter,
+    Pooling, PoolingParameters, PoolingType, RadarProcessing, Relu, RiceEncoder, RiceParameters,
+    Softmax, SoftmaxAxis, StableSoftmax, TemplateMatching, Voting, WaveletTransform2dFloating,
+    WaveletTransform2dInteger, WaveletTransformFloating, WaveletTransformInteger, LRN,
 };
 
 impl<T: Number> MatMul<T> for Matrix2d<T> {
     fn multiply_row(&self, other: &Matrix2d<T>, result_row: &mut [T], row_idx: usize) {
         let i = row_idx;
-        for j in 0..other.cols {
+        // other is transposed, the row has one element per row of other
+        for (j, el) in result_row.iter_mut().enumerate() {
             let mut sum = T::zero();
             for k in 0..self.cols {
                 sum += self.data[i][k] * other.data[j][k];
             }
-            result_row[j] = sum; // note that j is already the position in the chunk
+            *el = sum; // note that j is already the position in the chunk
         }
     }
 
@@ -32,6 +50,55 @@ impl<T: Number> MatMul<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Number> AbftMatMul<T> for Matrix2d<T> {
+    fn column_checksums(&self) -> Matrix2d<T> {
+        let rows = self.row_slices();
+        let mut data: Vec<Vec<T>> = rows.iter().map(|row| row.to_vec()).collect();
+        data.push(abft::column_sums(&rows));
+        Matrix2d::new(data, self.rows + 1, self.cols)
+    }
+
+    fn row_checksums(&self) -> Matrix2d<T> {
+        let data = self
+            .row_slices()
+            .into_iter()
+            .map(|row| {
+                let mut row = row.to_vec();
+                row.push(row.iter().copied().sum());
+                row
+            })
+            .collect();
+        Matrix2d::new(data, self.rows, self.cols + 1)
+    }
+
+    fn abft_verify(
+        &mut self,
+        tolerance: f64,
+        result: &mut Matrix2d<T>,
+    ) -> Result<AbftReport, Error> {
+        if self.rows != result.rows + 1 || self.cols != result.cols + 1 {
+            return Err(Error::InvalidDimensions);
+        }
+        let report = abft::verify(&mut self.row_slices_mut(), tolerance);
+        for (result_row, row) in result.row_slices_mut().into_iter().zip(self.row_slices()) {
+            result_row.copy_from_slice(&row[..result_row.len()]);
+        }
+        Ok(report)
+    }
+
+    fn abft_multiply(
+        &self,
+        other: &Matrix2d<T>,
+        tolerance: f64,
+        result: &mut Matrix2d<T>,
+    ) -> Result<AbftReport, Error> {
+        let mut product = Matrix2d::zeroes(result.rows + 1, result.cols + 1);
+        self.column_checksums()
+            .multiply(&other.row_checksums(), &mut product)?;
+        product.abft_verify(tolerance, result)
+    }
+}
+
 impl<T: Number> Relu<T> for Matrix2d<T> {
     fn relu_row(&self, result_row: &mut [T], row_idx: usize) {
         let i = row_idx;
@@ -56,6 +123,115 @@ impl<T: Number> Relu<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Float> Activation<T> for Matrix2d<T> {
+    fn activation_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        function: ActivationFunction<T>,
+    ) {
+        for (result_el, el) in result_row.iter_mut().zip(&self.data[row_idx]) {
+            *result_el = function.apply(*el);
+        }
+    }
+
+    fn activation(&self, result: &mut Self, function: ActivationFunction<T>) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| self.activation_row(result_row, i, function));
+        Ok(())
+    }
+}
+
+impl<T: Float> Dense<T> for Matrix2d<T> {
+    fn dense_row(
+        &self,
+        input_transposed: &Self,
+        bias: &[T],
+        result_row: &mut [T],
+        row_idx: usize,
+        activation: Option<ActivationFunction<T>>,
+    ) {
+        let weights = &self.data[row_idx];
+        for (j, result_el) in result_row.iter_mut().enumerate() {
+            let sample = &input_transposed.data[j];
+            let sum = weights
+                .iter()
+                .zip(sample)
+                .fold(bias[row_idx], |acc, (w, x)| acc + *w * *x);
+            *result_el = match activation {
+                Some(function) => function.apply(sum),
+                None => sum,
+            };
+        }
+    }
+
+    fn dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+    ) -> Result<(), Error> {
+        if self.cols != input.rows
+            || self.rows != result.rows
+            || input.cols != result.cols
+            || bias.len() != self.rows
+        {
+            return Err(Error::InvalidDimensions);
+        }
+
+        let input_transposed = input.transpose();
+
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.dense_row(&input_transposed, bias, result_row, i, activation)
+            });
+        Ok(())
+    }
+}
+
+impl<T: Float> BatchNorm<T> for Matrix2d<T> {
+    fn batch_norm_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        parameters: &BatchNormParameters<T>,
+    ) {
+        let i = row_idx;
+        let factor = parameters.scale[i] / (parameters.variance[i] + parameters.epsilon).sqrt();
+        for (result_el, el) in result_row.iter_mut().zip(&self.data[i]) {
+            *result_el = (*el - parameters.mean[i]) * factor + parameters.shift[i];
+        }
+    }
+
+    fn batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        parameters.check_features(self.rows)?;
+
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| self.batch_norm_row(result_row, i, parameters));
+        Ok(())
+    }
+}
+
 impl<T: Number + num_traits::Float> Softmax<T> for Matrix2d<T> {
     fn softmax_row(&self, result_row: &mut [T], row_idx: usize) -> T {
         let i = row_idx;
@@ -89,6 +265,109 @@ impl<T: Number + num_traits::Float> Softmax<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Float> StableSoftmax<T> for Matrix2d<T> {
+    fn softmax_max_row(&self, max: &mut [T], row_idx: usize, axis: SoftmaxAxis) {
+        let row = &self.data[row_idx];
+        for (j, el) in row.iter().enumerate() {
+            let g = axis.statistic_idx(j);
+            if *el > max[g] {
+                max[g] = *el;
+            }
+        }
+    }
+
+    fn softmax_exp_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &mut [T],
+    ) {
+        let row = &self.data[row_idx];
+        for (j, (result_el, el)) in result_row.iter_mut().zip(row).enumerate() {
+            let g = axis.statistic_idx(j);
+            *result_el = (*el - max[g]).exp();
+            sum[g] += *result_el;
+        }
+    }
+
+    fn softmax_normalize_row(
+        &self,
+        result_row: &mut [T],
+        row_idx: usize,
+        axis: SoftmaxAxis,
+        max: &[T],
+        sum: &[T],
+        log: bool,
+    ) {
+        let row = &self.data[row_idx];
+        for (j, (result_el, el)) in result_row.iter_mut().zip(row).enumerate() {
+            let g = axis.statistic_idx(j);
+            if log {
+                *result_el = *el - max[g] - sum[g].ln();
+            } else {
+                *result_el /= sum[g];
+            }
+        }
+    }
+
+    fn stable_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.stable_softmax_helper(result, axis, false)
+    }
+
+    fn log_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error> {
+        self.stable_softmax_helper(result, axis, true)
+    }
+}
+
+impl<T: Float> Matrix2d<T> {
+    fn stable_softmax_helper(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        log: bool,
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+
+        if axis == SoftmaxAxis::Rows {
+            // every row is normalised on its own, a single pass is enough
+            result
+                .data
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, result_row)| {
+                    let mut max = [T::neg_infinity()];
+                    let mut sum = [T::zero()];
+                    self.softmax_max_row(&mut max, i, axis);
+                    self.softmax_exp_row(result_row, i, axis, &max, &mut sum);
+                    self.softmax_normalize_row(result_row, i, axis, &max, &sum, log);
+                });
+            return Ok(());
+        }
+
+        let mut max = vec![T::neg_infinity(); axis.n_statistics(self.cols)];
+        let mut sum = vec![T::zero(); axis.n_statistics(self.cols)];
+
+        (0..self.rows).for_each(|i| self.softmax_max_row(&mut max, i, axis));
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| self.softmax_exp_row(result_row, i, axis, &max, &mut sum));
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.softmax_normalize_row(result_row, i, axis, &max, &sum, log)
+            });
+        Ok(())
+    }
+}
+
 impl<T: Number> MaxPooling<T> for Matrix2d<T> {
     fn max_pooling_row(
         &self,
@@ -130,6 +409,103 @@ impl<T: Number> MaxPooling<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Number> Pooling<T> for Matrix2d<T> {
+    fn pooling_row(
+        &self,
+        result_row: &mut [T],
+        mut indices_row: Option<&mut [usize]>,
+        row_idx: usize,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) {
+        let rows = parameters.row_window(row_idx, self.rows);
+        for j in 0..result_row.len() {
+            let cols = parameters.col_window(j, self.cols);
+            let mut selected = (rows.start, cols.start);
+            let mut acc = match pooling_type {
+                PoolingType::Max => self.data[rows.start][cols.start],
+                PoolingType::Average => T::zero(),
+            };
+            for k in rows.clone() {
+                for l in cols.clone() {
+                    if pooling_type == PoolingType::Max && acc < self.data[k][l] {
+                        selected = (k, l);
+                    }
+                    acc = pooling_type.combine(acc, self.data[k][l]);
+                }
+            }
+            result_row[j] = pooling_type.finalize(acc, rows.len() * cols.len());
+            if let Some(indices_row) = indices_row.as_deref_mut() {
+                indices_row[j] = selected.0 * self.cols + selected.1;
+            }
+        }
+    }
+
+    fn pooling(
+        &self,
+        result: &mut Matrix2d<T>,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols) {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| {
+                self.pooling_row(result_row, None, i, pooling_type, parameters)
+            });
+        Ok(())
+    }
+
+    fn max_pooling_indices(
+        &self,
+        result: &mut Matrix2d<T>,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error> {
+        if parameters.output_dimensions(self.rows, self.cols)? != (result.rows, result.cols)
+            || indices.len() != result.rows * result.cols
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        result
+            .data
+            .iter_mut()
+            .zip(indices.chunks_mut(result.cols))
+            .enumerate()
+            .for_each(|(i, (result_row, indices_row))| {
+                self.pooling_row(
+                    result_row,
+                    Some(indices_row),
+                    i,
+                    PoolingType::Max,
+                    parameters,
+                )
+            });
+        Ok(())
+    }
+
+    fn global_pooling_row(&self, row_idx: usize, pooling_type: PoolingType) -> T {
+        let row = &self.data[row_idx];
+        row.iter()
+            .skip(1)
+            .fold(row[0], |acc, el| pooling_type.combine(acc, *el))
+    }
+
+    fn global_pooling(&self, pooling_type: PoolingType) -> Result<T, Error> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(Error::InvalidDimensions);
+        }
+        let acc = (1..self.rows).fold(self.global_pooling_row(0, pooling_type), |acc, i| {
+            pooling_type.combine(acc, self.global_pooling_row(i, pooling_type))
+        });
+        Ok(pooling_type.finalize(acc, self.rows * self.cols))
+    }
+}
+
 macro_rules! impl_correlation {
     ($self_type: tt, $output_type: tt) => {
         impl Correlation for Matrix2d<$self_type> {
@@ -186,6 +562,133 @@ impl_correlation!(i32, f32);
 impl_correlation!(f32, f32);
 impl_correlation!(f64, f64);
 
+macro_rules! impl_fft_2d {
+    ($t: tt) => {
+        // the transform of a row is the same as the one of the 1d matrix
+        impl FastFourierTransformHelper<$t> for Matrix2d<$t> {
+            fn fft_helper(result: &mut [$t], nn: usize) {
+                Matrix1d::<$t>::fft_helper(result, nn);
+            }
+
+            fn ifft_helper(result: &mut [$t], nn: usize) {
+                Matrix1d::<$t>::ifft_helper(result, nn);
+            }
+        }
+
+        impl FastFourierTransform2d<$t> for Matrix2d<$t> {
+            fn complex_transpose_row(&self, result_row: &mut [$t], row_idx: usize) {
+                for (i, result_el) in result_row.chunks_exact_mut(2).enumerate() {
+                    result_el[0] = self.data[i][2 * row_idx];
+                    result_el[1] = self.data[i][2 * row_idx + 1];
+                }
+            }
+
+            fn fft_2d(&self, result: &mut Self) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || self.rows.count_ones() != 1
+                    || self.cols.count_ones() != 1
+                    || self.cols < 2
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                for (result_row, row) in result.data.iter_mut().zip(&self.data) {
+                    result_row.copy_from_slice(row);
+                    Self::fft_helper(result_row, self.cols >> 1);
+                }
+
+                // each row of the transposed matrix is a column of complex numbers
+                let mut transposed = Self::zeroes(self.cols >> 1, self.rows << 1);
+                transposed
+                    .data
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, transposed_row)| {
+                        result.complex_transpose_row(transposed_row, i);
+                        Self::fft_helper(transposed_row, self.rows);
+                    });
+
+                result
+                    .data
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| transposed.complex_transpose_row(result_row, i));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_fft_2d!(f32);
+impl_fft_2d!(f64);
+
+macro_rules! impl_radar_processing {
+    ($t: tt) => {
+        impl RadarProcessing<$t> for Matrix2d<$t> {
+            fn compression_row(
+                &self,
+                result_row: &mut [$t],
+                row_idx: usize,
+                matched_filter: &[$t],
+            ) {
+                let nn = self.cols >> 1;
+                result_row.copy_from_slice(&self.data[row_idx]);
+                Self::fft_helper(result_row, nn);
+                for (el, filter) in result_row
+                    .chunks_exact_mut(2)
+                    .zip(matched_filter.chunks_exact(2))
+                {
+                    let re = el[0] * filter[0] - el[1] * filter[1];
+                    el[1] = el[0] * filter[1] + el[1] * filter[0];
+                    el[0] = re;
+                }
+                Self::ifft_helper(result_row, nn);
+            }
+
+            fn compression(&self, matched_filter: &[$t], result: &mut Self) -> Result<(), Error> {
+                if self.rows != result.rows
+                    || self.cols != result.cols
+                    || matched_filter.len() != self.cols
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+                if (self.cols >> 1).count_ones() != 1 {
+                    return Err(Error::UnsupportedSize);
+                }
+
+                result
+                    .data
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| {
+                        self.compression_row(result_row, i, matched_filter)
+                    });
+                Ok(())
+            }
+
+            fn corner_turn(&self, result: &mut Self) -> Result<(), Error> {
+                if self.cols % 2 != 0
+                    || result.rows != self.cols >> 1
+                    || result.cols != self.rows << 1
+                {
+                    return Err(Error::InvalidDimensions);
+                }
+
+                result
+                    .data
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(i, result_row)| self.complex_transpose_row(result_row, i));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_radar_processing!(f32);
+impl_radar_processing!(f64);
+
 use crate::Padding;
 impl<T: Number> Convolution<T> for Matrix2d<T> {
     fn convolute_row(&self, kernel: &Self, result_row: &mut [T], row_idx: usize) {
@@ -256,6 +759,55 @@ impl<T: Float> LRN<T> for Matrix2d<T> {
     }
 }
 
+impl<T: Float> GeneralLRN<T> for Matrix2d<T> {
+    fn general_lrn_row(&self, result_row: &mut [T], row_idx: usize, parameters: &LRNParameters<T>) {
+        if parameters.n == 1 {
+            // same as the original benchmark
+            return self.lrn_row(
+                result_row,
+                row_idx,
+                parameters.alpha,
+                parameters.beta,
+                parameters.k,
+            );
+        }
+        let i = row_idx;
+        let radius = parameters.n / 2;
+        let rows = i.saturating_sub(radius)..(i + radius + 1).min(self.rows);
+        for (j, result_el) in result_row.iter_mut().enumerate() {
+            let cols = match parameters.region {
+                LRNRegion::AcrossChannels => j..j + 1,
+                LRNRegion::WithinChannel => {
+                    j.saturating_sub(radius)..(j + radius + 1).min(self.cols)
+                }
+            };
+            let mut sum = T::zero();
+            for k in rows.clone() {
+                for l in cols.clone() {
+                    sum += self.data[k][l] * self.data[k][l];
+                }
+            }
+            *result_el =
+                self.data[i][j] / (parameters.k + parameters.alpha * sum).powf(parameters.beta);
+        }
+    }
+
+    fn general_lrn(&self, result: &mut Self, parameters: &LRNParameters<T>) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        if parameters.n % 2 != 1 {
+            return Err(Error::InvalidKernelDimensions);
+        }
+        result
+            .data
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, result_row)| self.general_lrn_row(result_row, i, parameters));
+        Ok(())
+    }
+}
+
 // TODO: note that right now the data has a minimum size for the algorithm to work
 //       should at least document this in the error
 impl WaveletTransformInteger<i32> for Matrix2d<i32> {
@@ -264,41 +816,11 @@ impl WaveletTransformInteger<i32> for Matrix2d<i32> {
         result_top_half: &[i32],
         element_idx: usize,
     ) -> i32 {
-        let i = element_idx;
-        let data = &self.data[0];
-
-        if i == 0 {
-            data[0] - (-(result_top_half[0] as f32 / 2.0) + 0.5) as i32
-        } else {
-            data[2 * i]
-                - (-((result_top_half[i - 1] + result_top_half[i]) as f32 / 4.0) + 0.5) as i32
-        }
+        wavelet::forward_integer_low_element(&self.data[0], result_top_half, element_idx)
     }
+
     fn wavelet_transform_top_half_element(&self, element_idx: usize, size: usize) -> i32 {
-        let i = element_idx;
-        let data = &self.data[0];
-
-        if i == 0 {
-            data[1]
-                - (((9.0 / 16.0) * (data[0] + data[2]) as f32)
-                    - ((1.0 / 16.0) * (data[2] + data[4]) as f32)
-                    + (1.0 / 2.0)) as i32
-        } else if i == size - 2 {
-            data[2 * size - 3]
-                - (((9.0 / 16.0) * (data[2 * size - 4] + data[2 * size - 2]) as f32)
-                    - ((1.0 / 16.0) * (data[2 * size - 6] + data[2 * size - 2]) as f32)
-                    + (1.0 / 2.0)) as i32
-        } else if i == size - 1 {
-            data[2 * size - 1]
-                - (((9.0 / 8.0) * (data[2 * size - 2]) as f32)
-                    - ((1.0 / 8.0) * (data[2 * size - 4]) as f32)
-                    + (1.0 / 2.0)) as i32
-        } else {
-            data[2 * i + 1]
-                - (((9.0 / 16.0) * (data[2 * i] + data[2 * i + 2]) as f32)
-                    - ((1.0 / 16.0) * (data[2 * i - 2] + data[2 * i + 4]) as f32)
-                    + (1.0 / 2.0)) as i32
-        }
+        wavelet::forward_integer_high_element(&self.data[0][..2 * size], element_idx)
     }
 
     fn wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
@@ -348,44 +870,115 @@ impl<T: Float> WaveletTransformFloating<T> for Matrix2d<T> {
             return Err(Error::InvalidDimensions);
         }
 
-        let hi_start = -(low_pass_filter_size as isize / 2);
-        let hi_end = (low_pass_filter_size / 2) as isize;
-        let gi_start = -(high_pass_filter_size as isize / 2);
-        let gi_end = (high_pass_filter_size / 2) as isize;
-
-        for i in 0..size {
-            let mut sum_value_low = T::zero();
-            // process the lowpass filter
-            for hi in hi_start..hi_end + 1 {
-                let x_position = (2 * i) as isize + hi;
-                let x_position = if x_position < 0 {
-                    x_position * -1
-                } else if x_position > full_size as isize - 1 {
-                    full_size as isize - 1 - (x_position - (full_size as isize - 1))
-                } else {
-                    x_position
-                };
-                sum_value_low +=
-                    low_pass_filter[(hi + hi_end) as usize] * self.data[0][x_position as usize];
-            }
-            result.data[0][i] = sum_value_low;
-
-            let mut sum_value_high = T::zero();
-            // process the highpass filter
-            for gi in gi_start..gi_end + 1 {
-                let x_position = (2 * i) as isize + gi + 1;
-                let x_position = if x_position < 0 {
-                    x_position * -1
-                } else if x_position > full_size as isize - 1 {
-                    full_size as isize - 1 - (x_position - (full_size as isize - 1))
-                } else {
-                    x_position
-                };
-                sum_value_high +=
-                    high_pass_filter[(gi + gi_end) as usize] * self.data[0][x_position as usize];
-            }
-            result.data[0][i + size] = sum_value_high;
+        wavelet::forward_floating(
+            &self.data[0],
+            &mut result.data[0],
+            &low_pass_filter[..low_pass_filter_size],
+            &high_pass_filter[..high_pass_filter_size],
+        );
+        Ok(())
+    }
+}
+
+impl WaveletTransform2dInteger<i32> for Matrix2d<i32> {
+    fn wavelet_transform_2d(&self, result: &mut Self, levels: usize) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [i32]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        wavelet::levels_2d(
+            &mut rows,
+            levels,
+            &wavelet::forward_integer,
+            rows::for_each_row,
+        );
+        Ok(())
+    }
+}
+
+impl<T: Float> WaveletTransform2dFloating<T> for Matrix2d<T> {
+    fn wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        if self.rows != result.rows || self.cols != result.cols {
+            return Err(Error::InvalidDimensions);
+        }
+        wavelet::check_levels_2d(self.rows, self.cols, levels)?;
+
+        result.data.clone_from(&self.data);
+        let mut rows: Vec<&mut [T]> = result
+            .data
+            .iter_mut()
+            .map(|row| row.as_mut_slice())
+            .collect();
+        let transform = |data: &[T], transformed: &mut [T]| {
+            wavelet::forward_floating(data, transformed, low_pass_filter, high_pass_filter)
+        };
+        wavelet::levels_2d(&mut rows, levels, &transform, rows::for_each_row);
+        Ok(())
+    }
+}
+
+impl InverseWaveletTransformInteger<i32> for Matrix2d<i32> {
+    fn inverse_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
         }
+        let coefficients = self.data[0].as_slice();
+
+        // even samples first, the odd ones are predicted from them
+        let even: Vec<i32> = (0..size)
+            .map(|idx| wavelet::inverse_integer_even_element(coefficients, idx))
+            .collect();
+
+        result.data[0]
+            .chunks_exact_mut(2)
+            .enumerate()
+            .for_each(|(idx, el)| {
+                el[0] = even[idx];
+                el[1] = wavelet::inverse_integer_odd_element(coefficients, &even, idx);
+            });
+
+        Ok(())
+    }
+}
+
+impl<T: Float> InverseWaveletTransformFloating<T> for Matrix2d<T> {
+    fn inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error> {
+        let full_size = size * 2;
+        if self.rows != 1 || self.cols != full_size || result.rows != 1 || result.cols != full_size
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let coefficients = self.data[0].as_slice();
+
+        result.data[0].iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = wavelet::inverse_floating_element(
+                coefficients,
+                idx,
+                low_pass_filter,
+                high_pass_filter,
+            );
+        });
         Ok(())
     }
 }
@@ -421,3 +1014,199 @@ impl<T: Number> FirFilter<T> for Matrix2d<T> {
         Ok(())
     }
 }
+
+impl<T: Number> MultirateFirFilter<T> for Matrix2d<T> {
+    fn fir_decimate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error> {
+        if self.rows != 1 || result.rows != 1 || result.cols != filter.decimated_len(self.cols)? {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[0][..];
+        result.data[0].iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = filter.decimate_element(input, idx);
+        });
+        Ok(())
+    }
+
+    fn fir_interpolate(&self, filter: &PolyphaseFilter<T>, result: &mut Self) -> Result<(), Error> {
+        if self.rows != 1
+            || result.rows != 1
+            || result.cols != filter.interpolated_len(self.cols)?
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let input = &self.data[0][..];
+        result.data[0].iter_mut().enumerate().for_each(|(idx, el)| {
+            *el = filter.interpolate_element(input, idx);
+        });
+        Ok(())
+    }
+}
+
+impl BitPlaneEncoder for Matrix2d<i32> {
+    fn bit_plane_encode(&self, parameters: &BpeParameters) -> Result<Vec<u8>, Error> {
+        ccsds_122::encode(self.rows, self.cols, |i, j| self.data[i][j], parameters)
+    }
+
+    fn bit_plane_decode(&mut self, data: &[u8]) -> Result<(), Error> {
+        ccsds_122::decode(data, self.rows, self.cols, |i, j, value| {
+            self.data[i][j] = value
+        })
+    }
+}
+
+impl RiceEncoder for Matrix2d<i32> {
+    fn rice_encode(&self, parameters: &RiceParameters) -> Result<Vec<u8>, Error> {
+        ccsds_121::encode(&self.data.concat(), parameters)
+    }
+
+    fn rice_decode(&mut self, data: &[u8], parameters: &RiceParameters) -> Result<(), Error> {
+        let mut samples = vec![0; self.rows * self.cols];
+        ccsds_121::decode(data, &mut samples, parameters)?;
+        for (row, samples) in self.data.iter_mut().zip(samples.chunks(self.cols)) {
+            row.copy_from_slice(samples);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Number> ImageCalibration<T> for Matrix2d<T> {
+    fn offset_correction(&self, offsets: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::offset_correction(
+            &self.row_slices(),
+            &offsets.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn bad_pixel_correction(&self, bad_pixels: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::bad_pixel_correction(
+            &self.row_slices(),
+            &bad_pixels.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+    ) -> Result<(), Error> {
+        let neighbours: Vec<_> = neighbours.iter().map(|frame| frame.row_slices()).collect();
+        calibration::radiation_scrubbing(
+            &self.row_slices(),
+            &neighbours,
+            threshold,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn gain_correction(&self, gains: &Self, result: &mut Self) -> Result<(), Error> {
+        calibration::gain_correction(
+            &self.row_slices(),
+            &gains.row_slices(),
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn spatial_binning(&self, factor: usize, result: &mut Self) -> Result<(), Error> {
+        calibration::spatial_binning(
+            &self.row_slices(),
+            factor,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+
+    fn co_add(&self, accumulator: &mut Self) -> Result<(), Error> {
+        calibration::co_add(
+            &self.row_slices(),
+            &mut accumulator.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+impl<T: Number> IirFilter<T> for Matrix2d<T> {
+    fn iir_filter(&self, cascade: &BiquadCascade<T>, result: &mut Self) -> Result<(), Error> {
+        iir::filter(
+            &self.row_slices(),
+            cascade,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+impl<T: Number> CrossCorrelation<T> for Matrix2d<T> {
+    fn cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+    ) -> Result<CorrelationPeak<T>, Error> {
+        cross_correlation::cross_correlation(
+            &self.row_slices(),
+            &other.row_slices(),
+            max_lags,
+            &mut result.row_slices_mut(),
+            rows::for_each_row,
+        )
+    }
+}
+
+macro_rules! impl_template_matching {
+    ($self_type: tt, $output_type: tt) => {
+        impl TemplateMatching for Matrix2d<$self_type> {
+            type Output = $output_type;
+            type Map = Matrix2d<$output_type>;
+            fn template_matching(
+                &self,
+                template: &Self,
+                result: &mut Self::Map,
+            ) -> Result<CorrelationPeak<Self::Output>, Error> {
+                cross_correlation::template_matching(
+                    &self.row_slices(),
+                    &template.row_slices(),
+                    &mut result.row_slices_mut(),
+                    rows::for_each_row,
+                )
+            }
+        }
+    };
+}
+
+impl_template_matching!(i32, f32);
+impl_template_matching!(f32, f32);
+impl_template_matching!(f64, f64);
+
+impl<T: Number> FaultInjection<T> for Matrix2d<T> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn modify_bits(&mut self, row: usize, col: usize, function: impl FnOnce(u64) -> u64) -> u64 {
+        let element = &mut self.data[row][col];
+        let bits = element.to_raw_bits();
+        let mask = (u64::MAX >> (64 - T::BITS)) & (function(bits) ^ bits);
+        *element = T::from_raw_bits(bits ^ mask);
+        mask
+    }
+}
+
+impl<T: Number> Voting for Matrix2d<T> {
+    fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+        if replicas
+            .iter()
+            .any(|replica| (replica.rows, replica.cols) != (self.rows, self.cols))
+        {
+            return Err(Error::InvalidDimensions);
+        }
+        let [a, b, c] = replicas.map(|replica| replica.row_slices());
+        Ok(tmr::vote_rows(&mut self.row_slices_mut(), [&a, &b, &c]))
+    }
+}
@@ -121,6 +121,110 @@ impl FormatNumber for f16 {
         format!("{:10.5}", f32::from(self))
     }
 }
+/// Raw bits of a value, used to inject faults
+pub trait Bits: Sized {
+    const BITS: u32;
+    fn to_raw_bits(self) -> u64;
+    /// Only the lowest `BITS` bits are used
+    fn from_raw_bits(bits: u64) -> Self;
+}
+
+macro_rules! impl_bits {
+    ($t: ty, $bits: ty) => {
+        impl Bits for $t {
+            const BITS: u32 = <$bits>::BITS;
+            fn to_raw_bits(self) -> u64 {
+                self.to_bits() as u64
+            }
+            fn from_raw_bits(bits: u64) -> Self {
+                <$t>::from_bits(bits as $bits)
+            }
+        }
+    };
+}
+
+impl_bits!(f32, u32);
+impl_bits!(f64, u64);
+impl_bits!(f16, u16);
+
+impl Bits for i32 {
+    const BITS: u32 = i32::BITS;
+    fn to_raw_bits(self) -> u64 {
+        self as u32 as u64
+    }
+    fn from_raw_bits(bits: u64) -> Self {
+        bits as u32 as i32
+    }
+}
+
+/// Fixed point arithmetic of the filter coefficients: a coefficient of an integer type is a fixed
+/// point number with `fraction_bits` fractional bits, the floating point types ignore them
+pub trait FixedPoint: Sized {
+    /// Sum of the products of `values` and `coefficients`, the integer types accumulate the
+    /// products in double width and round the sum once to the nearest integer
+    fn fixed_dot<const N: usize>(
+        values: [Self; N],
+        coefficients: [Self; N],
+        fraction_bits: u32,
+    ) -> Self;
+    fn fixed_to_f64(self, fraction_bits: u32) -> f64;
+    /// `value` rounded to the nearest fixed point number
+    fn fixed_from_f64(value: f64, fraction_bits: u32) -> Self;
+}
+
+macro_rules! impl_fixed_point_float {
+    ($t: ty, $to_f64: expr, $from_f64: expr) => {
+        impl FixedPoint for $t {
+            fn fixed_dot<const N: usize>(
+                values: [Self; N],
+                coefficients: [Self; N],
+                _: u32,
+            ) -> Self {
+                values
+                    .into_iter()
+                    .zip(coefficients)
+                    .fold(<$t>::from($from_f64(0.0)), |sum, (value, coefficient)| {
+                        sum + value * coefficient
+                    })
+            }
+            fn fixed_to_f64(self, _: u32) -> f64 {
+                $to_f64(self)
+            }
+            fn fixed_from_f64(value: f64, _: u32) -> Self {
+                $from_f64(value)
+            }
+        }
+    };
+}
+
+impl_fixed_point_float!(f32, |value: f32| value as f64, |value: f64| value as f32);
+impl_fixed_point_float!(f64, |value: f64| value, |value: f64| value);
+impl_fixed_point_float!(f16, f64::from, f16::from_f64);
+
+impl FixedPoint for i32 {
+    fn fixed_dot<const N: usize>(
+        values: [Self; N],
+        coefficients: [Self; N],
+        fraction_bits: u32,
+    ) -> Self {
+        let sum: i64 = values
+            .into_iter()
+            .zip(coefficients)
+            .map(|(value, coefficient)| value as i64 * coefficient as i64)
+            .sum();
+        if fraction_bits == 0 {
+            return sum as i32;
+        }
+        ((sum + (1 << (fraction_bits - 1))) >> fraction_bits) as i32
+    }
+    fn fixed_to_f64(self, fraction_bits: u32) -> f64 {
+        self as f64 / (1u64 << fraction_bits) as f64
+    }
+    fn fixed_from_f64(value: f64, fraction_bits: u32) -> Self {
+        (value * (1u64 << fraction_bits) as f64).round() as i32
+    }
+}
+
 pub trait Number:
     num_traits::NumAssignRef
     + RngRange
@@ -131,7 +235,10 @@ pub trait Number:
     + for<'a> std::iter::Sum<&'a Self>
     + std::iter::Sum<Self>
     + num_traits::AsPrimitive<f64>
+    + num_traits::FromPrimitive
     + FormatNumber
+    + Bits
+    + FixedPoint
 {
 }
 
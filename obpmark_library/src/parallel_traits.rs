@@ -1,5 +1,10 @@
-use crate::number_traits::Float;
-use crate::{Error, FastFourierTransformHelper, Padding};
+use crate::number_traits::{Float, Integer, Number};
+use crate::{
+    AbftReport, ActivationFunction, BatchNormParameters, BiquadCascade, BlockCipher, BpeParameters,
+    CorrelationPeak, Error, FastFourierTransformHelper, LRNParameters, Padding, PolyphaseFilter,
+    PoolingParameters, PoolingType, PredictorParameters, RadarProcessing, RiceParameters,
+    SoftmaxAxis, StftParameters, TemplateMatching,
+};
 
 pub trait ParallelMatMul {
     fn parallel_multiply(
@@ -10,6 +15,17 @@ pub trait ParallelMatMul {
     ) -> Result<(), Error>;
 }
 
+/// See `AbftMatMul`, only the multiplication is parallel
+pub trait ParallelAbftMatMul {
+    fn parallel_abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<AbftReport, Error>;
+}
+
 pub trait ParallelConvolution {
     fn parallel_convolute(
         &self,
@@ -24,10 +40,54 @@ pub trait ParallelRelu {
     fn parallel_relu(&self, result: &mut Self, n_threads: usize) -> Result<(), Error>;
 }
 
+pub trait ParallelActivation<T: Float> {
+    fn parallel_activation(
+        &self,
+        result: &mut Self,
+        function: ActivationFunction<T>,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelDense<T: Float> {
+    fn parallel_dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelBatchNorm<T: Float> {
+    fn parallel_batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
 pub trait ParallelSoftmax {
     fn parallel_softmax(&self, result: &mut Self, n_threads: usize) -> Result<(), Error>;
 }
 
+pub trait ParallelStableSoftmax {
+    fn parallel_stable_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_log_softmax(
+        &self,
+        result: &mut Self,
+        axis: SoftmaxAxis,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
 pub trait ParallelMaxPooling {
     fn parallel_max_pooling(
         &self,
@@ -38,6 +98,30 @@ pub trait ParallelMaxPooling {
     ) -> Result<(), Error>;
 }
 
+pub trait ParallelPooling<T> {
+    fn parallel_pooling(
+        &self,
+        result: &mut Self,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+
+    fn parallel_max_pooling_indices(
+        &self,
+        result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+
+    fn parallel_global_pooling(
+        &self,
+        pooling_type: PoolingType,
+        n_threads: usize,
+    ) -> Result<T, Error>;
+}
+
 pub trait ParallelLRN<T: Float> {
     fn parallel_lrn(
         &self,
@@ -49,6 +133,15 @@ pub trait ParallelLRN<T: Float> {
     ) -> Result<(), Error>;
 }
 
+pub trait ParallelGeneralLRN<T: Float> {
+    fn parallel_general_lrn(
+        &self,
+        result: &mut Self,
+        parameters: &LRNParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
 pub trait ParallelFiniteImpulseResponseFilter {
     fn parallel_fir_filter(
         &self,
@@ -58,6 +151,119 @@ pub trait ParallelFiniteImpulseResponseFilter {
     ) -> Result<(), Error>;
 }
 
+pub trait ParallelMultirateFirFilter<T> {
+    fn parallel_fir_decimate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+
+    fn parallel_fir_interpolate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelIirFilter<T> {
+    fn parallel_iir_filter(
+        &self,
+        cascade: &BiquadCascade<T>,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelCorrelation {
+    type Output;
+    fn parallel_correlate(&self, other: &Self, n_threads: usize) -> Result<Self::Output, Error>;
+}
+
+pub trait ParallelCrossCorrelation<T> {
+    fn parallel_cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<CorrelationPeak<T>, Error>;
+}
+
+pub trait ParallelTemplateMatching: TemplateMatching {
+    fn parallel_template_matching(
+        &self,
+        template: &Self,
+        result: &mut Self::Map,
+        n_threads: usize,
+    ) -> Result<CorrelationPeak<Self::Output>, Error>;
+}
+
+pub trait ParallelWaveletTransformInteger<T: Integer> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelWaveletTransformFloating<T: Float> {
+    fn parallel_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelInverseWaveletTransformInteger<T: Integer> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelInverseWaveletTransformFloating<T: Float> {
+    fn parallel_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelWaveletTransform2dInteger<T: Integer> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelWaveletTransform2dFloating<T: Float> {
+    fn parallel_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelFastFourierTransform<T>: FastFourierTransformHelper<T> {
+    fn parallel_fft(&mut self, nn: usize, n_threads: usize) -> Result<(), Error>;
+}
+
 pub trait ParallelFastFourierTransformWindowed<T>: FastFourierTransformHelper<T> {
     fn parallel_fft_windowed(
         &self,
@@ -66,3 +272,99 @@ pub trait ParallelFastFourierTransformWindowed<T>: FastFourierTransformHelper<T>
         n_threads: usize,
     ) -> Result<(), Error>;
 }
+
+pub trait ParallelShortTimeFourierTransform<T>: FastFourierTransformHelper<T> {
+    fn parallel_stft(
+        &self,
+        result: &mut Self,
+        parameters: &StftParameters<T>,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelFastFourierTransform2d<T>: FastFourierTransformHelper<T> {
+    fn parallel_fft_2d(&self, result: &mut Self, n_threads: usize) -> Result<(), Error>;
+}
+
+pub trait ParallelRadarProcessing<T>: RadarProcessing<T> {
+    fn parallel_compression(
+        &self,
+        matched_filter: &[T],
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_corner_turn(&self, result: &mut Self, n_threads: usize) -> Result<(), Error>;
+}
+
+/// The reference intervals are coded independently, the output is the same of `RiceEncoder`
+pub trait ParallelRiceEncoder {
+    fn parallel_rice_encode(
+        &self,
+        parameters: &RiceParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// The bands are encoded independently, the output is the same of `PredictiveEncoder`
+pub trait ParallelPredictiveEncoder {
+    fn parallel_predictive_encode(
+        &self,
+        parameters: &PredictorParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error>;
+}
+
+/// The segments are coded independently, the output is the same of `BitPlaneEncoder`
+pub trait ParallelBitPlaneEncoder {
+    fn parallel_bit_plane_encode(
+        &self,
+        parameters: &BpeParameters,
+        n_threads: usize,
+    ) -> Result<Vec<u8>, Error>;
+}
+
+pub trait ParallelBlockCipher: BlockCipher {
+    fn parallel_ecb_encrypt(&self, data: &mut [u8], n_threads: usize) -> Result<(), Error>;
+    fn parallel_ecb_decrypt(&self, data: &mut [u8], n_threads: usize) -> Result<(), Error>;
+    fn parallel_ctr(
+        &self,
+        counter: &[u8; 16],
+        data: &mut [u8],
+        n_threads: usize,
+    ) -> Result<(), Error>;
+}
+
+pub trait ParallelImageCalibration<T: Number> {
+    fn parallel_offset_correction(
+        &self,
+        offsets: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_bad_pixel_correction(
+        &self,
+        bad_pixels: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_gain_correction(
+        &self,
+        gains: &Self,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_spatial_binning(
+        &self,
+        factor: usize,
+        result: &mut Self,
+        n_threads: usize,
+    ) -> Result<(), Error>;
+    fn parallel_co_add(&self, accumulator: &mut Self, n_threads: usize) -> Result<(), Error>;
+}
@@ -1,10 +1,25 @@
-use crate::number_traits::Float;
-use crate::{Error, FastFourierTransformHelper, Padding};
+use crate::number_traits::{Float, Integer, Number};
+use crate::{
+    AbftReport, ActivationFunction, BatchNormParameters, BiquadCascade, BlockCipher, BpeParameters,
+    CorrelationPeak, Error, FastFourierTransformHelper, LRNParameters, Padding, PolyphaseFilter,
+    PoolingParameters, PoolingType, PredictorParameters, RadarProcessing, RiceParameters,
+    SoftmaxAxis, StftParameters, TemplateMatching,
+};
 
 pub trait RayonMatMul {
     fn rayon_multiply(&self, other: &Self, result: &mut Self) -> Result<(), Error>;
 }
 
+/// See `AbftMatMul`, only the multiplication is parallel
+pub trait RayonAbftMatMul {
+    fn rayon_abft_multiply(
+        &self,
+        other: &Self,
+        tolerance: f64,
+        result: &mut Self,
+    ) -> Result<AbftReport, Error>;
+}
+
 pub trait RayonMaxPooling {
     fn rayon_max_pooling(
         &self,
@@ -14,10 +29,33 @@ pub trait RayonMaxPooling {
     ) -> Result<(), Error>;
 }
 
+pub trait RayonPooling<T> {
+    fn rayon_pooling(
+        &self,
+        result: &mut Self,
+        pooling_type: PoolingType,
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error>;
+
+    fn rayon_max_pooling_indices(
+        &self,
+        result: &mut Self,
+        indices: &mut [usize],
+        parameters: &PoolingParameters,
+    ) -> Result<(), Error>;
+
+    fn rayon_global_pooling(&self, pooling_type: PoolingType) -> Result<T, Error>;
+}
+
 pub trait RayonSoftmax {
     fn rayon_softmax(&self, result: &mut Self) -> Result<(), Error>;
 }
 
+pub trait RayonStableSoftmax {
+    fn rayon_stable_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error>;
+    fn rayon_log_softmax(&self, result: &mut Self, axis: SoftmaxAxis) -> Result<(), Error>;
+}
+
 pub trait RayonConvolution {
     fn rayon_convolute(
         &self,
@@ -31,19 +69,183 @@ pub trait RayonRelu {
     fn rayon_relu(&self, result: &mut Self) -> Result<(), Error>;
 }
 
+pub trait RayonActivation<T: Float> {
+    fn rayon_activation(
+        &self,
+        result: &mut Self,
+        function: ActivationFunction<T>,
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonDense<T: Float> {
+    fn rayon_dense(
+        &self,
+        input: &Self,
+        bias: &[T],
+        result: &mut Self,
+        activation: Option<ActivationFunction<T>>,
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonBatchNorm<T: Float> {
+    fn rayon_batch_norm(
+        &self,
+        result: &mut Self,
+        parameters: &BatchNormParameters<T>,
+    ) -> Result<(), Error>;
+}
+
 pub trait RayonLRN<T: Float> {
     fn rayon_lrn(&self, result: &mut Self, alpha: T, beta: T, k: T) -> Result<(), Error>;
 }
 
+pub trait RayonGeneralLRN<T: Float> {
+    fn rayon_general_lrn(
+        &self,
+        result: &mut Self,
+        parameters: &LRNParameters<T>,
+    ) -> Result<(), Error>;
+}
+
 pub trait RayonFiniteImpulseResponseFilter {
     fn rayon_fir_filter(&self, kernel: &Self, result: &mut Self) -> Result<(), Error>;
 }
 
+pub trait RayonMultirateFirFilter<T> {
+    fn rayon_fir_decimate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+    ) -> Result<(), Error>;
+
+    fn rayon_fir_interpolate(
+        &self,
+        filter: &PolyphaseFilter<T>,
+        result: &mut Self,
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonIirFilter<T> {
+    fn rayon_iir_filter(&self, cascade: &BiquadCascade<T>, result: &mut Self) -> Result<(), Error>;
+}
+
+pub trait RayonWaveletTransformInteger<T: Integer> {
+    fn rayon_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error>;
+}
+
+pub trait RayonWaveletTransformFloating<T: Float> {
+    fn rayon_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonInverseWaveletTransformInteger<T: Integer> {
+    fn rayon_inverse_wavelet_transform(&self, result: &mut Self, size: usize) -> Result<(), Error>;
+}
+
+pub trait RayonInverseWaveletTransformFloating<T: Float> {
+    fn rayon_inverse_wavelet_transform(
+        &self,
+        result: &mut Self,
+        size: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonWaveletTransform2dInteger<T: Integer> {
+    fn rayon_wavelet_transform_2d(&self, result: &mut Self, levels: usize) -> Result<(), Error>;
+}
+
+pub trait RayonWaveletTransform2dFloating<T: Float> {
+    fn rayon_wavelet_transform_2d(
+        &self,
+        result: &mut Self,
+        levels: usize,
+        low_pass_filter: &[T],
+        high_pass_filter: &[T],
+    ) -> Result<(), Error>;
+}
+
+pub trait RayonFastFourierTransform<T>: FastFourierTransformHelper<T> {
+    fn rayon_fft(&mut self, nn: usize) -> Result<(), Error>;
+}
+
 pub trait RayonFastFourierTransformWindowed<T>: FastFourierTransformHelper<T> {
     fn rayon_fft_windowed(&self, window: usize, result: &mut Self) -> Result<(), Error>;
 }
 
+pub trait RayonShortTimeFourierTransform<T>: FastFourierTransformHelper<T> {
+    fn rayon_stft(&self, result: &mut Self, parameters: &StftParameters<T>) -> Result<(), Error>;
+}
+
+pub trait RayonFastFourierTransform2d<T>: FastFourierTransformHelper<T> {
+    fn rayon_fft_2d(&self, result: &mut Self) -> Result<(), Error>;
+}
+
+pub trait RayonRadarProcessing<T>: RadarProcessing<T> {
+    fn rayon_compression(&self, matched_filter: &[T], result: &mut Self) -> Result<(), Error>;
+    fn rayon_corner_turn(&self, result: &mut Self) -> Result<(), Error>;
+}
+
 pub trait RayonCorrelation {
     type Output;
     fn rayon_correlate(&self, other: &Self) -> Result<Self::Output, Error>;
 }
+
+pub trait RayonCrossCorrelation<T> {
+    fn rayon_cross_correlation(
+        &self,
+        other: &Self,
+        max_lags: (usize, usize),
+        result: &mut Self,
+    ) -> Result<CorrelationPeak<T>, Error>;
+}
+
+pub trait RayonTemplateMatching: TemplateMatching {
+    fn rayon_template_matching(
+        &self,
+        template: &Self,
+        result: &mut Self::Map,
+    ) -> Result<CorrelationPeak<Self::Output>, Error>;
+}
+
+/// The reference intervals are coded independently, the output is the same of `RiceEncoder`
+pub trait RayonRiceEncoder {
+    fn rayon_rice_encode(&self, parameters: &RiceParameters) -> Result<Vec<u8>, Error>;
+}
+
+/// The bands are encoded independently, the output is the same of `PredictiveEncoder`
+pub trait RayonPredictiveEncoder {
+    fn rayon_predictive_encode(&self, parameters: &PredictorParameters) -> Result<Vec<u8>, Error>;
+}
+
+/// The segments are coded independently, the output is the same of `BitPlaneEncoder`
+pub trait RayonBitPlaneEncoder {
+    fn rayon_bit_plane_encode(&self, parameters: &BpeParameters) -> Result<Vec<u8>, Error>;
+}
+
+pub trait RayonBlockCipher: BlockCipher {
+    fn rayon_ecb_encrypt(&self, data: &mut [u8]) -> Result<(), Error>;
+    fn rayon_ecb_decrypt(&self, data: &mut [u8]) -> Result<(), Error>;
+    fn rayon_ctr(&self, counter: &[u8; 16], data: &mut [u8]);
+}
+
+pub trait RayonImageCalibration<T: Number> {
+    fn rayon_offset_correction(&self, offsets: &Self, result: &mut Self) -> Result<(), Error>;
+    fn rayon_bad_pixel_correction(&self, bad_pixels: &Self, result: &mut Self)
+        -> Result<(), Error>;
+    fn rayon_radiation_scrubbing(
+        &self,
+        neighbours: &[&Self],
+        threshold: T,
+        result: &mut Self,
+    ) -> Result<(), Error>;
+    fn rayon_gain_correction(&self, gains: &Self, result: &mut Self) -> Result<(), Error>;
+    fn rayon_spatial_binning(&self, factor: usize, result: &mut Self) -> Result<(), Error>;
+    fn rayon_co_add(&self, accumulator: &mut Self) -> Result<(), Error>;
+}
@@ -0,0 +1,50 @@
+//! Executors that apply a function to every row of a matrix given as a slice of rows, together
+//! with the index of the row
+//! The kernels made of independent rows take the executor as their `for_each_row` argument, so
+//! that one implementation serves the sequential, std parallel and rayon strategies
+use crate::number_traits::Number;
+
+use rayon::prelude::*;
+use std::thread;
+
+/// Sequential executor
+pub(crate) fn for_each_row<T: Number>(
+    rows: &mut [&mut [T]],
+    function: &(dyn Fn(usize, &mut [T]) + Sync),
+) {
+    rows.iter_mut()
+        .enumerate()
+        .for_each(|(i, row)| function(i, row));
+}
+
+/// Std parallel executor, the rows are split in `n_threads` chunks
+pub(crate) fn parallel_for_each_row<T: Number>(
+    rows: &mut [&mut [T]],
+    function: &(dyn Fn(usize, &mut [T]) + Sync),
+    n_threads: usize,
+) {
+    let rows_per_thread = (rows.len() - 1) / n_threads + 1;
+    thread::scope(|s| {
+        rows.chunks_mut(rows_per_thread)
+            .enumerate()
+            .for_each(|(chunk_idx, chunk)| {
+                let start_row = chunk_idx * rows_per_thread;
+                s.spawn(move || {
+                    chunk
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(i, row)| function(start_row + i, row));
+                });
+            });
+    });
+}
+
+/// Rayon executor, every row is a task
+pub(crate) fn rayon_for_each_row<T: Number>(
+    rows: &mut [&mut [T]],
+    function: &(dyn Fn(usize, &mut [T]) + Sync),
+) {
+    rows.par_iter_mut()
+        .enumerate()
+        .for_each(|(i, row)| function(i, row));
+}
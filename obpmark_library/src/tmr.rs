@@ -0,0 +1,349 @@
+//! Triple modular redundancy: a kernel is run three times on separate result buffers and the
+//! results are voted element by element, a fault that hits a single execution is masked
+use std::fmt;
+use std::ops::AddAssign;
+use std::thread;
+
+use half::f16;
+
+use crate::number_traits::{Bits, Number};
+use crate::{AbftReport, CorrelationPeak, Error, Voting};
+
+/// How the three executions of the kernel are run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TmrMode {
+    /// One after the other on the calling thread
+    Sequential,
+    /// On three concurrent threads
+    Concurrent,
+}
+
+/// Statistics of the voting
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TmrReport {
+    /// Voted elements
+    pub elements: usize,
+    /// Elements where a replica differs from the other two, the majority wins
+    pub corrected: usize,
+    /// Elements where the three replicas differ, the first one is kept
+    pub uncorrectable: usize,
+}
+
+impl TmrReport {
+    /// Elements where the replicas do not agree
+    pub fn mismatches(&self) -> usize {
+        self.corrected + self.uncorrectable
+    }
+}
+
+impl AddAssign for TmrReport {
+    fn add_assign(&mut self, other: TmrReport) {
+        self.elements += other.elements;
+        self.corrected += other.corrected;
+        self.uncorrectable += other.uncorrectable;
+    }
+}
+
+impl fmt::Display for TmrReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Voted elements: {}, mismatches: {} ({} corrected, {} uncorrectable)",
+            self.elements,
+            self.mismatches(),
+            self.corrected,
+            self.uncorrectable
+        )
+    }
+}
+
+/// Votes the rows of the three replicas into `result`, the elements are compared bit by bit
+pub(crate) fn vote_rows<T: Number>(result: &mut [&mut [T]], replicas: [&[&[T]]; 3]) -> TmrReport {
+    let mut report = TmrReport::default();
+    for (i, result_row) in result.iter_mut().enumerate() {
+        let [a, b, c] = replicas.map(|replica| replica[i]);
+        for (el, ((a, b), c)) in result_row.iter_mut().zip(a.iter().zip(b).zip(c)) {
+            let (a_bits, b_bits, c_bits) = (a.to_raw_bits(), b.to_raw_bits(), c.to_raw_bits());
+            *el = match (a_bits == b_bits, a_bits == c_bits, b_bits == c_bits) {
+                (true, true, _) => *a,
+                (true, false, _) | (false, true, _) => {
+                    report.corrected += 1;
+                    *a
+                }
+                (false, false, true) => {
+                    report.corrected += 1;
+                    *b
+                }
+                (false, false, false) => {
+                    report.uncorrectable += 1;
+                    *a
+                }
+            };
+        }
+        report.elements += result_row.len();
+    }
+    report
+}
+
+/// Writes in `result` the majority of three values, a single voted element
+fn vote_value<V: Clone>(
+    result: &mut V,
+    [a, b, c]: [&V; 3],
+    same: impl Fn(&V, &V) -> bool,
+) -> TmrReport {
+    let mut report = TmrReport {
+        elements: 1,
+        ..TmrReport::default()
+    };
+    let majority = match (same(a, b), same(a, c), same(b, c)) {
+        (true, true, _) => a,
+        (true, false, _) | (false, true, _) => {
+            report.corrected += 1;
+            a
+        }
+        (false, false, true) => {
+            report.corrected += 1;
+            b
+        }
+        (false, false, false) => {
+            report.uncorrectable += 1;
+            a
+        }
+    };
+    result.clone_from(majority);
+    report
+}
+
+/// Whether the voting finds no difference between `a` and `b`
+fn identical<V: Voting>(a: &V, b: &V) -> bool {
+    let mut scratch = a.clone();
+    scratch
+        .vote([a, b, b])
+        .is_ok_and(|report| report.mismatches() == 0)
+}
+
+macro_rules! impl_voting_number {
+    ($($t: ty),*) => {
+        $(
+            impl Voting for $t {
+                fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+                    Ok(vote_value(self, replicas, |a, b| a.to_raw_bits() == b.to_raw_bits()))
+                }
+            }
+        )*
+    };
+}
+
+impl_voting_number!(f32, f64, i32, f16);
+
+macro_rules! impl_voting_eq {
+    ($($t: ty),*) => {
+        $(
+            impl Voting for $t {
+                fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+                    Ok(vote_value(self, replicas, |a, b| a == b))
+                }
+            }
+        )*
+    };
+}
+
+impl_voting_eq!(u8, usize, AbftReport);
+
+/// The peak is a single element, the value is compared bit by bit
+impl<T: Number> Voting for CorrelationPeak<T> {
+    fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+        Ok(vote_value(self, replicas, |a, b| {
+            (a.row, a.col, a.value.to_raw_bits()) == (b.row, b.col, b.value.to_raw_bits())
+        }))
+    }
+}
+
+/// Element by element, replicas of different lengths (e.g. a variable length stream corrupted in
+/// one execution) are voted as a whole, as a single element
+impl<E: Voting> Voting for Vec<E> {
+    fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+        let [a, b, c] = replicas;
+        if a.len() != b.len() || a.len() != c.len() {
+            return Ok(vote_value(self, replicas, |x, y| {
+                x.len() == y.len() && identical(x, y)
+            }));
+        }
+        self.clone_from(a);
+        let mut report = TmrReport::default();
+        for (i, el) in self.iter_mut().enumerate() {
+            report += el.vote([&a[i], &b[i], &c[i]])?;
+        }
+        Ok(report)
+    }
+}
+
+macro_rules! impl_voting_tuple {
+    ($($t: ident $i: tt),*) => {
+        /// Each output of the kernel is voted on its own
+        impl<$($t: Voting),*> Voting for ($($t,)*) {
+            fn vote(&mut self, replicas: [&Self; 3]) -> Result<TmrReport, Error> {
+                let mut report = TmrReport::default();
+                $(report += self.$i.vote(replicas.map(|replica| &replica.$i))?;)*
+                Ok(report)
+            }
+        }
+    };
+}
+
+impl_voting_tuple!(A 0, B 1);
+impl_voting_tuple!(A 0, B 1, C 2);
+
+/// Runs `kernel` three times, each one on a separate copy of `result` (so in-place kernels get
+/// the initial content), and votes the three results into `result`
+/// The kernel has to be deterministic (for the concurrent mode, also with respect to the other
+/// executions), the first error of an execution is returned
+pub fn tmr<M: Voting>(
+    mode: TmrMode,
+    result: &mut M,
+    kernel: impl Fn(&mut M) -> Result<(), Error> + Sync,
+) -> Result<TmrReport, Error> {
+    let mut replicas = [(); 3].map(|_| result.clone());
+    match mode {
+        TmrMode::Sequential => {
+            for replica in replicas.iter_mut() {
+                kernel(replica)?;
+            }
+        }
+        TmrMode::Concurrent => {
+            let kernel = &kernel;
+            thread::scope(|s| {
+                let handles: Vec<_> = replicas
+                    .iter_mut()
+                    .map(|replica| s.spawn(move || kernel(replica)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .try_for_each(|handle| handle.join().unwrap())
+            })?;
+        }
+    }
+    result.vote(replicas.each_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix_1d::Matrix1d;
+    use crate::{BaseMatrix, FaultInjection, Relu};
+
+    #[test]
+    fn voting() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let a = Matrix1d::<f32>::from_random_seed(8, 6, 5, -10.0, 10.0);
+        let mut golden = Matrix1d::zeroes(6, 5);
+        a.relu(&mut golden).unwrap();
+
+        // the n-th execution flips its own bit of the elements in `faulty`
+        let run = |mode, faulty: &[(usize, usize, usize)]| {
+            let executions = AtomicUsize::new(0);
+            let mut result = Matrix1d::zeroes(6, 5);
+            let report = tmr(mode, &mut result, |replica: &mut Matrix1d<f32>| {
+                a.relu(replica)?;
+                let execution = executions.fetch_add(1, Ordering::SeqCst);
+                for &(n, row, col) in faulty {
+                    if n == execution {
+                        replica.modify_bits(row, col, |bits| bits ^ 1 << (3 + n));
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(executions.into_inner(), 3);
+            (report, result.get_data())
+        };
+
+        for mode in [TmrMode::Sequential, TmrMode::Concurrent] {
+            let (report, result) = run(mode, &[]);
+            assert_eq!(report.mismatches(), 0);
+            assert_eq!(report.elements, 30);
+            assert_eq!(result, golden.get_data());
+        }
+        // a fault in a single execution is voted out, also in different elements
+        let (report, result) = run(TmrMode::Sequential, &[(0, 1, 1), (1, 2, 3), (2, 5, 4)]);
+        assert_eq!(report.corrected, 3);
+        assert_eq!(result, golden.get_data());
+        // the same element hit in two executions has no majority
+        let (report, result) = run(TmrMode::Sequential, &[(0, 1, 1), (2, 1, 1)]);
+        assert_eq!(
+            report,
+            TmrReport {
+                elements: 30,
+                corrected: 0,
+                uncorrectable: 1
+            }
+        );
+        assert_ne!(result[1][1].to_bits(), golden.get_data()[1][1].to_bits());
+
+        let mut wrong = Matrix1d::zeroes(5, 6);
+        assert!(tmr(TmrMode::Concurrent, &mut wrong, |replica| a.relu(replica)).is_err());
+        let replicas = [(); 3].map(|_| Matrix1d::zeroes(6, 5));
+        assert!(wrong.vote(replicas.each_ref()).is_err());
+    }
+
+    #[test]
+    fn voting_outputs() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // an in-place kernel with a byte stream and a peak, `fault` hits the n-th execution
+        let run = |fault: fn(usize, &mut Vec<u8>, &mut CorrelationPeak<f32>)| {
+            let executions = AtomicUsize::new(0);
+            let peak = CorrelationPeak {
+                row: 0,
+                col: 0,
+                value: 0.0,
+            };
+            let mut result = ((0..20).collect::<Vec<u8>>(), peak);
+            let report = tmr(TmrMode::Concurrent, &mut result, |(stream, peak)| {
+                stream.iter_mut().for_each(|byte| *byte ^= 0x5a);
+                *peak = CorrelationPeak {
+                    row: 2,
+                    col: 3,
+                    value: 0.5,
+                };
+                fault(executions.fetch_add(1, Ordering::SeqCst), stream, peak);
+                Ok(())
+            })
+            .unwrap();
+            (report, result)
+        };
+        let golden: Vec<u8> = (0..20).map(|byte| byte ^ 0x5a).collect();
+        let golden_peak = CorrelationPeak {
+            row: 2,
+            col: 3,
+            value: 0.5,
+        };
+
+        let (report, (stream, peak)) = run(|_, _, _| ());
+        assert_eq!(report.elements, 21);
+        assert_eq!(report.mismatches(), 0);
+        assert_eq!((stream, peak), (golden.clone(), golden_peak));
+
+        // each output is voted on its own
+        let (report, (stream, peak)) = run(|n, stream, peak| match n {
+            0 => stream[4] ^= 1,
+            1 => peak.value = f32::NAN,
+            _ => (),
+        });
+        assert_eq!(report.corrected, 2);
+        assert_eq!((stream, peak), (golden.clone(), golden_peak));
+
+        // a stream with a different length is voted as a whole
+        let (report, (stream, _)) = run(|n, stream, _| {
+            if n == 2 {
+                stream.pop();
+            }
+        });
+        assert_eq!(report.elements, 2);
+        assert_eq!(report.corrected, 1);
+        assert_eq!(stream, golden);
+        let (report, _) = run(|n, stream, _| stream.truncate(10 + n));
+        assert_eq!(report.uncorrectable, 1);
+    }
+}
@@ -0,0 +1,232 @@
+//! 9/7 wavelet transforms of CCSDS 122.0, float and integer
+//! The transformed data has the low pass coefficients in the first half and the high pass
+//! coefficients in the second half
+use crate::number_traits::{Float, Number};
+use crate::Error;
+
+/// High pass coefficient `element_idx` of the forward integer transform of `data`
+pub(crate) fn forward_integer_high_element(data: &[i32], element_idx: usize) -> i32 {
+    let i = element_idx;
+    let size = data.len() / 2;
+
+    if i == 0 {
+        data[1]
+            - (((9.0 / 16.0) * (data[0] + data[2]) as f32)
+                - ((1.0 / 16.0) * (data[2] + data[4]) as f32)
+                + 0.5) as i32
+    } else if i == size - 2 {
+        data[2 * size - 3]
+            - (((9.0 / 16.0) * (data[2 * size - 4] + data[2 * size - 2]) as f32)
+                - ((1.0 / 16.0) * (data[2 * size - 6] + data[2 * size - 2]) as f32)
+                + (1.0 / 2.0)) as i32
+    } else if i == size - 1 {
+        data[2 * size - 1]
+            - (((9.0 / 8.0) * (data[2 * size - 2]) as f32)
+                - ((1.0 / 8.0) * (data[2 * size - 4]) as f32)
+                + 0.5) as i32
+    } else {
+        data[2 * i + 1]
+            - (((9.0 / 16.0) * (data[2 * i] + data[2 * i + 2]) as f32)
+                - ((1.0 / 16.0) * (data[2 * i - 2] + data[2 * i + 4]) as f32)
+                + (1.0 / 2.0)) as i32
+    }
+}
+
+/// Low pass coefficient `element_idx` of the forward integer transform of `data`, `high` holds
+/// the high pass coefficients
+pub(crate) fn forward_integer_low_element(data: &[i32], high: &[i32], element_idx: usize) -> i32 {
+    let i = element_idx;
+
+    if i == 0 {
+        data[0] - (-(high[0] as f32 / 2.0) + 0.5) as i32
+    } else {
+        data[2 * i] - (-((high[i - 1] + high[i]) as f32 / 4.0) + 0.5) as i32
+    }
+}
+
+/// Forward integer transform of `data` into `result`, both of even length of at least 6
+pub(crate) fn forward_integer(data: &[i32], result: &mut [i32]) {
+    let size = data.len() / 2;
+    let (low, high) = result.split_at_mut(size);
+    for (idx, el) in high.iter_mut().enumerate() {
+        *el = forward_integer_high_element(data, idx);
+    }
+    for (idx, el) in low.iter_mut().enumerate() {
+        *el = forward_integer_low_element(data, high, idx);
+    }
+}
+
+/// Mirrors positions outside of `0..full_size` back inside (whole sample symmetric extension)
+fn mirror(position: isize, full_size: isize) -> usize {
+    if position < 0 {
+        -position as usize
+    } else if position > full_size - 1 {
+        (full_size - 1 - (position - (full_size - 1))) as usize
+    } else {
+        position as usize
+    }
+}
+
+/// Coefficient of the forward floating point transform of `data` obtained by applying the
+/// centered `filter` at position `center`
+pub(crate) fn forward_floating_element<T: Float>(data: &[T], center: usize, filter: &[T]) -> T {
+    let end = (filter.len() / 2) as isize;
+    let mut sum = T::zero();
+    for f in -end..end + 1 {
+        let position = mirror(center as isize + f, data.len() as isize);
+        sum += filter[(f + end) as usize] * data[position];
+    }
+    sum
+}
+
+/// Forward floating point transform of `data` into `result`, both of even length
+pub(crate) fn forward_floating<T: Float>(
+    data: &[T],
+    result: &mut [T],
+    low_pass_filter: &[T],
+    high_pass_filter: &[T],
+) {
+    let size = data.len() / 2;
+    let (low, high) = result.split_at_mut(size);
+    for (idx, (low_el, high_el)) in low.iter_mut().zip(high).enumerate() {
+        *low_el = forward_floating_element(data, 2 * idx, low_pass_filter);
+        *high_el = forward_floating_element(data, 2 * idx + 1, high_pass_filter);
+    }
+}
+
+/// Checks that a `rows` x `cols` image can be decomposed in `levels` levels: both dimensions
+/// have to be divisible by `2^levels` and the last level has to transform at least 6 samples,
+/// the minimum of the integer transform
+pub(crate) fn check_levels_2d(rows: usize, cols: usize, levels: usize) -> Result<(), Error> {
+    if levels == 0 || levels >= usize::BITS as usize {
+        return Err(Error::UnsupportedSize);
+    }
+    for dim in [rows, cols] {
+        if !dim.is_multiple_of(1 << levels) || (dim >> levels) < 3 {
+            return Err(Error::UnsupportedSize);
+        }
+    }
+    Ok(())
+}
+
+/// Applies `levels` levels of the 2d transform in place on the image made of `rows`, each level
+/// transforms the rows and then the columns of the LL subband of the previous level
+/// The columns are transposed into rows, so that all the passes are made of independent rows
+/// and the strategies only differ in `for_each_row` (see `rows`)
+pub(crate) fn levels_2d<T: Number>(
+    rows: &mut [&mut [T]],
+    levels: usize,
+    transform: &(impl Fn(&[T], &mut [T]) + Sync),
+    for_each_row: impl Fn(&mut [&mut [T]], &(dyn Fn(usize, &mut [T]) + Sync)),
+) {
+    let (full_rows, full_cols) = (rows.len(), rows[0].len());
+    for level in 0..levels {
+        let (h, w) = (full_rows >> level, full_cols >> level);
+
+        let mut region: Vec<&mut [T]> = rows.iter_mut().take(h).map(|row| &mut row[..w]).collect();
+        for_each_row(&mut region, &|_, row| {
+            let buffer = row.to_vec();
+            transform(&buffer, row);
+        });
+
+        let mut transposed = vec![T::zero(); w * h];
+        let mut columns: Vec<&mut [T]> = transposed.chunks_mut(h).collect();
+        let region_ref = &region;
+        for_each_row(&mut columns, &|j, column| {
+            let buffer: Vec<T> = region_ref.iter().map(|row| row[j]).collect();
+            transform(&buffer, column);
+        });
+
+        let columns_ref = &columns;
+        for_each_row(&mut region, &|i, row| {
+            for (el, column) in row.iter_mut().zip(columns_ref.iter()) {
+                *el = column[i];
+            }
+        });
+    }
+}
+
+/// Even sample `2 * element_idx` of the inverse integer transform, undoes the update step
+pub(crate) fn inverse_integer_even_element(coefficients: &[i32], element_idx: usize) -> i32 {
+    let i = element_idx;
+    let size = coefficients.len() / 2;
+    let high = &coefficients[size..];
+
+    // same rounding as the forward transform, so that the round trip is lossless
+    if i == 0 {
+        coefficients[0] + (-(high[0] as f32 / 2.0) + 0.5) as i32
+    } else {
+        coefficients[i] + (-((high[i - 1] + high[i]) as f32 / 4.0) + 0.5) as i32
+    }
+}
+
+/// Odd sample `2 * element_idx + 1` of the inverse integer transform, undoes the predict step
+/// `even` holds the even samples computed by `inverse_integer_even_element`
+pub(crate) fn inverse_integer_odd_element(
+    coefficients: &[i32],
+    even: &[i32],
+    element_idx: usize,
+) -> i32 {
+    let i = element_idx;
+    let size = coefficients.len() / 2;
+    let high = coefficients[size + i];
+
+    if i == 0 {
+        high + (((9.0 / 16.0) * (even[0] + even[1]) as f32)
+            - ((1.0 / 16.0) * (even[1] + even[2]) as f32)
+            + 0.5) as i32
+    } else if i == size - 2 {
+        high + (((9.0 / 16.0) * (even[size - 2] + even[size - 1]) as f32)
+            - ((1.0 / 16.0) * (even[size - 3] + even[size - 1]) as f32)
+            + 0.5) as i32
+    } else if i == size - 1 {
+        high + (((9.0 / 8.0) * (even[size - 1]) as f32) - ((1.0 / 8.0) * (even[size - 2]) as f32)
+            + 0.5) as i32
+    } else {
+        high + (((9.0 / 16.0) * (even[i] + even[i + 1]) as f32)
+            - ((1.0 / 16.0) * (even[i - 1] + even[i + 2]) as f32)
+            + 0.5) as i32
+    }
+}
+
+/// Sample `element_idx` of the inverse floating point transform
+/// The filters are the synthesis ones, both of odd length and centered, the low pass is applied
+/// to the low pass coefficients (at the even positions) and the high pass to the high pass
+/// coefficients (at the odd positions)
+/// The coefficients are extended with the symmetry induced by the symmetric extension of the
+/// signal used in the forward transform
+pub(crate) fn inverse_floating_element<T: Float>(
+    coefficients: &[T],
+    element_idx: usize,
+    low_pass_filter: &[T],
+    high_pass_filter: &[T],
+) -> T {
+    let size = (coefficients.len() / 2) as isize;
+    let n = element_idx as isize;
+    let hi_end = (low_pass_filter.len() / 2) as isize;
+    let gi_end = (high_pass_filter.len() / 2) as isize;
+
+    let mut sum = T::zero();
+    for hi in -hi_end..hi_end + 1 {
+        let position = n - hi;
+        if position & 1 == 1 {
+            continue;
+        }
+        // low pass coefficients are symmetric around 0 and around size - 1 / 2
+        let j = (position / 2).abs();
+        let j = if j > size - 1 { 2 * size - 1 - j } else { j };
+        sum += low_pass_filter[(hi + hi_end) as usize] * coefficients[j as usize];
+    }
+    for gi in -gi_end..gi_end + 1 {
+        let position = n - gi;
+        if position & 1 == 0 {
+            continue;
+        }
+        // high pass coefficients are symmetric around -1 / 2 and around size - 1
+        let j = (position - 1) / 2;
+        let j = if j < 0 { -j - 1 } else { j };
+        let j = if j > size - 1 { 2 * size - 2 - j } else { j };
+        sum += high_pass_filter[(gi + gi_end) as usize] * coefficients[(size + j) as usize];
+    }
+    sum
+}